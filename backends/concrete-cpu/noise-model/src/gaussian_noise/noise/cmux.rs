@@ -1,4 +1,6 @@
-use super::external_product_glwe::variance_external_product_glwe;
+use super::external_product_glwe::{
+    variance_external_product_glwe, variance_external_product_glwe_with_key,
+};
 
 // only valid in the blind rotate case
 pub fn variance_cmux(
@@ -20,3 +22,30 @@ pub fn variance_cmux(
         variance_ggsw,
     )
 }
+
+/// Same as [`variance_cmux`] for a glwe key whose coefficients have the given variance and squared
+/// expectation, instead of a binary key.
+#[allow(clippy::too_many_arguments)]
+pub fn variance_cmux_with_key(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+    key_variance: f64,
+    key_square_expectation: f64,
+) -> f64 {
+    variance_external_product_glwe_with_key(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_ggsw,
+        key_variance,
+        key_square_expectation,
+    )
+}
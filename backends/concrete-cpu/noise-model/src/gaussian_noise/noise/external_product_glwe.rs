@@ -9,6 +9,33 @@ pub fn variance_external_product_glwe(
     ciphertext_modulus_log: u32,
     fft_precision: u32,
     variance_ggsw: f64,
+) -> f64 {
+    variance_external_product_glwe_with_key(
+        glwe_dimension,
+        polynomial_size,
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        fft_precision,
+        variance_ggsw,
+        1. / 4.,
+        square(1. / 2.),
+    )
+}
+
+/// Same as [`variance_external_product_glwe`] for a glwe key whose coefficients have the given
+/// variance and squared expectation, instead of a binary key.
+#[allow(clippy::too_many_arguments)]
+pub fn variance_external_product_glwe_with_key(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    fft_precision: u32,
+    variance_ggsw: f64,
+    key_variance: f64,
+    key_square_expectation: f64,
 ) -> f64 {
     theoretical_variance_external_product_glwe(
         glwe_dimension,
@@ -17,6 +44,8 @@ pub fn variance_external_product_glwe(
         level,
         ciphertext_modulus_log,
         variance_ggsw,
+        key_variance,
+        key_square_expectation,
     ) + fft_noise_variance_external_product_glwe(
         glwe_dimension,
         polynomial_size,
@@ -27,6 +56,7 @@ pub fn variance_external_product_glwe(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn theoretical_variance_external_product_glwe(
     glwe_dimension: u64,
     polynomial_size: u64,
@@ -34,12 +64,14 @@ fn theoretical_variance_external_product_glwe(
     level: u64,
     ciphertext_modulus_log: u32,
     variance_ggsw: f64,
+    key_variance: f64,
+    key_square_expectation: f64,
 ) -> f64 {
-    let variance_key_coefficient_binary: f64 =
-        modular_variance_to_variance(1. / 4., ciphertext_modulus_log);
+    let variance_key_coefficient: f64 =
+        modular_variance_to_variance(key_variance, ciphertext_modulus_log);
 
-    let square_expectation_key_coefficient_binary: f64 =
-        modular_variance_to_variance(square(1. / 2.), ciphertext_modulus_log);
+    let square_expectation_key_coefficient: f64 =
+        modular_variance_to_variance(key_square_expectation, ciphertext_modulus_log);
 
     let k = glwe_dimension as f64;
     let b = 2_f64.powi(log2_base as i32);
@@ -52,9 +84,9 @@ fn theoretical_variance_external_product_glwe(
     let res_2 = (q_square - b2l) / (24. * b2l)
         * (modular_variance_to_variance(1., ciphertext_modulus_log)
             + k * big_n
-                * (variance_key_coefficient_binary + square_expectation_key_coefficient_binary))
-        + k * big_n / 8. * variance_key_coefficient_binary
-        + 1. / 16. * square(1. - k * big_n) * square_expectation_key_coefficient_binary;
+                * (variance_key_coefficient + square_expectation_key_coefficient))
+        + k * big_n / 8. * variance_key_coefficient
+        + 1. / 16. * square(1. - k * big_n) * square_expectation_key_coefficient;
 
     res_1 + res_2
 }
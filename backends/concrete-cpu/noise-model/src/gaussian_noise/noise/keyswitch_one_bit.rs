@@ -7,11 +7,31 @@ pub fn variance_keyswitch_one_bit(
     ciphertext_modulus_log: u32,
     variance_ksk: f64,
 ) -> f64 {
-    let variance_key_coefficient_binary: f64 =
-        modular_variance_to_variance(1. / 4., ciphertext_modulus_log);
+    variance_keyswitch_one_bit_with_key(
+        log2_base,
+        level,
+        ciphertext_modulus_log,
+        variance_ksk,
+        1. / 4.,
+        square(1. / 2.),
+    )
+}
+
+/// Same as [`variance_keyswitch_one_bit`] for an input key whose coefficients have the given
+/// variance and squared expectation, instead of a binary key.
+pub fn variance_keyswitch_one_bit_with_key(
+    log2_base: u64,
+    level: u64,
+    ciphertext_modulus_log: u32,
+    variance_ksk: f64,
+    key_variance: f64,
+    key_square_expectation: f64,
+) -> f64 {
+    let variance_key_coefficient: f64 =
+        modular_variance_to_variance(key_variance, ciphertext_modulus_log);
 
-    let square_expectation_key_coefficient_binary: f64 =
-        modular_variance_to_variance(square(1. / 2.), ciphertext_modulus_log);
+    let square_expectation_key_coefficient: f64 =
+        modular_variance_to_variance(key_square_expectation, ciphertext_modulus_log);
 
     let base = 2_f64.powi(log2_base as i32);
     let b2l = 2_f64.powi((log2_base * 2 * level) as i32);
@@ -19,10 +39,10 @@ pub fn variance_keyswitch_one_bit(
 
     // res 2
     let res_2 = (q_square / (12. * b2l) - 1. / 12.)
-        * (variance_key_coefficient_binary + square_expectation_key_coefficient_binary);
+        * (variance_key_coefficient + square_expectation_key_coefficient);
 
     // res 3
-    let res_3 = 1. / 4. * variance_key_coefficient_binary;
+    let res_3 = 1. / 4. * variance_key_coefficient;
 
     // res 4
     let res_4 = (level as f64) * variance_ksk * (square(base) + 2.) / 12.;
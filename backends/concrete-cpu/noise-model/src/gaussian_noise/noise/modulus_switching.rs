@@ -4,12 +4,35 @@ pub fn estimate_modulus_switching_noise_with_binary_key(
     internal_ks_output_lwe_dimension: u64,
     glwe_log2_polynomial_size: u64,
     ciphertext_modulus_log: u32,
+) -> f64 {
+    estimate_modulus_switching_noise_with_key(
+        internal_ks_output_lwe_dimension,
+        glwe_log2_polynomial_size,
+        ciphertext_modulus_log,
+        1. / 4.,
+        square(1. / 2.),
+    )
+}
+
+/// Same as [`estimate_modulus_switching_noise_with_binary_key`] for a key whose coefficients have
+/// the given variance and squared expectation.
+pub fn estimate_modulus_switching_noise_with_key(
+    internal_ks_output_lwe_dimension: u64,
+    glwe_log2_polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+    key_variance: f64,
+    key_square_expectation: f64,
 ) -> f64 {
     let nb_msb = glwe_log2_polynomial_size + 1;
 
     let w = 2_f64.powi(nb_msb as i32);
     let n = internal_ks_output_lwe_dimension as f64;
+    // E[s^2], 1/2 for a binary key
+    let key_second_moment = key_variance + key_square_expectation;
 
-    (1. / 12. + n / 24.) / square(w)
-        + modular_variance_to_variance(-1. / 12. + n / 48., ciphertext_modulus_log)
+    (1. / 12. + n * key_second_moment / 12.) / square(w)
+        + modular_variance_to_variance(
+            -1. / 12. + n * key_second_moment / 24.,
+            ciphertext_modulus_log,
+        )
 }
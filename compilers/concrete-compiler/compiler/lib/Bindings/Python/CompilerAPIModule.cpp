@@ -283,6 +283,11 @@ void mlir::concretelang::python::populateCompilerAPISubmodule(
             restriction.glwe_log_polynomial_sizes.push_back(v);
           },
           "Add an available glwe log poly size to the restriction")
+      .def(
+          "add_available_glwe_polynomial_size",
+          [](concrete_optimizer::restriction::RangeRestriction &restriction,
+             uint64_t v) { restriction.glwe_polynomial_sizes.push_back(v); },
+          "Add an available glwe poly size to the restriction")
       .def(
           "add_available_glwe_dimension",
           [](concrete_optimizer::restriction::RangeRestriction &restriction,
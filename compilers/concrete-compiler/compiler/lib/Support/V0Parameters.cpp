@@ -12,6 +12,7 @@
 #include <chrono>
 #include <cmath>
 #include <iostream>
+#include <limits>
 #include <optional>
 
 #include "llvm/Support/raw_ostream.h"
@@ -40,6 +41,28 @@ concrete_optimizer::Options options_from_config(optimizer::Config config) {
       std::shared_ptr<concrete_optimizer::restriction::RangeRestriction>(),
       /* .keyset_restriction = */
       std::shared_ptr<concrete_optimizer::restriction::KeysetRestriction>(),
+      /* .gpu_pbs_type = */ concrete_optimizer::GpuPbsType::Amortized,
+      /* .gpu_number_of_sm = */ 0,
+      /* .maximum_acceptable_complexity = */
+      std::numeric_limits<double>::infinity(),
+      /* .crt_decomposition_hint = */ {},
+      /* .security_curve_json = */ "",
+      /* .complexity_to_seconds = */ 0.0,
+      /* .allow_fast_keyswitch = */ true,
+      /* .objective = */ concrete_optimizer::Objective::MinComplexity,
+      /* .objective_complexity_weight = */ 1.0,
+      /* .objective_key_size_weight = */ 0.0,
+      /* .objective_batch_size = */ 0,
+      /* .pinned_glwe_dimension = */ {},
+      /* .pinned_polynomial_size = */ {},
+      /* .remote_pbs_latency = */ 0.0,
+      /* .remote_pbs_bandwidth = */ 0.0,
+      /* .quiet = */ false,
+      /* .gpu_batch_size = */ 0,
+      /* .secret_key_variance = */ 0.0,
+      /* .key_sharing_groups = */ {},
+      /* .error_on_infeasible = */ false,
+      /* .search_space = */ {},
   };
   if (config.range_restriction) {
     options.range_restriction = config.range_restriction;
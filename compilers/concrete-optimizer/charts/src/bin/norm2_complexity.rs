@@ -3,7 +3,7 @@ use concrete_optimizer::computing_cost::cpu::CpuComplexity;
 use concrete_optimizer::config;
 use concrete_optimizer::global_parameters::DEFAULT_DOMAINS;
 use concrete_optimizer::optimization::atomic_pattern::{self as optimize_atomic_pattern};
-use concrete_optimizer::optimization::config::{Config, SearchSpace};
+use concrete_optimizer::optimization::config::{Config, Objective, SearchSpace};
 use concrete_optimizer::optimization::decomposition;
 use concrete_optimizer::optimization::wop_atomic_pattern::optimize as optimize_wop_atomic_pattern;
 
@@ -36,6 +36,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         glwe_dimensions,
         internal_lwe_dimensions,
         levelled_only_lwe_dimensions: DEFAULT_DOMAINS.free_lwe,
+        crt_decomposition_hint: vec![],
+        allow_fast_keyswitch: true,
+        key_sharing_groups: vec![],
     };
 
     let precision = 8;
@@ -51,6 +54,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         ciphertext_modulus_log,
         fft_precision,
         complexity_model: &CpuComplexity::default(),
+        objective: Objective::MinComplexity,
     };
 
     let cache = decomposition::cache(
@@ -60,6 +64,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         true,
         ciphertext_modulus_log,
         53,
+        config::KeyDistribution::Binary,
     );
 
     let solutions: Vec<_> = log_norm2s
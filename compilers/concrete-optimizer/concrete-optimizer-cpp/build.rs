@@ -1,6 +1,7 @@
 fn main() {
     cxx_build::bridge("src/concrete-optimizer.rs")
         .std("c++17")
+        .include("src/cpp")
         .compile("concrete-optimizer-bridge");
 
     println!("cargo:rustc-link-lib=static=concrete-optimizer-bridge");
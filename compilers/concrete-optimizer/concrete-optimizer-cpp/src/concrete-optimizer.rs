@@ -1,35 +1,54 @@
 #![allow(clippy::boxed_local)]
 #![allow(clippy::too_many_arguments)]
 
-use core::panic;
-
 use concrete_optimizer::computing_cost::cpu::CpuComplexity;
 use concrete_optimizer::config;
-use concrete_optimizer::config::ProcessingUnit;
+use concrete_optimizer::config::{KeyDistribution, ProcessingUnit};
 use concrete_optimizer::dag::operator::{
     self, FunctionTable, LevelledComplexity, OperatorIndex, Precision, Shape,
 };
-use concrete_optimizer::dag::unparametrized;
-use concrete_optimizer::optimization::config::{Config, SearchSpace};
+use concrete_optimizer::dag::unparametrized::{
+    self, CompositionError, DagEditError, DagMergeError,
+};
+use concrete_optimizer::optimization::config::{
+    Config, Objective, ParameterRestrictions, SearchSpace,
+};
 use concrete_optimizer::optimization::dag::multi_parameters::keys_spec::CircuitSolution;
 use concrete_optimizer::optimization::dag::multi_parameters::optimize::{
-    KeysetRestriction, MacroParameters, NoSearchSpaceRestriction, RangeRestriction,
-    SearchSpaceRestriction,
+    search_space_size, KeysetInfo, KeysetRestriction, LweBootstrapKeyInfo, LweKeyswitchKeyInfo,
+    LweSecretKeyInfo, MacroParameters, NoSearchSpaceRestriction, PartitionFeasibility,
+    PartitionReport, RangeRestriction,
 };
 use concrete_optimizer::optimization::dag::multi_parameters::partition_cut::PartitionCut;
-use concrete_optimizer::optimization::dag::multi_parameters::virtual_circuit::generate_virtual_parameters;
-use concrete_optimizer::optimization::dag::multi_parameters::{keys_spec, PartitionIndex};
+use concrete_optimizer::optimization::dag::multi_parameters::virtual_circuit::{
+    generate_virtual_parameters, generate_virtual_parameters_with_conversions,
+};
+use concrete_optimizer::optimization::dag::multi_parameters::{
+    keys_spec, optimize_generic, PartitionIndex,
+};
+use concrete_optimizer::optimization::dag::solo_key::optimize::{
+    evaluate, operator_budgets, operator_noises, OperatorBudget, OperatorNoise,
+};
 use concrete_optimizer::optimization::dag::solo_key::optimize_generic::{
     Encoding, Solution as DagSolution,
 };
 use concrete_optimizer::optimization::decomposition;
 use concrete_optimizer::optimization::decomposition::cmux::MaxVarianceError;
+use concrete_optimizer::optimization::progress::Progress;
 use concrete_optimizer::parameters::{
     BrDecompositionParameters, GlweParameters, KsDecompositionParameters,
 };
-use concrete_optimizer::utils::cache::persistent::default_cache_dir;
+use concrete_optimizer::security::{SecurityCurve, SecurityCurveGuard, SecurityCurvePoint};
+use concrete_optimizer::utils::cache::lru::LruCache;
+use concrete_optimizer::utils::cache::persistent::{clear_cache_dir, default_cache_dir};
+use concrete_optimizer::utils::log::{self, Level, QuietGuard};
 use concrete_optimizer::utils::viz::Viz;
 use cxx::CxxString;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::OnceCell;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{LazyLock, Mutex};
 
 fn no_solution() -> ffi::Solution {
     ffi::Solution {
@@ -45,28 +64,175 @@ fn no_dag_solution() -> ffi::DagSolution {
     }
 }
 
+// With error_on_infeasible, the p_error 1.0 sentinel solution becomes an error describing `problem`.
+// Applied by every entry point returning a single Dag or v0 solution. The entry points returning
+// several solutions keep the sentinels, and optimize_multi reports through is_feasible.
+fn check_feasible(options: &ffi::Options, p_error: f64, problem: &str) -> Result<(), String> {
+    if options.error_on_infeasible && p_error >= 1.0 {
+        return Err(format!(
+            "No feasible parameters for {problem} with p_error <= {} and complexity <= {}",
+            options.maximum_acceptable_error_probability, options.maximum_acceptable_complexity
+        ));
+    }
+    Ok(())
+}
+
+fn search_space(options: &ffi::Options) -> SearchSpace {
+    let mut search_space = SearchSpace {
+        crt_decomposition_hint: options.crt_decomposition_hint.clone(),
+        allow_fast_keyswitch: options.allow_fast_keyswitch,
+        key_sharing_groups: options
+            .key_sharing_groups
+            .iter()
+            .map(|group| group.partitions.clone())
+            .collect(),
+        ..SearchSpace::default(processing_unit(options))
+    };
+    if !options.pinned_glwe_dimension.is_empty() {
+        let mut glwe_dimensions = options.pinned_glwe_dimension.clone();
+        glwe_dimensions.sort_unstable();
+        glwe_dimensions.dedup();
+        search_space.glwe_dimensions = glwe_dimensions;
+    }
+    if !options.pinned_polynomial_size.is_empty() {
+        // a size which is not a power of two can never be used
+        let mut log_polynomial_sizes: Vec<u64> = options
+            .pinned_polynomial_size
+            .iter()
+            .filter(|size| size.is_power_of_two())
+            .map(|size| u64::from(size.ilog2()))
+            .collect();
+        log_polynomial_sizes.sort_unstable();
+        log_polynomial_sizes.dedup();
+        search_space.glwe_log_polynomial_sizes = log_polynomial_sizes;
+    }
+    search_space.restrict(&parameter_restrictions(&options.search_space));
+    if let Some(curve) = security_curve(options) {
+        // dimensions outside of a custom curve are insecure
+        search_space
+            .internal_lwe_dimensions
+            .retain(|&lwe_dimension| curve.covers(lwe_dimension));
+    }
+    search_space
+}
+
+// 0 means unbounded, for the minimums as for the maximums
+fn parameter_restrictions(bounds: &ffi::SearchSpaceOptions) -> ParameterRestrictions {
+    let max = |bound: u64| if bound == 0 { u64::MAX } else { bound };
+    ParameterRestrictions {
+        log2_polynomial_size_min: bounds.log2_polynomial_size_min,
+        log2_polynomial_size_max: max(bounds.log2_polynomial_size_max),
+        glwe_dimension_min: bounds.glwe_dimension_min,
+        glwe_dimension_max: max(bounds.glwe_dimension_max),
+        internal_lwe_dimension_min: bounds.internal_lwe_dimension_min,
+        internal_lwe_dimension_max: max(bounds.internal_lwe_dimension_max),
+    }
+}
+
+fn security_curve_from_json(input: &str) -> Result<SecurityCurve, String> {
+    let points: Vec<SecurityCurvePoint> =
+        serde_json::from_str(input).map_err(|err| err.to_string())?;
+    SecurityCurve::new(points).map_err(|err| err.to_string())
+}
+
+fn check_security_curve_json(input: &str) -> Result<(), String> {
+    security_curve_from_json(input).map(|_| ())
+}
+
+fn validate_options(options: &ffi::Options) -> Result<(), String> {
+    if !options.security_curve_json.is_empty() {
+        check_security_curve_json(&options.security_curve_json)?;
+    }
+    config(options).validate().map_err(|err| err.to_string())
+}
+
+// An invalid curve is reported by validate_options, the optimization itself finds no secure
+// dimension.
+fn security_curve(options: &ffi::Options) -> Option<SecurityCurve> {
+    if options.security_curve_json.is_empty() {
+        return None;
+    }
+    Some(
+        security_curve_from_json(&options.security_curve_json).unwrap_or_else(|err| {
+            log::emit(
+                Level::Warning,
+                &format!("optimizer: Invalid security curve, no dimension is secure: {err}"),
+            );
+            SecurityCurve::default()
+        }),
+    )
+}
+
+// Scopes the quiet flag and the custom security curve of the options to the current call.
+struct OptionsGuard {
+    _quiet: QuietGuard,
+    _curve: Option<SecurityCurveGuard>,
+}
+
+// The quiet flag and the custom security curve, if any, are used by the current thread until the
+// guard is dropped.
+fn install_options(options: &ffi::Options) -> OptionsGuard {
+    // Installed first, parsing the curve may log
+    let quiet = log::set_quiet(options.quiet);
+    OptionsGuard {
+        _quiet: quiet,
+        _curve: security_curve(options).map(SecurityCurve::install),
+    }
+}
+
 fn caches_from(options: &ffi::Options) -> decomposition::PersistDecompCaches {
-    if !options.cache_on_disk {
-        println!("optimizer: Using stateless cache.");
+    // The caches on disk only depend on the security level, not on a custom curve
+    let cache_on_disk = options.cache_on_disk && options.security_curve_json.is_empty();
+    if !cache_on_disk {
+        log::emit(Level::Info, "optimizer: Using stateless cache.");
         let cache_dir = default_cache_dir();
-        println!("optimizer: To clear the cache, remove directory {cache_dir}");
+        log::emit(
+            Level::Info,
+            &format!("optimizer: To clear the cache, remove directory {cache_dir}"),
+        );
     }
     let processing_unit = processing_unit(options);
     decomposition::cache(
         options.security_level,
         processing_unit,
-        Some(ProcessingUnit::Cpu.complexity_model()),
-        options.cache_on_disk,
+        Some(processing_unit.complexity_model()),
+        cache_on_disk,
         options.ciphertext_modulus_log,
         options.fft_precision,
+        key_distribution(options),
     )
 }
 
+fn key_distribution(options: &ffi::Options) -> KeyDistribution {
+    // 0 means binary keys
+    if options.secret_key_variance > 0.0 {
+        KeyDistribution::Centered {
+            variance: options.secret_key_variance,
+        }
+    } else {
+        KeyDistribution::Binary
+    }
+}
+
 #[derive(Clone)]
 pub struct ExternalPartition(
     concrete_optimizer::optimization::dag::multi_parameters::partition_cut::ExternalPartition,
 );
 
+impl ExternalPartition {
+    fn name(&self) -> String {
+        self.0.name.clone()
+    }
+
+    fn max_variance(&self) -> f64 {
+        self.0.max_variance
+    }
+
+    fn variance(&self) -> f64 {
+        self.0.variance
+    }
+}
+
 pub fn get_external_partition(
     name: String,
     log2_polynomial_size: u64,
@@ -74,8 +240,8 @@ pub fn get_external_partition(
     internal_dim: u64,
     max_variance: f64,
     variance: f64,
-) -> Box<ExternalPartition> {
-    Box::new(ExternalPartition(
+) -> Result<Box<ExternalPartition>, String> {
+    let partition =
         concrete_optimizer::optimization::dag::multi_parameters::partition_cut::ExternalPartition {
             name,
             macro_params: MacroParameters {
@@ -87,8 +253,9 @@ pub fn get_external_partition(
             },
             max_variance,
             variance,
-        },
-    ))
+        };
+    partition.check().map_err(|err| err.to_string())?;
+    Ok(Box::new(ExternalPartition(partition)))
 }
 
 pub fn get_noise_br(
@@ -98,40 +265,118 @@ pub fn get_noise_br(
     lwe_dim: u64,
     pbs_level: u64,
     pbs_log2_base: u64,
-) -> f64 {
+) -> Result<f64, MaxVarianceError> {
+    let _options = install_options(options);
     let cache = caches_from(options).caches();
-    match decomposition::cmux::get_noise_br(
+    decomposition::cmux::get_noise_br(
         cache,
         log2_polynomial_size,
         glwe_dimension,
         lwe_dim,
         pbs_level,
         Some(pbs_log2_base),
-    ) {
-        Ok(max_variance) => max_variance,
-        Err(err) => match err {
-            MaxVarianceError::PbsBaseLogNotFound => panic!("pbs base log didn't match"),
-            MaxVarianceError::PbsLevelNotFound => panic!("pbs level not found"),
-        },
-    }
+    )
 }
 
-fn optimize_bootstrap(precision: u64, noise_factor: f64, options: &ffi::Options) -> ffi::Solution {
-    // Support composable since there is no dag
-    let processing_unit = processing_unit(options);
+pub fn get_noise_ks(
+    options: &ffi::Options,
+    input_lwe_dim: u64,
+    output_lwe_dim: u64,
+    ks_level: u64,
+    ks_log2_base: u64,
+) -> Result<f64, MaxVarianceError> {
+    let _options = install_options(options);
+    let cache = caches_from(options).caches();
+    decomposition::keyswitch::get_noise_ks(
+        cache,
+        input_lwe_dim,
+        output_lwe_dim,
+        ks_level,
+        Some(ks_log2_base),
+    )
+}
 
-    let config = Config {
-        security_level: options.security_level,
-        maximum_acceptable_error_probability: options.maximum_acceptable_error_probability,
-        key_sharing: options.key_sharing,
-        ciphertext_modulus_log: options.ciphertext_modulus_log,
-        fft_precision: options.fft_precision,
-        complexity_model: &CpuComplexity::default(),
+// Noise of the modulus switching of a lwe_dim ciphertext before a blind rotate
+pub fn get_noise_modulus_switch(
+    options: &ffi::Options,
+    lwe_dim: u64,
+    log2_polynomial_size: u64,
+) -> f64 {
+    key_distribution(options).modulus_switching_noise(
+        lwe_dim,
+        log2_polynomial_size,
+        options.ciphertext_modulus_log,
+    )
+}
+
+// The blind rotate decompositions kept by the optimizer for these glwe parameters, by increasing
+// complexity and decreasing variance
+pub fn enumerate_pbs_decompositions(
+    options: &ffi::Options,
+    log2_polynomial_size: u64,
+    glwe_dimension: u64,
+    lwe_dim: u64,
+) -> Vec<ffi::DecompositionCandidate> {
+    let _options = install_options(options);
+    let mut cache = caches_from(options).caches();
+    cache
+        .cmux
+        .pareto_quantities(GlweParameters {
+            log2_polynomial_size,
+            glwe_dimension,
+        })
+        .iter()
+        .map(|quantity| ffi::DecompositionCandidate {
+            level: quantity.decomp.level,
+            base_log: quantity.decomp.log2_base,
+            max_variance: quantity.noise_br(lwe_dim),
+            complexity: quantity.complexity_br(lwe_dim),
+        })
+        .collect()
+}
+
+fn optimize_bootstrap(
+    precision: u64,
+    noise_factor: f64,
+    options: &ffi::Options,
+) -> Result<ffi::Solution, String> {
+    optimize_atomic_pattern(precision, noise_factor, 1, options)
+}
+
+// sum_size is the number of ciphertexts summed by the dot preceding the keyswitch and PBS
+fn optimize_atomic_pattern(
+    precision: u64,
+    noise_factor: f64,
+    sum_size: u64,
+    options: &ffi::Options,
+) -> Result<ffi::Solution, String> {
+    let solution =
+        optimize_atomic_pattern_or_no_solution(precision, noise_factor, sum_size, options);
+    let problem = if sum_size == 1 {
+        format!("a {precision}-bit bootstrap with noise factor {noise_factor}")
+    } else {
+        format!(
+            "a {precision}-bit atomic pattern summing {sum_size} ciphertexts with noise factor {noise_factor}"
+        )
     };
+    check_feasible(options, solution.p_error, &problem)?;
+    Ok(solution)
+}
 
-    let sum_size = 1;
+fn optimize_atomic_pattern_or_no_solution(
+    precision: u64,
+    noise_factor: f64,
+    sum_size: u64,
+    options: &ffi::Options,
+) -> ffi::Solution {
+    let _options = install_options(options);
+    // Support composable since there is no dag
+    let config = config(options);
+    if config.validate().is_err() {
+        return no_solution();
+    }
 
-    let search_space = SearchSpace::default(processing_unit);
+    let search_space = search_space(options);
 
     let result = concrete_optimizer::optimization::atomic_pattern::optimize_one(
         sum_size,
@@ -143,6 +388,7 @@ fn optimize_bootstrap(precision: u64, noise_factor: f64, options: &ffi::Options)
     );
     result
         .best_solution
+        .filter(|solution| solution.complexity <= options.maximum_acceptable_complexity)
         .map_or_else(no_solution, |solution| solution.into())
 }
 
@@ -163,6 +409,7 @@ impl From<&ffi::Solution> for ffi::DagSolution {
             br_decomposition_base_log: sol.br_decomposition_base_log,
             complexity: sol.complexity,
             noise_max: sol.noise_max,
+            noise_margin: sol.noise_margin,
             p_error: sol.p_error,
             global_p_error: f64::NAN,
             use_wop_pbs: false,
@@ -171,6 +418,7 @@ impl From<&ffi::Solution> for ffi::DagSolution {
             pp_decomposition_level_count: 0,
             pp_decomposition_base_log: 0,
             crt_decomposition: vec![],
+            chosen_encoding: ffi::Encoding::Native,
         }
     }
 }
@@ -212,6 +460,7 @@ impl From<&ffi::CircuitSolution> for ffi::DagSolution {
             br_decomposition_base_log: bootstrap_key.br_decomposition_parameter.log2_base,
             complexity: sol.complexity,
             noise_max: f64::NAN,
+            noise_margin: f64::NAN,
             p_error: sol.p_error,
             global_p_error: sol.global_p_error,
             use_wop_pbs,
@@ -220,6 +469,11 @@ impl From<&ffi::CircuitSolution> for ffi::DagSolution {
             pp_decomposition_level_count,
             pp_decomposition_base_log,
             crt_decomposition: sol.crt_decomposition.clone(),
+            chosen_encoding: if use_wop_pbs {
+                ffi::Encoding::Crt
+            } else {
+                ffi::Encoding::Native
+            },
         }
     }
 }
@@ -237,6 +491,7 @@ impl From<concrete_optimizer::optimization::atomic_pattern::Solution> for ffi::S
             br_decomposition_base_log: a.br_decomposition_base_log,
             complexity: a.complexity,
             noise_max: a.noise_max,
+            noise_margin: a.noise_margin,
             p_error: a.p_error,
         }
     }
@@ -256,6 +511,7 @@ impl From<DagSolution> for ffi::DagSolution {
                 br_decomposition_base_log: sol.br_decomposition_base_log,
                 complexity: sol.complexity,
                 noise_max: sol.noise_max,
+                noise_margin: sol.noise_margin,
                 p_error: sol.p_error,
                 global_p_error: sol.global_p_error,
                 use_wop_pbs: false,
@@ -264,6 +520,7 @@ impl From<DagSolution> for ffi::DagSolution {
                 pp_decomposition_level_count: 0,
                 pp_decomposition_base_log: 0,
                 crt_decomposition: vec![],
+                chosen_encoding: ffi::Encoding::Native,
             },
             DagSolution::WopSolution(sol) => Self {
                 input_lwe_dimension: sol.input_lwe_dimension,
@@ -276,6 +533,7 @@ impl From<DagSolution> for ffi::DagSolution {
                 br_decomposition_base_log: sol.br_decomposition_base_log,
                 complexity: sol.complexity,
                 noise_max: sol.noise_max,
+                noise_margin: sol.noise_margin,
                 p_error: sol.p_error,
                 global_p_error: sol.global_p_error,
                 use_wop_pbs: true,
@@ -284,92 +542,101 @@ impl From<DagSolution> for ffi::DagSolution {
                 pp_decomposition_level_count: sol.pp_decomposition_level_count,
                 pp_decomposition_base_log: sol.pp_decomposition_base_log,
                 crt_decomposition: sol.crt_decomposition,
+                chosen_encoding: ffi::Encoding::Crt,
             },
         }
     }
 }
 
-fn convert_to_circuit_solution(sol: &ffi::DagSolution, dag: &Dag) -> ffi::CircuitSolution {
-    let big_key = ffi::SecretLweKey {
-        identifier: 0,
-        polynomial_size: sol.glwe_polynomial_size,
-        glwe_dimension: sol.glwe_dimension,
-        description: "big representation".into(),
-    };
-    let small_key = ffi::SecretLweKey {
-        identifier: 1,
-        polynomial_size: sol.internal_ks_output_lwe_dimension,
-        glwe_dimension: 1,
-        description: "small representation".into(),
-    };
-    let keyswitch_key = ffi::KeySwitchKey {
-        identifier: 0,
-        input_key: big_key.clone(),
-        output_key: small_key.clone(),
-        ks_decomposition_parameter: ffi::KsDecompositionParameters {
-            level: sol.ks_decomposition_level_count,
-            log2_base: sol.ks_decomposition_base_log,
-        },
-        description: "tlu keyswitch".into(),
-    };
-    let bootstrap_key = ffi::BootstrapKey {
-        identifier: 0,
-        input_key: small_key.clone(),
-        output_key: big_key.clone(),
-        br_decomposition_parameter: ffi::BrDecompositionParameters {
-            level: sol.br_decomposition_level_count,
-            log2_base: sol.br_decomposition_base_log,
-        },
-        description: "tlu bootstrap".into(),
-    };
-    let circuit_bootstrap_keys = if sol.use_wop_pbs {
-        vec![ffi::CircuitBoostrapKey {
+impl ffi::DagSolution {
+    fn required_keys(&self) -> ffi::CircuitKeys {
+        let big_key = ffi::SecretLweKey {
             identifier: 0,
-            representation_key: big_key.clone(),
-            br_decomposition_parameter: ffi::BrDecompositionParameters {
-                level: sol.cb_decomposition_level_count,
-                log2_base: sol.cb_decomposition_base_log,
+            polynomial_size: self.glwe_polynomial_size,
+            glwe_dimension: self.glwe_dimension,
+            description: "big representation".into(),
+        };
+        let small_key = ffi::SecretLweKey {
+            identifier: 1,
+            polynomial_size: self.internal_ks_output_lwe_dimension,
+            glwe_dimension: 1,
+            description: "small representation".into(),
+        };
+        let keyswitch_key = ffi::KeySwitchKey {
+            identifier: 0,
+            input_key: big_key.clone(),
+            output_key: small_key.clone(),
+            ks_decomposition_parameter: ffi::KsDecompositionParameters {
+                level: self.ks_decomposition_level_count,
+                log2_base: self.ks_decomposition_base_log,
             },
-            description: "circuit bootstrap for woppbs".into(),
-        }]
-    } else {
-        vec![]
-    };
-    let private_functional_packing_keys = if sol.use_wop_pbs {
-        vec![ffi::PrivateFunctionalPackingBoostrapKey {
+            description: "tlu keyswitch".into(),
+        };
+        let bootstrap_key = ffi::BootstrapKey {
             identifier: 0,
-            representation_key: big_key.clone(),
+            input_key: small_key.clone(),
+            output_key: big_key.clone(),
             br_decomposition_parameter: ffi::BrDecompositionParameters {
-                level: sol.pp_decomposition_level_count,
-                log2_base: sol.pp_decomposition_base_log,
+                level: self.br_decomposition_level_count,
+                log2_base: self.br_decomposition_base_log,
             },
-            description: "private functional packing for woppbs".into(),
-        }]
-    } else {
-        vec![]
-    };
+            description: "tlu bootstrap".into(),
+        };
+        let circuit_bootstrap_keys = if self.use_wop_pbs {
+            vec![ffi::CircuitBoostrapKey {
+                identifier: 0,
+                representation_key: big_key.clone(),
+                br_decomposition_parameter: ffi::BrDecompositionParameters {
+                    level: self.cb_decomposition_level_count,
+                    log2_base: self.cb_decomposition_base_log,
+                },
+                description: "circuit bootstrap for woppbs".into(),
+            }]
+        } else {
+            vec![]
+        };
+        let private_functional_packing_keys = if self.use_wop_pbs {
+            vec![ffi::PrivateFunctionalPackingBoostrapKey {
+                identifier: 0,
+                representation_key: big_key.clone(),
+                br_decomposition_parameter: ffi::BrDecompositionParameters {
+                    level: self.pp_decomposition_level_count,
+                    log2_base: self.pp_decomposition_base_log,
+                },
+                description: "private functional packing for woppbs".into(),
+            }]
+        } else {
+            vec![]
+        };
+        ffi::CircuitKeys {
+            secret_keys: [big_key, small_key].into(),
+            keyswitch_keys: [keyswitch_key].into(),
+            bootstrap_keys: [bootstrap_key].into(),
+            conversion_keyswitch_keys: [].into(),
+            circuit_bootstrap_keys,
+            private_functional_packing_keys,
+        }
+    }
+}
+
+fn convert_to_circuit_solution(sol: &ffi::DagSolution, dag: &Dag) -> ffi::CircuitSolution {
+    let circuit_keys = sol.required_keys();
     let instruction_keys = ffi::InstructionKeys {
-        input_key: big_key.identifier,
-        tlu_keyswitch_key: keyswitch_key.identifier,
-        tlu_bootstrap_key: bootstrap_key.identifier,
-        tlu_circuit_bootstrap_key: circuit_bootstrap_keys
+        input_key: circuit_keys.secret_keys[0].identifier,
+        tlu_keyswitch_key: circuit_keys.keyswitch_keys[0].identifier,
+        tlu_bootstrap_key: circuit_keys.bootstrap_keys[0].identifier,
+        tlu_circuit_bootstrap_key: circuit_keys
+            .circuit_bootstrap_keys
             .last()
             .map_or(keys_spec::NO_KEY_ID, |v| v.identifier),
-        tlu_private_functional_packing_key: private_functional_packing_keys
+        tlu_private_functional_packing_key: circuit_keys
+            .private_functional_packing_keys
             .last()
             .map_or(keys_spec::NO_KEY_ID, |v| v.identifier),
-        output_key: big_key.identifier,
+        output_key: circuit_keys.secret_keys[0].identifier,
         extra_conversion_keys: vec![],
     };
     let instructions_keys = vec![instruction_keys; dag.0.len()];
-    let circuit_keys = ffi::CircuitKeys {
-        secret_keys: [big_key, small_key].into(),
-        keyswitch_keys: [keyswitch_key].into(),
-        bootstrap_keys: [bootstrap_key].into(),
-        conversion_keyswitch_keys: [].into(),
-        circuit_bootstrap_keys,
-        private_functional_packing_keys,
-    };
     let is_feasible = sol.p_error < 1.0;
     let error_msg = if is_feasible {
         ""
@@ -377,6 +644,7 @@ fn convert_to_circuit_solution(sol: &ffi::DagSolution, dag: &Dag) -> ffi::Circui
         "No crypto-parameters for the given constraints"
     }
     .into();
+    let unshared_secret_key_count = circuit_keys.secret_keys.len() as u64;
     ffi::CircuitSolution {
         circuit_keys,
         instructions_keys,
@@ -386,6 +654,8 @@ fn convert_to_circuit_solution(sol: &ffi::DagSolution, dag: &Dag) -> ffi::Circui
         global_p_error: sol.global_p_error,
         is_feasible,
         error_msg,
+        used_external_partitions: vec![],
+        unshared_secret_key_count,
     }
 }
 
@@ -400,6 +670,37 @@ impl From<CircuitSolution> for ffi::CircuitSolution {
             global_p_error: v.global_p_error,
             is_feasible: v.is_feasible,
             error_msg: v.error_msg,
+            used_external_partitions: v.used_external_partitions,
+            unshared_secret_key_count: v.unshared_secret_key_count,
+        }
+    }
+}
+
+impl From<PartitionFeasibility> for ffi::PartitionFeasibility {
+    fn from(v: PartitionFeasibility) -> Self {
+        match v {
+            PartitionFeasibility::Unknown => Self::Unknown,
+            PartitionFeasibility::Feasible => Self::Feasible,
+            PartitionFeasibility::Unfeasible => Self::Unfeasible,
+        }
+    }
+}
+
+impl From<PartitionReport> for ffi::PartitionReport {
+    fn from(v: PartitionReport) -> Self {
+        let (min_precision, max_precision) = v.precisions.unwrap_or_default();
+        let macro_params = v.macro_params;
+        Self {
+            partition: v.partition.0 as u64,
+            has_lut: v.precisions.is_some(),
+            min_precision,
+            max_precision,
+            has_macro_parameters: macro_params.is_some(),
+            glwe_dimension: macro_params.map_or(0, |p| p.glwe_params.glwe_dimension),
+            glwe_polynomial_size: macro_params.map_or(0, |p| p.glwe_params.polynomial_size()),
+            internal_lwe_dimension: macro_params.map_or(0, |p| p.internal_dim),
+            feasibility: v.feasibility.into(),
+            complexity: v.complexity.unwrap_or(0.0),
         }
     }
 }
@@ -413,6 +714,116 @@ impl ffi::CircuitSolution {
     fn dump(&self) -> String {
         format!("{self:#?}")
     }
+
+    // One line per differing field, keys are compared by position
+    fn diff(&self, other: &Self) -> String {
+        let mut lines = vec![];
+        let mut diff_field = |name: &str, a: String, b: String| {
+            if a != b {
+                lines.push(format!("{name}: {a} -> {b}"));
+            }
+        };
+        diff_field(
+            "is_feasible",
+            self.is_feasible.to_string(),
+            other.is_feasible.to_string(),
+        );
+        diff_field("error_msg", self.error_msg.clone(), other.error_msg.clone());
+        diff_field(
+            "complexity",
+            self.complexity.to_string(),
+            other.complexity.to_string(),
+        );
+        diff_field(
+            "p_error",
+            self.p_error.to_string(),
+            other.p_error.to_string(),
+        );
+        diff_field(
+            "global_p_error",
+            self.global_p_error.to_string(),
+            other.global_p_error.to_string(),
+        );
+        diff_field(
+            "crt_decomposition",
+            format!("{:?}", self.crt_decomposition),
+            format!("{:?}", other.crt_decomposition),
+        );
+        diff_field(
+            "used_external_partitions",
+            format!("{:?}", self.used_external_partitions),
+            format!("{:?}", other.used_external_partitions),
+        );
+        diff_field(
+            "unshared_secret_key_count",
+            self.unshared_secret_key_count.to_string(),
+            other.unshared_secret_key_count.to_string(),
+        );
+        let keys = &self.circuit_keys;
+        let other_keys = &other.circuit_keys;
+        diff_list(
+            &mut lines,
+            "secret_keys",
+            &keys.secret_keys,
+            &other_keys.secret_keys,
+        );
+        diff_list(
+            &mut lines,
+            "keyswitch_keys",
+            &keys.keyswitch_keys,
+            &other_keys.keyswitch_keys,
+        );
+        diff_list(
+            &mut lines,
+            "bootstrap_keys",
+            &keys.bootstrap_keys,
+            &other_keys.bootstrap_keys,
+        );
+        diff_list(
+            &mut lines,
+            "conversion_keyswitch_keys",
+            &keys.conversion_keyswitch_keys,
+            &other_keys.conversion_keyswitch_keys,
+        );
+        diff_list(
+            &mut lines,
+            "circuit_bootstrap_keys",
+            &keys.circuit_bootstrap_keys,
+            &other_keys.circuit_bootstrap_keys,
+        );
+        diff_list(
+            &mut lines,
+            "private_functional_packing_keys",
+            &keys.private_functional_packing_keys,
+            &other_keys.private_functional_packing_keys,
+        );
+        diff_list(
+            &mut lines,
+            "instructions_keys",
+            &self.instructions_keys,
+            &other.instructions_keys,
+        );
+        lines.join("\n")
+    }
+}
+
+fn diff_list<T: std::fmt::Debug>(lines: &mut Vec<String>, name: &str, a: &[T], b: &[T]) {
+    if a.len() != b.len() {
+        lines.push(format!("{name}: {} -> {} entries", a.len(), b.len()));
+    }
+    for i in 0..a.len().max(b.len()) {
+        match (a.get(i), b.get(i)) {
+            (Some(a), Some(b)) => {
+                let (a, b) = (format!("{a:?}"), format!("{b:?}"));
+                if a != b {
+                    lines.push(format!("{name}[{i}]: {a} -> {b}"));
+                }
+            }
+            (Some(a), None) => lines.push(format!("{name}[{i}]: {a:?} -> none")),
+            (None, Some(b)) => lines.push(format!("{name}[{i}]: none -> {b:?}")),
+            (None, None) => unreachable!(),
+        }
+    }
 }
 
 impl From<KsDecompositionParameters> for ffi::KsDecompositionParameters {
@@ -541,10 +952,98 @@ fn NO_KEY_ID() -> u64 {
     keys_spec::NO_KEY_ID
 }
 
-pub struct Dag(unparametrized::Dag);
+// The partition assignment is only used by MultiParamStrategy::Explicit
+pub struct Dag(unparametrized::Dag, Vec<PartitionIndex>);
 
 fn empty() -> Box<Dag> {
-    Box::new(Dag(unparametrized::Dag::new()))
+    Box::new(Dag(unparametrized::Dag::new(), vec![]))
+}
+
+// Json form of a Dag, the partition assignment is empty if never set
+#[derive(Serialize, Deserialize)]
+struct DagJson {
+    dag: unparametrized::Dag,
+    #[serde(default)]
+    partition_assignment: Vec<u64>,
+}
+
+fn dag_from_json(input: &str) -> Result<Box<Dag>, String> {
+    let DagJson {
+        dag,
+        partition_assignment,
+    } = serde_json::from_str(input).map_err(|err| err.to_string())?;
+    let mut dag = Box::new(Dag(dag, vec![]));
+    if !partition_assignment.is_empty() {
+        dag.set_partition_assignment(&partition_assignment)?;
+    }
+    Ok(dag)
+}
+
+// Number of solutions kept by SOLUTION_CACHE
+const SOLUTION_CACHE_CAPACITY: usize = 256;
+
+// Most recent solutions of Dag::optimize, keyed on Dag::solution_cache_key
+static SOLUTION_CACHE: LazyLock<Mutex<LruCache<Vec<u8>, ffi::DagSolution>>> =
+    LazyLock::new(|| Mutex::new(LruCache::new(SOLUTION_CACHE_CAPACITY)));
+
+// Records the bytes fed to it, so that a cache key is compared in full rather than by its hash
+#[derive(Default)]
+struct KeyBytes(Vec<u8>);
+
+impl Hasher for KeyBytes {
+    fn finish(&self) -> u64 {
+        unreachable!("KeyBytes only records the key bytes")
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.extend_from_slice(bytes);
+    }
+}
+
+fn clear_solution_cache() {
+    SOLUTION_CACHE.lock().unwrap().clear();
+}
+
+fn optimize_many(dags: &[Box<Dag>], options: &ffi::Options) -> Vec<ffi::DagSolution> {
+    let _options = install_options(options);
+    // The decomposition caches are shared by all dags, and only loaded on a solution cache miss.
+    let caches = OnceCell::new();
+    dags.iter()
+        .map(|dag| dag.optimize_with_caches(options, &caches))
+        .collect()
+}
+
+fn evaluate_solution(
+    dag: &Dag,
+    solution: &ffi::DagSolution,
+    options: &ffi::Options,
+) -> ffi::DagSolution {
+    // Only native solutions of a single circuit can be evaluated
+    if solution.use_wop_pbs || dag.0.is_composed() {
+        return no_dag_solution();
+    }
+    let _options = install_options(options);
+    let config = config(options);
+    if config.validate().is_err() {
+        return no_dag_solution();
+    }
+    let solution = concrete_optimizer::optimization::atomic_pattern::Solution {
+        input_lwe_dimension: solution.input_lwe_dimension,
+        internal_ks_output_lwe_dimension: solution.internal_ks_output_lwe_dimension,
+        ks_decomposition_level_count: solution.ks_decomposition_level_count,
+        ks_decomposition_base_log: solution.ks_decomposition_base_log,
+        glwe_polynomial_size: solution.glwe_polynomial_size,
+        glwe_dimension: solution.glwe_dimension,
+        br_decomposition_level_count: solution.br_decomposition_level_count,
+        br_decomposition_base_log: solution.br_decomposition_base_log,
+        complexity: f64::NAN,
+        noise_max: f64::NAN,
+        noise_margin: f64::NAN,
+        p_error: f64::NAN,
+        global_p_error: f64::NAN,
+    };
+    evaluate(&dag.0, config, &solution, &caches_from(options))
+        .map_or_else(no_dag_solution, |sol| DagSolution::WpSolution(sol).into())
 }
 
 impl Dag {
@@ -556,6 +1055,18 @@ impl Dag {
         self.0.viz_string()
     }
 
+    fn to_json(&self) -> String {
+        let dag_json = DagJson {
+            dag: self.0.clone(),
+            partition_assignment: self.1.iter().map(|partition| partition.0 as u64).collect(),
+        };
+        serde_json::to_string(&dag_json).unwrap()
+    }
+
+    fn to_dot(&self) -> String {
+        self.0.to_dot()
+    }
+
     fn get_input_indices(&self) -> Vec<ffi::OperatorIndex> {
         self.0
             .get_input_operators_iter()
@@ -570,40 +1081,269 @@ impl Dag {
             .collect()
     }
 
-    fn optimize(&self, options: &ffi::Options) -> ffi::DagSolution {
-        let processing_unit = processing_unit(options);
-        let config = Config {
-            security_level: options.security_level,
-            maximum_acceptable_error_probability: options.maximum_acceptable_error_probability,
-            key_sharing: options.key_sharing,
-            ciphertext_modulus_log: options.ciphertext_modulus_log,
-            fft_precision: options.fft_precision,
-            complexity_model: &CpuComplexity::default(),
+    fn optimize(&self, options: &ffi::Options) -> Result<ffi::DagSolution, String> {
+        let solution = self.optimize_or_no_solution(options);
+        check_feasible(options, solution.p_error, self.problem())?;
+        Ok(solution)
+    }
+
+    // Describes the optimized dag in check_feasible errors
+    fn problem(&self) -> &'static str {
+        if self.0.is_composed() {
+            "a composed dag, only supported by optimize_multi"
+        } else {
+            "the dag"
+        }
+    }
+
+    fn optimize_or_no_solution(&self, options: &ffi::Options) -> ffi::DagSolution {
+        let _options = install_options(options);
+        self.optimize_with_caches(options, &OnceCell::new())
+    }
+
+    // The decomposition caches are only loaded if the solution is not cached yet
+    fn optimize_with_caches(
+        &self,
+        options: &ffi::Options,
+        caches: &OnceCell<decomposition::PersistDecompCaches>,
+    ) -> ffi::DagSolution {
+        let key = self.solution_cache_key(options);
+        if let Some(solution) = SOLUTION_CACHE.lock().unwrap().get(&key) {
+            return solution.clone();
+        }
+        let caches = caches.get_or_init(|| caches_from(options));
+        let solution = self
+            .optimize_solution(options, caches, &mut Progress::none())
+            .map(ffi::DagSolution::from)
+            .unwrap_or_else(no_dag_solution);
+        SOLUTION_CACHE.lock().unwrap().insert(key, solution.clone());
+        solution
+    }
+
+    // Structure of the dag and options used by optimize_solution, compared in full on a lookup
+    fn solution_cache_key(&self, options: &ffi::Options) -> Vec<u8> {
+        let mut hasher = KeyBytes::default();
+        self.0.hash_structure(&mut hasher);
+        options.security_level.hash(&mut hasher);
+        options
+            .maximum_acceptable_error_probability
+            .to_bits()
+            .hash(&mut hasher);
+        options.key_sharing.hash(&mut hasher);
+        options.default_log_norm2_woppbs.to_bits().hash(&mut hasher);
+        options.use_gpu_constraints.hash(&mut hasher);
+        options.encoding.repr.hash(&mut hasher);
+        options.ciphertext_modulus_log.hash(&mut hasher);
+        options.fft_precision.hash(&mut hasher);
+        options.gpu_pbs_type.repr.hash(&mut hasher);
+        options.gpu_number_of_sm.hash(&mut hasher);
+        options.gpu_batch_size.hash(&mut hasher);
+        let bounds = &options.search_space;
+        bounds.internal_lwe_dimension_min.hash(&mut hasher);
+        bounds.internal_lwe_dimension_max.hash(&mut hasher);
+        bounds.glwe_dimension_min.hash(&mut hasher);
+        bounds.glwe_dimension_max.hash(&mut hasher);
+        bounds.log2_polynomial_size_min.hash(&mut hasher);
+        bounds.log2_polynomial_size_max.hash(&mut hasher);
+        bounds.decomposition_level_min.hash(&mut hasher);
+        bounds.decomposition_level_max.hash(&mut hasher);
+        options
+            .maximum_acceptable_complexity
+            .to_bits()
+            .hash(&mut hasher);
+        options.crt_decomposition_hint.hash(&mut hasher);
+        options.security_curve_json.hash(&mut hasher);
+        options.objective.repr.hash(&mut hasher);
+        options.objective_batch_size.hash(&mut hasher);
+        options
+            .objective_complexity_weight
+            .to_bits()
+            .hash(&mut hasher);
+        options
+            .objective_key_size_weight
+            .to_bits()
+            .hash(&mut hasher);
+        options.pinned_glwe_dimension.hash(&mut hasher);
+        options.pinned_polynomial_size.hash(&mut hasher);
+        options.remote_pbs_latency.to_bits().hash(&mut hasher);
+        options.remote_pbs_bandwidth.to_bits().hash(&mut hasher);
+        options.secret_key_variance.to_bits().hash(&mut hasher);
+        for group in &options.key_sharing_groups {
+            group.partitions.hash(&mut hasher);
+        }
+        hasher.0
+    }
+
+    fn optimize_with_noise_map(
+        &self,
+        options: &ffi::Options,
+    ) -> Result<ffi::DagSolutionWithNoiseMap, String> {
+        let _options = install_options(options);
+        let config = config(options);
+        let caches = caches_from(options);
+        let Some(solution) = self.optimize_solution(options, &caches, &mut Progress::none()) else {
+            check_feasible(options, 1.0, self.problem())?;
+            return Ok(ffi::DagSolutionWithNoiseMap {
+                solution: no_dag_solution(),
+                operators: vec![],
+            });
+        };
+        // The noise map is only defined for the native encoding
+        let operators = match &solution {
+            DagSolution::WpSolution(sol) => operator_noises(&self.0, config, sol, &caches)
+                .ok_or("The solution decompositions are missing from the caches")?,
+            DagSolution::WopSolution(_) => vec![],
+        };
+        Ok(ffi::DagSolutionWithNoiseMap {
+            solution: solution.into(),
+            operators: vec_into(operators),
+        })
+    }
+
+    fn optimize_with_budget_report(
+        &self,
+        options: &ffi::Options,
+    ) -> Result<ffi::DagSolutionWithBudgetReport, String> {
+        let _options = install_options(options);
+        let config = config(options);
+        let caches = caches_from(options);
+        let Some(solution) = self.optimize_solution(options, &caches, &mut Progress::none()) else {
+            check_feasible(options, 1.0, self.problem())?;
+            return Ok(ffi::DagSolutionWithBudgetReport {
+                solution: no_dag_solution(),
+                operators: vec![],
+            });
         };
+        // The budgets are only defined for the native encoding
+        let operators = match &solution {
+            DagSolution::WpSolution(sol) => operator_budgets(&self.0, config, sol, &caches)
+                .ok_or("The solution decompositions are missing from the caches")?,
+            DagSolution::WopSolution(_) => vec![],
+        };
+        Ok(ffi::DagSolutionWithBudgetReport {
+            solution: solution.into(),
+            operators: vec_into(operators),
+        })
+    }
+
+    // Always runs the search, the solution cache is not used
+    fn optimize_with_progress(
+        &self,
+        options: &ffi::Options,
+        progress: &ProgressCounter,
+    ) -> Result<ffi::DagSolution, String> {
+        let _options = install_options(options);
+        let solution = self
+            .optimize_solution(
+                options,
+                &caches_from(options),
+                &mut Progress::new(&|step| progress.0.store(step, Ordering::Relaxed)),
+            )
+            .map(ffi::DagSolution::from)
+            .unwrap_or_else(no_dag_solution);
+        check_feasible(options, solution.p_error, self.problem())?;
+        Ok(solution)
+    }
+
+    fn optimize_solution(
+        &self,
+        options: &ffi::Options,
+        caches: &decomposition::PersistDecompCaches,
+        progress: &mut Progress<'_>,
+    ) -> Option<DagSolution> {
+        let config = config(options);
+        if config.validate().is_err() {
+            return None;
+        }
 
-        let search_space = SearchSpace::default(processing_unit);
+        let search_space = search_space(options);
 
         let encoding = options.encoding.into();
 
         if self.0.is_composed() {
-            return no_dag_solution();
+            return None;
         }
 
-        let result = concrete_optimizer::optimization::dag::solo_key::optimize_generic::optimize(
+        concrete_optimizer::optimization::dag::solo_key::optimize_generic::optimize(
             &self.0,
             config,
             &search_space,
             encoding,
             options.default_log_norm2_woppbs,
+            caches,
+            progress,
+        )
+        .filter(|solution| solution.complexity() <= options.maximum_acceptable_complexity)
+    }
+
+    // One solution per security level, in the security levels order.
+    // The decomposition caches depend on the security level, only the dag is shared.
+    fn optimize_multi_security(
+        &self,
+        security_levels: &[u64],
+        options: &ffi::Options,
+    ) -> Vec<ffi::DagSolution> {
+        security_levels
+            .iter()
+            .map(|&security_level| {
+                self.optimize_or_no_solution(&ffi::Options {
+                    security_level,
+                    ..options.clone()
+                })
+            })
+            .collect()
+    }
+
+    // One solution per candidate fft precision, in the candidates order
+    fn optimize_sweep_fft_precision(
+        &self,
+        options: &ffi::Options,
+        candidates: &[u32],
+    ) -> Vec<ffi::DagSolution> {
+        candidates
+            .iter()
+            .map(|&fft_precision| {
+                self.optimize_or_no_solution(&ffi::Options {
+                    fft_precision,
+                    ..options.clone()
+                })
+            })
+            .collect()
+    }
+
+    fn optimize_pareto(&self, options: &ffi::Options) -> Vec<ffi::DagSolution> {
+        let _options = install_options(options);
+        let config = config(options);
+        if config.validate().is_err() {
+            return vec![];
+        }
+
+        let search_space = search_space(options);
+
+        if self.0.is_composed() {
+            return vec![];
+        }
+
+        concrete_optimizer::optimization::dag::solo_key::optimize::optimize_pareto(
+            &self.0,
+            config,
+            &search_space,
             &caches_from(options),
-        );
-        result.map_or_else(no_dag_solution, |solution| solution.into())
+        )
+        .into_iter()
+        .filter(|solution| solution.complexity <= options.maximum_acceptable_complexity)
+        .map(|solution| DagSolution::WpSolution(solution).into())
+        .collect()
     }
 
     fn get_circuit_count(&self) -> usize {
         self.0.get_circuit_count()
     }
 
+    fn get_circuit_names(&self) -> Vec<String> {
+        self.0.get_circuit_names()
+    }
+
     unsafe fn add_composition<'a>(
         &mut self,
         from_func: &'a CxxString,
@@ -628,6 +1368,28 @@ impl Dag {
         self.0.add_composition(from_index, to_index);
     }
 
+    fn add_composition_by_index(
+        &mut self,
+        from_output: ffi::OperatorIndex,
+        to_input: ffi::OperatorIndex,
+    ) -> Result<(), DagEditError> {
+        self.0
+            .try_add_composition(from_output.into(), to_input.into())
+    }
+
+    fn check_compositions(&self) -> Vec<ffi::CompositionError> {
+        self.0
+            .check_compositions()
+            .into_iter()
+            .map(ffi::CompositionError::from)
+            .collect()
+    }
+
+    // A composed dag can only be optimized by optimize_multi
+    fn is_composed(&self) -> bool {
+        self.0.is_composed()
+    }
+
     fn add_all_compositions(&mut self) {
         let froms = self
             .0
@@ -642,81 +1404,426 @@ impl Dag {
         self.0.add_compositions(froms, tos);
     }
 
-    fn optimize_multi(&self, options: &ffi::Options) -> ffi::CircuitSolution {
-        let processing_unit = processing_unit(options);
-        let config = Config {
-            security_level: options.security_level,
-            maximum_acceptable_error_probability: options.maximum_acceptable_error_probability,
-            key_sharing: options.key_sharing,
-            ciphertext_modulus_log: options.ciphertext_modulus_log,
-            fft_precision: options.fft_precision,
-            complexity_model: &CpuComplexity::default(),
-        };
-        let search_space = SearchSpace::default(processing_unit);
+    fn add_all_compositions_matching(&mut self) {
+        let tos = self.0.get_input_operators_iter().collect::<Vec<_>>();
+        let compositions = self
+            .0
+            .get_output_operators_iter()
+            .flat_map(|from| {
+                tos.iter()
+                    .filter(move |to| to.precision == from.precision && to.shape == from.shape)
+                    .map(move |to| (from.id, to.id))
+            })
+            .collect::<Vec<_>>();
+        for (from, to) in compositions {
+            self.0.add_composition(from, to);
+        }
+    }
 
-        let encoding = options.encoding.into();
+    fn try_get_operator(
+        &self,
+        index: ffi::OperatorIndex,
+    ) -> Result<unparametrized::DagOperator<'_>, String> {
+        self.0
+            .try_get_operator(index.into())
+            .ok_or_else(|| format!("Operator %{} does not exist", index.index))
+    }
+
+    fn get_operator_precision(&self, index: ffi::OperatorIndex) -> Result<Precision, String> {
+        self.try_get_operator(index).map(|op| *op.precision)
+    }
+
+    fn get_operator_shape(&self, index: ffi::OperatorIndex) -> Result<Vec<u64>, String> {
+        self.try_get_operator(index)
+            .map(|op| op.shape.dimensions_size.clone())
+    }
+
+    fn get_operator_comment(&self, index: ffi::OperatorIndex) -> Result<String, String> {
+        self.try_get_operator(index).map(|op| op.comment.clone())
+    }
+
+    // The log2 norm2 used to partition the luts with ByPrecisionAndNorm2
+    fn get_operator_norm2(&self, index: ffi::OperatorIndex) -> Result<f64, String> {
+        let _ = self.try_get_operator(index)?;
+        PartitionCut::lut_log_norm2(&self.0, index.into())
+            .ok_or_else(|| format!("Operator %{} is not a lookup table", index.index))
+    }
+
+    fn operator_kind_histogram(&self) -> Vec<ffi::OperatorKindCount> {
+        self.0
+            .operator_kind_histogram()
+            .into_iter()
+            .map(|(name, count)| ffi::OperatorKindCount {
+                name: name.into(),
+                count: count as u64,
+            })
+            .collect()
+    }
+
+    // The partition assignment is kept only if both dags are fully assigned
+    fn merge(&mut self, other: &Self) -> Result<Vec<ffi::OperatorIndex>, DagMergeError> {
+        let assigned = self.1.len() == self.0.len() && other.1.len() == other.0.len();
+        let mapping = self.0.merge(&other.0)?;
+        if assigned {
+            self.1.extend_from_slice(&other.1);
+        } else {
+            self.1.clear();
+        }
+        Ok(mapping.into_iter().map(ffi::OperatorIndex::from).collect())
+    }
+
+    fn remove_operator(&mut self, index: ffi::OperatorIndex) -> Result<(), DagEditError> {
+        self.0.remove_operator(index.into())?;
+        if index.index < self.1.len() {
+            let _ = self.1.remove(index.index);
+        }
+        Ok(())
+    }
+
+    fn replace_lut_table(
+        &mut self,
+        index: ffi::OperatorIndex,
+        table: &[u64],
+    ) -> Result<(), DagEditError> {
+        let table = FunctionTable {
+            values: table.to_owned(),
+        };
+        self.0.replace_lut_table(index.into(), table)
+    }
+
+    fn set_partition_assignment(&mut self, assignment: &[u64]) -> Result<(), String> {
+        if assignment.len() != self.0.len() {
+            return Err(format!(
+                "Partition assignment has {} entries for {} operators",
+                assignment.len(),
+                self.0.len()
+            ));
+        }
+        self.1 = assignment
+            .iter()
+            .map(|&partition| PartitionIndex(partition as usize))
+            .collect();
+        Ok(())
+    }
+
+    fn partition_cut(&self, options: &ffi::Options) -> Result<PartitionCut, String> {
         #[allow(clippy::wildcard_in_or_patterns)]
-        let p_cut = match options.multi_param_strategy {
+        match options.multi_param_strategy {
             ffi::MultiParamStrategy::ByPrecisionAndNorm2 => {
-                PartitionCut::maximal_partitionning(&self.0)
+                Ok(PartitionCut::maximal_partitionning(&self.0))
+            }
+            ffi::MultiParamStrategy::Explicit => PartitionCut::from_assignment(&self.0, &self.1),
+            ffi::MultiParamStrategy::ByPrecision | _ => {
+                Ok(PartitionCut::for_each_precision(&self.0))
             }
-            ffi::MultiParamStrategy::ByPrecision | _ => PartitionCut::for_each_precision(&self.0),
+        }
+    }
+
+    fn is_feasible(&self, options: &ffi::Options) -> bool {
+        let _options = install_options(options);
+        let config = config(options);
+        if config.validate().is_err() {
+            return false;
+        }
+        let search_space = search_space(options);
+
+        let encoding = options.encoding.into();
+        let Ok(p_cut) = self.partition_cut(options) else {
+            return false;
         };
-        let circuit_sol =
-            if !options.keyset_restriction.is_null() && !options.range_restriction.is_null() {
-                concrete_optimizer::optimization::dag::multi_parameters::optimize_generic::optimize(
-                    &self.0,
-                    config,
-                    &search_space,
-                    &(
-                        (*options.keyset_restriction).clone(),
-                        (*options.range_restriction).clone(),
-                    ),
-                    encoding,
-                    options.default_log_norm2_woppbs,
-                    &caches_from(options),
-                    &Some(p_cut),
-                )
-            } else if !options.keyset_restriction.is_null() {
-                concrete_optimizer::optimization::dag::multi_parameters::optimize_generic::optimize(
-                    &self.0,
-                    config,
-                    &search_space,
-                    &*options.keyset_restriction,
-                    encoding,
-                    options.default_log_norm2_woppbs,
-                    &caches_from(options),
-                    &Some(p_cut),
-                )
-            } else if !options.range_restriction.is_null() {
-                concrete_optimizer::optimization::dag::multi_parameters::optimize_generic::optimize(
-                    &self.0,
-                    config,
-                    &search_space,
-                    &*options.range_restriction,
-                    encoding,
-                    options.default_log_norm2_woppbs,
-                    &caches_from(options),
-                    &Some(p_cut),
-                )
-            } else {
-                concrete_optimizer::optimization::dag::multi_parameters::optimize_generic::optimize(
+        let keyset_restriction = options
+            .keyset_restriction
+            .as_ref()
+            .map(KeysetRestriction::from);
+        let range_restriction = options_range_restriction(options);
+        match (keyset_restriction, range_restriction) {
+            (Some(keyset_restriction), Some(range_restriction)) => optimize_generic::is_feasible(
+                &self.0,
+                config,
+                &search_space,
+                &(keyset_restriction, range_restriction),
+                encoding,
+                options.default_log_norm2_woppbs,
+                &caches_from(options),
+                &Some(p_cut),
+            ),
+            (Some(keyset_restriction), None) => optimize_generic::is_feasible(
+                &self.0,
+                config,
+                &search_space,
+                &keyset_restriction,
+                encoding,
+                options.default_log_norm2_woppbs,
+                &caches_from(options),
+                &Some(p_cut),
+            ),
+            (None, Some(range_restriction)) => optimize_generic::is_feasible(
+                &self.0,
+                config,
+                &search_space,
+                &range_restriction,
+                encoding,
+                options.default_log_norm2_woppbs,
+                &caches_from(options),
+                &Some(p_cut),
+            ),
+            (None, None) => optimize_generic::is_feasible(
+                &self.0,
+                config,
+                &search_space,
+                &NoSearchSpaceRestriction,
+                encoding,
+                options.default_log_norm2_woppbs,
+                &caches_from(options),
+                &Some(p_cut),
+            ),
+        }
+    }
+
+    fn optimize_multi(&self, options: &ffi::Options) -> ffi::CircuitSolution {
+        self.optimize_multi_with_report(options).solution
+    }
+
+    fn optimize_multi_cancellable(
+        &self,
+        options: &ffi::Options,
+        cancel: &CancellationToken,
+    ) -> ffi::CircuitSolution {
+        self.optimize_multi_search(
+            options,
+            options_keyset_restriction(options).as_ref(),
+            &cancel.0,
+            &mut Progress::none(),
+        )
+        .solution
+    }
+
+    fn optimize_multi_with_progress(
+        &self,
+        options: &ffi::Options,
+        progress: &ProgressCounter,
+    ) -> ffi::CircuitSolution {
+        self.optimize_multi_search(
+            options,
+            options_keyset_restriction(options).as_ref(),
+            &AtomicBool::new(false),
+            &mut Progress::new(&|step| progress.0.store(step, Ordering::Relaxed)),
+        )
+        .solution
+    }
+
+    fn optimize_multi_with_report(&self, options: &ffi::Options) -> ffi::CircuitSolutionWithReport {
+        self.optimize_multi_search(
+            options,
+            options_keyset_restriction(options).as_ref(),
+            &AtomicBool::new(false),
+            &mut Progress::none(),
+        )
+    }
+
+    // The prepared restriction replaces options.keyset_restriction
+    fn optimize_multi_with_prepared(
+        &self,
+        options: &ffi::Options,
+        restriction: &PreparedRestriction,
+    ) -> ffi::CircuitSolution {
+        self.optimize_multi_search(
+            options,
+            Some(&restriction.0),
+            &AtomicBool::new(false),
+            &mut Progress::none(),
+        )
+        .solution
+    }
+
+    fn optimize_multi_search(
+        &self,
+        options: &ffi::Options,
+        keyset_restriction: Option<&KeysetRestriction>,
+        cancel: &AtomicBool,
+        progress: &mut Progress<'_>,
+    ) -> ffi::CircuitSolutionWithReport {
+        let _options = install_options(options);
+        let config = config(options);
+        if let Err(err) = config.validate() {
+            return ffi::CircuitSolutionWithReport {
+                solution: CircuitSolution::no_solution(err.to_string()).into(),
+                partitions: vec![],
+            };
+        }
+        let search_space = search_space(options);
+
+        let encoding = options.encoding.into();
+        let p_cut = match self.partition_cut(options) {
+            Ok(p_cut) => p_cut,
+            Err(err) => {
+                return ffi::CircuitSolutionWithReport {
+                    solution: CircuitSolution::no_solution(err).into(),
+                    partitions: vec![],
+                }
+            }
+        };
+        let range_restriction = options_range_restriction(options);
+        let (circuit_sol, partitions) = match (keyset_restriction, range_restriction) {
+            (Some(keyset_restriction), Some(range_restriction)) => {
+                optimize_generic::optimize_with_report(
                     &self.0,
                     config,
                     &search_space,
-                    &NoSearchSpaceRestriction,
+                    &(keyset_restriction, range_restriction),
                     encoding,
                     options.default_log_norm2_woppbs,
                     &caches_from(options),
                     &Some(p_cut),
+                    cancel,
+                    progress,
                 )
-            };
-        circuit_sol.into()
+            }
+            (Some(keyset_restriction), None) => optimize_generic::optimize_with_report(
+                &self.0,
+                config,
+                &search_space,
+                &keyset_restriction,
+                encoding,
+                options.default_log_norm2_woppbs,
+                &caches_from(options),
+                &Some(p_cut),
+                cancel,
+                progress,
+            ),
+            (None, Some(range_restriction)) => optimize_generic::optimize_with_report(
+                &self.0,
+                config,
+                &search_space,
+                &range_restriction,
+                encoding,
+                options.default_log_norm2_woppbs,
+                &caches_from(options),
+                &Some(p_cut),
+                cancel,
+                progress,
+            ),
+            (None, None) => optimize_generic::optimize_with_report(
+                &self.0,
+                config,
+                &search_space,
+                &NoSearchSpaceRestriction,
+                encoding,
+                options.default_log_norm2_woppbs,
+                &caches_from(options),
+                &Some(p_cut),
+                cancel,
+                progress,
+            ),
+        };
+        let circuit_sol = if circuit_sol.is_feasible
+            && circuit_sol.complexity > options.maximum_acceptable_complexity
+        {
+            CircuitSolution::no_solution(
+                "No crypto-parameters within the maximum acceptable complexity",
+            )
+        } else {
+            circuit_sol
+        };
+        ffi::CircuitSolutionWithReport {
+            solution: circuit_sol.into(),
+            partitions: vec_into(partitions),
+        }
+    }
+}
+
+// The decomposition levels bounds of options.search_space are applied as a range restriction
+fn options_range_restriction(options: &ffi::Options) -> Option<RangeRestriction> {
+    let range_restriction = options
+        .range_restriction
+        .as_ref()
+        .map(RangeRestriction::from);
+    let bounds = &options.search_space;
+    if bounds.decomposition_level_min == 0 && bounds.decomposition_level_max == 0 {
+        return range_restriction;
     }
+    let level_max = match bounds.decomposition_level_max {
+        0 => u64::from(options.ciphertext_modulus_log),
+        level_max => level_max,
+    };
+    let levels = bounds.decomposition_level_min.max(1)..=level_max;
+    let restrict_levels = |level_count: &mut Vec<u64>| {
+        if level_count.is_empty() {
+            *level_count = levels.clone().collect();
+        } else {
+            level_count.retain(|level| levels.contains(level));
+        }
+        if level_count.is_empty() {
+            // an empty list would mean unrestricted, level 0 is never available
+            level_count.push(0);
+        }
+    };
+    let mut range_restriction = range_restriction.unwrap_or(RangeRestriction {
+        glwe_log_polynomial_sizes: vec![],
+        glwe_polynomial_sizes: vec![],
+        glwe_dimensions: vec![],
+        internal_lwe_dimensions: vec![],
+        pbs_level_count: vec![],
+        pbs_base_log: vec![],
+        ks_level_count: vec![],
+        ks_base_log: vec![],
+    });
+    restrict_levels(&mut range_restriction.pbs_level_count);
+    restrict_levels(&mut range_restriction.ks_level_count);
+    Some(range_restriction)
+}
+
+fn options_keyset_restriction(options: &ffi::Options) -> Option<KeysetRestriction> {
+    options
+        .keyset_restriction
+        .as_ref()
+        .map(KeysetRestriction::from)
 }
 
 pub struct DagBuilder<'dag>(unparametrized::DagBuilder<'dag>);
 
+/// A keyset restriction converted and checked once, to be reused by several optimizations.
+pub struct PreparedRestriction(KeysetRestriction);
+
+fn prepare_keyset_restriction(
+    restriction: &ffi::KeysetRestriction,
+) -> Result<Box<PreparedRestriction>, String> {
+    let restriction = KeysetRestriction::from(restriction);
+    restriction.check().map_err(|err| err.to_string())?;
+    Ok(Box::new(PreparedRestriction(restriction)))
+}
+
+/// Shared flag to stop a running `optimize_multi_cancellable` from another thread.
+pub struct CancellationToken(AtomicBool);
+
+fn cancellation_token() -> Box<CancellationToken> {
+    Box::new(CancellationToken(AtomicBool::new(false)))
+}
+
+impl CancellationToken {
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Number of macro parameters enumerated by a running `optimize_with_progress` or
+/// `optimize_multi_with_progress`, to be polled from another thread.
+pub struct ProgressCounter(AtomicU32);
+
+fn progress_counter() -> Box<ProgressCounter> {
+    Box::new(ProgressCounter(AtomicU32::new(0)))
+}
+
+impl ProgressCounter {
+    fn steps(&self) -> u32 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
 impl DagBuilder<'_> {
     fn add_input(
         &mut self,
@@ -733,6 +1840,73 @@ impl DagBuilder<'_> {
             .into()
     }
 
+    fn add_input_with_variance(
+        &mut self,
+        out_precision: Precision,
+        out_shape: &[u64],
+        input_variance: f64,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, String> {
+        if !(input_variance.is_finite() && input_variance >= 0.0) {
+            return Err(format!(
+                "input variance must be finite and non-negative, got {input_variance}"
+            ));
+        }
+        let out_shape = Shape {
+            dimensions_size: out_shape.to_owned(),
+        };
+
+        Ok(self
+            .0
+            .add_input_with_variance(out_precision, out_shape, input_variance, location.0.clone())
+            .into())
+    }
+
+    fn add_input_in_partition(
+        &mut self,
+        out_precision: Precision,
+        out_shape: &[u64],
+        partition: &ExternalPartition,
+        location: &Location,
+    ) -> ffi::OperatorIndex {
+        let out_shape = Shape {
+            dimensions_size: out_shape.to_owned(),
+        };
+
+        self.0
+            .add_input_in_partition(
+                out_precision,
+                out_shape,
+                partition.0.clone(),
+                location.0.clone(),
+            )
+            .into()
+    }
+
+    // The returned indices are in the order of the specs
+    fn add_inputs(
+        &mut self,
+        specs: &[ffi::InputSpec],
+        location: &Location,
+    ) -> Vec<ffi::OperatorIndex> {
+        specs
+            .iter()
+            .map(|spec| self.add_input(spec.precision, &spec.shape, location))
+            .collect()
+    }
+
+    fn add_input_with_comment(
+        &mut self,
+        out_precision: Precision,
+        out_shape: &[u64],
+        comment: &str,
+        location: &Location,
+    ) -> ffi::OperatorIndex {
+        let input = self.add_input(out_precision, out_shape, location);
+        self.0.set_operator_comment(input.into(), comment);
+        input
+    }
+
     fn add_zero_noise(
         &mut self,
         out_precision: Precision,
@@ -754,14 +1928,90 @@ impl DagBuilder<'_> {
         table: &[u64],
         out_precision: Precision,
         location: &Location,
-    ) -> ffi::OperatorIndex {
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
         let table = FunctionTable {
             values: table.to_owned(),
         };
 
         self.0
-            .add_lut(input.into(), table, out_precision, location.0.clone())
-            .into()
+            .try_add_lut(input.into(), table, out_precision, location.0.clone())
+            .map(ffi::OperatorIndex::from)
+    }
+
+    fn add_lut_with_comment(
+        &mut self,
+        input: ffi::OperatorIndex,
+        table: &[u64],
+        out_precision: Precision,
+        comment: &str,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        let lut = self.add_lut(input, table, out_precision, location)?;
+        self.0.set_operator_comment(lut.into(), comment);
+        Ok(lut)
+    }
+
+    fn add_signed_lut(
+        &mut self,
+        input: ffi::OperatorIndex,
+        table: &[u64],
+        out_precision: Precision,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        let table = FunctionTable {
+            values: table.to_owned(),
+        };
+        self.0.check_lut_table(input.into(), &table)?;
+
+        Ok(self
+            .0
+            .add_signed_lut(input.into(), table, out_precision, location.0.clone())
+            .into())
+    }
+
+    fn add_multi_lut(
+        &mut self,
+        input: ffi::OperatorIndex,
+        tables: &[ffi::LutTable],
+        out_precision: Precision,
+        location: &Location,
+    ) -> Result<Vec<ffi::OperatorIndex>, DagEditError> {
+        let tables: Vec<FunctionTable> = tables
+            .iter()
+            .map(|table| FunctionTable {
+                values: table.values.clone(),
+            })
+            .collect();
+
+        Ok(self
+            .0
+            .add_multi_lut(input.into(), &tables, out_precision, location.0.clone())?
+            .into_iter()
+            .map(ffi::OperatorIndex::from)
+            .collect())
+    }
+
+    fn add_rounded_lut(
+        &mut self,
+        input: ffi::OperatorIndex,
+        table: &[u64],
+        rounded_precision: Precision,
+        out_precision: Precision,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        let table = FunctionTable {
+            values: table.to_owned(),
+        };
+
+        self.0
+            .try_add_rounded_lut(
+                input.into(),
+                table,
+                rounded_precision,
+                out_precision,
+                location.0.clone(),
+            )
+            .map(ffi::OperatorIndex::from)
     }
 
     #[allow(clippy::boxed_local)]
@@ -770,10 +2020,55 @@ impl DagBuilder<'_> {
         inputs: &[ffi::OperatorIndex],
         weights: Box<Weights>,
         location: &Location,
-    ) -> ffi::OperatorIndex {
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        let inputs: Vec<OperatorIndex> = inputs.iter().copied().map(Into::into).collect();
+
+        match *weights {
+            Weights::Integer(weights) => self.0.try_add_dot(inputs, weights, location.0.clone()),
+            Weights::Float(weights) => {
+                self.0
+                    .try_add_float_dot(inputs, weights, location.0.clone())
+            }
+        }
+        .map(Into::into)
+    }
+
+    // A negative manual_norm2 keeps the norm2 derived from the weights
+    #[allow(clippy::boxed_local)]
+    fn add_dot_with_norm(
+        &mut self,
+        inputs: &[ffi::OperatorIndex],
+        weights: Box<Weights>,
+        manual_norm2: f64,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
         let inputs: Vec<OperatorIndex> = inputs.iter().copied().map(Into::into).collect();
 
-        self.0.add_dot(inputs, weights.0, location.0.clone()).into()
+        match *weights {
+            Weights::Integer(weights) => {
+                self.0
+                    .try_add_dot_with_norm(inputs, weights, manual_norm2, location.0.clone())
+            }
+            Weights::Float(weights) => self.0.try_add_float_dot_with_norm(
+                inputs,
+                weights,
+                manual_norm2,
+                location.0.clone(),
+            ),
+        }
+        .map(Into::into)
+    }
+
+    fn add_dot_with_comment(
+        &mut self,
+        inputs: &[ffi::OperatorIndex],
+        weights: Box<Weights>,
+        comment: &str,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        let dot = self.add_dot(inputs, weights, location)?;
+        self.0.set_operator_comment(dot.into(), comment);
+        Ok(dot)
     }
 
     fn add_linear_noise(
@@ -810,201 +2105,1127 @@ impl DagBuilder<'_> {
             .into()
     }
 
-    fn add_max_noise(
-        &mut self,
-        inputs: &[ffi::OperatorIndex],
-        out_shape: &[u64],
-        location: &Location,
-    ) -> ffi::OperatorIndex {
-        let inputs: Vec<OperatorIndex> = inputs.iter().copied().map(Into::into).collect();
+    fn add_broadcast_linear_noise(
+        &mut self,
+        inputs: &[ffi::OperatorIndex],
+        lwe_dim_cost_factor: f64,
+        fixed_cost: f64,
+        weights: &[f64],
+        comment: &str,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        let inputs: Vec<OperatorIndex> = inputs.iter().copied().map(Into::into).collect();
+
+        let complexity = LevelledComplexity {
+            lwe_dim_cost_factor,
+            fixed_cost,
+        };
+
+        self.0
+            .try_add_broadcast_linear_noise(
+                inputs,
+                complexity,
+                weights,
+                comment,
+                location.0.clone(),
+            )
+            .map(ffi::OperatorIndex::from)
+    }
+
+    fn add_broadcast_max_noise(
+        &mut self,
+        inputs: &[ffi::OperatorIndex],
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        let inputs: Vec<OperatorIndex> = inputs.iter().copied().map(Into::into).collect();
+
+        self.0
+            .try_add_broadcast_max_noise(inputs, location.0.clone())
+            .map(ffi::OperatorIndex::from)
+    }
+
+    fn add_sub(
+        &mut self,
+        lhs: ffi::OperatorIndex,
+        rhs: ffi::OperatorIndex,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        self.0
+            .try_add_sub(lhs.into(), rhs.into(), location.0.clone())
+            .map(ffi::OperatorIndex::from)
+    }
+
+    fn add_negate(
+        &mut self,
+        input: ffi::OperatorIndex,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        self.0
+            .try_add_negate(input.into(), location.0.clone())
+            .map(ffi::OperatorIndex::from)
+    }
+
+    fn add_identity(
+        &mut self,
+        input: ffi::OperatorIndex,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        self.0
+            .try_add_identity(input.into(), location.0.clone())
+            .map(ffi::OperatorIndex::from)
+    }
+
+    fn add_max_noise(
+        &mut self,
+        inputs: &[ffi::OperatorIndex],
+        out_shape: &[u64],
+        location: &Location,
+    ) -> ffi::OperatorIndex {
+        let inputs: Vec<OperatorIndex> = inputs.iter().copied().map(Into::into).collect();
+
+        let out_shape = Shape {
+            dimensions_size: out_shape.to_owned(),
+        };
+
+        self.0
+            .add_max_noise(inputs, out_shape, location.0.clone())
+            .into()
+    }
+
+    fn add_tree_max(
+        &mut self,
+        inputs: &[ffi::OperatorIndex],
+        out_shape: &[u64],
+        out_precision: Precision,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        let inputs: Vec<OperatorIndex> = inputs.iter().copied().map(Into::into).collect();
+
+        let out_shape = Shape {
+            dimensions_size: out_shape.to_owned(),
+        };
+
+        self.0
+            .try_add_tree_max(inputs, out_shape, out_precision, location.0.clone())
+            .map(Into::into)
+    }
+
+    fn add_tree_pbs(
+        &mut self,
+        input: ffi::OperatorIndex,
+        table: &[u64],
+        out_precision: Precision,
+        decomposition_base: Precision,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        let table = FunctionTable {
+            values: table.to_owned(),
+        };
+
+        self.0
+            .try_add_tree_pbs(
+                input.into(),
+                table,
+                out_precision,
+                decomposition_base,
+                location.0.clone(),
+            )
+            .map(ffi::OperatorIndex::from)
+    }
+
+    fn add_round_op(
+        &mut self,
+        input: ffi::OperatorIndex,
+        rounded_precision: Precision,
+        location: &Location,
+    ) -> ffi::OperatorIndex {
+        self.0
+            .add_round_op(input.into(), rounded_precision, location.0.clone())
+            .into()
+    }
+
+    fn add_unsafe_cast_op(
+        &mut self,
+        input: ffi::OperatorIndex,
+        new_precision: Precision,
+        location: &Location,
+    ) -> ffi::OperatorIndex {
+        self.0
+            .add_unsafe_cast(input.into(), new_precision, location.0.clone())
+            .into()
+    }
+
+    fn add_change_partition_with_src(
+        &mut self,
+        input: ffi::OperatorIndex,
+        src_partition: &ExternalPartition,
+        location: &Location,
+    ) -> ffi::OperatorIndex {
+        self.0
+            .add_change_partition(
+                input.into(),
+                Some(src_partition.0.clone()),
+                None,
+                location.0.clone(),
+            )
+            .into()
+    }
+
+    fn add_change_partition_with_dst(
+        &mut self,
+        input: ffi::OperatorIndex,
+        dst_partition: &ExternalPartition,
+        location: &Location,
+    ) -> ffi::OperatorIndex {
+        self.0
+            .add_change_partition(
+                input.into(),
+                None,
+                Some(dst_partition.0.clone()),
+                location.0.clone(),
+            )
+            .into()
+    }
+
+    fn add_change_partition(
+        &mut self,
+        input: ffi::OperatorIndex,
+        src_partition: &ExternalPartition,
+        dst_partition: &ExternalPartition,
+        location: &Location,
+    ) -> ffi::OperatorIndex {
+        self.0
+            .add_change_partition(
+                input.into(),
+                Some(src_partition.0.clone()),
+                Some(dst_partition.0.clone()),
+                location.0.clone(),
+            )
+            .into()
+    }
+
+    fn add_keyswitch(
+        &mut self,
+        input: ffi::OperatorIndex,
+        dst_partition: &ExternalPartition,
+        location: &Location,
+    ) -> ffi::OperatorIndex {
+        self.0
+            .add_keyswitch(input.into(), dst_partition.0.clone(), location.0.clone())
+            .into()
+    }
+
+    fn tag_operator_as_output(&mut self, op: ffi::OperatorIndex) {
+        self.0.tag_operator_as_output(op.into());
+    }
+
+    fn tag_operators_as_output(&mut self, ops: &[ffi::OperatorIndex]) -> Result<(), DagEditError> {
+        let ops: Vec<OperatorIndex> = ops.iter().copied().map(Into::into).collect();
+        self.0.tag_operators_as_output(&ops)
+    }
+
+    fn force_bootstrap(
+        &mut self,
+        op: ffi::OperatorIndex,
+        location: &Location,
+    ) -> Result<ffi::OperatorIndex, DagEditError> {
+        self.0
+            .force_bootstrap(op.into(), location.0.clone())
+            .map(Into::into)
+    }
+
+    fn dump(&self) -> String {
+        format!("{}", self.0.get_circuit())
+    }
+}
+
+#[derive(Clone)]
+pub struct Location(operator::Location);
+
+fn location_unknown() -> Box<Location> {
+    Box::new(Location(operator::Location::Unknown))
+}
+
+fn cache_directory() -> String {
+    default_cache_dir()
+}
+
+// Has no effect without options.cache_on_disk, as nothing would be kept
+fn warm_cache(options: &ffi::Options) {
+    let _quiet = log::set_quiet(options.quiet);
+    caches_from(options).warm(&search_space(options));
+}
+
+// Number of macro parameters candidates explored for each partition
+fn estimate_search_space_size(options: &ffi::Options) -> u64 {
+    let _quiet = log::set_quiet(options.quiet);
+    let search_space = search_space(options);
+    let keyset_restriction = options_keyset_restriction(options);
+    let range_restriction = options_range_restriction(options);
+    match (keyset_restriction, range_restriction) {
+        (Some(keyset_restriction), Some(range_restriction)) => {
+            search_space_size(&search_space, &(keyset_restriction, range_restriction))
+        }
+        (Some(keyset_restriction), None) => search_space_size(&search_space, &keyset_restriction),
+        (None, Some(range_restriction)) => search_space_size(&search_space, &range_restriction),
+        (None, None) => search_space_size(&search_space, &NoSearchSpaceRestriction),
+    }
+}
+
+fn clear_cache() -> Result<(), String> {
+    clear_cache_dir().map_err(|err| format!("clear_cache: {err}"))
+}
+
+// cxx cannot pass a C++ function pointer to Rust, so the log callback is installed with this C
+// function, declared in src/cpp/concrete-optimizer-log.hpp. The callback receives the level
+// (0 error, 1 warning, 2 info, 3 debug) and the utf8 message, which is not nul terminated.
+// Returns false if a callback is already installed.
+#[no_mangle]
+pub extern "C" fn concrete_optimizer_set_log_callback(
+    callback: extern "C" fn(level: u8, msg: *const u8, len: usize),
+) -> bool {
+    log::set_callback(Box::new(move |level, msg| {
+        callback(level, msg.as_ptr(), msg.len());
+    }))
+    .is_ok()
+}
+
+fn location_from_string(string: &str) -> Box<Location> {
+    let location: Vec<&str> = string.split(':').collect();
+    // Unparsable line or column markers are dropped rather than aborting.
+    let location = match location[..] {
+        [file] => operator::Location::File(file.into()),
+        [file, line] => match line.parse() {
+            Ok(line) => operator::Location::Line(file.into(), line),
+            Err(_) => operator::Location::File(file.into()),
+        },
+        [file, line, column] => match (line.parse(), column.parse()) {
+            (Ok(line), Ok(column)) => operator::Location::LineColumn(file.into(), line, column),
+            (Ok(line), Err(_)) => operator::Location::Line(file.into(), line),
+            (Err(_), _) => operator::Location::File(file.into()),
+        },
+        _ => operator::Location::Unknown,
+    };
+    Box::new(Location(location))
+}
+
+fn virtual_partition_definitions(
+    inputs: Vec<ffi::PartitionDefinition>,
+) -> Vec<
+    concrete_optimizer::optimization::dag::multi_parameters::virtual_circuit::PartitionDefinition,
+> {
+    inputs
+        .into_iter()
+        .map(
+            |ffi::PartitionDefinition { precision, norm2 }| concrete_optimizer::optimization::dag::multi_parameters::virtual_circuit::PartitionDefinition { precision, norm2 },
+        )
+        .collect()
+}
+
+fn generate_virtual_keyset_info(
+    inputs: Vec<ffi::PartitionDefinition>,
+    generate_fks: bool,
+    options: &ffi::Options,
+) -> ffi::CircuitKeys {
+    let _options = install_options(options);
+    let config = config(options);
+    generate_virtual_parameters(virtual_partition_definitions(inputs), generate_fks, config).into()
+}
+
+// Only generates the keyswitch keys of each partition and of the given conversions
+fn generate_virtual_keyset_info_with_conversions(
+    inputs: Vec<ffi::PartitionDefinition>,
+    conversions: Vec<ffi::PartitionConversion>,
+    options: &ffi::Options,
+) -> Result<ffi::CircuitKeys, String> {
+    let _options = install_options(options);
+    let config = config(options);
+    config.validate().map_err(|err| err.to_string())?;
+    let conversions: Vec<_> = conversions
+        .iter()
+        .map(|conversion| (conversion.src as usize, conversion.dst as usize))
+        .collect();
+    generate_virtual_parameters_with_conversions(
+        virtual_partition_definitions(inputs),
+        &conversions,
+        config,
+        &caches_from(options),
+    )
+    .map(Into::into)
+}
+
+pub enum Weights {
+    Integer(operator::Weights),
+    // the noise uses the real weights instead of their integer rounding
+    Float(operator::FloatWeights),
+}
+
+fn vector(weights: &[i64]) -> Box<Weights> {
+    Box::new(Weights::Integer(operator::Weights::vector(weights)))
+}
+
+fn number(weight: i64) -> Box<Weights> {
+    Box::new(Weights::Integer(operator::Weights::number(weight)))
+}
+
+fn try_vector(weights: &[i64]) -> Result<Box<Weights>, String> {
+    operator::Weights::try_vector(weights)
+        .map(|weights| Box::new(Weights::Integer(weights)))
+        .map_err(|err| err.to_string())
+}
+
+fn try_number(weight: i64) -> Result<Box<Weights>, String> {
+    operator::Weights::try_number(weight)
+        .map(|weights| Box::new(Weights::Integer(weights)))
+        .map_err(|err| err.to_string())
+}
+
+fn float_vector(weights: &[f64]) -> Box<Weights> {
+    Box::new(Weights::Float(operator::FloatWeights::vector(weights)))
+}
+
+impl From<OperatorIndex> for ffi::OperatorIndex {
+    fn from(oi: OperatorIndex) -> Self {
+        Self { index: oi.0 }
+    }
+}
+
+impl From<OperatorNoise> for ffi::OperatorNoise {
+    fn from(noise: OperatorNoise) -> Self {
+        Self {
+            index: noise.index.into(),
+            variance: noise.variance,
+            margin: noise.margin,
+        }
+    }
+}
+
+impl From<OperatorBudget> for ffi::OperatorBudget {
+    fn from(budget: OperatorBudget) -> Self {
+        Self {
+            index: budget.index.into(),
+            p_error: budget.p_error,
+        }
+    }
+}
+
+impl From<CompositionError> for ffi::CompositionError {
+    fn from(error: CompositionError) -> Self {
+        Self {
+            message: error.to_string(),
+            from_circuit: error.from_circuit,
+            from: error.from.into(),
+            from_precision: error.from_precision,
+            to_circuit: error.to_circuit,
+            to: error.to.into(),
+            to_precision: error.to_precision,
+        }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<OperatorIndex> for ffi::OperatorIndex {
+    fn into(self) -> OperatorIndex {
+        OperatorIndex(self.index)
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<Encoding> for ffi::Encoding {
+    fn into(self) -> Encoding {
+        match self {
+            Self::Auto => Encoding::Auto,
+            Self::Native => Encoding::Native,
+            Self::Crt => Encoding::Crt,
+            _ => unreachable!("Internal error: Invalid encoding"),
+        }
+    }
+}
+
+impl Default for ffi::Encoding {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
+// Complexity model of every config, the processing unit only changes the decomposition caches
+static CPU_COMPLEXITY: LazyLock<CpuComplexity> = LazyLock::new(CpuComplexity::default);
+
+fn config(options: &ffi::Options) -> Config<'static> {
+    Config {
+        security_level: options.security_level,
+        maximum_acceptable_error_probability: options.maximum_acceptable_error_probability,
+        key_sharing: options.key_sharing,
+        ciphertext_modulus_log: options.ciphertext_modulus_log,
+        fft_precision: options.fft_precision,
+        complexity_model: &*CPU_COMPLEXITY,
+        objective: objective(options),
+    }
+}
+
+fn objective(options: &ffi::Options) -> Objective {
+    match options.objective {
+        ffi::Objective::MinComplexity => Objective::MinComplexity,
+        ffi::Objective::MinKeySize => Objective::MinKeySize,
+        ffi::Objective::Weighted => Objective::Weighted {
+            complexity_weight: options.objective_complexity_weight,
+            key_size_weight: options.objective_key_size_weight,
+        },
+        ffi::Objective::MaxThroughput => Objective::MaxThroughput {
+            batch_size: options.objective_batch_size,
+        },
+        _ => unreachable!("Internal error: Invalid objective"),
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<config::GpuPbsType> for ffi::GpuPbsType {
+    fn into(self) -> config::GpuPbsType {
+        match self {
+            Self::Amortized => config::GpuPbsType::Amortized,
+            Self::LowLatency => config::GpuPbsType::Lowlat,
+            _ => unreachable!("Internal error: Invalid gpu pbs type"),
+        }
+    }
+}
+
+impl From<&ffi::RangeRestriction> for RangeRestriction {
+    fn from(v: &ffi::RangeRestriction) -> Self {
+        Self {
+            glwe_log_polynomial_sizes: v.glwe_log_polynomial_sizes.clone(),
+            glwe_polynomial_sizes: v.glwe_polynomial_sizes.clone(),
+            glwe_dimensions: v.glwe_dimensions.clone(),
+            internal_lwe_dimensions: v.internal_lwe_dimensions.clone(),
+            pbs_level_count: v.pbs_level_count.clone(),
+            pbs_base_log: v.pbs_base_log.clone(),
+            ks_level_count: v.ks_level_count.clone(),
+            ks_base_log: v.ks_base_log.clone(),
+        }
+    }
+}
+
+impl From<RangeRestriction> for ffi::RangeRestriction {
+    fn from(v: RangeRestriction) -> Self {
+        Self {
+            glwe_log_polynomial_sizes: v.glwe_log_polynomial_sizes,
+            glwe_polynomial_sizes: v.glwe_polynomial_sizes,
+            glwe_dimensions: v.glwe_dimensions,
+            internal_lwe_dimensions: v.internal_lwe_dimensions,
+            pbs_level_count: v.pbs_level_count,
+            pbs_base_log: v.pbs_base_log,
+            ks_level_count: v.ks_level_count,
+            ks_base_log: v.ks_base_log,
+        }
+    }
+}
+
+impl From<&ffi::LweSecretKeyInfo> for LweSecretKeyInfo {
+    fn from(v: &ffi::LweSecretKeyInfo) -> Self {
+        Self {
+            lwe_dimension: v.lwe_dimension,
+        }
+    }
+}
+
+impl From<LweSecretKeyInfo> for ffi::LweSecretKeyInfo {
+    fn from(v: LweSecretKeyInfo) -> Self {
+        Self {
+            lwe_dimension: v.lwe_dimension,
+        }
+    }
+}
+
+impl From<&ffi::LweBootstrapKeyInfo> for LweBootstrapKeyInfo {
+    fn from(v: &ffi::LweBootstrapKeyInfo) -> Self {
+        Self {
+            level_count: v.level_count,
+            base_log: v.base_log,
+            glwe_dimension: v.glwe_dimension,
+            polynomial_size: v.polynomial_size,
+            input_lwe_dimension: v.input_lwe_dimension,
+        }
+    }
+}
+
+impl From<LweBootstrapKeyInfo> for ffi::LweBootstrapKeyInfo {
+    fn from(v: LweBootstrapKeyInfo) -> Self {
+        Self {
+            level_count: v.level_count,
+            base_log: v.base_log,
+            glwe_dimension: v.glwe_dimension,
+            polynomial_size: v.polynomial_size,
+            input_lwe_dimension: v.input_lwe_dimension,
+        }
+    }
+}
+
+impl From<&ffi::LweKeyswitchKeyInfo> for LweKeyswitchKeyInfo {
+    fn from(v: &ffi::LweKeyswitchKeyInfo) -> Self {
+        Self {
+            level_count: v.level_count,
+            base_log: v.base_log,
+            input_lwe_dimension: v.input_lwe_dimension,
+            output_lwe_dimension: v.output_lwe_dimension,
+        }
+    }
+}
+
+impl From<LweKeyswitchKeyInfo> for ffi::LweKeyswitchKeyInfo {
+    fn from(v: LweKeyswitchKeyInfo) -> Self {
+        Self {
+            level_count: v.level_count,
+            base_log: v.base_log,
+            input_lwe_dimension: v.input_lwe_dimension,
+            output_lwe_dimension: v.output_lwe_dimension,
+        }
+    }
+}
+
+impl From<&ffi::KeysetRestriction> for KeysetRestriction {
+    fn from(v: &ffi::KeysetRestriction) -> Self {
+        Self {
+            info: KeysetInfo {
+                lwe_secret_keys: v.info.lwe_secret_keys.iter().map(Into::into).collect(),
+                lwe_bootstrap_keys: v.info.lwe_bootstrap_keys.iter().map(Into::into).collect(),
+                lwe_keyswitch_keys: v.info.lwe_keyswitch_keys.iter().map(Into::into).collect(),
+            },
+        }
+    }
+}
+
+impl From<KeysetRestriction> for ffi::KeysetRestriction {
+    fn from(v: KeysetRestriction) -> Self {
+        Self {
+            info: ffi::KeysetInfo {
+                lwe_secret_keys: vec_into(v.info.lwe_secret_keys),
+                lwe_bootstrap_keys: vec_into(v.info.lwe_bootstrap_keys),
+                lwe_keyswitch_keys: vec_into(v.info.lwe_keyswitch_keys),
+            },
+        }
+    }
+}
+
+impl ffi::RangeRestriction {
+    fn range_restriction_to_json(&self) -> String {
+        serde_json::to_string(&RangeRestriction::from(self)).unwrap()
+    }
+}
+
+fn range_restriction_from_json(input: &str) -> Result<ffi::RangeRestriction, String> {
+    let restriction: RangeRestriction =
+        serde_json::from_str(input).map_err(|err| err.to_string())?;
+    restriction.validate().map_err(|err| err.to_string())?;
+    Ok(restriction.into())
+}
+
+impl ffi::KeysetRestriction {
+    fn keyset_restriction_to_json(&self) -> String {
+        serde_json::to_string(&KeysetRestriction::from(self)).unwrap()
+    }
+}
+
+fn keyset_restriction_from_json(input: &str) -> Result<ffi::KeysetRestriction, serde_json::Error> {
+    let restriction: KeysetRestriction = serde_json::from_str(input)?;
+    Ok(restriction.into())
+}
+
+// Json does not support nan and infinities, they are stored as strings
+mod non_finite_f64 {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_finite() {
+            serializer.serialize_f64(*value)
+        } else {
+            serializer.serialize_str(&value.to_string())
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Number(f64),
+            NonFinite(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Number(value) => Ok(value),
+            Repr::NonFinite(value) => value.parse().map_err(D::Error::custom),
+        }
+    }
+}
+
+// Shared structs cannot derive serde traits, they are implemented from a remote definition
+macro_rules! impl_serde_with_remote {
+    ($ty:ty, $remote:ty) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                <$remote>::serialize(self, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                <$remote>::deserialize(deserializer)
+            }
+        }
+    };
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::Encoding")]
+struct EncodingDef {
+    repr: u8,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::DagSolution")]
+struct DagSolutionDef {
+    input_lwe_dimension: u64,
+    internal_ks_output_lwe_dimension: u64,
+    ks_decomposition_level_count: u64,
+    ks_decomposition_base_log: u64,
+    glwe_polynomial_size: u64,
+    glwe_dimension: u64,
+    br_decomposition_level_count: u64,
+    br_decomposition_base_log: u64,
+    #[serde(with = "non_finite_f64")]
+    complexity: f64,
+    #[serde(with = "non_finite_f64")]
+    noise_max: f64,
+    #[serde(with = "non_finite_f64", default)]
+    noise_margin: f64,
+    #[serde(with = "non_finite_f64")]
+    p_error: f64,
+    #[serde(with = "non_finite_f64")]
+    global_p_error: f64,
+    use_wop_pbs: bool,
+    cb_decomposition_level_count: u64,
+    cb_decomposition_base_log: u64,
+    pp_decomposition_level_count: u64,
+    pp_decomposition_base_log: u64,
+    crt_decomposition: Vec<u64>,
+    #[serde(with = "EncodingDef", default)]
+    chosen_encoding: ffi::Encoding,
+}
+impl_serde_with_remote!(ffi::DagSolution, DagSolutionDef);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::BrDecompositionParameters")]
+struct BrDecompositionParametersDef {
+    level: u64,
+    log2_base: u64,
+}
+impl_serde_with_remote!(ffi::BrDecompositionParameters, BrDecompositionParametersDef);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::KsDecompositionParameters")]
+struct KsDecompositionParametersDef {
+    level: u64,
+    log2_base: u64,
+}
+impl_serde_with_remote!(ffi::KsDecompositionParameters, KsDecompositionParametersDef);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::SecretLweKey")]
+struct SecretLweKeyDef {
+    identifier: u64,
+    polynomial_size: u64,
+    glwe_dimension: u64,
+    description: String,
+}
+impl_serde_with_remote!(ffi::SecretLweKey, SecretLweKeyDef);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::BootstrapKey")]
+struct BootstrapKeyDef {
+    identifier: u64,
+    input_key: ffi::SecretLweKey,
+    output_key: ffi::SecretLweKey,
+    br_decomposition_parameter: ffi::BrDecompositionParameters,
+    description: String,
+}
+impl_serde_with_remote!(ffi::BootstrapKey, BootstrapKeyDef);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::KeySwitchKey")]
+struct KeySwitchKeyDef {
+    identifier: u64,
+    input_key: ffi::SecretLweKey,
+    output_key: ffi::SecretLweKey,
+    ks_decomposition_parameter: ffi::KsDecompositionParameters,
+    description: String,
+}
+impl_serde_with_remote!(ffi::KeySwitchKey, KeySwitchKeyDef);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::ConversionKeySwitchKey")]
+struct ConversionKeySwitchKeyDef {
+    identifier: u64,
+    input_key: ffi::SecretLweKey,
+    output_key: ffi::SecretLweKey,
+    ks_decomposition_parameter: ffi::KsDecompositionParameters,
+    fast_keyswitch: bool,
+    description: String,
+}
+impl_serde_with_remote!(ffi::ConversionKeySwitchKey, ConversionKeySwitchKeyDef);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::CircuitBoostrapKey")]
+struct CircuitBoostrapKeyDef {
+    identifier: u64,
+    representation_key: ffi::SecretLweKey,
+    br_decomposition_parameter: ffi::BrDecompositionParameters,
+    description: String,
+}
+impl_serde_with_remote!(ffi::CircuitBoostrapKey, CircuitBoostrapKeyDef);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::PrivateFunctionalPackingBoostrapKey")]
+struct PrivateFunctionalPackingBoostrapKeyDef {
+    identifier: u64,
+    representation_key: ffi::SecretLweKey,
+    br_decomposition_parameter: ffi::BrDecompositionParameters,
+    description: String,
+}
+impl_serde_with_remote!(
+    ffi::PrivateFunctionalPackingBoostrapKey,
+    PrivateFunctionalPackingBoostrapKeyDef
+);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::CircuitKeys")]
+struct CircuitKeysDef {
+    secret_keys: Vec<ffi::SecretLweKey>,
+    keyswitch_keys: Vec<ffi::KeySwitchKey>,
+    bootstrap_keys: Vec<ffi::BootstrapKey>,
+    conversion_keyswitch_keys: Vec<ffi::ConversionKeySwitchKey>,
+    circuit_bootstrap_keys: Vec<ffi::CircuitBoostrapKey>,
+    private_functional_packing_keys: Vec<ffi::PrivateFunctionalPackingBoostrapKey>,
+}
+impl_serde_with_remote!(ffi::CircuitKeys, CircuitKeysDef);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::InstructionKeys")]
+struct InstructionKeysDef {
+    input_key: u64,
+    tlu_keyswitch_key: u64,
+    tlu_bootstrap_key: u64,
+    tlu_circuit_bootstrap_key: u64,
+    tlu_private_functional_packing_key: u64,
+    output_key: u64,
+    extra_conversion_keys: Vec<u64>,
+}
+impl_serde_with_remote!(ffi::InstructionKeys, InstructionKeysDef);
+
+#[derive(Serialize, Deserialize)]
+#[serde(remote = "ffi::CircuitSolution")]
+struct CircuitSolutionDef {
+    circuit_keys: ffi::CircuitKeys,
+    instructions_keys: Vec<ffi::InstructionKeys>,
+    crt_decomposition: Vec<u64>,
+    #[serde(with = "non_finite_f64")]
+    complexity: f64,
+    #[serde(with = "non_finite_f64")]
+    p_error: f64,
+    #[serde(with = "non_finite_f64")]
+    global_p_error: f64,
+    is_feasible: bool,
+    error_msg: String,
+    #[serde(default)]
+    used_external_partitions: Vec<String>,
+    #[serde(default)]
+    unshared_secret_key_count: u64,
+}
+impl_serde_with_remote!(ffi::CircuitSolution, CircuitSolutionDef);
+
+impl ffi::DagSolution {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    fn estimated_seconds(&self, options: &ffi::Options) -> f64 {
+        if options.complexity_to_seconds == 0.0 {
+            return f64::NAN;
+        }
+        self.complexity * options.complexity_to_seconds
+    }
 
-        let out_shape = Shape {
-            dimensions_size: out_shape.to_owned(),
+    // Size in bytes of the tlu bootstrap key, 0 for an infeasible solution
+    fn bootstrap_key_bytes(&self) -> u64 {
+        if self.glwe_polynomial_size == 0 {
+            return 0;
+        }
+        let glwe_params = GlweParameters {
+            log2_polynomial_size: self.glwe_polynomial_size.ilog2() as u64,
+            glwe_dimension: self.glwe_dimension,
         };
-
-        self.0
-            .add_max_noise(inputs, out_shape, location.0.clone())
-            .into()
+        decomposition::common::bootstrap_key_size(
+            self.internal_ks_output_lwe_dimension,
+            self.br_decomposition_level_count,
+            glwe_params,
+        ) as u64
     }
 
-    fn add_round_op(
-        &mut self,
-        input: ffi::OperatorIndex,
-        rounded_precision: Precision,
-        location: &Location,
-    ) -> ffi::OperatorIndex {
-        self.0
-            .add_round_op(input.into(), rounded_precision, location.0.clone())
-            .into()
+    // Size in bytes of the tlu keyswitch key, 0 for an infeasible solution
+    fn keyswitch_key_bytes(&self) -> u64 {
+        decomposition::common::keyswitch_key_size(
+            self.input_lwe_dimension,
+            self.ks_decomposition_level_count,
+            self.internal_ks_output_lwe_dimension,
+        ) as u64
     }
 
-    fn add_unsafe_cast_op(
-        &mut self,
-        input: ffi::OperatorIndex,
-        new_precision: Precision,
-        location: &Location,
-    ) -> ffi::OperatorIndex {
-        self.0
-            .add_unsafe_cast(input.into(), new_precision, location.0.clone())
-            .into()
+    fn log2_p_error(&self) -> f64 {
+        log2_p_error(self.p_error)
     }
+}
 
-    fn add_change_partition_with_src(
-        &mut self,
-        input: ffi::OperatorIndex,
-        src_partition: &ExternalPartition,
-        location: &Location,
-    ) -> ffi::OperatorIndex {
-        self.0
-            .add_change_partition(
-                input.into(),
-                Some(src_partition.0.clone()),
-                None,
-                location.0.clone(),
-            )
-            .into()
+impl ffi::Solution {
+    fn log2_p_error(&self) -> f64 {
+        log2_p_error(self.p_error)
     }
+}
 
-    fn add_change_partition_with_dst(
-        &mut self,
-        input: ffi::OperatorIndex,
-        dst_partition: &ExternalPartition,
-        location: &Location,
-    ) -> ffi::OperatorIndex {
-        self.0
-            .add_change_partition(
-                input.into(),
-                None,
-                Some(dst_partition.0.clone()),
-                location.0.clone(),
-            )
-            .into()
+// Reliability as -log2(p_error) bits, infinite for a null p_error and 0 for an infeasible solution
+fn log2_p_error(p_error: f64) -> f64 {
+    if p_error == 0.0 {
+        return f64::INFINITY;
     }
+    -p_error.log2()
+}
 
-    fn tag_operator_as_output(&mut self, op: ffi::OperatorIndex) {
-        self.0.tag_operator_as_output(op.into());
-    }
+fn dag_solution_from_json(input: &str) -> Result<ffi::DagSolution, serde_json::Error> {
+    serde_json::from_str(input)
+}
 
-    fn dump(&self) -> String {
-        format!("{}", self.0.get_circuit())
+impl ffi::CircuitSolution {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
     }
 }
 
-#[derive(Clone)]
-pub struct Location(operator::Location);
+fn circuit_solution_from_json(input: &str) -> Result<ffi::CircuitSolution, serde_json::Error> {
+    serde_json::from_str(input)
+}
 
-fn location_unknown() -> Box<Location> {
-    Box::new(Location(operator::Location::Unknown))
+// Binary format of circuit solutions, all numbers are little endian.
+// Bump the version on any layout change, older versions are rejected.
+const CIRCUIT_SOLUTION_MAGIC: &[u8; 4] = b"COCS";
+const CIRCUIT_SOLUTION_FORMAT_VERSION: u32 = 3;
+
+trait BinaryEncode {
+    fn encode(&self, out: &mut Vec<u8>);
 }
 
-fn location_from_string(string: &str) -> Box<Location> {
-    let location: Vec<&str> = string.split(':').collect();
-    match location[..] {
-        [file] => Box::new(Location(operator::Location::File(file.into()))),
-        [file, line] => Box::new(Location(operator::Location::Line(
-            file.into(),
-            line.parse().unwrap(),
-        ))),
-        [file, line, column] => Box::new(Location(operator::Location::LineColumn(
-            file.into(),
-            line.parse().unwrap(),
-            column.parse().unwrap(),
-        ))),
-        _ => Box::new(Location(operator::Location::Unknown)),
-    }
+trait BinaryDecode: Sized {
+    fn decode(input: &mut &[u8]) -> Result<Self, String>;
 }
 
-fn generate_virtual_keyset_info(
-    inputs: Vec<ffi::PartitionDefinition>,
-    generate_fks: bool,
-    options: &ffi::Options,
-) -> ffi::CircuitKeys {
-    let config = Config {
-        security_level: options.security_level,
-        maximum_acceptable_error_probability: options.maximum_acceptable_error_probability,
-        key_sharing: options.key_sharing,
-        ciphertext_modulus_log: options.ciphertext_modulus_log,
-        fft_precision: options.fft_precision,
-        complexity_model: &CpuComplexity::default(),
-    };
-    generate_virtual_parameters(
-        inputs
-            .into_iter()
-            .map(
-                |ffi::PartitionDefinition { precision, norm2 }| concrete_optimizer::optimization::dag::multi_parameters::virtual_circuit::PartitionDefinition { precision, norm2 },
-            )
-            .collect(),
-        generate_fks,
-        config
-    )
-    .into()
+fn take_bytes<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if input.len() < len {
+        return Err("Truncated circuit solution".into());
+    }
+    let (bytes, rest) = input.split_at(len);
+    *input = rest;
+    Ok(bytes)
 }
 
-pub struct Weights(operator::Weights);
+impl BinaryEncode for u64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.to_le_bytes());
+    }
+}
 
-fn vector(weights: &[i64]) -> Box<Weights> {
-    Box::new(Weights(operator::Weights::vector(weights)))
+impl BinaryDecode for u64 {
+    fn decode(input: &mut &[u8]) -> Result<Self, String> {
+        Ok(Self::from_le_bytes(
+            take_bytes(input, 8)?.try_into().unwrap(),
+        ))
+    }
 }
 
-fn number(weight: i64) -> Box<Weights> {
-    Box::new(Weights(operator::Weights::number(weight)))
+impl BinaryEncode for f64 {
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.to_bits().encode(out);
+    }
 }
 
-impl From<OperatorIndex> for ffi::OperatorIndex {
-    fn from(oi: OperatorIndex) -> Self {
-        Self { index: oi.0 }
+impl BinaryDecode for f64 {
+    fn decode(input: &mut &[u8]) -> Result<Self, String> {
+        u64::decode(input).map(Self::from_bits)
     }
 }
 
-#[allow(clippy::from_over_into)]
-impl Into<OperatorIndex> for ffi::OperatorIndex {
-    fn into(self) -> OperatorIndex {
-        OperatorIndex(self.index)
+impl BinaryEncode for bool {
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(u8::from(*self));
     }
 }
 
-#[allow(clippy::from_over_into)]
-impl Into<Encoding> for ffi::Encoding {
-    fn into(self) -> Encoding {
-        match self {
-            Self::Auto => Encoding::Auto,
-            Self::Native => Encoding::Native,
-            Self::Crt => Encoding::Crt,
-            _ => unreachable!("Internal error: Invalid encoding"),
+impl BinaryDecode for bool {
+    fn decode(input: &mut &[u8]) -> Result<Self, String> {
+        match take_bytes(input, 1)?[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => Err(format!("Invalid boolean {v} in circuit solution")),
         }
     }
 }
 
-impl ffi::RangeRestriction {
-    fn range_restriction_to_json(&self) -> String {
-        unsafe {
-            serde_json::to_string(std::mem::transmute::<&Self, &RangeRestriction>(self)).unwrap()
+impl BinaryEncode for String {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode(out);
+        out.extend_from_slice(self.as_bytes());
+    }
+}
+
+impl BinaryDecode for String {
+    fn decode(input: &mut &[u8]) -> Result<Self, String> {
+        let len = u64::decode(input)? as usize;
+        let bytes = take_bytes(input, len)?;
+        Self::from_utf8(bytes.to_vec()).map_err(|err| err.to_string())
+    }
+}
+
+impl<T: BinaryEncode> BinaryEncode for Vec<T> {
+    fn encode(&self, out: &mut Vec<u8>) {
+        (self.len() as u64).encode(out);
+        for v in self {
+            v.encode(out);
         }
     }
 }
 
-fn range_restriction_from_json(input: &str) -> ffi::RangeRestriction {
-    unsafe {
-        std::mem::transmute::<RangeRestriction, ffi::RangeRestriction>(
-            serde_json::from_str(input).unwrap(),
-        )
+impl<T: BinaryDecode> BinaryDecode for Vec<T> {
+    fn decode(input: &mut &[u8]) -> Result<Self, String> {
+        let len = u64::decode(input)? as usize;
+        // the length is untrusted, it cannot be used to preallocate
+        let mut result = Self::new();
+        for _ in 0..len {
+            result.push(T::decode(input)?);
+        }
+        Ok(result)
     }
 }
 
-impl ffi::KeysetRestriction {
-    fn keyset_restriction_to_json(&self) -> String {
-        unsafe {
-            serde_json::to_string(std::mem::transmute::<&Self, &KeysetRestriction>(self)).unwrap()
+// Fields are encoded in the listed order
+macro_rules! impl_binary_struct {
+    ($ty:ty { $($field:ident),* $(,)? }) => {
+        impl BinaryEncode for $ty {
+            fn encode(&self, out: &mut Vec<u8>) {
+                $(self.$field.encode(out);)*
+            }
         }
+
+        impl BinaryDecode for $ty {
+            fn decode(input: &mut &[u8]) -> Result<Self, String> {
+                Ok(Self {
+                    $($field: BinaryDecode::decode(input)?,)*
+                })
+            }
+        }
+    };
+}
+
+impl_binary_struct!(ffi::BrDecompositionParameters { level, log2_base });
+impl_binary_struct!(ffi::KsDecompositionParameters { level, log2_base });
+impl_binary_struct!(ffi::SecretLweKey {
+    identifier,
+    polynomial_size,
+    glwe_dimension,
+    description,
+});
+impl_binary_struct!(ffi::BootstrapKey {
+    identifier,
+    input_key,
+    output_key,
+    br_decomposition_parameter,
+    description,
+});
+impl_binary_struct!(ffi::KeySwitchKey {
+    identifier,
+    input_key,
+    output_key,
+    ks_decomposition_parameter,
+    description,
+});
+impl_binary_struct!(ffi::ConversionKeySwitchKey {
+    identifier,
+    input_key,
+    output_key,
+    ks_decomposition_parameter,
+    fast_keyswitch,
+    description,
+});
+impl_binary_struct!(ffi::CircuitBoostrapKey {
+    identifier,
+    representation_key,
+    br_decomposition_parameter,
+    description,
+});
+impl_binary_struct!(ffi::PrivateFunctionalPackingBoostrapKey {
+    identifier,
+    representation_key,
+    br_decomposition_parameter,
+    description,
+});
+impl_binary_struct!(ffi::CircuitKeys {
+    secret_keys,
+    keyswitch_keys,
+    bootstrap_keys,
+    conversion_keyswitch_keys,
+    circuit_bootstrap_keys,
+    private_functional_packing_keys,
+});
+impl_binary_struct!(ffi::InstructionKeys {
+    input_key,
+    tlu_keyswitch_key,
+    tlu_bootstrap_key,
+    tlu_circuit_bootstrap_key,
+    tlu_private_functional_packing_key,
+    output_key,
+    extra_conversion_keys,
+});
+impl_binary_struct!(ffi::CircuitSolution {
+    circuit_keys,
+    instructions_keys,
+    crt_decomposition,
+    complexity,
+    p_error,
+    global_p_error,
+    is_feasible,
+    error_msg,
+    used_external_partitions,
+    unshared_secret_key_count,
+});
+
+impl ffi::CircuitSolution {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut out = CIRCUIT_SOLUTION_MAGIC.to_vec();
+        out.extend_from_slice(&CIRCUIT_SOLUTION_FORMAT_VERSION.to_le_bytes());
+        self.encode(&mut out);
+        out
     }
 }
 
-fn keyset_restriction_from_json(input: &str) -> ffi::KeysetRestriction {
-    unsafe {
-        std::mem::transmute::<KeysetRestriction, ffi::KeysetRestriction>(
-            serde_json::from_str(input).unwrap(),
-        )
+fn circuit_solution_from_bytes(bytes: &[u8]) -> Result<ffi::CircuitSolution, String> {
+    let mut input = bytes;
+    if take_bytes(&mut input, 4)? != CIRCUIT_SOLUTION_MAGIC {
+        return Err("Not a circuit solution".into());
+    }
+    let version = u32::from_le_bytes(take_bytes(&mut input, 4)?.try_into().unwrap());
+    if version != CIRCUIT_SOLUTION_FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported circuit solution format version {version}, expected {CIRCUIT_SOLUTION_FORMAT_VERSION}"
+        ));
     }
+    let solution = ffi::CircuitSolution::decode(&mut input)?;
+    if !input.is_empty() {
+        return Err("Trailing bytes after circuit solution".into());
+    }
+    Ok(solution)
 }
 
 #[allow(
@@ -1014,11 +3235,31 @@ fn keyset_restriction_from_json(input: &str) -> ffi::KeysetRestriction {
 )]
 #[cxx::bridge]
 mod ffi {
+    unsafe extern "C++" {
+        include!("concrete-optimizer-log.hpp");
+    }
+
     #[namespace = "concrete_optimizer"]
     extern "Rust" {
 
         #[namespace = "concrete_optimizer::v0"]
-        fn optimize_bootstrap(precision: u64, noise_factor: f64, options: &Options) -> Solution;
+        fn optimize_bootstrap(
+            precision: u64,
+            noise_factor: f64,
+            options: &Options,
+        ) -> Result<Solution>;
+
+        #[namespace = "concrete_optimizer::v0"]
+        fn optimize_atomic_pattern(
+            precision: u64,
+            noise_factor: f64,
+            sum_size: u64,
+            options: &Options,
+        ) -> Result<Solution>;
+
+        fn check_security_curve_json(input: &str) -> Result<()>;
+
+        fn validate_options(options: &Options) -> Result<()>;
 
         #[namespace = "concrete_optimizer::utils"]
         fn convert_to_dag_solution(solution: &Solution) -> DagSolution;
@@ -1026,6 +3267,9 @@ mod ffi {
         #[namespace = "concrete_optimizer::utils"]
         fn convert_to_circuit_solution(solution: &DagSolution, dag: &Dag) -> CircuitSolution;
 
+        #[namespace = "concrete_optimizer::dag"]
+        fn required_keys(self: &DagSolution) -> CircuitKeys;
+
         type Dag;
 
         type DagBuilder<'dag>;
@@ -1034,12 +3278,53 @@ mod ffi {
 
         type ExternalPartition;
 
+        fn name(self: &ExternalPartition) -> String;
+
+        fn max_variance(self: &ExternalPartition) -> f64;
+
+        fn variance(self: &ExternalPartition) -> f64;
+
+        type CancellationToken;
+
+        #[namespace = "concrete_optimizer::utils"]
+        fn cancellation_token() -> Box<CancellationToken>;
+
+        fn cancel(self: &CancellationToken);
+
+        fn is_cancelled(self: &CancellationToken) -> bool;
+
+        type ProgressCounter;
+
+        type PreparedRestriction;
+
+        #[namespace = "concrete_optimizer::restriction"]
+        fn prepare_keyset_restriction(
+            restriction: &KeysetRestriction,
+        ) -> Result<Box<PreparedRestriction>>;
+
+        #[namespace = "concrete_optimizer::utils"]
+        fn progress_counter() -> Box<ProgressCounter>;
+
+        fn steps(self: &ProgressCounter) -> u32;
+
         #[namespace = "concrete_optimizer::utils"]
         fn location_unknown() -> Box<Location>;
 
         #[namespace = "concrete_optimizer::utils"]
         fn location_from_string(string: &str) -> Box<Location>;
 
+        #[namespace = "concrete_optimizer::utils"]
+        fn cache_directory() -> String;
+
+        #[namespace = "concrete_optimizer::utils"]
+        fn clear_cache() -> Result<()>;
+
+        #[namespace = "concrete_optimizer::utils"]
+        fn warm_cache(options: &Options);
+
+        #[namespace = "concrete_optimizer::utils"]
+        fn estimate_search_space_size(options: &Options) -> u64;
+
         #[namespace = "concrete_optimizer::utils"]
         fn generate_virtual_keyset_info(
             partitions: Vec<PartitionDefinition>,
@@ -1047,6 +3332,13 @@ mod ffi {
             options: &Options,
         ) -> CircuitKeys;
 
+        #[namespace = "concrete_optimizer::utils"]
+        fn generate_virtual_keyset_info_with_conversions(
+            partitions: Vec<PartitionDefinition>,
+            conversions: Vec<PartitionConversion>,
+            options: &Options,
+        ) -> Result<CircuitKeys>;
+
         #[namespace = "concrete_optimizer::utils"]
         fn get_external_partition(
             name: String,
@@ -1055,7 +3347,7 @@ mod ffi {
             internal_dim: u64,
             max_variance: f64,
             variance: f64,
-        ) -> Box<ExternalPartition>;
+        ) -> Result<Box<ExternalPartition>>;
 
         #[namespace = "concrete_optimizer::utils"]
         fn get_noise_br(
@@ -1065,15 +3357,46 @@ mod ffi {
             lwe_dim: u64,
             pbs_level: u64,
             pbs_log2_base: u64,
+        ) -> Result<f64>;
+
+        #[namespace = "concrete_optimizer::utils"]
+        fn get_noise_ks(
+            options: &Options,
+            input_lwe_dim: u64,
+            output_lwe_dim: u64,
+            ks_level: u64,
+            ks_log2_base: u64,
+        ) -> Result<f64>;
+
+        #[namespace = "concrete_optimizer::utils"]
+        fn get_noise_modulus_switch(
+            options: &Options,
+            lwe_dim: u64,
+            log2_polynomial_size: u64,
         ) -> f64;
 
+        #[namespace = "concrete_optimizer::utils"]
+        fn enumerate_pbs_decompositions(
+            options: &Options,
+            log2_polynomial_size: u64,
+            glwe_dimension: u64,
+            lwe_dim: u64,
+        ) -> Vec<DecompositionCandidate>;
+
         #[namespace = "concrete_optimizer::dag"]
         fn empty() -> Box<Dag>;
 
+        #[namespace = "concrete_optimizer::dag"]
+        fn dag_from_json(input: &str) -> Result<Box<Dag>>;
+
         unsafe fn builder(self: &mut Dag, circuit: String) -> Box<DagBuilder<'_>>;
 
         fn dump(self: &Dag) -> String;
 
+        fn to_json(self: &Dag) -> String;
+
+        fn to_dot(self: &Dag) -> String;
+
         fn dump(self: &DagBuilder) -> String;
 
         unsafe fn add_input(
@@ -1083,6 +3406,36 @@ mod ffi {
             location: &Location,
         ) -> OperatorIndex;
 
+        unsafe fn add_input_with_variance(
+            self: &mut DagBuilder<'_>,
+            out_precision: u8,
+            out_shape: &[u64],
+            input_variance: f64,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_input_in_partition(
+            self: &mut DagBuilder<'_>,
+            out_precision: u8,
+            out_shape: &[u64],
+            partition: &ExternalPartition,
+            location: &Location,
+        ) -> OperatorIndex;
+
+        unsafe fn add_inputs(
+            self: &mut DagBuilder<'_>,
+            specs: &[InputSpec],
+            location: &Location,
+        ) -> Vec<OperatorIndex>;
+
+        unsafe fn add_input_with_comment(
+            self: &mut DagBuilder<'_>,
+            out_precision: u8,
+            out_shape: &[u64],
+            comment: &str,
+            location: &Location,
+        ) -> OperatorIndex;
+
         unsafe fn add_zero_noise(
             self: &mut DagBuilder<'_>,
             out_precision: u8,
@@ -1092,18 +3445,68 @@ mod ffi {
 
         unsafe fn add_lut(
             self: &mut DagBuilder<'_>,
-            input: OperatorIndex,
-            table: &[u64],
-            out_precision: u8,
+            input: OperatorIndex,
+            table: &[u64],
+            out_precision: u8,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_lut_with_comment(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            table: &[u64],
+            out_precision: u8,
+            comment: &str,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_signed_lut(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            table: &[u64],
+            out_precision: u8,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_multi_lut(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            tables: &[LutTable],
+            out_precision: u8,
+            location: &Location,
+        ) -> Result<Vec<OperatorIndex>>;
+
+        unsafe fn add_rounded_lut(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            table: &[u64],
+            rounded_precision: u8,
+            out_precision: u8,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_dot(
+            self: &mut DagBuilder<'_>,
+            inputs: &[OperatorIndex],
+            weights: Box<Weights>,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_dot_with_norm(
+            self: &mut DagBuilder<'_>,
+            inputs: &[OperatorIndex],
+            weights: Box<Weights>,
+            manual_norm2: f64,
             location: &Location,
-        ) -> OperatorIndex;
+        ) -> Result<OperatorIndex>;
 
-        unsafe fn add_dot(
+        unsafe fn add_dot_with_comment(
             self: &mut DagBuilder<'_>,
             inputs: &[OperatorIndex],
             weights: Box<Weights>,
+            comment: &str,
             location: &Location,
-        ) -> OperatorIndex;
+        ) -> Result<OperatorIndex>;
 
         unsafe fn add_linear_noise(
             self: &mut DagBuilder<'_>,
@@ -1116,6 +3519,41 @@ mod ffi {
             location: &Location,
         ) -> OperatorIndex;
 
+        unsafe fn add_broadcast_linear_noise(
+            self: &mut DagBuilder<'_>,
+            inputs: &[OperatorIndex],
+            lwe_dim_cost_factor: f64,
+            fixed_cost: f64,
+            weights: &[f64],
+            comment: &str,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_broadcast_max_noise(
+            self: &mut DagBuilder<'_>,
+            inputs: &[OperatorIndex],
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_sub(
+            self: &mut DagBuilder<'_>,
+            lhs: OperatorIndex,
+            rhs: OperatorIndex,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_negate(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_identity(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
         unsafe fn add_max_noise(
             self: &mut DagBuilder<'_>,
             inputs: &[OperatorIndex],
@@ -1123,6 +3561,23 @@ mod ffi {
             location: &Location,
         ) -> OperatorIndex;
 
+        unsafe fn add_tree_max(
+            self: &mut DagBuilder<'_>,
+            inputs: &[OperatorIndex],
+            out_shape: &[u64],
+            out_precision: u8,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        unsafe fn add_tree_pbs(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            table: &[u64],
+            out_precision: u8,
+            decomposition_base: u8,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
         unsafe fn add_round_op(
             self: &mut DagBuilder<'_>,
             input: OperatorIndex,
@@ -1151,9 +3606,66 @@ mod ffi {
             location: &Location,
         ) -> OperatorIndex;
 
+        fn add_change_partition(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            src_partition: &ExternalPartition,
+            dst_partition: &ExternalPartition,
+            location: &Location,
+        ) -> OperatorIndex;
+
+        fn add_keyswitch(
+            self: &mut DagBuilder<'_>,
+            input: OperatorIndex,
+            dst_partition: &ExternalPartition,
+            location: &Location,
+        ) -> OperatorIndex;
+
         unsafe fn tag_operator_as_output(self: &mut DagBuilder<'_>, op: OperatorIndex);
 
-        fn optimize(self: &Dag, options: &Options) -> DagSolution;
+        unsafe fn tag_operators_as_output(
+            self: &mut DagBuilder<'_>,
+            ops: &[OperatorIndex],
+        ) -> Result<()>;
+
+        /// Adds an identity bootstrap on `op`, which must not have users yet, and returns its
+        /// index. The existing indices are not changed.
+        fn force_bootstrap(
+            self: &mut DagBuilder<'_>,
+            op: OperatorIndex,
+            location: &Location,
+        ) -> Result<OperatorIndex>;
+
+        fn optimize(self: &Dag, options: &Options) -> Result<DagSolution>;
+
+        fn optimize_with_progress(
+            self: &Dag,
+            options: &Options,
+            progress: &ProgressCounter,
+        ) -> Result<DagSolution>;
+
+        fn optimize_pareto(self: &Dag, options: &Options) -> Vec<DagSolution>;
+
+        fn optimize_multi_security(
+            self: &Dag,
+            security_levels: &[u64],
+            options: &Options,
+        ) -> Vec<DagSolution>;
+
+        fn optimize_sweep_fft_precision(
+            self: &Dag,
+            options: &Options,
+            candidates: &[u32],
+        ) -> Vec<DagSolution>;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn optimize_many(dags: &[Box<Dag>], options: &Options) -> Vec<DagSolution>;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn clear_solution_cache();
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn evaluate_solution(dag: &Dag, solution: &DagSolution, options: &Options) -> DagSolution;
 
         unsafe fn add_composition<'a>(
             self: &mut Dag,
@@ -1163,14 +3675,70 @@ mod ffi {
             to_pos: usize,
         );
 
+        fn add_composition_by_index(
+            self: &mut Dag,
+            from_output: OperatorIndex,
+            to_input: OperatorIndex,
+        ) -> Result<()>;
+
         fn add_all_compositions(self: &mut Dag);
 
+        fn add_all_compositions_matching(self: &mut Dag);
+
+        fn check_compositions(self: &Dag) -> Vec<CompositionError>;
+
+        fn is_composed(self: &Dag) -> bool;
+
+        fn merge(self: &mut Dag, other: &Dag) -> Result<Vec<OperatorIndex>>;
+
+        fn remove_operator(self: &mut Dag, index: OperatorIndex) -> Result<()>;
+
+        fn replace_lut_table(self: &mut Dag, index: OperatorIndex, table: &[u64]) -> Result<()>;
+
+        fn set_partition_assignment(self: &mut Dag, assignment: &[u64]) -> Result<()>;
+
         #[namespace = "concrete_optimizer::dag"]
         fn dump(self: &CircuitSolution) -> String;
 
         #[namespace = "concrete_optimizer::dag"]
         fn short_dump(self: &CircuitSolution) -> String;
 
+        #[namespace = "concrete_optimizer::dag"]
+        fn diff(self: &CircuitSolution, other: &CircuitSolution) -> String;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn to_json(self: &CircuitSolution) -> String;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn circuit_solution_from_json(input: &str) -> Result<CircuitSolution>;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn to_bytes(self: &CircuitSolution) -> Vec<u8>;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn circuit_solution_from_bytes(bytes: &[u8]) -> Result<CircuitSolution>;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn to_json(self: &DagSolution) -> String;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn estimated_seconds(self: &DagSolution, options: &Options) -> f64;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn bootstrap_key_bytes(self: &DagSolution) -> u64;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn keyswitch_key_bytes(self: &DagSolution) -> u64;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn log2_p_error(self: &DagSolution) -> f64;
+
+        #[namespace = "concrete_optimizer::v0"]
+        fn log2_p_error(self: &Solution) -> f64;
+
+        #[namespace = "concrete_optimizer::dag"]
+        fn dag_solution_from_json(input: &str) -> Result<DagSolution>;
+
         type Weights;
 
         #[namespace = "concrete_optimizer::weights"]
@@ -1179,27 +3747,80 @@ mod ffi {
         #[namespace = "concrete_optimizer::weights"]
         fn number(weight: i64) -> Box<Weights>;
 
+        #[namespace = "concrete_optimizer::weights"]
+        fn try_vector(weights: &[i64]) -> Result<Box<Weights>>;
+
+        #[namespace = "concrete_optimizer::weights"]
+        fn try_number(weight: i64) -> Result<Box<Weights>>;
+
+        #[namespace = "concrete_optimizer::weights"]
+        fn float_vector(weights: &[f64]) -> Box<Weights>;
+
         fn get_circuit_count(self: &Dag) -> usize;
 
+        fn get_circuit_names(self: &Dag) -> Vec<String>;
+
+        fn optimize_with_noise_map(
+            self: &Dag,
+            options: &Options,
+        ) -> Result<DagSolutionWithNoiseMap>;
+
+        fn optimize_with_budget_report(
+            self: &Dag,
+            options: &Options,
+        ) -> Result<DagSolutionWithBudgetReport>;
+
         fn optimize_multi(self: &Dag, options: &Options) -> CircuitSolution;
 
+        fn is_feasible(self: &Dag, options: &Options) -> bool;
+
+        fn optimize_multi_with_report(self: &Dag, options: &Options) -> CircuitSolutionWithReport;
+
+        fn optimize_multi_cancellable(
+            self: &Dag,
+            options: &Options,
+            cancel: &CancellationToken,
+        ) -> CircuitSolution;
+
+        fn optimize_multi_with_prepared(
+            self: &Dag,
+            options: &Options,
+            restriction: &PreparedRestriction,
+        ) -> CircuitSolution;
+
+        fn optimize_multi_with_progress(
+            self: &Dag,
+            options: &Options,
+            progress: &ProgressCounter,
+        ) -> CircuitSolution;
+
         fn get_input_indices(self: &Dag) -> Vec<OperatorIndex>;
 
         fn get_output_indices(self: &Dag) -> Vec<OperatorIndex>;
 
+        fn get_operator_precision(self: &Dag, index: OperatorIndex) -> Result<u8>;
+
+        fn get_operator_shape(self: &Dag, index: OperatorIndex) -> Result<Vec<u64>>;
+
+        fn get_operator_comment(self: &Dag, index: OperatorIndex) -> Result<String>;
+
+        fn get_operator_norm2(self: &Dag, index: OperatorIndex) -> Result<f64>;
+
+        fn operator_kind_histogram(self: &Dag) -> Vec<OperatorKindCount>;
+
         fn NO_KEY_ID() -> u64;
 
         #[namespace = "concrete_optimizer::restriction"]
         fn range_restriction_to_json(self: &RangeRestriction) -> String;
 
         #[namespace = "concrete_optimizer::restriction"]
-        fn range_restriction_from_json(input: &str) -> RangeRestriction;
+        fn range_restriction_from_json(input: &str) -> Result<RangeRestriction>;
 
         #[namespace = "concrete_optimizer::restriction"]
         fn keyset_restriction_to_json(self: &KeysetRestriction) -> String;
 
         #[namespace = "concrete_optimizer::restriction"]
-        fn keyset_restriction_from_json(input: &str) -> KeysetRestriction;
+        fn keyset_restriction_from_json(input: &str) -> Result<KeysetRestriction>;
     }
 
     #[derive(Debug, Clone, Copy)]
@@ -1210,12 +3831,53 @@ mod ffi {
         Crt,
     }
 
-    #[derive(Clone, Copy)]
+    #[derive(Debug, Clone, Copy)]
+    #[namespace = "concrete_optimizer"]
+    pub enum Objective {
+        MinComplexity,
+        MinKeySize,
+        Weighted,
+        MaxThroughput,
+    }
+
+    #[derive(Debug, Clone, Copy)]
     #[namespace = "concrete_optimizer::dag"]
     struct OperatorIndex {
         index: usize,
     }
 
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone)]
+    pub struct CompositionError {
+        pub from_circuit: String,
+        pub from: OperatorIndex,
+        pub from_precision: u8,
+        pub to_circuit: String,
+        pub to: OperatorIndex,
+        pub to_precision: u8,
+        pub message: String,
+    }
+
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone)]
+    pub struct LutTable {
+        pub values: Vec<u64>,
+    }
+
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone)]
+    pub struct InputSpec {
+        pub precision: u8,
+        pub shape: Vec<u64>,
+    }
+
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone)]
+    pub struct OperatorKindCount {
+        pub name: String,
+        pub count: u64,
+    }
+
     #[namespace = "concrete_optimizer::v0"]
     #[derive(Debug, Clone, Copy, Default)]
     pub struct Solution {
@@ -1229,7 +3891,8 @@ mod ffi {
         pub br_decomposition_base_log: u64,        //b(BR)
         pub complexity: f64,
         pub noise_max: f64,
-        pub p_error: f64, // error probability
+        pub noise_margin: f64, // failure bound variance / noise_max, near 1.0 means fragile
+        pub p_error: f64,      // error probability
     }
 
     #[namespace = "concrete_optimizer::dag"]
@@ -1245,7 +3908,8 @@ mod ffi {
         pub br_decomposition_base_log: u64,        //b(BR)
         pub complexity: f64,
         pub noise_max: f64,
-        pub p_error: f64, // error probability
+        pub noise_margin: f64, // failure bound variance / noise_max, near 1.0 means fragile
+        pub p_error: f64,      // error probability
         pub global_p_error: f64,
         pub use_wop_pbs: bool,
         pub cb_decomposition_level_count: u64,
@@ -1253,6 +3917,7 @@ mod ffi {
         pub pp_decomposition_level_count: u64,
         pub pp_decomposition_base_log: u64,
         pub crt_decomposition: Vec<u64>,
+        pub chosen_encoding: Encoding, // Native or Crt, as resolved from Options::encoding
     }
 
     #[derive(Debug, Clone, Copy)]
@@ -1260,12 +3925,21 @@ mod ffi {
     pub enum MultiParamStrategy {
         ByPrecision,
         ByPrecisionAndNorm2,
+        Explicit, // uses Dag::set_partition_assignment
+    }
+
+    #[derive(Debug, Clone, Copy)]
+    #[namespace = "concrete_optimizer"]
+    pub enum GpuPbsType {
+        Amortized,
+        LowLatency,
     }
 
     #[namespace = "concrete_optimizer::restriction"]
     #[derive(Debug, Clone)]
     pub struct RangeRestriction {
         pub glwe_log_polynomial_sizes: Vec<u64>,
+        pub glwe_polynomial_sizes: Vec<u64>,
         pub glwe_dimensions: Vec<u64>,
         pub internal_lwe_dimensions: Vec<u64>,
         pub pbs_level_count: Vec<u64>,
@@ -1274,6 +3948,27 @@ mod ffi {
         pub ks_base_log: Vec<u64>,
     }
 
+    // Partitions which must share their big secret key
+    #[namespace = "concrete_optimizer"]
+    #[derive(Debug, Clone, Default)]
+    pub struct KeySharingGroup {
+        pub partitions: Vec<u64>,
+    }
+
+    // Inclusive bounds of the searched parameters, 0 means unbounded
+    #[namespace = "concrete_optimizer"]
+    #[derive(Debug, Clone, Copy, Default)]
+    pub struct SearchSpaceOptions {
+        pub internal_lwe_dimension_min: u64,
+        pub internal_lwe_dimension_max: u64,
+        pub glwe_dimension_min: u64,
+        pub glwe_dimension_max: u64,
+        pub log2_polynomial_size_min: u64,
+        pub log2_polynomial_size_max: u64,
+        pub decomposition_level_min: u64, // only applied by the multi parameters optimization
+        pub decomposition_level_max: u64, // only applied by the multi parameters optimization
+    }
+
     #[namespace = "concrete_optimizer"]
     #[derive(Debug, Clone)]
     pub struct Options {
@@ -1289,6 +3984,27 @@ mod ffi {
         pub fft_precision: u32,
         pub range_restriction: SharedPtr<RangeRestriction>, // SharedPtr used for Options since optionals are not available...
         pub keyset_restriction: SharedPtr<KeysetRestriction>, // SharedPtr used for Options since optionals are not available...
+        pub gpu_pbs_type: GpuPbsType,
+        pub gpu_number_of_sm: u64,
+        pub maximum_acceptable_complexity: f64, // f64::INFINITY means unconstrained
+        pub crt_decomposition_hint: Vec<u64>,   // empty means optimizer-chosen
+        pub security_curve_json: String,        // empty means the built-in security curves
+        pub complexity_to_seconds: f64,         // 0 means uncalibrated
+        pub allow_fast_keyswitch: bool, // false restricts conversions to standard keyswitches
+        pub objective: Objective,
+        pub objective_complexity_weight: f64, // only used by Objective::Weighted
+        pub objective_key_size_weight: f64,   // only used by Objective::Weighted
+        pub objective_batch_size: u64,        // gpu Objective::MaxThroughput only, 0 means 1
+        pub pinned_glwe_dimension: Vec<u64>,  // empty means free
+        pub pinned_polynomial_size: Vec<u64>, // empty means free
+        pub remote_pbs_latency: f64, // in complexity units, 0 with a 0 bandwidth means local pbs
+        pub remote_pbs_bandwidth: f64, // in bits per complexity unit, 0 means unlimited
+        pub quiet: bool,             // true suppresses all diagnostic messages but errors
+        pub gpu_batch_size: u64,     // pbs evaluated together by the amortized gpu model, 0 means 1
+        pub secret_key_variance: f64, // variance of the secret keys coefficients, 0 means binary keys
+        pub key_sharing_groups: Vec<KeySharingGroup>, // honored by the multi parameters optimization
+        pub error_on_infeasible: bool, // single solution optimizations throw instead of returning p_error 1.0
+        pub search_space: SearchSpaceOptions,
     }
 
     #[namespace = "concrete_optimizer::dag"]
@@ -1397,6 +4113,67 @@ mod ffi {
         pub global_p_error: f64,
         pub is_feasible: bool,
         pub error_msg: String,
+        pub used_external_partitions: Vec<String>, // names of the external partitions in the keyset
+        pub unshared_secret_key_count: u64, // secret keys without key sharing, to compare with circuit_keys
+    }
+
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone, Copy)]
+    pub enum PartitionFeasibility {
+        Unknown,
+        Feasible,
+        Unfeasible,
+    }
+
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone)]
+    pub struct PartitionReport {
+        pub partition: u64,
+        pub has_lut: bool,
+        pub min_precision: u8, // lut input precisions, when has_lut
+        pub max_precision: u8,
+        pub has_macro_parameters: bool,
+        pub glwe_dimension: u64,
+        pub glwe_polynomial_size: u64,
+        pub internal_lwe_dimension: u64,
+        pub feasibility: PartitionFeasibility,
+        pub complexity: f64, // partition share of the circuit complexity, when has_macro_parameters
+    }
+
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone, Copy)]
+    pub struct OperatorNoise {
+        pub index: OperatorIndex,
+        pub variance: f64,
+        pub margin: f64, // safe variance bound minus variance
+    }
+
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone)]
+    pub struct DagSolutionWithNoiseMap {
+        pub solution: DagSolution,
+        pub operators: Vec<OperatorNoise>,
+    }
+
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone, Copy)]
+    pub struct OperatorBudget {
+        pub index: OperatorIndex,
+        pub p_error: f64, // failure probability of the operator
+    }
+
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone)]
+    pub struct DagSolutionWithBudgetReport {
+        pub solution: DagSolution,
+        pub operators: Vec<OperatorBudget>,
+    }
+
+    #[namespace = "concrete_optimizer::dag"]
+    #[derive(Debug, Clone)]
+    pub struct CircuitSolutionWithReport {
+        pub solution: CircuitSolution,
+        pub partitions: Vec<PartitionReport>,
     }
 
     #[namespace = "concrete_optimizer::restriction"]
@@ -1438,167 +4215,77 @@ mod ffi {
         pub info: KeysetInfo,
     }
 
+    #[namespace = "concrete_optimizer::utils"]
+    #[derive(Debug, Clone)]
+    pub struct DecompositionCandidate {
+        pub level: u64,
+        pub base_log: u64,
+        pub max_variance: f64,
+        pub complexity: f64,
+    }
+
     #[namespace = "concrete_optimizer::utils"]
     #[derive(Debug, Clone)]
     pub struct PartitionDefinition {
         pub precision: u8,
         pub norm2: f64,
     }
+
+    #[namespace = "concrete_optimizer::utils"]
+    #[derive(Debug, Clone)]
+    pub struct PartitionConversion {
+        pub src: u64, // index in the partition definitions
+        pub dst: u64,
+    }
 }
 
 fn processing_unit(options: &ffi::Options) -> ProcessingUnit {
-    if options.use_gpu_constraints {
+    objective(options).processing_unit(if options.use_gpu_constraints {
         config::ProcessingUnit::Gpu {
-            pbs_type: config::GpuPbsType::Amortized,
-            number_of_sm: 1,
+            pbs_type: options.gpu_pbs_type.into(),
+            // 0 means unspecified
+            number_of_sm: options.gpu_number_of_sm.max(1),
+            // 0 means unspecified
+            batch_size: options.gpu_batch_size.max(1),
+        }
+    } else if options.remote_pbs_latency > 0.0 || options.remote_pbs_bandwidth > 0.0 {
+        config::ProcessingUnit::Remote {
+            per_pbs_latency: options.remote_pbs_latency,
+            // 0 means unlimited
+            bandwidth: if options.remote_pbs_bandwidth > 0.0 {
+                options.remote_pbs_bandwidth
+            } else {
+                f64::INFINITY
+            },
         }
     } else {
         config::ProcessingUnit::Cpu
-    }
-}
-
-impl SearchSpaceRestriction for ffi::RangeRestriction {
-    fn is_available_glwe(&self, partition: PartitionIndex, glwe_params: GlweParameters) -> bool {
-        unsafe {
-            std::mem::transmute::<&Self, &RangeRestriction>(self)
-                .is_available_glwe(partition, glwe_params)
-        }
-    }
-
-    fn is_available_macro(
-        &self,
-        partition: PartitionIndex,
-        macro_parameters: MacroParameters,
-    ) -> bool {
-        unsafe {
-            std::mem::transmute::<&Self, &RangeRestriction>(self)
-                .is_available_macro(partition, macro_parameters)
-        }
-    }
-
-    fn is_available_micro_pbs(
-        &self,
-        partition: PartitionIndex,
-        macro_parameters: MacroParameters,
-        pbs_parameters: BrDecompositionParameters,
-    ) -> bool {
-        unsafe {
-            std::mem::transmute::<&Self, &RangeRestriction>(self).is_available_micro_pbs(
-                partition,
-                macro_parameters,
-                pbs_parameters,
-            )
-        }
-    }
-
-    fn is_available_micro_ks(
-        &self,
-        from_partition: PartitionIndex,
-        from_macro: MacroParameters,
-        to_partition: PartitionIndex,
-        to_macro: MacroParameters,
-        ks_parameters: KsDecompositionParameters,
-    ) -> bool {
-        unsafe {
-            std::mem::transmute::<&Self, &RangeRestriction>(self).is_available_micro_ks(
-                from_partition,
-                from_macro,
-                to_partition,
-                to_macro,
-                ks_parameters,
-            )
-        }
-    }
-
-    fn is_available_micro_fks(
-        &self,
-        from_partition: PartitionIndex,
-        from_macro: MacroParameters,
-        to_partition: PartitionIndex,
-        to_macro: MacroParameters,
-        ks_parameters: KsDecompositionParameters,
-    ) -> bool {
-        unsafe {
-            std::mem::transmute::<&Self, &RangeRestriction>(self).is_available_micro_fks(
-                from_partition,
-                from_macro,
-                to_partition,
-                to_macro,
-                ks_parameters,
-            )
-        }
-    }
+    })
 }
 
-impl SearchSpaceRestriction for ffi::KeysetRestriction {
-    fn is_available_glwe(&self, partition: PartitionIndex, glwe_params: GlweParameters) -> bool {
-        unsafe {
-            std::mem::transmute::<&Self, &KeysetRestriction>(self)
-                .is_available_glwe(partition, glwe_params)
-        }
-    }
-
-    fn is_available_macro(
-        &self,
-        partition: PartitionIndex,
-        macro_parameters: MacroParameters,
-    ) -> bool {
-        unsafe {
-            std::mem::transmute::<&Self, &KeysetRestriction>(self)
-                .is_available_macro(partition, macro_parameters)
-        }
-    }
-
-    fn is_available_micro_pbs(
-        &self,
-        partition: PartitionIndex,
-        macro_parameters: MacroParameters,
-        pbs_parameters: BrDecompositionParameters,
-    ) -> bool {
-        unsafe {
-            std::mem::transmute::<&Self, &KeysetRestriction>(self).is_available_micro_pbs(
-                partition,
-                macro_parameters,
-                pbs_parameters,
-            )
-        }
-    }
-
-    fn is_available_micro_ks(
-        &self,
-        from_partition: PartitionIndex,
-        from_macro: MacroParameters,
-        to_partition: PartitionIndex,
-        to_macro: MacroParameters,
-        ks_parameters: KsDecompositionParameters,
-    ) -> bool {
-        unsafe {
-            std::mem::transmute::<&Self, &KeysetRestriction>(self).is_available_micro_ks(
-                from_partition,
-                from_macro,
-                to_partition,
-                to_macro,
-                ks_parameters,
-            )
-        }
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn is_available_micro_fks(
-        &self,
-        from_partition: PartitionIndex,
-        from_macro: MacroParameters,
-        to_partition: PartitionIndex,
-        to_macro: MacroParameters,
-        ks_parameters: KsDecompositionParameters,
-    ) -> bool {
-        unsafe {
-            std::mem::transmute::<&Self, &KeysetRestriction>(self).is_available_micro_fks(
-                from_partition,
-                from_macro,
-                to_partition,
-                to_macro,
-                ks_parameters,
-            )
-        }
+    #[test]
+    fn test_location_from_string() {
+        let parse = |string| location_from_string(string).0;
+        assert_eq!(
+            parse("file.py:3:7"),
+            operator::Location::LineColumn("file.py".into(), 3, 7)
+        );
+        assert_eq!(
+            parse("file.py:abc"),
+            operator::Location::File("file.py".into())
+        );
+        assert_eq!(
+            parse("file.py:3:abc"),
+            operator::Location::Line("file.py".into(), 3)
+        );
+        assert_eq!(
+            parse("file.py:abc:7"),
+            operator::Location::File("file.py".into())
+        );
+        assert_eq!(parse("a:1:2:3"), operator::Location::Unknown);
     }
 }
@@ -0,0 +1,10 @@
+#pragma once
+#include <cstddef>
+#include <cstdint>
+
+// Installs the callback receiving the optimizer messages instead of stderr.
+// The callback receives the level (0 error, 1 warning, 2 info, 3 debug) and
+// the utf8 message, which is not nul terminated.
+// Returns false if a callback is already installed.
+extern "C" bool concrete_optimizer_set_log_callback(
+    void (*callback)(uint8_t level, const char *msg, size_t len));
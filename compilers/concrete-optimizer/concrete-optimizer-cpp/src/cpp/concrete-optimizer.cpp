@@ -1,8 +1,10 @@
+#include "concrete-optimizer-log.hpp"
 #include <algorithm>
 #include <array>
 #include <cassert>
 #include <cstddef>
 #include <cstdint>
+#include <exception>
 #include <initializer_list>
 #include <iterator>
 #include <memory>
@@ -843,6 +845,27 @@ template <typename T>
 Vec<T>::Vec(unsafe_bitcopy_t, const Vec &bits) noexcept : repr(bits.repr) {}
 #endif // CXXBRIDGE1_RUST_VEC
 
+#ifndef CXXBRIDGE1_RUST_ERROR
+#define CXXBRIDGE1_RUST_ERROR
+class Error final : public std::exception {
+public:
+  Error(const Error &);
+  Error(Error &&) noexcept;
+  ~Error() noexcept override;
+
+  Error &operator=(const Error &) &;
+  Error &operator=(Error &&) &noexcept;
+
+  const char *what() const noexcept override;
+
+private:
+  Error() noexcept = default;
+  friend impl<Error>;
+  const char *msg;
+  std::size_t len;
+};
+#endif // CXXBRIDGE1_RUST_ERROR
+
 #ifndef CXXBRIDGE1_RUST_OPAQUE
 #define CXXBRIDGE1_RUST_OPAQUE
 class Opaque {
@@ -921,6 +944,13 @@ std::size_t align_of() {
 }
 #endif // CXXBRIDGE1_LAYOUT
 
+namespace repr {
+struct PtrLen final {
+  void *ptr;
+  ::std::size_t len;
+};
+} // namespace repr
+
 namespace detail {
 template <typename T, typename = void *>
 struct operator_new {
@@ -947,6 +977,19 @@ union MaybeUninit {
   MaybeUninit() {}
   ~MaybeUninit() {}
 };
+
+namespace {
+template <>
+class impl<Error> final {
+public:
+  static Error error(repr::PtrLen repr) noexcept {
+    Error error;
+    error.msg = static_cast<char const *>(repr.ptr);
+    error.len = repr.len;
+    return error;
+  }
+};
+} // namespace
 } // namespace cxxbridge1
 } // namespace rust
 
@@ -957,12 +1000,23 @@ namespace concrete_optimizer {
   struct DagBuilder;
   struct Location;
   struct ExternalPartition;
+  struct CancellationToken;
+  struct ProgressCounter;
+  struct PreparedRestriction;
   struct Weights;
   enum class Encoding : ::std::uint8_t;
+  enum class Objective : ::std::uint8_t;
   enum class MultiParamStrategy : ::std::uint8_t;
+  enum class GpuPbsType : ::std::uint8_t;
+  struct KeySharingGroup;
+  struct SearchSpaceOptions;
   struct Options;
   namespace dag {
     struct OperatorIndex;
+    struct CompositionError;
+    struct LutTable;
+    struct InputSpec;
+    struct OperatorKindCount;
     struct DagSolution;
     struct BrDecompositionParameters;
     struct KsDecompositionParameters;
@@ -973,6 +1027,13 @@ namespace concrete_optimizer {
     struct CircuitBoostrapKey;
     struct InstructionKeys;
     struct CircuitSolution;
+    enum class PartitionFeasibility : ::std::uint8_t;
+    struct PartitionReport;
+    struct OperatorNoise;
+    struct DagSolutionWithNoiseMap;
+    struct OperatorBudget;
+    struct DagSolutionWithBudgetReport;
+    struct CircuitSolutionWithReport;
   }
   namespace v0 {
     struct Solution;
@@ -986,7 +1047,9 @@ namespace concrete_optimizer {
     struct KeysetRestriction;
   }
   namespace utils {
+    struct DecompositionCandidate;
     struct PartitionDefinition;
+    struct PartitionConversion;
   }
 }
 
@@ -996,13 +1059,40 @@ namespace concrete_optimizer {
 struct Dag final : public ::rust::Opaque {
   ::rust::Box<::concrete_optimizer::DagBuilder> builder(::rust::String circuit) noexcept;
   ::rust::String dump() const noexcept;
-  ::concrete_optimizer::dag::DagSolution optimize(::concrete_optimizer::Options const &options) const noexcept;
+  ::rust::String to_json() const noexcept;
+  ::rust::String to_dot() const noexcept;
+  ::concrete_optimizer::dag::DagSolution optimize(::concrete_optimizer::Options const &options) const;
+  ::concrete_optimizer::dag::DagSolution optimize_with_progress(::concrete_optimizer::Options const &options, ::concrete_optimizer::ProgressCounter const &progress) const;
+  ::rust::Vec<::concrete_optimizer::dag::DagSolution> optimize_pareto(::concrete_optimizer::Options const &options) const noexcept;
+  ::rust::Vec<::concrete_optimizer::dag::DagSolution> optimize_multi_security(::rust::Slice<::std::uint64_t const> security_levels, ::concrete_optimizer::Options const &options) const noexcept;
+  ::rust::Vec<::concrete_optimizer::dag::DagSolution> optimize_sweep_fft_precision(::concrete_optimizer::Options const &options, ::rust::Slice<::std::uint32_t const> candidates) const noexcept;
   void add_composition(::std::string const &from_func, ::std::size_t from_pos, ::std::string const &to_func, ::std::size_t to_pos) noexcept;
+  void add_composition_by_index(::concrete_optimizer::dag::OperatorIndex from_output, ::concrete_optimizer::dag::OperatorIndex to_input);
   void add_all_compositions() noexcept;
+  void add_all_compositions_matching() noexcept;
+  ::rust::Vec<::concrete_optimizer::dag::CompositionError> check_compositions() const noexcept;
+  bool is_composed() const noexcept;
+  ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> merge(::concrete_optimizer::Dag const &other);
+  void remove_operator(::concrete_optimizer::dag::OperatorIndex index);
+  void replace_lut_table(::concrete_optimizer::dag::OperatorIndex index, ::rust::Slice<::std::uint64_t const> table);
+  void set_partition_assignment(::rust::Slice<::std::uint64_t const> assignment);
   ::std::size_t get_circuit_count() const noexcept;
+  ::rust::Vec<::rust::String> get_circuit_names() const noexcept;
+  ::concrete_optimizer::dag::DagSolutionWithNoiseMap optimize_with_noise_map(::concrete_optimizer::Options const &options) const;
+  ::concrete_optimizer::dag::DagSolutionWithBudgetReport optimize_with_budget_report(::concrete_optimizer::Options const &options) const;
   ::concrete_optimizer::dag::CircuitSolution optimize_multi(::concrete_optimizer::Options const &options) const noexcept;
+  bool is_feasible(::concrete_optimizer::Options const &options) const noexcept;
+  ::concrete_optimizer::dag::CircuitSolutionWithReport optimize_multi_with_report(::concrete_optimizer::Options const &options) const noexcept;
+  ::concrete_optimizer::dag::CircuitSolution optimize_multi_cancellable(::concrete_optimizer::Options const &options, ::concrete_optimizer::CancellationToken const &cancel) const noexcept;
+  ::concrete_optimizer::dag::CircuitSolution optimize_multi_with_prepared(::concrete_optimizer::Options const &options, ::concrete_optimizer::PreparedRestriction const &restriction) const noexcept;
+  ::concrete_optimizer::dag::CircuitSolution optimize_multi_with_progress(::concrete_optimizer::Options const &options, ::concrete_optimizer::ProgressCounter const &progress) const noexcept;
   ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> get_input_indices() const noexcept;
   ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> get_output_indices() const noexcept;
+  ::std::uint8_t get_operator_precision(::concrete_optimizer::dag::OperatorIndex index) const;
+  ::rust::Vec<::std::uint64_t> get_operator_shape(::concrete_optimizer::dag::OperatorIndex index) const;
+  ::rust::String get_operator_comment(::concrete_optimizer::dag::OperatorIndex index) const;
+  double get_operator_norm2(::concrete_optimizer::dag::OperatorIndex index) const;
+  ::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> operator_kind_histogram() const noexcept;
   ~Dag() = delete;
 
 private:
@@ -1019,16 +1109,41 @@ private:
 struct DagBuilder final : public ::rust::Opaque {
   ::rust::String dump() const noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_input(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_input_with_variance(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, double input_variance, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_input_in_partition(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::ExternalPartition const &partition, ::concrete_optimizer::Location const &location) noexcept;
+  ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> add_inputs(::rust::Slice<::concrete_optimizer::dag::InputSpec const> specs, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_input_with_comment(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::rust::Str comment, ::concrete_optimizer::Location const &location) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_zero_noise(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept;
-  ::concrete_optimizer::dag::OperatorIndex add_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location) noexcept;
-  ::concrete_optimizer::dag::OperatorIndex add_dot(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_lut_with_comment(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::rust::Str comment, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_signed_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location);
+  ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> add_multi_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::concrete_optimizer::dag::LutTable const> tables, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_rounded_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t rounded_precision, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_dot(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_dot_with_norm(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, double manual_norm2, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_dot_with_comment(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, ::rust::Str comment, ::concrete_optimizer::Location const &location);
   ::concrete_optimizer::dag::OperatorIndex add_linear_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, double lwe_dim_cost_factor, double fixed_cost, ::rust::Slice<double const> weights, ::rust::Slice<::std::uint64_t const> out_shape, ::rust::Str comment, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_broadcast_linear_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, double lwe_dim_cost_factor, double fixed_cost, ::rust::Slice<double const> weights, ::rust::Str comment, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_broadcast_max_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_sub(::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_negate(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_identity(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::Location const &location);
   ::concrete_optimizer::dag::OperatorIndex add_max_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_tree_max(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Slice<::std::uint64_t const> out_shape, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_tree_pbs(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::std::uint8_t decomposition_base, ::concrete_optimizer::Location const &location);
   ::concrete_optimizer::dag::OperatorIndex add_round_op(::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision, ::concrete_optimizer::Location const &location) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_unsafe_cast_op(::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision, ::concrete_optimizer::Location const &location) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_change_partition_with_src(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &src_partition, ::concrete_optimizer::Location const &location) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_change_partition_with_dst(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_change_partition(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &src_partition, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_keyswitch(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept;
   void tag_operator_as_output(::concrete_optimizer::dag::OperatorIndex op) noexcept;
+  void tag_operators_as_output(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> ops);
+
+  // Adds an identity bootstrap on `op`, which must not have users yet, and returns its
+  // index. The existing indices are not changed.
+  ::concrete_optimizer::dag::OperatorIndex force_bootstrap(::concrete_optimizer::dag::OperatorIndex op, ::concrete_optimizer::Location const &location);
+
   ~DagBuilder() = delete;
 
 private:
@@ -1057,6 +1172,9 @@ private:
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$ExternalPartition
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$ExternalPartition
 struct ExternalPartition final : public ::rust::Opaque {
+  ::rust::String name() const noexcept;
+  double max_variance() const noexcept;
+  double variance() const noexcept;
   ~ExternalPartition() = delete;
 
 private:
@@ -1068,6 +1186,51 @@ private:
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$ExternalPartition
 
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$CancellationToken
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$CancellationToken
+struct CancellationToken final : public ::rust::Opaque {
+  void cancel() const noexcept;
+  bool is_cancelled() const noexcept;
+  ~CancellationToken() = delete;
+
+private:
+  friend ::rust::layout;
+  struct layout {
+    static ::std::size_t size() noexcept;
+    static ::std::size_t align() noexcept;
+  };
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$CancellationToken
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$ProgressCounter
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$ProgressCounter
+struct ProgressCounter final : public ::rust::Opaque {
+  ::std::uint32_t steps() const noexcept;
+  ~ProgressCounter() = delete;
+
+private:
+  friend ::rust::layout;
+  struct layout {
+    static ::std::size_t size() noexcept;
+    static ::std::size_t align() noexcept;
+  };
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$ProgressCounter
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$PreparedRestriction
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$PreparedRestriction
+struct PreparedRestriction final : public ::rust::Opaque {
+  ~PreparedRestriction() = delete;
+
+private:
+  friend ::rust::layout;
+  struct layout {
+    static ::std::size_t size() noexcept;
+    static ::std::size_t align() noexcept;
+  };
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$PreparedRestriction
+
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$Weights
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$Weights
 struct Weights final : public ::rust::Opaque {
@@ -1091,6 +1254,16 @@ enum class Encoding : ::std::uint8_t {
 };
 #endif // CXXBRIDGE1_ENUM_concrete_optimizer$Encoding
 
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$Objective
+#define CXXBRIDGE1_ENUM_concrete_optimizer$Objective
+enum class Objective : ::std::uint8_t {
+  MinComplexity = 0,
+  MinKeySize = 1,
+  Weighted = 2,
+  MaxThroughput = 3,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$Objective
+
 namespace dag {
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorIndex
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorIndex
@@ -1100,6 +1273,50 @@ struct OperatorIndex final {
   using IsRelocatable = ::std::true_type;
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorIndex
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CompositionError
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CompositionError
+struct CompositionError final {
+  ::rust::String from_circuit;
+  ::concrete_optimizer::dag::OperatorIndex from;
+  ::std::uint8_t from_precision;
+  ::rust::String to_circuit;
+  ::concrete_optimizer::dag::OperatorIndex to;
+  ::std::uint8_t to_precision;
+  ::rust::String message;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CompositionError
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$LutTable
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$LutTable
+struct LutTable final {
+  ::rust::Vec<::std::uint64_t> values;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$LutTable
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$InputSpec
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$InputSpec
+struct InputSpec final {
+  ::std::uint8_t precision;
+  ::rust::Vec<::std::uint64_t> shape;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$InputSpec
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorKindCount
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorKindCount
+struct OperatorKindCount final {
+  ::rust::String name;
+  ::std::uint64_t count;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorKindCount
 } // namespace dag
 
 namespace v0 {
@@ -1116,8 +1333,10 @@ struct Solution final {
   ::std::uint64_t br_decomposition_base_log;
   double complexity;
   double noise_max;
+  double noise_margin;
   double p_error;
 
+  double log2_p_error() const noexcept;
   using IsRelocatable = ::std::true_type;
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$v0$Solution
@@ -1137,6 +1356,7 @@ struct DagSolution final {
   ::std::uint64_t br_decomposition_base_log;
   double complexity;
   double noise_max;
+  double noise_margin;
   double p_error;
   double global_p_error;
   bool use_wop_pbs;
@@ -1145,7 +1365,14 @@ struct DagSolution final {
   ::std::uint64_t pp_decomposition_level_count;
   ::std::uint64_t pp_decomposition_base_log;
   ::rust::Vec<::std::uint64_t> crt_decomposition;
-
+  ::concrete_optimizer::Encoding chosen_encoding;
+
+  ::CircuitKeys required_keys() const noexcept;
+  ::rust::String to_json() const noexcept;
+  double estimated_seconds(::concrete_optimizer::Options const &options) const noexcept;
+  ::std::uint64_t bootstrap_key_bytes() const noexcept;
+  ::std::uint64_t keyswitch_key_bytes() const noexcept;
+  double log2_p_error() const noexcept;
   using IsRelocatable = ::std::true_type;
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolution
@@ -1156,14 +1383,24 @@ struct DagSolution final {
 enum class MultiParamStrategy : ::std::uint8_t {
   ByPrecision = 0,
   ByPrecisionAndNorm2 = 1,
+  Explicit = 2,
 };
 #endif // CXXBRIDGE1_ENUM_concrete_optimizer$MultiParamStrategy
 
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$GpuPbsType
+#define CXXBRIDGE1_ENUM_concrete_optimizer$GpuPbsType
+enum class GpuPbsType : ::std::uint8_t {
+  Amortized = 0,
+  LowLatency = 1,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$GpuPbsType
+
 namespace restriction {
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$restriction$RangeRestriction
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$restriction$RangeRestriction
 struct RangeRestriction final {
   ::rust::Vec<::std::uint64_t> glwe_log_polynomial_sizes;
+  ::rust::Vec<::std::uint64_t> glwe_polynomial_sizes;
   ::rust::Vec<::std::uint64_t> glwe_dimensions;
   ::rust::Vec<::std::uint64_t> internal_lwe_dimensions;
   ::rust::Vec<::std::uint64_t> pbs_level_count;
@@ -1177,6 +1414,31 @@ struct RangeRestriction final {
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$restriction$RangeRestriction
 } // namespace restriction
 
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$KeySharingGroup
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$KeySharingGroup
+struct KeySharingGroup final {
+  ::rust::Vec<::std::uint64_t> partitions;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$KeySharingGroup
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$SearchSpaceOptions
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$SearchSpaceOptions
+struct SearchSpaceOptions final {
+  ::std::uint64_t internal_lwe_dimension_min;
+  ::std::uint64_t internal_lwe_dimension_max;
+  ::std::uint64_t glwe_dimension_min;
+  ::std::uint64_t glwe_dimension_max;
+  ::std::uint64_t log2_polynomial_size_min;
+  ::std::uint64_t log2_polynomial_size_max;
+  ::std::uint64_t decomposition_level_min;
+  ::std::uint64_t decomposition_level_max;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$SearchSpaceOptions
+
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$Options
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$Options
 struct Options final {
@@ -1192,6 +1454,27 @@ struct Options final {
   ::std::uint32_t fft_precision;
   ::std::shared_ptr<::concrete_optimizer::restriction::RangeRestriction> range_restriction;
   ::std::shared_ptr<::concrete_optimizer::restriction::KeysetRestriction> keyset_restriction;
+  ::concrete_optimizer::GpuPbsType gpu_pbs_type;
+  ::std::uint64_t gpu_number_of_sm;
+  double maximum_acceptable_complexity;
+  ::rust::Vec<::std::uint64_t> crt_decomposition_hint;
+  ::rust::String security_curve_json;
+  double complexity_to_seconds;
+  bool allow_fast_keyswitch;
+  ::concrete_optimizer::Objective objective;
+  double objective_complexity_weight;
+  double objective_key_size_weight;
+  ::std::uint64_t objective_batch_size;
+  ::rust::Vec<::std::uint64_t> pinned_glwe_dimension;
+  ::rust::Vec<::std::uint64_t> pinned_polynomial_size;
+  double remote_pbs_latency;
+  double remote_pbs_bandwidth;
+  bool quiet;
+  ::std::uint64_t gpu_batch_size;
+  double secret_key_variance;
+  ::rust::Vec<::concrete_optimizer::KeySharingGroup> key_sharing_groups;
+  bool error_on_infeasible;
+  ::concrete_optimizer::SearchSpaceOptions search_space;
 
   using IsRelocatable = ::std::true_type;
 };
@@ -1338,12 +1621,95 @@ struct CircuitSolution final {
   double global_p_error;
   bool is_feasible;
   ::rust::String error_msg;
+  ::rust::Vec<::rust::String> used_external_partitions;
+  ::std::uint64_t unshared_secret_key_count;
 
   ::rust::String dump() const noexcept;
   ::rust::String short_dump() const noexcept;
+  ::rust::String diff(::concrete_optimizer::dag::CircuitSolution const &other) const noexcept;
+  ::rust::String to_json() const noexcept;
+  ::rust::Vec<::std::uint8_t> to_bytes() const noexcept;
   using IsRelocatable = ::std::true_type;
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CircuitSolution
+
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$dag$PartitionFeasibility
+#define CXXBRIDGE1_ENUM_concrete_optimizer$dag$PartitionFeasibility
+enum class PartitionFeasibility : ::std::uint8_t {
+  Unknown = 0,
+  Feasible = 1,
+  Unfeasible = 2,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$dag$PartitionFeasibility
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$PartitionReport
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$PartitionReport
+struct PartitionReport final {
+  ::std::uint64_t partition;
+  bool has_lut;
+  ::std::uint8_t min_precision;
+  ::std::uint8_t max_precision;
+  bool has_macro_parameters;
+  ::std::uint64_t glwe_dimension;
+  ::std::uint64_t glwe_polynomial_size;
+  ::std::uint64_t internal_lwe_dimension;
+  ::concrete_optimizer::dag::PartitionFeasibility feasibility;
+  double complexity;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$PartitionReport
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorNoise
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorNoise
+struct OperatorNoise final {
+  ::concrete_optimizer::dag::OperatorIndex index;
+  double variance;
+  double margin;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorNoise
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithNoiseMap
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithNoiseMap
+struct DagSolutionWithNoiseMap final {
+  ::concrete_optimizer::dag::DagSolution solution;
+  ::rust::Vec<::concrete_optimizer::dag::OperatorNoise> operators;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithNoiseMap
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorBudget
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorBudget
+struct OperatorBudget final {
+  ::concrete_optimizer::dag::OperatorIndex index;
+  double p_error;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorBudget
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithBudgetReport
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithBudgetReport
+struct DagSolutionWithBudgetReport final {
+  ::concrete_optimizer::dag::DagSolution solution;
+  ::rust::Vec<::concrete_optimizer::dag::OperatorBudget> operators;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithBudgetReport
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CircuitSolutionWithReport
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CircuitSolutionWithReport
+struct CircuitSolutionWithReport final {
+  ::concrete_optimizer::dag::CircuitSolution solution;
+  ::rust::Vec<::concrete_optimizer::dag::PartitionReport> partitions;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CircuitSolutionWithReport
 } // namespace dag
 
 namespace restriction {
@@ -1404,6 +1770,18 @@ struct KeysetRestriction final {
 } // namespace restriction
 
 namespace utils {
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$utils$DecompositionCandidate
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$utils$DecompositionCandidate
+struct DecompositionCandidate final {
+  ::std::uint64_t level;
+  ::std::uint64_t base_log;
+  double max_variance;
+  double complexity;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$utils$DecompositionCandidate
+
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionDefinition
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionDefinition
 struct PartitionDefinition final {
@@ -1413,14 +1791,32 @@ struct PartitionDefinition final {
   using IsRelocatable = ::std::true_type;
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionDefinition
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionConversion
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionConversion
+struct PartitionConversion final {
+  ::std::uint64_t src;
+  ::std::uint64_t dst;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionConversion
 } // namespace utils
 
 namespace v0 {
 extern "C" {
-::concrete_optimizer::v0::Solution concrete_optimizer$v0$cxxbridge1$optimize_bootstrap(::std::uint64_t precision, double noise_factor, ::concrete_optimizer::Options const &options) noexcept;
+::rust::repr::PtrLen concrete_optimizer$v0$cxxbridge1$optimize_bootstrap(::std::uint64_t precision, double noise_factor, ::concrete_optimizer::Options const &options, ::concrete_optimizer::v0::Solution *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$v0$cxxbridge1$optimize_atomic_pattern(::std::uint64_t precision, double noise_factor, ::std::uint64_t sum_size, ::concrete_optimizer::Options const &options, ::concrete_optimizer::v0::Solution *return$) noexcept;
 } // extern "C"
 } // namespace v0
 
+extern "C" {
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$check_security_curve_json(::rust::Str input) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$validate_options(::concrete_optimizer::Options const &options) noexcept;
+} // extern "C"
+
 namespace utils {
 extern "C" {
 void concrete_optimizer$utils$cxxbridge1$convert_to_dag_solution(::concrete_optimizer::v0::Solution const &solution, ::concrete_optimizer::dag::DagSolution *return$) noexcept;
@@ -1429,6 +1825,12 @@ void concrete_optimizer$utils$cxxbridge1$convert_to_circuit_solution(::concrete_
 } // extern "C"
 } // namespace utils
 
+namespace dag {
+extern "C" {
+void concrete_optimizer$dag$cxxbridge1$DagSolution$required_keys(::concrete_optimizer::dag::DagSolution const &self, ::CircuitKeys *return$) noexcept;
+} // extern "C"
+} // namespace dag
+
 extern "C" {
 ::std::size_t concrete_optimizer$cxxbridge1$Dag$operator$sizeof() noexcept;
 ::std::size_t concrete_optimizer$cxxbridge1$Dag$operator$alignof() noexcept;
@@ -1438,6 +1840,46 @@ extern "C" {
 ::std::size_t concrete_optimizer$cxxbridge1$Location$operator$alignof() noexcept;
 ::std::size_t concrete_optimizer$cxxbridge1$ExternalPartition$operator$sizeof() noexcept;
 ::std::size_t concrete_optimizer$cxxbridge1$ExternalPartition$operator$alignof() noexcept;
+
+void concrete_optimizer$cxxbridge1$ExternalPartition$name(::concrete_optimizer::ExternalPartition const &self, ::rust::String *return$) noexcept;
+
+double concrete_optimizer$cxxbridge1$ExternalPartition$max_variance(::concrete_optimizer::ExternalPartition const &self) noexcept;
+
+double concrete_optimizer$cxxbridge1$ExternalPartition$variance(::concrete_optimizer::ExternalPartition const &self) noexcept;
+::std::size_t concrete_optimizer$cxxbridge1$CancellationToken$operator$sizeof() noexcept;
+::std::size_t concrete_optimizer$cxxbridge1$CancellationToken$operator$alignof() noexcept;
+} // extern "C"
+
+namespace utils {
+extern "C" {
+::concrete_optimizer::CancellationToken *concrete_optimizer$utils$cxxbridge1$cancellation_token() noexcept;
+} // extern "C"
+} // namespace utils
+
+extern "C" {
+void concrete_optimizer$cxxbridge1$CancellationToken$cancel(::concrete_optimizer::CancellationToken const &self) noexcept;
+
+bool concrete_optimizer$cxxbridge1$CancellationToken$is_cancelled(::concrete_optimizer::CancellationToken const &self) noexcept;
+::std::size_t concrete_optimizer$cxxbridge1$ProgressCounter$operator$sizeof() noexcept;
+::std::size_t concrete_optimizer$cxxbridge1$ProgressCounter$operator$alignof() noexcept;
+::std::size_t concrete_optimizer$cxxbridge1$PreparedRestriction$operator$sizeof() noexcept;
+::std::size_t concrete_optimizer$cxxbridge1$PreparedRestriction$operator$alignof() noexcept;
+} // extern "C"
+
+namespace restriction {
+extern "C" {
+::rust::repr::PtrLen concrete_optimizer$restriction$cxxbridge1$prepare_keyset_restriction(::concrete_optimizer::restriction::KeysetRestriction const &restriction, ::rust::Box<::concrete_optimizer::PreparedRestriction> *return$) noexcept;
+} // extern "C"
+} // namespace restriction
+
+namespace utils {
+extern "C" {
+::concrete_optimizer::ProgressCounter *concrete_optimizer$utils$cxxbridge1$progress_counter() noexcept;
+} // extern "C"
+} // namespace utils
+
+extern "C" {
+::std::uint32_t concrete_optimizer$cxxbridge1$ProgressCounter$steps(::concrete_optimizer::ProgressCounter const &self) noexcept;
 } // extern "C"
 
 namespace utils {
@@ -1446,17 +1888,35 @@ extern "C" {
 
 ::concrete_optimizer::Location *concrete_optimizer$utils$cxxbridge1$location_from_string(::rust::Str string) noexcept;
 
+void concrete_optimizer$utils$cxxbridge1$cache_directory(::rust::String *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$utils$cxxbridge1$clear_cache() noexcept;
+
+void concrete_optimizer$utils$cxxbridge1$warm_cache(::concrete_optimizer::Options const &options) noexcept;
+
+::std::uint64_t concrete_optimizer$utils$cxxbridge1$estimate_search_space_size(::concrete_optimizer::Options const &options) noexcept;
+
 void concrete_optimizer$utils$cxxbridge1$generate_virtual_keyset_info(::rust::Vec<::concrete_optimizer::utils::PartitionDefinition> *partitions, bool generate_fks, ::concrete_optimizer::Options const &options, ::CircuitKeys *return$) noexcept;
 
-::concrete_optimizer::ExternalPartition *concrete_optimizer$utils$cxxbridge1$get_external_partition(::rust::String *name, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t internal_dim, double max_variance, double variance) noexcept;
+::rust::repr::PtrLen concrete_optimizer$utils$cxxbridge1$generate_virtual_keyset_info_with_conversions(::rust::Vec<::concrete_optimizer::utils::PartitionDefinition> *partitions, ::rust::Vec<::concrete_optimizer::utils::PartitionConversion> *conversions, ::concrete_optimizer::Options const &options, ::CircuitKeys *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$utils$cxxbridge1$get_external_partition(::rust::String *name, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t internal_dim, double max_variance, double variance, ::rust::Box<::concrete_optimizer::ExternalPartition> *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$utils$cxxbridge1$get_noise_br(::concrete_optimizer::Options const &options, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t lwe_dim, ::std::uint64_t pbs_level, ::std::uint64_t pbs_log2_base, double *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$utils$cxxbridge1$get_noise_ks(::concrete_optimizer::Options const &options, ::std::uint64_t input_lwe_dim, ::std::uint64_t output_lwe_dim, ::std::uint64_t ks_level, ::std::uint64_t ks_log2_base, double *return$) noexcept;
 
-double concrete_optimizer$utils$cxxbridge1$get_noise_br(::concrete_optimizer::Options const &options, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t lwe_dim, ::std::uint64_t pbs_level, ::std::uint64_t pbs_log2_base) noexcept;
+double concrete_optimizer$utils$cxxbridge1$get_noise_modulus_switch(::concrete_optimizer::Options const &options, ::std::uint64_t lwe_dim, ::std::uint64_t log2_polynomial_size) noexcept;
+
+void concrete_optimizer$utils$cxxbridge1$enumerate_pbs_decompositions(::concrete_optimizer::Options const &options, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t lwe_dim, ::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> *return$) noexcept;
 } // extern "C"
 } // namespace utils
 
 namespace dag {
 extern "C" {
 ::concrete_optimizer::Dag *concrete_optimizer$dag$cxxbridge1$empty() noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$dag$cxxbridge1$dag_from_json(::rust::Str input, ::rust::Box<::concrete_optimizer::Dag> *return$) noexcept;
 } // extern "C"
 } // namespace dag
 
@@ -1465,20 +1925,58 @@ extern "C" {
 
 void concrete_optimizer$cxxbridge1$Dag$dump(::concrete_optimizer::Dag const &self, ::rust::String *return$) noexcept;
 
+void concrete_optimizer$cxxbridge1$Dag$to_json(::concrete_optimizer::Dag const &self, ::rust::String *return$) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$to_dot(::concrete_optimizer::Dag const &self, ::rust::String *return$) noexcept;
+
 void concrete_optimizer$cxxbridge1$DagBuilder$dump(::concrete_optimizer::DagBuilder const &self, ::rust::String *return$) noexcept;
 
 ::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_input(::concrete_optimizer::DagBuilder &self, ::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept;
 
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_input_with_variance(::concrete_optimizer::DagBuilder &self, ::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, double input_variance, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_input_in_partition(::concrete_optimizer::DagBuilder &self, ::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::ExternalPartition const &partition, ::concrete_optimizer::Location const &location) noexcept;
+
+void concrete_optimizer$cxxbridge1$DagBuilder$add_inputs(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::InputSpec const> specs, ::concrete_optimizer::Location const &location, ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> *return$) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_input_with_comment(::concrete_optimizer::DagBuilder &self, ::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::rust::Str comment, ::concrete_optimizer::Location const &location) noexcept;
+
 ::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_zero_noise(::concrete_optimizer::DagBuilder &self, ::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept;
 
-::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_lut(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location) noexcept;
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_lut(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_lut_with_comment(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::rust::Str comment, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_signed_lut(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_multi_lut(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::concrete_optimizer::dag::LutTable const> tables, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location, ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_rounded_lut(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t rounded_precision, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
 
-::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_dot(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::concrete_optimizer::Weights *weights, ::concrete_optimizer::Location const &location) noexcept;
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_dot(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::concrete_optimizer::Weights *weights, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_dot_with_norm(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::concrete_optimizer::Weights *weights, double manual_norm2, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_dot_with_comment(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::concrete_optimizer::Weights *weights, ::rust::Str comment, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
 
 ::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_linear_noise(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, double lwe_dim_cost_factor, double fixed_cost, ::rust::Slice<double const> weights, ::rust::Slice<::std::uint64_t const> out_shape, ::rust::Str comment, ::concrete_optimizer::Location const &location) noexcept;
 
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_broadcast_linear_noise(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, double lwe_dim_cost_factor, double fixed_cost, ::rust::Slice<double const> weights, ::rust::Str comment, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_broadcast_max_noise(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_sub(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_negate(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_identity(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
 ::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_max_noise(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept;
 
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_tree_max(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Slice<::std::uint64_t const> out_shape, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$add_tree_pbs(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::std::uint8_t decomposition_base, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
 ::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_round_op(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision, ::concrete_optimizer::Location const &location) noexcept;
 
 ::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_unsafe_cast_op(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision, ::concrete_optimizer::Location const &location) noexcept;
@@ -1487,13 +1985,57 @@ void concrete_optimizer$cxxbridge1$DagBuilder$dump(::concrete_optimizer::DagBuil
 
 ::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_change_partition_with_dst(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept;
 
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_change_partition(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &src_partition, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept;
+
+::concrete_optimizer::dag::OperatorIndex concrete_optimizer$cxxbridge1$DagBuilder$add_keyswitch(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept;
+
 void concrete_optimizer$cxxbridge1$DagBuilder$tag_operator_as_output(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex op) noexcept;
 
-void concrete_optimizer$cxxbridge1$Dag$optimize(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::concrete_optimizer::dag::DagSolution *return$) noexcept;
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$tag_operators_as_output(::concrete_optimizer::DagBuilder &self, ::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> ops) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$DagBuilder$force_bootstrap(::concrete_optimizer::DagBuilder &self, ::concrete_optimizer::dag::OperatorIndex op, ::concrete_optimizer::Location const &location, ::concrete_optimizer::dag::OperatorIndex *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$optimize(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::concrete_optimizer::dag::DagSolution *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$optimize_with_progress(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::concrete_optimizer::ProgressCounter const &progress, ::concrete_optimizer::dag::DagSolution *return$) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$optimize_pareto(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::rust::Vec<::concrete_optimizer::dag::DagSolution> *return$) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$optimize_multi_security(::concrete_optimizer::Dag const &self, ::rust::Slice<::std::uint64_t const> security_levels, ::concrete_optimizer::Options const &options, ::rust::Vec<::concrete_optimizer::dag::DagSolution> *return$) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$optimize_sweep_fft_precision(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::rust::Slice<::std::uint32_t const> candidates, ::rust::Vec<::concrete_optimizer::dag::DagSolution> *return$) noexcept;
+} // extern "C"
+
+namespace dag {
+extern "C" {
+void concrete_optimizer$dag$cxxbridge1$optimize_many(::rust::Slice<::rust::Box<::concrete_optimizer::Dag> const> dags, ::concrete_optimizer::Options const &options, ::rust::Vec<::concrete_optimizer::dag::DagSolution> *return$) noexcept;
+
+void concrete_optimizer$dag$cxxbridge1$clear_solution_cache() noexcept;
+
+void concrete_optimizer$dag$cxxbridge1$evaluate_solution(::concrete_optimizer::Dag const &dag, ::concrete_optimizer::dag::DagSolution const &solution, ::concrete_optimizer::Options const &options, ::concrete_optimizer::dag::DagSolution *return$) noexcept;
+} // extern "C"
+} // namespace dag
 
+extern "C" {
 void concrete_optimizer$cxxbridge1$Dag$add_composition(::concrete_optimizer::Dag &self, ::std::string const &from_func, ::std::size_t from_pos, ::std::string const &to_func, ::std::size_t to_pos) noexcept;
 
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$add_composition_by_index(::concrete_optimizer::Dag &self, ::concrete_optimizer::dag::OperatorIndex from_output, ::concrete_optimizer::dag::OperatorIndex to_input) noexcept;
+
 void concrete_optimizer$cxxbridge1$Dag$add_all_compositions(::concrete_optimizer::Dag &self) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$add_all_compositions_matching(::concrete_optimizer::Dag &self) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$check_compositions(::concrete_optimizer::Dag const &self, ::rust::Vec<::concrete_optimizer::dag::CompositionError> *return$) noexcept;
+
+bool concrete_optimizer$cxxbridge1$Dag$is_composed(::concrete_optimizer::Dag const &self) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$merge(::concrete_optimizer::Dag &self, ::concrete_optimizer::Dag const &other, ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$remove_operator(::concrete_optimizer::Dag &self, ::concrete_optimizer::dag::OperatorIndex index) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$replace_lut_table(::concrete_optimizer::Dag &self, ::concrete_optimizer::dag::OperatorIndex index, ::rust::Slice<::std::uint64_t const> table) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$set_partition_assignment(::concrete_optimizer::Dag &self, ::rust::Slice<::std::uint64_t const> assignment) noexcept;
 } // extern "C"
 
 namespace dag {
@@ -1501,6 +2043,38 @@ extern "C" {
 void concrete_optimizer$dag$cxxbridge1$CircuitSolution$dump(::concrete_optimizer::dag::CircuitSolution const &self, ::rust::String *return$) noexcept;
 
 void concrete_optimizer$dag$cxxbridge1$CircuitSolution$short_dump(::concrete_optimizer::dag::CircuitSolution const &self, ::rust::String *return$) noexcept;
+
+void concrete_optimizer$dag$cxxbridge1$CircuitSolution$diff(::concrete_optimizer::dag::CircuitSolution const &self, ::concrete_optimizer::dag::CircuitSolution const &other, ::rust::String *return$) noexcept;
+
+void concrete_optimizer$dag$cxxbridge1$CircuitSolution$to_json(::concrete_optimizer::dag::CircuitSolution const &self, ::rust::String *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$dag$cxxbridge1$circuit_solution_from_json(::rust::Str input, ::concrete_optimizer::dag::CircuitSolution *return$) noexcept;
+
+void concrete_optimizer$dag$cxxbridge1$CircuitSolution$to_bytes(::concrete_optimizer::dag::CircuitSolution const &self, ::rust::Vec<::std::uint8_t> *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$dag$cxxbridge1$circuit_solution_from_bytes(::rust::Slice<::std::uint8_t const> bytes, ::concrete_optimizer::dag::CircuitSolution *return$) noexcept;
+
+void concrete_optimizer$dag$cxxbridge1$DagSolution$to_json(::concrete_optimizer::dag::DagSolution const &self, ::rust::String *return$) noexcept;
+
+double concrete_optimizer$dag$cxxbridge1$DagSolution$estimated_seconds(::concrete_optimizer::dag::DagSolution const &self, ::concrete_optimizer::Options const &options) noexcept;
+
+::std::uint64_t concrete_optimizer$dag$cxxbridge1$DagSolution$bootstrap_key_bytes(::concrete_optimizer::dag::DagSolution const &self) noexcept;
+
+::std::uint64_t concrete_optimizer$dag$cxxbridge1$DagSolution$keyswitch_key_bytes(::concrete_optimizer::dag::DagSolution const &self) noexcept;
+
+double concrete_optimizer$dag$cxxbridge1$DagSolution$log2_p_error(::concrete_optimizer::dag::DagSolution const &self) noexcept;
+} // extern "C"
+} // namespace dag
+
+namespace v0 {
+extern "C" {
+double concrete_optimizer$v0$cxxbridge1$Solution$log2_p_error(::concrete_optimizer::v0::Solution const &self) noexcept;
+} // extern "C"
+} // namespace v0
+
+namespace dag {
+extern "C" {
+::rust::repr::PtrLen concrete_optimizer$dag$cxxbridge1$dag_solution_from_json(::rust::Str input, ::concrete_optimizer::dag::DagSolution *return$) noexcept;
 } // extern "C"
 } // namespace dag
 
@@ -1514,18 +2088,50 @@ extern "C" {
 ::concrete_optimizer::Weights *concrete_optimizer$weights$cxxbridge1$vector(::rust::Slice<::std::int64_t const> weights) noexcept;
 
 ::concrete_optimizer::Weights *concrete_optimizer$weights$cxxbridge1$number(::std::int64_t weight) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$weights$cxxbridge1$try_vector(::rust::Slice<::std::int64_t const> weights, ::rust::Box<::concrete_optimizer::Weights> *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$weights$cxxbridge1$try_number(::std::int64_t weight, ::rust::Box<::concrete_optimizer::Weights> *return$) noexcept;
+
+::concrete_optimizer::Weights *concrete_optimizer$weights$cxxbridge1$float_vector(::rust::Slice<double const> weights) noexcept;
 } // extern "C"
 } // namespace weights
 
 extern "C" {
 ::std::size_t concrete_optimizer$cxxbridge1$Dag$get_circuit_count(::concrete_optimizer::Dag const &self) noexcept;
 
+void concrete_optimizer$cxxbridge1$Dag$get_circuit_names(::concrete_optimizer::Dag const &self, ::rust::Vec<::rust::String> *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$optimize_with_noise_map(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::concrete_optimizer::dag::DagSolutionWithNoiseMap *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$optimize_with_budget_report(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::concrete_optimizer::dag::DagSolutionWithBudgetReport *return$) noexcept;
+
 void concrete_optimizer$cxxbridge1$Dag$optimize_multi(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::concrete_optimizer::dag::CircuitSolution *return$) noexcept;
 
+bool concrete_optimizer$cxxbridge1$Dag$is_feasible(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$optimize_multi_with_report(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::concrete_optimizer::dag::CircuitSolutionWithReport *return$) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$optimize_multi_cancellable(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::concrete_optimizer::CancellationToken const &cancel, ::concrete_optimizer::dag::CircuitSolution *return$) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$optimize_multi_with_prepared(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::concrete_optimizer::PreparedRestriction const &restriction, ::concrete_optimizer::dag::CircuitSolution *return$) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$optimize_multi_with_progress(::concrete_optimizer::Dag const &self, ::concrete_optimizer::Options const &options, ::concrete_optimizer::ProgressCounter const &progress, ::concrete_optimizer::dag::CircuitSolution *return$) noexcept;
+
 void concrete_optimizer$cxxbridge1$Dag$get_input_indices(::concrete_optimizer::Dag const &self, ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> *return$) noexcept;
 
 void concrete_optimizer$cxxbridge1$Dag$get_output_indices(::concrete_optimizer::Dag const &self, ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> *return$) noexcept;
 
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$get_operator_precision(::concrete_optimizer::Dag const &self, ::concrete_optimizer::dag::OperatorIndex index, ::std::uint8_t *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$get_operator_shape(::concrete_optimizer::Dag const &self, ::concrete_optimizer::dag::OperatorIndex index, ::rust::Vec<::std::uint64_t> *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$get_operator_comment(::concrete_optimizer::Dag const &self, ::concrete_optimizer::dag::OperatorIndex index, ::rust::String *return$) noexcept;
+
+::rust::repr::PtrLen concrete_optimizer$cxxbridge1$Dag$get_operator_norm2(::concrete_optimizer::Dag const &self, ::concrete_optimizer::dag::OperatorIndex index, double *return$) noexcept;
+
+void concrete_optimizer$cxxbridge1$Dag$operator_kind_histogram(::concrete_optimizer::Dag const &self, ::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> *return$) noexcept;
+
 ::std::uint64_t concrete_optimizer$cxxbridge1$NO_KEY_ID() noexcept;
 } // extern "C"
 
@@ -1533,20 +2139,48 @@ namespace restriction {
 extern "C" {
 void concrete_optimizer$restriction$cxxbridge1$RangeRestriction$range_restriction_to_json(::concrete_optimizer::restriction::RangeRestriction const &self, ::rust::String *return$) noexcept;
 
-void concrete_optimizer$restriction$cxxbridge1$range_restriction_from_json(::rust::Str input, ::concrete_optimizer::restriction::RangeRestriction *return$) noexcept;
+::rust::repr::PtrLen concrete_optimizer$restriction$cxxbridge1$range_restriction_from_json(::rust::Str input, ::concrete_optimizer::restriction::RangeRestriction *return$) noexcept;
 
 void concrete_optimizer$restriction$cxxbridge1$KeysetRestriction$keyset_restriction_to_json(::concrete_optimizer::restriction::KeysetRestriction const &self, ::rust::String *return$) noexcept;
 
-void concrete_optimizer$restriction$cxxbridge1$keyset_restriction_from_json(::rust::Str input, ::concrete_optimizer::restriction::KeysetRestriction *return$) noexcept;
+::rust::repr::PtrLen concrete_optimizer$restriction$cxxbridge1$keyset_restriction_from_json(::rust::Str input, ::concrete_optimizer::restriction::KeysetRestriction *return$) noexcept;
 } // extern "C"
 } // namespace restriction
 
 namespace v0 {
-::concrete_optimizer::v0::Solution optimize_bootstrap(::std::uint64_t precision, double noise_factor, ::concrete_optimizer::Options const &options) noexcept {
-  return concrete_optimizer$v0$cxxbridge1$optimize_bootstrap(precision, noise_factor, options);
+::concrete_optimizer::v0::Solution optimize_bootstrap(::std::uint64_t precision, double noise_factor, ::concrete_optimizer::Options const &options) {
+  ::rust::MaybeUninit<::concrete_optimizer::v0::Solution> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$v0$cxxbridge1$optimize_bootstrap(precision, noise_factor, options, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::v0::Solution optimize_atomic_pattern(::std::uint64_t precision, double noise_factor, ::std::uint64_t sum_size, ::concrete_optimizer::Options const &options) {
+  ::rust::MaybeUninit<::concrete_optimizer::v0::Solution> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$v0$cxxbridge1$optimize_atomic_pattern(precision, noise_factor, sum_size, options, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
 }
 } // namespace v0
 
+void check_security_curve_json(::rust::Str input) {
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$check_security_curve_json(input);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+}
+
+void validate_options(::concrete_optimizer::Options const &options) {
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$validate_options(options);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+}
+
 namespace utils {
 ::concrete_optimizer::dag::DagSolution convert_to_dag_solution(::concrete_optimizer::v0::Solution const &solution) noexcept {
   ::rust::MaybeUninit<::concrete_optimizer::dag::DagSolution> return$;
@@ -1561,6 +2195,14 @@ namespace utils {
 }
 } // namespace utils
 
+namespace dag {
+::CircuitKeys DagSolution::required_keys() const noexcept {
+  ::rust::MaybeUninit<::CircuitKeys> return$;
+  concrete_optimizer$dag$cxxbridge1$DagSolution$required_keys(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+} // namespace dag
+
 ::std::size_t Dag::layout::size() noexcept {
   return concrete_optimizer$cxxbridge1$Dag$operator$sizeof();
 }
@@ -1593,6 +2235,79 @@ namespace utils {
   return concrete_optimizer$cxxbridge1$ExternalPartition$operator$alignof();
 }
 
+::rust::String ExternalPartition::name() const noexcept {
+  ::rust::MaybeUninit<::rust::String> return$;
+  concrete_optimizer$cxxbridge1$ExternalPartition$name(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+
+double ExternalPartition::max_variance() const noexcept {
+  return concrete_optimizer$cxxbridge1$ExternalPartition$max_variance(*this);
+}
+
+double ExternalPartition::variance() const noexcept {
+  return concrete_optimizer$cxxbridge1$ExternalPartition$variance(*this);
+}
+
+::std::size_t CancellationToken::layout::size() noexcept {
+  return concrete_optimizer$cxxbridge1$CancellationToken$operator$sizeof();
+}
+
+::std::size_t CancellationToken::layout::align() noexcept {
+  return concrete_optimizer$cxxbridge1$CancellationToken$operator$alignof();
+}
+
+namespace utils {
+::rust::Box<::concrete_optimizer::CancellationToken> cancellation_token() noexcept {
+  return ::rust::Box<::concrete_optimizer::CancellationToken>::from_raw(concrete_optimizer$utils$cxxbridge1$cancellation_token());
+}
+} // namespace utils
+
+void CancellationToken::cancel() const noexcept {
+  concrete_optimizer$cxxbridge1$CancellationToken$cancel(*this);
+}
+
+bool CancellationToken::is_cancelled() const noexcept {
+  return concrete_optimizer$cxxbridge1$CancellationToken$is_cancelled(*this);
+}
+
+::std::size_t ProgressCounter::layout::size() noexcept {
+  return concrete_optimizer$cxxbridge1$ProgressCounter$operator$sizeof();
+}
+
+::std::size_t ProgressCounter::layout::align() noexcept {
+  return concrete_optimizer$cxxbridge1$ProgressCounter$operator$alignof();
+}
+
+::std::size_t PreparedRestriction::layout::size() noexcept {
+  return concrete_optimizer$cxxbridge1$PreparedRestriction$operator$sizeof();
+}
+
+::std::size_t PreparedRestriction::layout::align() noexcept {
+  return concrete_optimizer$cxxbridge1$PreparedRestriction$operator$alignof();
+}
+
+namespace restriction {
+::rust::Box<::concrete_optimizer::PreparedRestriction> prepare_keyset_restriction(::concrete_optimizer::restriction::KeysetRestriction const &restriction) {
+  ::rust::MaybeUninit<::rust::Box<::concrete_optimizer::PreparedRestriction>> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$restriction$cxxbridge1$prepare_keyset_restriction(restriction, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+} // namespace restriction
+
+namespace utils {
+::rust::Box<::concrete_optimizer::ProgressCounter> progress_counter() noexcept {
+  return ::rust::Box<::concrete_optimizer::ProgressCounter>::from_raw(concrete_optimizer$utils$cxxbridge1$progress_counter());
+}
+} // namespace utils
+
+::std::uint32_t ProgressCounter::steps() const noexcept {
+  return concrete_optimizer$cxxbridge1$ProgressCounter$steps(*this);
+}
+
 namespace utils {
 ::rust::Box<::concrete_optimizer::Location> location_unknown() noexcept {
   return ::rust::Box<::concrete_optimizer::Location>::from_raw(concrete_optimizer$utils$cxxbridge1$location_unknown());
@@ -1602,6 +2317,27 @@ namespace utils {
   return ::rust::Box<::concrete_optimizer::Location>::from_raw(concrete_optimizer$utils$cxxbridge1$location_from_string(string));
 }
 
+::rust::String cache_directory() noexcept {
+  ::rust::MaybeUninit<::rust::String> return$;
+  concrete_optimizer$utils$cxxbridge1$cache_directory(&return$.value);
+  return ::std::move(return$.value);
+}
+
+void clear_cache() {
+  ::rust::repr::PtrLen error$ = concrete_optimizer$utils$cxxbridge1$clear_cache();
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+}
+
+void warm_cache(::concrete_optimizer::Options const &options) noexcept {
+  concrete_optimizer$utils$cxxbridge1$warm_cache(options);
+}
+
+::std::uint64_t estimate_search_space_size(::concrete_optimizer::Options const &options) noexcept {
+  return concrete_optimizer$utils$cxxbridge1$estimate_search_space_size(options);
+}
+
 ::CircuitKeys generate_virtual_keyset_info(::rust::Vec<::concrete_optimizer::utils::PartitionDefinition> partitions, bool generate_fks, ::concrete_optimizer::Options const &options) noexcept {
   ::rust::ManuallyDrop<::rust::Vec<::concrete_optimizer::utils::PartitionDefinition>> partitions$(::std::move(partitions));
   ::rust::MaybeUninit<::CircuitKeys> return$;
@@ -1609,12 +2345,52 @@ namespace utils {
   return ::std::move(return$.value);
 }
 
-::rust::Box<::concrete_optimizer::ExternalPartition> get_external_partition(::rust::String name, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t internal_dim, double max_variance, double variance) noexcept {
-  return ::rust::Box<::concrete_optimizer::ExternalPartition>::from_raw(concrete_optimizer$utils$cxxbridge1$get_external_partition(&name, log2_polynomial_size, glwe_dimension, internal_dim, max_variance, variance));
+::CircuitKeys generate_virtual_keyset_info_with_conversions(::rust::Vec<::concrete_optimizer::utils::PartitionDefinition> partitions, ::rust::Vec<::concrete_optimizer::utils::PartitionConversion> conversions, ::concrete_optimizer::Options const &options) {
+  ::rust::ManuallyDrop<::rust::Vec<::concrete_optimizer::utils::PartitionDefinition>> partitions$(::std::move(partitions));
+  ::rust::ManuallyDrop<::rust::Vec<::concrete_optimizer::utils::PartitionConversion>> conversions$(::std::move(conversions));
+  ::rust::MaybeUninit<::CircuitKeys> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$utils$cxxbridge1$generate_virtual_keyset_info_with_conversions(&partitions$.value, &conversions$.value, options, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
 }
 
-double get_noise_br(::concrete_optimizer::Options const &options, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t lwe_dim, ::std::uint64_t pbs_level, ::std::uint64_t pbs_log2_base) noexcept {
-  return concrete_optimizer$utils$cxxbridge1$get_noise_br(options, log2_polynomial_size, glwe_dimension, lwe_dim, pbs_level, pbs_log2_base);
+::rust::Box<::concrete_optimizer::ExternalPartition> get_external_partition(::rust::String name, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t internal_dim, double max_variance, double variance) {
+  ::rust::MaybeUninit<::rust::Box<::concrete_optimizer::ExternalPartition>> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$utils$cxxbridge1$get_external_partition(&name, log2_polynomial_size, glwe_dimension, internal_dim, max_variance, variance, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+double get_noise_br(::concrete_optimizer::Options const &options, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t lwe_dim, ::std::uint64_t pbs_level, ::std::uint64_t pbs_log2_base) {
+  ::rust::MaybeUninit<double> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$utils$cxxbridge1$get_noise_br(options, log2_polynomial_size, glwe_dimension, lwe_dim, pbs_level, pbs_log2_base, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+double get_noise_ks(::concrete_optimizer::Options const &options, ::std::uint64_t input_lwe_dim, ::std::uint64_t output_lwe_dim, ::std::uint64_t ks_level, ::std::uint64_t ks_log2_base) {
+  ::rust::MaybeUninit<double> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$utils$cxxbridge1$get_noise_ks(options, input_lwe_dim, output_lwe_dim, ks_level, ks_log2_base, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+double get_noise_modulus_switch(::concrete_optimizer::Options const &options, ::std::uint64_t lwe_dim, ::std::uint64_t log2_polynomial_size) noexcept {
+  return concrete_optimizer$utils$cxxbridge1$get_noise_modulus_switch(options, lwe_dim, log2_polynomial_size);
+}
+
+::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> enumerate_pbs_decompositions(::concrete_optimizer::Options const &options, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t lwe_dim) noexcept {
+  ::rust::MaybeUninit<::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate>> return$;
+  concrete_optimizer$utils$cxxbridge1$enumerate_pbs_decompositions(options, log2_polynomial_size, glwe_dimension, lwe_dim, &return$.value);
+  return ::std::move(return$.value);
 }
 } // namespace utils
 
@@ -1622,6 +2398,15 @@ namespace dag {
 ::rust::Box<::concrete_optimizer::Dag> empty() noexcept {
   return ::rust::Box<::concrete_optimizer::Dag>::from_raw(concrete_optimizer$dag$cxxbridge1$empty());
 }
+
+::rust::Box<::concrete_optimizer::Dag> dag_from_json(::rust::Str input) {
+  ::rust::MaybeUninit<::rust::Box<::concrete_optimizer::Dag>> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$dag$cxxbridge1$dag_from_json(input, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
 } // namespace dag
 
 ::rust::Box<::concrete_optimizer::DagBuilder> Dag::builder(::rust::String circuit) noexcept {
@@ -1634,6 +2419,18 @@ namespace dag {
   return ::std::move(return$.value);
 }
 
+::rust::String Dag::to_json() const noexcept {
+  ::rust::MaybeUninit<::rust::String> return$;
+  concrete_optimizer$cxxbridge1$Dag$to_json(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+
+::rust::String Dag::to_dot() const noexcept {
+  ::rust::MaybeUninit<::rust::String> return$;
+  concrete_optimizer$cxxbridge1$Dag$to_dot(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+
 ::rust::String DagBuilder::dump() const noexcept {
   ::rust::MaybeUninit<::rust::String> return$;
   concrete_optimizer$cxxbridge1$DagBuilder$dump(*this, &return$.value);
@@ -1644,26 +2441,176 @@ namespace dag {
   return concrete_optimizer$cxxbridge1$DagBuilder$add_input(*this, out_precision, out_shape, location);
 }
 
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_input_with_variance(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, double input_variance, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_input_with_variance(*this, out_precision, out_shape, input_variance, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_input_in_partition(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::ExternalPartition const &partition, ::concrete_optimizer::Location const &location) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_input_in_partition(*this, out_precision, out_shape, partition, location);
+}
+
+::rust::Vec<::concrete_optimizer::dag::OperatorIndex> DagBuilder::add_inputs(::rust::Slice<::concrete_optimizer::dag::InputSpec const> specs, ::concrete_optimizer::Location const &location) noexcept {
+  ::rust::MaybeUninit<::rust::Vec<::concrete_optimizer::dag::OperatorIndex>> return$;
+  concrete_optimizer$cxxbridge1$DagBuilder$add_inputs(*this, specs, location, &return$.value);
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_input_with_comment(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::rust::Str comment, ::concrete_optimizer::Location const &location) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_input_with_comment(*this, out_precision, out_shape, comment, location);
+}
+
 ::concrete_optimizer::dag::OperatorIndex DagBuilder::add_zero_noise(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept {
   return concrete_optimizer$cxxbridge1$DagBuilder$add_zero_noise(*this, out_precision, out_shape, location);
 }
 
-::concrete_optimizer::dag::OperatorIndex DagBuilder::add_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location) noexcept {
-  return concrete_optimizer$cxxbridge1$DagBuilder$add_lut(*this, input, table, out_precision, location);
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_lut(*this, input, table, out_precision, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_lut_with_comment(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::rust::Str comment, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_lut_with_comment(*this, input, table, out_precision, comment, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_signed_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_signed_lut(*this, input, table, out_precision, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::rust::Vec<::concrete_optimizer::dag::OperatorIndex> DagBuilder::add_multi_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::concrete_optimizer::dag::LutTable const> tables, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::rust::Vec<::concrete_optimizer::dag::OperatorIndex>> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_multi_lut(*this, input, tables, out_precision, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_rounded_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t rounded_precision, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_rounded_lut(*this, input, table, rounded_precision, out_precision, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_dot(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_dot(*this, inputs, weights.into_raw(), location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
 }
 
-::concrete_optimizer::dag::OperatorIndex DagBuilder::add_dot(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, ::concrete_optimizer::Location const &location) noexcept {
-  return concrete_optimizer$cxxbridge1$DagBuilder$add_dot(*this, inputs, weights.into_raw(), location);
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_dot_with_norm(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, double manual_norm2, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_dot_with_norm(*this, inputs, weights.into_raw(), manual_norm2, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_dot_with_comment(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, ::rust::Str comment, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_dot_with_comment(*this, inputs, weights.into_raw(), comment, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
 }
 
 ::concrete_optimizer::dag::OperatorIndex DagBuilder::add_linear_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, double lwe_dim_cost_factor, double fixed_cost, ::rust::Slice<double const> weights, ::rust::Slice<::std::uint64_t const> out_shape, ::rust::Str comment, ::concrete_optimizer::Location const &location) noexcept {
   return concrete_optimizer$cxxbridge1$DagBuilder$add_linear_noise(*this, inputs, lwe_dim_cost_factor, fixed_cost, weights, out_shape, comment, location);
 }
 
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_broadcast_linear_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, double lwe_dim_cost_factor, double fixed_cost, ::rust::Slice<double const> weights, ::rust::Str comment, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_broadcast_linear_noise(*this, inputs, lwe_dim_cost_factor, fixed_cost, weights, comment, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_broadcast_max_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_broadcast_max_noise(*this, inputs, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_sub(::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_sub(*this, lhs, rhs, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_negate(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_negate(*this, input, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_identity(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_identity(*this, input, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
 ::concrete_optimizer::dag::OperatorIndex DagBuilder::add_max_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept {
   return concrete_optimizer$cxxbridge1$DagBuilder$add_max_noise(*this, inputs, out_shape, location);
 }
 
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_tree_max(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Slice<::std::uint64_t const> out_shape, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_tree_max(*this, inputs, out_shape, out_precision, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_tree_pbs(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::std::uint8_t decomposition_base, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$add_tree_pbs(*this, input, table, out_precision, decomposition_base, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
 ::concrete_optimizer::dag::OperatorIndex DagBuilder::add_round_op(::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision, ::concrete_optimizer::Location const &location) noexcept {
   return concrete_optimizer$cxxbridge1$DagBuilder$add_round_op(*this, input, rounded_precision, location);
 }
@@ -1680,24 +2627,147 @@ namespace dag {
   return concrete_optimizer$cxxbridge1$DagBuilder$add_change_partition_with_dst(*this, input, dst_partition, location);
 }
 
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_change_partition(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &src_partition, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_change_partition(*this, input, src_partition, dst_partition, location);
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::add_keyswitch(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept {
+  return concrete_optimizer$cxxbridge1$DagBuilder$add_keyswitch(*this, input, dst_partition, location);
+}
+
 void DagBuilder::tag_operator_as_output(::concrete_optimizer::dag::OperatorIndex op) noexcept {
   concrete_optimizer$cxxbridge1$DagBuilder$tag_operator_as_output(*this, op);
 }
 
-::concrete_optimizer::dag::DagSolution Dag::optimize(::concrete_optimizer::Options const &options) const noexcept {
+void DagBuilder::tag_operators_as_output(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> ops) {
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$tag_operators_as_output(*this, ops);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+}
+
+::concrete_optimizer::dag::OperatorIndex DagBuilder::force_bootstrap(::concrete_optimizer::dag::OperatorIndex op, ::concrete_optimizer::Location const &location) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::OperatorIndex> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$DagBuilder$force_bootstrap(*this, op, location, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::DagSolution Dag::optimize(::concrete_optimizer::Options const &options) const {
   ::rust::MaybeUninit<::concrete_optimizer::dag::DagSolution> return$;
-  concrete_optimizer$cxxbridge1$Dag$optimize(*this, options, &return$.value);
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$optimize(*this, options, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::DagSolution Dag::optimize_with_progress(::concrete_optimizer::Options const &options, ::concrete_optimizer::ProgressCounter const &progress) const {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::DagSolution> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$optimize_with_progress(*this, options, progress, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
   return ::std::move(return$.value);
 }
 
+::rust::Vec<::concrete_optimizer::dag::DagSolution> Dag::optimize_pareto(::concrete_optimizer::Options const &options) const noexcept {
+  ::rust::MaybeUninit<::rust::Vec<::concrete_optimizer::dag::DagSolution>> return$;
+  concrete_optimizer$cxxbridge1$Dag$optimize_pareto(*this, options, &return$.value);
+  return ::std::move(return$.value);
+}
+
+::rust::Vec<::concrete_optimizer::dag::DagSolution> Dag::optimize_multi_security(::rust::Slice<::std::uint64_t const> security_levels, ::concrete_optimizer::Options const &options) const noexcept {
+  ::rust::MaybeUninit<::rust::Vec<::concrete_optimizer::dag::DagSolution>> return$;
+  concrete_optimizer$cxxbridge1$Dag$optimize_multi_security(*this, security_levels, options, &return$.value);
+  return ::std::move(return$.value);
+}
+
+::rust::Vec<::concrete_optimizer::dag::DagSolution> Dag::optimize_sweep_fft_precision(::concrete_optimizer::Options const &options, ::rust::Slice<::std::uint32_t const> candidates) const noexcept {
+  ::rust::MaybeUninit<::rust::Vec<::concrete_optimizer::dag::DagSolution>> return$;
+  concrete_optimizer$cxxbridge1$Dag$optimize_sweep_fft_precision(*this, options, candidates, &return$.value);
+  return ::std::move(return$.value);
+}
+
+namespace dag {
+::rust::Vec<::concrete_optimizer::dag::DagSolution> optimize_many(::rust::Slice<::rust::Box<::concrete_optimizer::Dag> const> dags, ::concrete_optimizer::Options const &options) noexcept {
+  ::rust::MaybeUninit<::rust::Vec<::concrete_optimizer::dag::DagSolution>> return$;
+  concrete_optimizer$dag$cxxbridge1$optimize_many(dags, options, &return$.value);
+  return ::std::move(return$.value);
+}
+
+void clear_solution_cache() noexcept {
+  concrete_optimizer$dag$cxxbridge1$clear_solution_cache();
+}
+
+::concrete_optimizer::dag::DagSolution evaluate_solution(::concrete_optimizer::Dag const &dag, ::concrete_optimizer::dag::DagSolution const &solution, ::concrete_optimizer::Options const &options) noexcept {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::DagSolution> return$;
+  concrete_optimizer$dag$cxxbridge1$evaluate_solution(dag, solution, options, &return$.value);
+  return ::std::move(return$.value);
+}
+} // namespace dag
+
 void Dag::add_composition(::std::string const &from_func, ::std::size_t from_pos, ::std::string const &to_func, ::std::size_t to_pos) noexcept {
   concrete_optimizer$cxxbridge1$Dag$add_composition(*this, from_func, from_pos, to_func, to_pos);
 }
 
+void Dag::add_composition_by_index(::concrete_optimizer::dag::OperatorIndex from_output, ::concrete_optimizer::dag::OperatorIndex to_input) {
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$add_composition_by_index(*this, from_output, to_input);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+}
+
 void Dag::add_all_compositions() noexcept {
   concrete_optimizer$cxxbridge1$Dag$add_all_compositions(*this);
 }
 
+void Dag::add_all_compositions_matching() noexcept {
+  concrete_optimizer$cxxbridge1$Dag$add_all_compositions_matching(*this);
+}
+
+::rust::Vec<::concrete_optimizer::dag::CompositionError> Dag::check_compositions() const noexcept {
+  ::rust::MaybeUninit<::rust::Vec<::concrete_optimizer::dag::CompositionError>> return$;
+  concrete_optimizer$cxxbridge1$Dag$check_compositions(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+
+bool Dag::is_composed() const noexcept {
+  return concrete_optimizer$cxxbridge1$Dag$is_composed(*this);
+}
+
+::rust::Vec<::concrete_optimizer::dag::OperatorIndex> Dag::merge(::concrete_optimizer::Dag const &other) {
+  ::rust::MaybeUninit<::rust::Vec<::concrete_optimizer::dag::OperatorIndex>> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$merge(*this, other, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+void Dag::remove_operator(::concrete_optimizer::dag::OperatorIndex index) {
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$remove_operator(*this, index);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+}
+
+void Dag::replace_lut_table(::concrete_optimizer::dag::OperatorIndex index, ::rust::Slice<::std::uint64_t const> table) {
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$replace_lut_table(*this, index, table);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+}
+
+void Dag::set_partition_assignment(::rust::Slice<::std::uint64_t const> assignment) {
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$set_partition_assignment(*this, assignment);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+}
+
 namespace dag {
 ::rust::String CircuitSolution::dump() const noexcept {
   ::rust::MaybeUninit<::rust::String> return$;
@@ -1705,38 +2775,187 @@ namespace dag {
   return ::std::move(return$.value);
 }
 
-::rust::String CircuitSolution::short_dump() const noexcept {
-  ::rust::MaybeUninit<::rust::String> return$;
-  concrete_optimizer$dag$cxxbridge1$CircuitSolution$short_dump(*this, &return$.value);
+::rust::String CircuitSolution::short_dump() const noexcept {
+  ::rust::MaybeUninit<::rust::String> return$;
+  concrete_optimizer$dag$cxxbridge1$CircuitSolution$short_dump(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+
+::rust::String CircuitSolution::diff(::concrete_optimizer::dag::CircuitSolution const &other) const noexcept {
+  ::rust::MaybeUninit<::rust::String> return$;
+  concrete_optimizer$dag$cxxbridge1$CircuitSolution$diff(*this, other, &return$.value);
+  return ::std::move(return$.value);
+}
+
+::rust::String CircuitSolution::to_json() const noexcept {
+  ::rust::MaybeUninit<::rust::String> return$;
+  concrete_optimizer$dag$cxxbridge1$CircuitSolution$to_json(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::CircuitSolution circuit_solution_from_json(::rust::Str input) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::CircuitSolution> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$dag$cxxbridge1$circuit_solution_from_json(input, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::rust::Vec<::std::uint8_t> CircuitSolution::to_bytes() const noexcept {
+  ::rust::MaybeUninit<::rust::Vec<::std::uint8_t>> return$;
+  concrete_optimizer$dag$cxxbridge1$CircuitSolution$to_bytes(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::CircuitSolution circuit_solution_from_bytes(::rust::Slice<::std::uint8_t const> bytes) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::CircuitSolution> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$dag$cxxbridge1$circuit_solution_from_bytes(bytes, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::rust::String DagSolution::to_json() const noexcept {
+  ::rust::MaybeUninit<::rust::String> return$;
+  concrete_optimizer$dag$cxxbridge1$DagSolution$to_json(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+
+double DagSolution::estimated_seconds(::concrete_optimizer::Options const &options) const noexcept {
+  return concrete_optimizer$dag$cxxbridge1$DagSolution$estimated_seconds(*this, options);
+}
+
+::std::uint64_t DagSolution::bootstrap_key_bytes() const noexcept {
+  return concrete_optimizer$dag$cxxbridge1$DagSolution$bootstrap_key_bytes(*this);
+}
+
+::std::uint64_t DagSolution::keyswitch_key_bytes() const noexcept {
+  return concrete_optimizer$dag$cxxbridge1$DagSolution$keyswitch_key_bytes(*this);
+}
+
+double DagSolution::log2_p_error() const noexcept {
+  return concrete_optimizer$dag$cxxbridge1$DagSolution$log2_p_error(*this);
+}
+} // namespace dag
+
+namespace v0 {
+double Solution::log2_p_error() const noexcept {
+  return concrete_optimizer$v0$cxxbridge1$Solution$log2_p_error(*this);
+}
+} // namespace v0
+
+namespace dag {
+::concrete_optimizer::dag::DagSolution dag_solution_from_json(::rust::Str input) {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::DagSolution> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$dag$cxxbridge1$dag_solution_from_json(input, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+} // namespace dag
+
+::std::size_t Weights::layout::size() noexcept {
+  return concrete_optimizer$cxxbridge1$Weights$operator$sizeof();
+}
+
+::std::size_t Weights::layout::align() noexcept {
+  return concrete_optimizer$cxxbridge1$Weights$operator$alignof();
+}
+
+namespace weights {
+::rust::Box<::concrete_optimizer::Weights> vector(::rust::Slice<::std::int64_t const> weights) noexcept {
+  return ::rust::Box<::concrete_optimizer::Weights>::from_raw(concrete_optimizer$weights$cxxbridge1$vector(weights));
+}
+
+::rust::Box<::concrete_optimizer::Weights> number(::std::int64_t weight) noexcept {
+  return ::rust::Box<::concrete_optimizer::Weights>::from_raw(concrete_optimizer$weights$cxxbridge1$number(weight));
+}
+
+::rust::Box<::concrete_optimizer::Weights> try_vector(::rust::Slice<::std::int64_t const> weights) {
+  ::rust::MaybeUninit<::rust::Box<::concrete_optimizer::Weights>> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$weights$cxxbridge1$try_vector(weights, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::rust::Box<::concrete_optimizer::Weights> try_number(::std::int64_t weight) {
+  ::rust::MaybeUninit<::rust::Box<::concrete_optimizer::Weights>> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$weights$cxxbridge1$try_number(weight, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::rust::Box<::concrete_optimizer::Weights> float_vector(::rust::Slice<double const> weights) noexcept {
+  return ::rust::Box<::concrete_optimizer::Weights>::from_raw(concrete_optimizer$weights$cxxbridge1$float_vector(weights));
+}
+} // namespace weights
+
+::std::size_t Dag::get_circuit_count() const noexcept {
+  return concrete_optimizer$cxxbridge1$Dag$get_circuit_count(*this);
+}
+
+::rust::Vec<::rust::String> Dag::get_circuit_names() const noexcept {
+  ::rust::MaybeUninit<::rust::Vec<::rust::String>> return$;
+  concrete_optimizer$cxxbridge1$Dag$get_circuit_names(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::DagSolutionWithNoiseMap Dag::optimize_with_noise_map(::concrete_optimizer::Options const &options) const {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::DagSolutionWithNoiseMap> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$optimize_with_noise_map(*this, options, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
   return ::std::move(return$.value);
 }
-} // namespace dag
 
-::std::size_t Weights::layout::size() noexcept {
-  return concrete_optimizer$cxxbridge1$Weights$operator$sizeof();
+::concrete_optimizer::dag::DagSolutionWithBudgetReport Dag::optimize_with_budget_report(::concrete_optimizer::Options const &options) const {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::DagSolutionWithBudgetReport> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$optimize_with_budget_report(*this, options, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
 }
 
-::std::size_t Weights::layout::align() noexcept {
-  return concrete_optimizer$cxxbridge1$Weights$operator$alignof();
+::concrete_optimizer::dag::CircuitSolution Dag::optimize_multi(::concrete_optimizer::Options const &options) const noexcept {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::CircuitSolution> return$;
+  concrete_optimizer$cxxbridge1$Dag$optimize_multi(*this, options, &return$.value);
+  return ::std::move(return$.value);
 }
 
-namespace weights {
-::rust::Box<::concrete_optimizer::Weights> vector(::rust::Slice<::std::int64_t const> weights) noexcept {
-  return ::rust::Box<::concrete_optimizer::Weights>::from_raw(concrete_optimizer$weights$cxxbridge1$vector(weights));
+bool Dag::is_feasible(::concrete_optimizer::Options const &options) const noexcept {
+  return concrete_optimizer$cxxbridge1$Dag$is_feasible(*this, options);
 }
 
-::rust::Box<::concrete_optimizer::Weights> number(::std::int64_t weight) noexcept {
-  return ::rust::Box<::concrete_optimizer::Weights>::from_raw(concrete_optimizer$weights$cxxbridge1$number(weight));
+::concrete_optimizer::dag::CircuitSolutionWithReport Dag::optimize_multi_with_report(::concrete_optimizer::Options const &options) const noexcept {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::CircuitSolutionWithReport> return$;
+  concrete_optimizer$cxxbridge1$Dag$optimize_multi_with_report(*this, options, &return$.value);
+  return ::std::move(return$.value);
 }
-} // namespace weights
 
-::std::size_t Dag::get_circuit_count() const noexcept {
-  return concrete_optimizer$cxxbridge1$Dag$get_circuit_count(*this);
+::concrete_optimizer::dag::CircuitSolution Dag::optimize_multi_cancellable(::concrete_optimizer::Options const &options, ::concrete_optimizer::CancellationToken const &cancel) const noexcept {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::CircuitSolution> return$;
+  concrete_optimizer$cxxbridge1$Dag$optimize_multi_cancellable(*this, options, cancel, &return$.value);
+  return ::std::move(return$.value);
 }
 
-::concrete_optimizer::dag::CircuitSolution Dag::optimize_multi(::concrete_optimizer::Options const &options) const noexcept {
+::concrete_optimizer::dag::CircuitSolution Dag::optimize_multi_with_prepared(::concrete_optimizer::Options const &options, ::concrete_optimizer::PreparedRestriction const &restriction) const noexcept {
   ::rust::MaybeUninit<::concrete_optimizer::dag::CircuitSolution> return$;
-  concrete_optimizer$cxxbridge1$Dag$optimize_multi(*this, options, &return$.value);
+  concrete_optimizer$cxxbridge1$Dag$optimize_multi_with_prepared(*this, options, restriction, &return$.value);
+  return ::std::move(return$.value);
+}
+
+::concrete_optimizer::dag::CircuitSolution Dag::optimize_multi_with_progress(::concrete_optimizer::Options const &options, ::concrete_optimizer::ProgressCounter const &progress) const noexcept {
+  ::rust::MaybeUninit<::concrete_optimizer::dag::CircuitSolution> return$;
+  concrete_optimizer$cxxbridge1$Dag$optimize_multi_with_progress(*this, options, progress, &return$.value);
   return ::std::move(return$.value);
 }
 
@@ -1752,6 +2971,48 @@ namespace weights {
   return ::std::move(return$.value);
 }
 
+::std::uint8_t Dag::get_operator_precision(::concrete_optimizer::dag::OperatorIndex index) const {
+  ::rust::MaybeUninit<::std::uint8_t> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$get_operator_precision(*this, index, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::rust::Vec<::std::uint64_t> Dag::get_operator_shape(::concrete_optimizer::dag::OperatorIndex index) const {
+  ::rust::MaybeUninit<::rust::Vec<::std::uint64_t>> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$get_operator_shape(*this, index, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::rust::String Dag::get_operator_comment(::concrete_optimizer::dag::OperatorIndex index) const {
+  ::rust::MaybeUninit<::rust::String> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$get_operator_comment(*this, index, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+double Dag::get_operator_norm2(::concrete_optimizer::dag::OperatorIndex index) const {
+  ::rust::MaybeUninit<double> return$;
+  ::rust::repr::PtrLen error$ = concrete_optimizer$cxxbridge1$Dag$get_operator_norm2(*this, index, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
+  return ::std::move(return$.value);
+}
+
+::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> Dag::operator_kind_histogram() const noexcept {
+  ::rust::MaybeUninit<::rust::Vec<::concrete_optimizer::dag::OperatorKindCount>> return$;
+  concrete_optimizer$cxxbridge1$Dag$operator_kind_histogram(*this, &return$.value);
+  return ::std::move(return$.value);
+}
+
 ::std::uint64_t NO_KEY_ID() noexcept {
   return concrete_optimizer$cxxbridge1$NO_KEY_ID();
 }
@@ -1763,9 +3024,12 @@ namespace restriction {
   return ::std::move(return$.value);
 }
 
-::concrete_optimizer::restriction::RangeRestriction range_restriction_from_json(::rust::Str input) noexcept {
+::concrete_optimizer::restriction::RangeRestriction range_restriction_from_json(::rust::Str input) {
   ::rust::MaybeUninit<::concrete_optimizer::restriction::RangeRestriction> return$;
-  concrete_optimizer$restriction$cxxbridge1$range_restriction_from_json(input, &return$.value);
+  ::rust::repr::PtrLen error$ = concrete_optimizer$restriction$cxxbridge1$range_restriction_from_json(input, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
   return ::std::move(return$.value);
 }
 
@@ -1775,15 +3039,30 @@ namespace restriction {
   return ::std::move(return$.value);
 }
 
-::concrete_optimizer::restriction::KeysetRestriction keyset_restriction_from_json(::rust::Str input) noexcept {
+::concrete_optimizer::restriction::KeysetRestriction keyset_restriction_from_json(::rust::Str input) {
   ::rust::MaybeUninit<::concrete_optimizer::restriction::KeysetRestriction> return$;
-  concrete_optimizer$restriction$cxxbridge1$keyset_restriction_from_json(input, &return$.value);
+  ::rust::repr::PtrLen error$ = concrete_optimizer$restriction$cxxbridge1$keyset_restriction_from_json(input, &return$.value);
+  if (error$.ptr) {
+    throw ::rust::impl<::rust::Error>::error(error$);
+  }
   return ::std::move(return$.value);
 }
 } // namespace restriction
 } // namespace concrete_optimizer
 
 extern "C" {
+::concrete_optimizer::CancellationToken *cxxbridge1$box$concrete_optimizer$CancellationToken$alloc() noexcept;
+void cxxbridge1$box$concrete_optimizer$CancellationToken$dealloc(::concrete_optimizer::CancellationToken *) noexcept;
+void cxxbridge1$box$concrete_optimizer$CancellationToken$drop(::rust::Box<::concrete_optimizer::CancellationToken> *ptr) noexcept;
+
+::concrete_optimizer::PreparedRestriction *cxxbridge1$box$concrete_optimizer$PreparedRestriction$alloc() noexcept;
+void cxxbridge1$box$concrete_optimizer$PreparedRestriction$dealloc(::concrete_optimizer::PreparedRestriction *) noexcept;
+void cxxbridge1$box$concrete_optimizer$PreparedRestriction$drop(::rust::Box<::concrete_optimizer::PreparedRestriction> *ptr) noexcept;
+
+::concrete_optimizer::ProgressCounter *cxxbridge1$box$concrete_optimizer$ProgressCounter$alloc() noexcept;
+void cxxbridge1$box$concrete_optimizer$ProgressCounter$dealloc(::concrete_optimizer::ProgressCounter *) noexcept;
+void cxxbridge1$box$concrete_optimizer$ProgressCounter$drop(::rust::Box<::concrete_optimizer::ProgressCounter> *ptr) noexcept;
+
 ::concrete_optimizer::Location *cxxbridge1$box$concrete_optimizer$Location$alloc() noexcept;
 void cxxbridge1$box$concrete_optimizer$Location$dealloc(::concrete_optimizer::Location *) noexcept;
 void cxxbridge1$box$concrete_optimizer$Location$drop(::rust::Box<::concrete_optimizer::Location> *ptr) noexcept;
@@ -1797,10 +3076,28 @@ void cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionDefinition$reserve_to
 void cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionDefinition$set_len(::rust::Vec<::concrete_optimizer::utils::PartitionDefinition> *ptr, ::std::size_t len) noexcept;
 void cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionDefinition$truncate(::rust::Vec<::concrete_optimizer::utils::PartitionDefinition> *ptr, ::std::size_t len) noexcept;
 
+void cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$new(::rust::Vec<::concrete_optimizer::utils::PartitionConversion> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$drop(::rust::Vec<::concrete_optimizer::utils::PartitionConversion> *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$len(::rust::Vec<::concrete_optimizer::utils::PartitionConversion> const *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$capacity(::rust::Vec<::concrete_optimizer::utils::PartitionConversion> const *ptr) noexcept;
+::concrete_optimizer::utils::PartitionConversion const *cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$data(::rust::Vec<::concrete_optimizer::utils::PartitionConversion> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$reserve_total(::rust::Vec<::concrete_optimizer::utils::PartitionConversion> *ptr, ::std::size_t new_cap) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$set_len(::rust::Vec<::concrete_optimizer::utils::PartitionConversion> *ptr, ::std::size_t len) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$truncate(::rust::Vec<::concrete_optimizer::utils::PartitionConversion> *ptr, ::std::size_t len) noexcept;
+
 ::concrete_optimizer::ExternalPartition *cxxbridge1$box$concrete_optimizer$ExternalPartition$alloc() noexcept;
 void cxxbridge1$box$concrete_optimizer$ExternalPartition$dealloc(::concrete_optimizer::ExternalPartition *) noexcept;
 void cxxbridge1$box$concrete_optimizer$ExternalPartition$drop(::rust::Box<::concrete_optimizer::ExternalPartition> *ptr) noexcept;
 
+void cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$new(::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$drop(::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$len(::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> const *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$capacity(::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> const *ptr) noexcept;
+::concrete_optimizer::utils::DecompositionCandidate const *cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$data(::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$reserve_total(::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> *ptr, ::std::size_t new_cap) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$set_len(::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> *ptr, ::std::size_t len) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$truncate(::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> *ptr, ::std::size_t len) noexcept;
+
 ::concrete_optimizer::Dag *cxxbridge1$box$concrete_optimizer$Dag$alloc() noexcept;
 void cxxbridge1$box$concrete_optimizer$Dag$dealloc(::concrete_optimizer::Dag *) noexcept;
 void cxxbridge1$box$concrete_optimizer$Dag$drop(::rust::Box<::concrete_optimizer::Dag> *ptr) noexcept;
@@ -1809,10 +3106,6 @@ void cxxbridge1$box$concrete_optimizer$Dag$drop(::rust::Box<::concrete_optimizer
 void cxxbridge1$box$concrete_optimizer$DagBuilder$dealloc(::concrete_optimizer::DagBuilder *) noexcept;
 void cxxbridge1$box$concrete_optimizer$DagBuilder$drop(::rust::Box<::concrete_optimizer::DagBuilder> *ptr) noexcept;
 
-::concrete_optimizer::Weights *cxxbridge1$box$concrete_optimizer$Weights$alloc() noexcept;
-void cxxbridge1$box$concrete_optimizer$Weights$dealloc(::concrete_optimizer::Weights *) noexcept;
-void cxxbridge1$box$concrete_optimizer$Weights$drop(::rust::Box<::concrete_optimizer::Weights> *ptr) noexcept;
-
 void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorIndex$new(::rust::Vec<::concrete_optimizer::dag::OperatorIndex> const *ptr) noexcept;
 void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorIndex$drop(::rust::Vec<::concrete_optimizer::dag::OperatorIndex> *ptr) noexcept;
 ::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorIndex$len(::rust::Vec<::concrete_optimizer::dag::OperatorIndex> const *ptr) noexcept;
@@ -1822,6 +3115,37 @@ void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorIndex$reserve_total(::ru
 void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorIndex$set_len(::rust::Vec<::concrete_optimizer::dag::OperatorIndex> *ptr, ::std::size_t len) noexcept;
 void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorIndex$truncate(::rust::Vec<::concrete_optimizer::dag::OperatorIndex> *ptr, ::std::size_t len) noexcept;
 
+::concrete_optimizer::Weights *cxxbridge1$box$concrete_optimizer$Weights$alloc() noexcept;
+void cxxbridge1$box$concrete_optimizer$Weights$dealloc(::concrete_optimizer::Weights *) noexcept;
+void cxxbridge1$box$concrete_optimizer$Weights$drop(::rust::Box<::concrete_optimizer::Weights> *ptr) noexcept;
+
+void cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$new(::rust::Vec<::concrete_optimizer::dag::DagSolution> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$drop(::rust::Vec<::concrete_optimizer::dag::DagSolution> *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$len(::rust::Vec<::concrete_optimizer::dag::DagSolution> const *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$capacity(::rust::Vec<::concrete_optimizer::dag::DagSolution> const *ptr) noexcept;
+::concrete_optimizer::dag::DagSolution const *cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$data(::rust::Vec<::concrete_optimizer::dag::DagSolution> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$reserve_total(::rust::Vec<::concrete_optimizer::dag::DagSolution> *ptr, ::std::size_t new_cap) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$set_len(::rust::Vec<::concrete_optimizer::dag::DagSolution> *ptr, ::std::size_t len) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$truncate(::rust::Vec<::concrete_optimizer::dag::DagSolution> *ptr, ::std::size_t len) noexcept;
+
+void cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$new(::rust::Vec<::concrete_optimizer::dag::CompositionError> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$drop(::rust::Vec<::concrete_optimizer::dag::CompositionError> *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$len(::rust::Vec<::concrete_optimizer::dag::CompositionError> const *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$capacity(::rust::Vec<::concrete_optimizer::dag::CompositionError> const *ptr) noexcept;
+::concrete_optimizer::dag::CompositionError const *cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$data(::rust::Vec<::concrete_optimizer::dag::CompositionError> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$reserve_total(::rust::Vec<::concrete_optimizer::dag::CompositionError> *ptr, ::std::size_t new_cap) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$set_len(::rust::Vec<::concrete_optimizer::dag::CompositionError> *ptr, ::std::size_t len) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$truncate(::rust::Vec<::concrete_optimizer::dag::CompositionError> *ptr, ::std::size_t len) noexcept;
+
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$new(::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$drop(::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$len(::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> const *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$capacity(::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> const *ptr) noexcept;
+::concrete_optimizer::dag::OperatorKindCount const *cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$data(::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$reserve_total(::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> *ptr, ::std::size_t new_cap) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$set_len(::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> *ptr, ::std::size_t len) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$truncate(::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> *ptr, ::std::size_t len) noexcept;
+
 static_assert(sizeof(::std::shared_ptr<::concrete_optimizer::restriction::RangeRestriction>) == 2 * sizeof(void *), "");
 static_assert(alignof(::std::shared_ptr<::concrete_optimizer::restriction::RangeRestriction>) == alignof(void *), "");
 void cxxbridge1$shared_ptr$concrete_optimizer$restriction$RangeRestriction$null(::std::shared_ptr<::concrete_optimizer::restriction::RangeRestriction> *ptr) noexcept {
@@ -1862,6 +3186,15 @@ void cxxbridge1$shared_ptr$concrete_optimizer$restriction$KeysetRestriction$drop
   self->~shared_ptr();
 }
 
+void cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$new(::rust::Vec<::concrete_optimizer::KeySharingGroup> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$drop(::rust::Vec<::concrete_optimizer::KeySharingGroup> *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$len(::rust::Vec<::concrete_optimizer::KeySharingGroup> const *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$capacity(::rust::Vec<::concrete_optimizer::KeySharingGroup> const *ptr) noexcept;
+::concrete_optimizer::KeySharingGroup const *cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$data(::rust::Vec<::concrete_optimizer::KeySharingGroup> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$reserve_total(::rust::Vec<::concrete_optimizer::KeySharingGroup> *ptr, ::std::size_t new_cap) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$set_len(::rust::Vec<::concrete_optimizer::KeySharingGroup> *ptr, ::std::size_t len) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$truncate(::rust::Vec<::concrete_optimizer::KeySharingGroup> *ptr, ::std::size_t len) noexcept;
+
 void cxxbridge1$rust_vec$concrete_optimizer$dag$SecretLweKey$new(::rust::Vec<::concrete_optimizer::dag::SecretLweKey> const *ptr) noexcept;
 void cxxbridge1$rust_vec$concrete_optimizer$dag$SecretLweKey$drop(::rust::Vec<::concrete_optimizer::dag::SecretLweKey> *ptr) noexcept;
 ::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$SecretLweKey$len(::rust::Vec<::concrete_optimizer::dag::SecretLweKey> const *ptr) noexcept;
@@ -1925,6 +3258,33 @@ void cxxbridge1$rust_vec$concrete_optimizer$dag$InstructionKeys$reserve_total(::
 void cxxbridge1$rust_vec$concrete_optimizer$dag$InstructionKeys$set_len(::rust::Vec<::concrete_optimizer::dag::InstructionKeys> *ptr, ::std::size_t len) noexcept;
 void cxxbridge1$rust_vec$concrete_optimizer$dag$InstructionKeys$truncate(::rust::Vec<::concrete_optimizer::dag::InstructionKeys> *ptr, ::std::size_t len) noexcept;
 
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$new(::rust::Vec<::concrete_optimizer::dag::OperatorNoise> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$drop(::rust::Vec<::concrete_optimizer::dag::OperatorNoise> *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$len(::rust::Vec<::concrete_optimizer::dag::OperatorNoise> const *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$capacity(::rust::Vec<::concrete_optimizer::dag::OperatorNoise> const *ptr) noexcept;
+::concrete_optimizer::dag::OperatorNoise const *cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$data(::rust::Vec<::concrete_optimizer::dag::OperatorNoise> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$reserve_total(::rust::Vec<::concrete_optimizer::dag::OperatorNoise> *ptr, ::std::size_t new_cap) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$set_len(::rust::Vec<::concrete_optimizer::dag::OperatorNoise> *ptr, ::std::size_t len) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$truncate(::rust::Vec<::concrete_optimizer::dag::OperatorNoise> *ptr, ::std::size_t len) noexcept;
+
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$new(::rust::Vec<::concrete_optimizer::dag::OperatorBudget> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$drop(::rust::Vec<::concrete_optimizer::dag::OperatorBudget> *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$len(::rust::Vec<::concrete_optimizer::dag::OperatorBudget> const *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$capacity(::rust::Vec<::concrete_optimizer::dag::OperatorBudget> const *ptr) noexcept;
+::concrete_optimizer::dag::OperatorBudget const *cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$data(::rust::Vec<::concrete_optimizer::dag::OperatorBudget> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$reserve_total(::rust::Vec<::concrete_optimizer::dag::OperatorBudget> *ptr, ::std::size_t new_cap) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$set_len(::rust::Vec<::concrete_optimizer::dag::OperatorBudget> *ptr, ::std::size_t len) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$truncate(::rust::Vec<::concrete_optimizer::dag::OperatorBudget> *ptr, ::std::size_t len) noexcept;
+
+void cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$new(::rust::Vec<::concrete_optimizer::dag::PartitionReport> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$drop(::rust::Vec<::concrete_optimizer::dag::PartitionReport> *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$len(::rust::Vec<::concrete_optimizer::dag::PartitionReport> const *ptr) noexcept;
+::std::size_t cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$capacity(::rust::Vec<::concrete_optimizer::dag::PartitionReport> const *ptr) noexcept;
+::concrete_optimizer::dag::PartitionReport const *cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$data(::rust::Vec<::concrete_optimizer::dag::PartitionReport> const *ptr) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$reserve_total(::rust::Vec<::concrete_optimizer::dag::PartitionReport> *ptr, ::std::size_t new_cap) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$set_len(::rust::Vec<::concrete_optimizer::dag::PartitionReport> *ptr, ::std::size_t len) noexcept;
+void cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$truncate(::rust::Vec<::concrete_optimizer::dag::PartitionReport> *ptr, ::std::size_t len) noexcept;
+
 void cxxbridge1$rust_vec$concrete_optimizer$restriction$LweSecretKeyInfo$new(::rust::Vec<::concrete_optimizer::restriction::LweSecretKeyInfo> const *ptr) noexcept;
 void cxxbridge1$rust_vec$concrete_optimizer$restriction$LweSecretKeyInfo$drop(::rust::Vec<::concrete_optimizer::restriction::LweSecretKeyInfo> *ptr) noexcept;
 ::std::size_t cxxbridge1$rust_vec$concrete_optimizer$restriction$LweSecretKeyInfo$len(::rust::Vec<::concrete_optimizer::restriction::LweSecretKeyInfo> const *ptr) noexcept;
@@ -1956,6 +3316,42 @@ void cxxbridge1$rust_vec$concrete_optimizer$restriction$LweKeyswitchKeyInfo$trun
 namespace rust {
 inline namespace cxxbridge1 {
 template <>
+::concrete_optimizer::CancellationToken *Box<::concrete_optimizer::CancellationToken>::allocation::alloc() noexcept {
+  return cxxbridge1$box$concrete_optimizer$CancellationToken$alloc();
+}
+template <>
+void Box<::concrete_optimizer::CancellationToken>::allocation::dealloc(::concrete_optimizer::CancellationToken *ptr) noexcept {
+  cxxbridge1$box$concrete_optimizer$CancellationToken$dealloc(ptr);
+}
+template <>
+void Box<::concrete_optimizer::CancellationToken>::drop() noexcept {
+  cxxbridge1$box$concrete_optimizer$CancellationToken$drop(this);
+}
+template <>
+::concrete_optimizer::PreparedRestriction *Box<::concrete_optimizer::PreparedRestriction>::allocation::alloc() noexcept {
+  return cxxbridge1$box$concrete_optimizer$PreparedRestriction$alloc();
+}
+template <>
+void Box<::concrete_optimizer::PreparedRestriction>::allocation::dealloc(::concrete_optimizer::PreparedRestriction *ptr) noexcept {
+  cxxbridge1$box$concrete_optimizer$PreparedRestriction$dealloc(ptr);
+}
+template <>
+void Box<::concrete_optimizer::PreparedRestriction>::drop() noexcept {
+  cxxbridge1$box$concrete_optimizer$PreparedRestriction$drop(this);
+}
+template <>
+::concrete_optimizer::ProgressCounter *Box<::concrete_optimizer::ProgressCounter>::allocation::alloc() noexcept {
+  return cxxbridge1$box$concrete_optimizer$ProgressCounter$alloc();
+}
+template <>
+void Box<::concrete_optimizer::ProgressCounter>::allocation::dealloc(::concrete_optimizer::ProgressCounter *ptr) noexcept {
+  cxxbridge1$box$concrete_optimizer$ProgressCounter$dealloc(ptr);
+}
+template <>
+void Box<::concrete_optimizer::ProgressCounter>::drop() noexcept {
+  cxxbridge1$box$concrete_optimizer$ProgressCounter$drop(this);
+}
+template <>
 ::concrete_optimizer::Location *Box<::concrete_optimizer::Location>::allocation::alloc() noexcept {
   return cxxbridge1$box$concrete_optimizer$Location$alloc();
 }
@@ -2000,6 +3396,38 @@ void Vec<::concrete_optimizer::utils::PartitionDefinition>::truncate(::std::size
   return cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionDefinition$truncate(this, len);
 }
 template <>
+Vec<::concrete_optimizer::utils::PartitionConversion>::Vec() noexcept {
+  cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$new(this);
+}
+template <>
+void Vec<::concrete_optimizer::utils::PartitionConversion>::drop() noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$drop(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::utils::PartitionConversion>::size() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$len(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::utils::PartitionConversion>::capacity() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$capacity(this);
+}
+template <>
+::concrete_optimizer::utils::PartitionConversion const *Vec<::concrete_optimizer::utils::PartitionConversion>::data() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$data(this);
+}
+template <>
+void Vec<::concrete_optimizer::utils::PartitionConversion>::reserve_total(::std::size_t new_cap) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$reserve_total(this, new_cap);
+}
+template <>
+void Vec<::concrete_optimizer::utils::PartitionConversion>::set_len(::std::size_t len) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$set_len(this, len);
+}
+template <>
+void Vec<::concrete_optimizer::utils::PartitionConversion>::truncate(::std::size_t len) {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$PartitionConversion$truncate(this, len);
+}
+template <>
 ::concrete_optimizer::ExternalPartition *Box<::concrete_optimizer::ExternalPartition>::allocation::alloc() noexcept {
   return cxxbridge1$box$concrete_optimizer$ExternalPartition$alloc();
 }
@@ -2012,6 +3440,38 @@ void Box<::concrete_optimizer::ExternalPartition>::drop() noexcept {
   cxxbridge1$box$concrete_optimizer$ExternalPartition$drop(this);
 }
 template <>
+Vec<::concrete_optimizer::utils::DecompositionCandidate>::Vec() noexcept {
+  cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$new(this);
+}
+template <>
+void Vec<::concrete_optimizer::utils::DecompositionCandidate>::drop() noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$drop(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::utils::DecompositionCandidate>::size() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$len(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::utils::DecompositionCandidate>::capacity() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$capacity(this);
+}
+template <>
+::concrete_optimizer::utils::DecompositionCandidate const *Vec<::concrete_optimizer::utils::DecompositionCandidate>::data() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$data(this);
+}
+template <>
+void Vec<::concrete_optimizer::utils::DecompositionCandidate>::reserve_total(::std::size_t new_cap) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$reserve_total(this, new_cap);
+}
+template <>
+void Vec<::concrete_optimizer::utils::DecompositionCandidate>::set_len(::std::size_t len) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$set_len(this, len);
+}
+template <>
+void Vec<::concrete_optimizer::utils::DecompositionCandidate>::truncate(::std::size_t len) {
+  return cxxbridge1$rust_vec$concrete_optimizer$utils$DecompositionCandidate$truncate(this, len);
+}
+template <>
 ::concrete_optimizer::Dag *Box<::concrete_optimizer::Dag>::allocation::alloc() noexcept {
   return cxxbridge1$box$concrete_optimizer$Dag$alloc();
 }
@@ -2036,18 +3496,6 @@ void Box<::concrete_optimizer::DagBuilder>::drop() noexcept {
   cxxbridge1$box$concrete_optimizer$DagBuilder$drop(this);
 }
 template <>
-::concrete_optimizer::Weights *Box<::concrete_optimizer::Weights>::allocation::alloc() noexcept {
-  return cxxbridge1$box$concrete_optimizer$Weights$alloc();
-}
-template <>
-void Box<::concrete_optimizer::Weights>::allocation::dealloc(::concrete_optimizer::Weights *ptr) noexcept {
-  cxxbridge1$box$concrete_optimizer$Weights$dealloc(ptr);
-}
-template <>
-void Box<::concrete_optimizer::Weights>::drop() noexcept {
-  cxxbridge1$box$concrete_optimizer$Weights$drop(this);
-}
-template <>
 Vec<::concrete_optimizer::dag::OperatorIndex>::Vec() noexcept {
   cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorIndex$new(this);
 }
@@ -2080,6 +3528,146 @@ void Vec<::concrete_optimizer::dag::OperatorIndex>::truncate(::std::size_t len)
   return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorIndex$truncate(this, len);
 }
 template <>
+::concrete_optimizer::Weights *Box<::concrete_optimizer::Weights>::allocation::alloc() noexcept {
+  return cxxbridge1$box$concrete_optimizer$Weights$alloc();
+}
+template <>
+void Box<::concrete_optimizer::Weights>::allocation::dealloc(::concrete_optimizer::Weights *ptr) noexcept {
+  cxxbridge1$box$concrete_optimizer$Weights$dealloc(ptr);
+}
+template <>
+void Box<::concrete_optimizer::Weights>::drop() noexcept {
+  cxxbridge1$box$concrete_optimizer$Weights$drop(this);
+}
+template <>
+Vec<::concrete_optimizer::dag::DagSolution>::Vec() noexcept {
+  cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$new(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::DagSolution>::drop() noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$drop(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::DagSolution>::size() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$len(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::DagSolution>::capacity() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$capacity(this);
+}
+template <>
+::concrete_optimizer::dag::DagSolution const *Vec<::concrete_optimizer::dag::DagSolution>::data() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$data(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::DagSolution>::reserve_total(::std::size_t new_cap) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$reserve_total(this, new_cap);
+}
+template <>
+void Vec<::concrete_optimizer::dag::DagSolution>::set_len(::std::size_t len) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$set_len(this, len);
+}
+template <>
+void Vec<::concrete_optimizer::dag::DagSolution>::truncate(::std::size_t len) {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$DagSolution$truncate(this, len);
+}
+template <>
+Vec<::concrete_optimizer::dag::CompositionError>::Vec() noexcept {
+  cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$new(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::CompositionError>::drop() noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$drop(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::CompositionError>::size() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$len(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::CompositionError>::capacity() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$capacity(this);
+}
+template <>
+::concrete_optimizer::dag::CompositionError const *Vec<::concrete_optimizer::dag::CompositionError>::data() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$data(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::CompositionError>::reserve_total(::std::size_t new_cap) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$reserve_total(this, new_cap);
+}
+template <>
+void Vec<::concrete_optimizer::dag::CompositionError>::set_len(::std::size_t len) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$set_len(this, len);
+}
+template <>
+void Vec<::concrete_optimizer::dag::CompositionError>::truncate(::std::size_t len) {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$CompositionError$truncate(this, len);
+}
+template <>
+Vec<::concrete_optimizer::dag::OperatorKindCount>::Vec() noexcept {
+  cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$new(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorKindCount>::drop() noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$drop(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::OperatorKindCount>::size() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$len(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::OperatorKindCount>::capacity() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$capacity(this);
+}
+template <>
+::concrete_optimizer::dag::OperatorKindCount const *Vec<::concrete_optimizer::dag::OperatorKindCount>::data() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$data(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorKindCount>::reserve_total(::std::size_t new_cap) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$reserve_total(this, new_cap);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorKindCount>::set_len(::std::size_t len) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$set_len(this, len);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorKindCount>::truncate(::std::size_t len) {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorKindCount$truncate(this, len);
+}
+template <>
+Vec<::concrete_optimizer::KeySharingGroup>::Vec() noexcept {
+  cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$new(this);
+}
+template <>
+void Vec<::concrete_optimizer::KeySharingGroup>::drop() noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$drop(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::KeySharingGroup>::size() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$len(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::KeySharingGroup>::capacity() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$capacity(this);
+}
+template <>
+::concrete_optimizer::KeySharingGroup const *Vec<::concrete_optimizer::KeySharingGroup>::data() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$data(this);
+}
+template <>
+void Vec<::concrete_optimizer::KeySharingGroup>::reserve_total(::std::size_t new_cap) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$reserve_total(this, new_cap);
+}
+template <>
+void Vec<::concrete_optimizer::KeySharingGroup>::set_len(::std::size_t len) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$set_len(this, len);
+}
+template <>
+void Vec<::concrete_optimizer::KeySharingGroup>::truncate(::std::size_t len) {
+  return cxxbridge1$rust_vec$concrete_optimizer$KeySharingGroup$truncate(this, len);
+}
+template <>
 Vec<::concrete_optimizer::dag::SecretLweKey>::Vec() noexcept {
   cxxbridge1$rust_vec$concrete_optimizer$dag$SecretLweKey$new(this);
 }
@@ -2304,6 +3892,102 @@ void Vec<::concrete_optimizer::dag::InstructionKeys>::truncate(::std::size_t len
   return cxxbridge1$rust_vec$concrete_optimizer$dag$InstructionKeys$truncate(this, len);
 }
 template <>
+Vec<::concrete_optimizer::dag::OperatorNoise>::Vec() noexcept {
+  cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$new(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorNoise>::drop() noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$drop(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::OperatorNoise>::size() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$len(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::OperatorNoise>::capacity() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$capacity(this);
+}
+template <>
+::concrete_optimizer::dag::OperatorNoise const *Vec<::concrete_optimizer::dag::OperatorNoise>::data() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$data(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorNoise>::reserve_total(::std::size_t new_cap) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$reserve_total(this, new_cap);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorNoise>::set_len(::std::size_t len) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$set_len(this, len);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorNoise>::truncate(::std::size_t len) {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorNoise$truncate(this, len);
+}
+template <>
+Vec<::concrete_optimizer::dag::OperatorBudget>::Vec() noexcept {
+  cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$new(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorBudget>::drop() noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$drop(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::OperatorBudget>::size() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$len(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::OperatorBudget>::capacity() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$capacity(this);
+}
+template <>
+::concrete_optimizer::dag::OperatorBudget const *Vec<::concrete_optimizer::dag::OperatorBudget>::data() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$data(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorBudget>::reserve_total(::std::size_t new_cap) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$reserve_total(this, new_cap);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorBudget>::set_len(::std::size_t len) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$set_len(this, len);
+}
+template <>
+void Vec<::concrete_optimizer::dag::OperatorBudget>::truncate(::std::size_t len) {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$OperatorBudget$truncate(this, len);
+}
+template <>
+Vec<::concrete_optimizer::dag::PartitionReport>::Vec() noexcept {
+  cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$new(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::PartitionReport>::drop() noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$drop(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::PartitionReport>::size() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$len(this);
+}
+template <>
+::std::size_t Vec<::concrete_optimizer::dag::PartitionReport>::capacity() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$capacity(this);
+}
+template <>
+::concrete_optimizer::dag::PartitionReport const *Vec<::concrete_optimizer::dag::PartitionReport>::data() const noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$data(this);
+}
+template <>
+void Vec<::concrete_optimizer::dag::PartitionReport>::reserve_total(::std::size_t new_cap) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$reserve_total(this, new_cap);
+}
+template <>
+void Vec<::concrete_optimizer::dag::PartitionReport>::set_len(::std::size_t len) noexcept {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$set_len(this, len);
+}
+template <>
+void Vec<::concrete_optimizer::dag::PartitionReport>::truncate(::std::size_t len) {
+  return cxxbridge1$rust_vec$concrete_optimizer$dag$PartitionReport$truncate(this, len);
+}
+template <>
 Vec<::concrete_optimizer::restriction::LweSecretKeyInfo>::Vec() noexcept {
   cxxbridge1$rust_vec$concrete_optimizer$restriction$LweSecretKeyInfo$new(this);
 }
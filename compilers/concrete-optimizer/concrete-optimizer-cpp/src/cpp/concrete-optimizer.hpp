@@ -1,4 +1,5 @@
 #pragma once
+#include "concrete-optimizer-log.hpp"
 #include <algorithm>
 #include <array>
 #include <cassert>
@@ -931,12 +932,23 @@ namespace concrete_optimizer {
   struct DagBuilder;
   struct Location;
   struct ExternalPartition;
+  struct CancellationToken;
+  struct ProgressCounter;
+  struct PreparedRestriction;
   struct Weights;
   enum class Encoding : ::std::uint8_t;
+  enum class Objective : ::std::uint8_t;
   enum class MultiParamStrategy : ::std::uint8_t;
+  enum class GpuPbsType : ::std::uint8_t;
+  struct KeySharingGroup;
+  struct SearchSpaceOptions;
   struct Options;
   namespace dag {
     struct OperatorIndex;
+    struct CompositionError;
+    struct LutTable;
+    struct InputSpec;
+    struct OperatorKindCount;
     struct DagSolution;
     struct BrDecompositionParameters;
     struct KsDecompositionParameters;
@@ -947,6 +959,13 @@ namespace concrete_optimizer {
     struct CircuitBoostrapKey;
     struct InstructionKeys;
     struct CircuitSolution;
+    enum class PartitionFeasibility : ::std::uint8_t;
+    struct PartitionReport;
+    struct OperatorNoise;
+    struct DagSolutionWithNoiseMap;
+    struct OperatorBudget;
+    struct DagSolutionWithBudgetReport;
+    struct CircuitSolutionWithReport;
   }
   namespace v0 {
     struct Solution;
@@ -960,7 +979,9 @@ namespace concrete_optimizer {
     struct KeysetRestriction;
   }
   namespace utils {
+    struct DecompositionCandidate;
     struct PartitionDefinition;
+    struct PartitionConversion;
   }
 }
 
@@ -970,13 +991,40 @@ namespace concrete_optimizer {
 struct Dag final : public ::rust::Opaque {
   ::rust::Box<::concrete_optimizer::DagBuilder> builder(::rust::String circuit) noexcept;
   ::rust::String dump() const noexcept;
-  ::concrete_optimizer::dag::DagSolution optimize(::concrete_optimizer::Options const &options) const noexcept;
+  ::rust::String to_json() const noexcept;
+  ::rust::String to_dot() const noexcept;
+  ::concrete_optimizer::dag::DagSolution optimize(::concrete_optimizer::Options const &options) const;
+  ::concrete_optimizer::dag::DagSolution optimize_with_progress(::concrete_optimizer::Options const &options, ::concrete_optimizer::ProgressCounter const &progress) const;
+  ::rust::Vec<::concrete_optimizer::dag::DagSolution> optimize_pareto(::concrete_optimizer::Options const &options) const noexcept;
+  ::rust::Vec<::concrete_optimizer::dag::DagSolution> optimize_multi_security(::rust::Slice<::std::uint64_t const> security_levels, ::concrete_optimizer::Options const &options) const noexcept;
+  ::rust::Vec<::concrete_optimizer::dag::DagSolution> optimize_sweep_fft_precision(::concrete_optimizer::Options const &options, ::rust::Slice<::std::uint32_t const> candidates) const noexcept;
   void add_composition(::std::string const &from_func, ::std::size_t from_pos, ::std::string const &to_func, ::std::size_t to_pos) noexcept;
+  void add_composition_by_index(::concrete_optimizer::dag::OperatorIndex from_output, ::concrete_optimizer::dag::OperatorIndex to_input);
   void add_all_compositions() noexcept;
+  void add_all_compositions_matching() noexcept;
+  ::rust::Vec<::concrete_optimizer::dag::CompositionError> check_compositions() const noexcept;
+  bool is_composed() const noexcept;
+  ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> merge(::concrete_optimizer::Dag const &other);
+  void remove_operator(::concrete_optimizer::dag::OperatorIndex index);
+  void replace_lut_table(::concrete_optimizer::dag::OperatorIndex index, ::rust::Slice<::std::uint64_t const> table);
+  void set_partition_assignment(::rust::Slice<::std::uint64_t const> assignment);
   ::std::size_t get_circuit_count() const noexcept;
+  ::rust::Vec<::rust::String> get_circuit_names() const noexcept;
+  ::concrete_optimizer::dag::DagSolutionWithNoiseMap optimize_with_noise_map(::concrete_optimizer::Options const &options) const;
+  ::concrete_optimizer::dag::DagSolutionWithBudgetReport optimize_with_budget_report(::concrete_optimizer::Options const &options) const;
   ::concrete_optimizer::dag::CircuitSolution optimize_multi(::concrete_optimizer::Options const &options) const noexcept;
+  bool is_feasible(::concrete_optimizer::Options const &options) const noexcept;
+  ::concrete_optimizer::dag::CircuitSolutionWithReport optimize_multi_with_report(::concrete_optimizer::Options const &options) const noexcept;
+  ::concrete_optimizer::dag::CircuitSolution optimize_multi_cancellable(::concrete_optimizer::Options const &options, ::concrete_optimizer::CancellationToken const &cancel) const noexcept;
+  ::concrete_optimizer::dag::CircuitSolution optimize_multi_with_prepared(::concrete_optimizer::Options const &options, ::concrete_optimizer::PreparedRestriction const &restriction) const noexcept;
+  ::concrete_optimizer::dag::CircuitSolution optimize_multi_with_progress(::concrete_optimizer::Options const &options, ::concrete_optimizer::ProgressCounter const &progress) const noexcept;
   ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> get_input_indices() const noexcept;
   ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> get_output_indices() const noexcept;
+  ::std::uint8_t get_operator_precision(::concrete_optimizer::dag::OperatorIndex index) const;
+  ::rust::Vec<::std::uint64_t> get_operator_shape(::concrete_optimizer::dag::OperatorIndex index) const;
+  ::rust::String get_operator_comment(::concrete_optimizer::dag::OperatorIndex index) const;
+  double get_operator_norm2(::concrete_optimizer::dag::OperatorIndex index) const;
+  ::rust::Vec<::concrete_optimizer::dag::OperatorKindCount> operator_kind_histogram() const noexcept;
   ~Dag() = delete;
 
 private:
@@ -993,16 +1041,41 @@ private:
 struct DagBuilder final : public ::rust::Opaque {
   ::rust::String dump() const noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_input(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_input_with_variance(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, double input_variance, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_input_in_partition(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::ExternalPartition const &partition, ::concrete_optimizer::Location const &location) noexcept;
+  ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> add_inputs(::rust::Slice<::concrete_optimizer::dag::InputSpec const> specs, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_input_with_comment(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::rust::Str comment, ::concrete_optimizer::Location const &location) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_zero_noise(::std::uint8_t out_precision, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept;
-  ::concrete_optimizer::dag::OperatorIndex add_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location) noexcept;
-  ::concrete_optimizer::dag::OperatorIndex add_dot(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_lut_with_comment(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::rust::Str comment, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_signed_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location);
+  ::rust::Vec<::concrete_optimizer::dag::OperatorIndex> add_multi_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::concrete_optimizer::dag::LutTable const> tables, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_rounded_lut(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t rounded_precision, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_dot(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_dot_with_norm(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, double manual_norm2, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_dot_with_comment(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Box<::concrete_optimizer::Weights> weights, ::rust::Str comment, ::concrete_optimizer::Location const &location);
   ::concrete_optimizer::dag::OperatorIndex add_linear_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, double lwe_dim_cost_factor, double fixed_cost, ::rust::Slice<double const> weights, ::rust::Slice<::std::uint64_t const> out_shape, ::rust::Str comment, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_broadcast_linear_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, double lwe_dim_cost_factor, double fixed_cost, ::rust::Slice<double const> weights, ::rust::Str comment, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_broadcast_max_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_sub(::concrete_optimizer::dag::OperatorIndex lhs, ::concrete_optimizer::dag::OperatorIndex rhs, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_negate(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_identity(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::Location const &location);
   ::concrete_optimizer::dag::OperatorIndex add_max_noise(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Slice<::std::uint64_t const> out_shape, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_tree_max(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> inputs, ::rust::Slice<::std::uint64_t const> out_shape, ::std::uint8_t out_precision, ::concrete_optimizer::Location const &location);
+  ::concrete_optimizer::dag::OperatorIndex add_tree_pbs(::concrete_optimizer::dag::OperatorIndex input, ::rust::Slice<::std::uint64_t const> table, ::std::uint8_t out_precision, ::std::uint8_t decomposition_base, ::concrete_optimizer::Location const &location);
   ::concrete_optimizer::dag::OperatorIndex add_round_op(::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision, ::concrete_optimizer::Location const &location) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_unsafe_cast_op(::concrete_optimizer::dag::OperatorIndex input, ::std::uint8_t rounded_precision, ::concrete_optimizer::Location const &location) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_change_partition_with_src(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &src_partition, ::concrete_optimizer::Location const &location) noexcept;
   ::concrete_optimizer::dag::OperatorIndex add_change_partition_with_dst(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_change_partition(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &src_partition, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept;
+  ::concrete_optimizer::dag::OperatorIndex add_keyswitch(::concrete_optimizer::dag::OperatorIndex input, ::concrete_optimizer::ExternalPartition const &dst_partition, ::concrete_optimizer::Location const &location) noexcept;
   void tag_operator_as_output(::concrete_optimizer::dag::OperatorIndex op) noexcept;
+  void tag_operators_as_output(::rust::Slice<::concrete_optimizer::dag::OperatorIndex const> ops);
+
+  // Adds an identity bootstrap on `op`, which must not have users yet, and returns its
+  // index. The existing indices are not changed.
+  ::concrete_optimizer::dag::OperatorIndex force_bootstrap(::concrete_optimizer::dag::OperatorIndex op, ::concrete_optimizer::Location const &location);
+
   ~DagBuilder() = delete;
 
 private:
@@ -1031,6 +1104,9 @@ private:
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$ExternalPartition
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$ExternalPartition
 struct ExternalPartition final : public ::rust::Opaque {
+  ::rust::String name() const noexcept;
+  double max_variance() const noexcept;
+  double variance() const noexcept;
   ~ExternalPartition() = delete;
 
 private:
@@ -1042,6 +1118,51 @@ private:
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$ExternalPartition
 
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$CancellationToken
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$CancellationToken
+struct CancellationToken final : public ::rust::Opaque {
+  void cancel() const noexcept;
+  bool is_cancelled() const noexcept;
+  ~CancellationToken() = delete;
+
+private:
+  friend ::rust::layout;
+  struct layout {
+    static ::std::size_t size() noexcept;
+    static ::std::size_t align() noexcept;
+  };
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$CancellationToken
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$ProgressCounter
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$ProgressCounter
+struct ProgressCounter final : public ::rust::Opaque {
+  ::std::uint32_t steps() const noexcept;
+  ~ProgressCounter() = delete;
+
+private:
+  friend ::rust::layout;
+  struct layout {
+    static ::std::size_t size() noexcept;
+    static ::std::size_t align() noexcept;
+  };
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$ProgressCounter
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$PreparedRestriction
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$PreparedRestriction
+struct PreparedRestriction final : public ::rust::Opaque {
+  ~PreparedRestriction() = delete;
+
+private:
+  friend ::rust::layout;
+  struct layout {
+    static ::std::size_t size() noexcept;
+    static ::std::size_t align() noexcept;
+  };
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$PreparedRestriction
+
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$Weights
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$Weights
 struct Weights final : public ::rust::Opaque {
@@ -1065,6 +1186,16 @@ enum class Encoding : ::std::uint8_t {
 };
 #endif // CXXBRIDGE1_ENUM_concrete_optimizer$Encoding
 
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$Objective
+#define CXXBRIDGE1_ENUM_concrete_optimizer$Objective
+enum class Objective : ::std::uint8_t {
+  MinComplexity = 0,
+  MinKeySize = 1,
+  Weighted = 2,
+  MaxThroughput = 3,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$Objective
+
 namespace dag {
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorIndex
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorIndex
@@ -1074,6 +1205,50 @@ struct OperatorIndex final {
   using IsRelocatable = ::std::true_type;
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorIndex
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CompositionError
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CompositionError
+struct CompositionError final {
+  ::rust::String from_circuit;
+  ::concrete_optimizer::dag::OperatorIndex from;
+  ::std::uint8_t from_precision;
+  ::rust::String to_circuit;
+  ::concrete_optimizer::dag::OperatorIndex to;
+  ::std::uint8_t to_precision;
+  ::rust::String message;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CompositionError
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$LutTable
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$LutTable
+struct LutTable final {
+  ::rust::Vec<::std::uint64_t> values;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$LutTable
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$InputSpec
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$InputSpec
+struct InputSpec final {
+  ::std::uint8_t precision;
+  ::rust::Vec<::std::uint64_t> shape;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$InputSpec
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorKindCount
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorKindCount
+struct OperatorKindCount final {
+  ::rust::String name;
+  ::std::uint64_t count;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorKindCount
 } // namespace dag
 
 namespace v0 {
@@ -1090,8 +1265,10 @@ struct Solution final {
   ::std::uint64_t br_decomposition_base_log;
   double complexity;
   double noise_max;
+  double noise_margin;
   double p_error;
 
+  double log2_p_error() const noexcept;
   using IsRelocatable = ::std::true_type;
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$v0$Solution
@@ -1111,6 +1288,7 @@ struct DagSolution final {
   ::std::uint64_t br_decomposition_base_log;
   double complexity;
   double noise_max;
+  double noise_margin;
   double p_error;
   double global_p_error;
   bool use_wop_pbs;
@@ -1119,7 +1297,14 @@ struct DagSolution final {
   ::std::uint64_t pp_decomposition_level_count;
   ::std::uint64_t pp_decomposition_base_log;
   ::rust::Vec<::std::uint64_t> crt_decomposition;
-
+  ::concrete_optimizer::Encoding chosen_encoding;
+
+  ::CircuitKeys required_keys() const noexcept;
+  ::rust::String to_json() const noexcept;
+  double estimated_seconds(::concrete_optimizer::Options const &options) const noexcept;
+  ::std::uint64_t bootstrap_key_bytes() const noexcept;
+  ::std::uint64_t keyswitch_key_bytes() const noexcept;
+  double log2_p_error() const noexcept;
   using IsRelocatable = ::std::true_type;
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolution
@@ -1130,14 +1315,24 @@ struct DagSolution final {
 enum class MultiParamStrategy : ::std::uint8_t {
   ByPrecision = 0,
   ByPrecisionAndNorm2 = 1,
+  Explicit = 2,
 };
 #endif // CXXBRIDGE1_ENUM_concrete_optimizer$MultiParamStrategy
 
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$GpuPbsType
+#define CXXBRIDGE1_ENUM_concrete_optimizer$GpuPbsType
+enum class GpuPbsType : ::std::uint8_t {
+  Amortized = 0,
+  LowLatency = 1,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$GpuPbsType
+
 namespace restriction {
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$restriction$RangeRestriction
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$restriction$RangeRestriction
 struct RangeRestriction final {
   ::rust::Vec<::std::uint64_t> glwe_log_polynomial_sizes;
+  ::rust::Vec<::std::uint64_t> glwe_polynomial_sizes;
   ::rust::Vec<::std::uint64_t> glwe_dimensions;
   ::rust::Vec<::std::uint64_t> internal_lwe_dimensions;
   ::rust::Vec<::std::uint64_t> pbs_level_count;
@@ -1151,6 +1346,31 @@ struct RangeRestriction final {
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$restriction$RangeRestriction
 } // namespace restriction
 
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$KeySharingGroup
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$KeySharingGroup
+struct KeySharingGroup final {
+  ::rust::Vec<::std::uint64_t> partitions;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$KeySharingGroup
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$SearchSpaceOptions
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$SearchSpaceOptions
+struct SearchSpaceOptions final {
+  ::std::uint64_t internal_lwe_dimension_min;
+  ::std::uint64_t internal_lwe_dimension_max;
+  ::std::uint64_t glwe_dimension_min;
+  ::std::uint64_t glwe_dimension_max;
+  ::std::uint64_t log2_polynomial_size_min;
+  ::std::uint64_t log2_polynomial_size_max;
+  ::std::uint64_t decomposition_level_min;
+  ::std::uint64_t decomposition_level_max;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$SearchSpaceOptions
+
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$Options
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$Options
 struct Options final {
@@ -1166,6 +1386,27 @@ struct Options final {
   ::std::uint32_t fft_precision;
   ::std::shared_ptr<::concrete_optimizer::restriction::RangeRestriction> range_restriction;
   ::std::shared_ptr<::concrete_optimizer::restriction::KeysetRestriction> keyset_restriction;
+  ::concrete_optimizer::GpuPbsType gpu_pbs_type;
+  ::std::uint64_t gpu_number_of_sm;
+  double maximum_acceptable_complexity;
+  ::rust::Vec<::std::uint64_t> crt_decomposition_hint;
+  ::rust::String security_curve_json;
+  double complexity_to_seconds;
+  bool allow_fast_keyswitch;
+  ::concrete_optimizer::Objective objective;
+  double objective_complexity_weight;
+  double objective_key_size_weight;
+  ::std::uint64_t objective_batch_size;
+  ::rust::Vec<::std::uint64_t> pinned_glwe_dimension;
+  ::rust::Vec<::std::uint64_t> pinned_polynomial_size;
+  double remote_pbs_latency;
+  double remote_pbs_bandwidth;
+  bool quiet;
+  ::std::uint64_t gpu_batch_size;
+  double secret_key_variance;
+  ::rust::Vec<::concrete_optimizer::KeySharingGroup> key_sharing_groups;
+  bool error_on_infeasible;
+  ::concrete_optimizer::SearchSpaceOptions search_space;
 
   using IsRelocatable = ::std::true_type;
 };
@@ -1312,12 +1553,95 @@ struct CircuitSolution final {
   double global_p_error;
   bool is_feasible;
   ::rust::String error_msg;
+  ::rust::Vec<::rust::String> used_external_partitions;
+  ::std::uint64_t unshared_secret_key_count;
 
   ::rust::String dump() const noexcept;
   ::rust::String short_dump() const noexcept;
+  ::rust::String diff(::concrete_optimizer::dag::CircuitSolution const &other) const noexcept;
+  ::rust::String to_json() const noexcept;
+  ::rust::Vec<::std::uint8_t> to_bytes() const noexcept;
   using IsRelocatable = ::std::true_type;
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CircuitSolution
+
+#ifndef CXXBRIDGE1_ENUM_concrete_optimizer$dag$PartitionFeasibility
+#define CXXBRIDGE1_ENUM_concrete_optimizer$dag$PartitionFeasibility
+enum class PartitionFeasibility : ::std::uint8_t {
+  Unknown = 0,
+  Feasible = 1,
+  Unfeasible = 2,
+};
+#endif // CXXBRIDGE1_ENUM_concrete_optimizer$dag$PartitionFeasibility
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$PartitionReport
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$PartitionReport
+struct PartitionReport final {
+  ::std::uint64_t partition;
+  bool has_lut;
+  ::std::uint8_t min_precision;
+  ::std::uint8_t max_precision;
+  bool has_macro_parameters;
+  ::std::uint64_t glwe_dimension;
+  ::std::uint64_t glwe_polynomial_size;
+  ::std::uint64_t internal_lwe_dimension;
+  ::concrete_optimizer::dag::PartitionFeasibility feasibility;
+  double complexity;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$PartitionReport
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorNoise
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorNoise
+struct OperatorNoise final {
+  ::concrete_optimizer::dag::OperatorIndex index;
+  double variance;
+  double margin;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorNoise
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithNoiseMap
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithNoiseMap
+struct DagSolutionWithNoiseMap final {
+  ::concrete_optimizer::dag::DagSolution solution;
+  ::rust::Vec<::concrete_optimizer::dag::OperatorNoise> operators;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithNoiseMap
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorBudget
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorBudget
+struct OperatorBudget final {
+  ::concrete_optimizer::dag::OperatorIndex index;
+  double p_error;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$OperatorBudget
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithBudgetReport
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithBudgetReport
+struct DagSolutionWithBudgetReport final {
+  ::concrete_optimizer::dag::DagSolution solution;
+  ::rust::Vec<::concrete_optimizer::dag::OperatorBudget> operators;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$DagSolutionWithBudgetReport
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CircuitSolutionWithReport
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CircuitSolutionWithReport
+struct CircuitSolutionWithReport final {
+  ::concrete_optimizer::dag::CircuitSolution solution;
+  ::rust::Vec<::concrete_optimizer::dag::PartitionReport> partitions;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$dag$CircuitSolutionWithReport
 } // namespace dag
 
 namespace restriction {
@@ -1378,6 +1702,18 @@ struct KeysetRestriction final {
 } // namespace restriction
 
 namespace utils {
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$utils$DecompositionCandidate
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$utils$DecompositionCandidate
+struct DecompositionCandidate final {
+  ::std::uint64_t level;
+  ::std::uint64_t base_log;
+  double max_variance;
+  double complexity;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$utils$DecompositionCandidate
+
 #ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionDefinition
 #define CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionDefinition
 struct PartitionDefinition final {
@@ -1387,43 +1723,105 @@ struct PartitionDefinition final {
   using IsRelocatable = ::std::true_type;
 };
 #endif // CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionDefinition
+
+#ifndef CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionConversion
+#define CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionConversion
+struct PartitionConversion final {
+  ::std::uint64_t src;
+  ::std::uint64_t dst;
+
+  using IsRelocatable = ::std::true_type;
+};
+#endif // CXXBRIDGE1_STRUCT_concrete_optimizer$utils$PartitionConversion
 } // namespace utils
 
 namespace v0 {
-::concrete_optimizer::v0::Solution optimize_bootstrap(::std::uint64_t precision, double noise_factor, ::concrete_optimizer::Options const &options) noexcept;
+::concrete_optimizer::v0::Solution optimize_bootstrap(::std::uint64_t precision, double noise_factor, ::concrete_optimizer::Options const &options);
+
+::concrete_optimizer::v0::Solution optimize_atomic_pattern(::std::uint64_t precision, double noise_factor, ::std::uint64_t sum_size, ::concrete_optimizer::Options const &options);
 } // namespace v0
 
+void check_security_curve_json(::rust::Str input);
+
+void validate_options(::concrete_optimizer::Options const &options);
+
 namespace utils {
 ::concrete_optimizer::dag::DagSolution convert_to_dag_solution(::concrete_optimizer::v0::Solution const &solution) noexcept;
 
 ::concrete_optimizer::dag::CircuitSolution convert_to_circuit_solution(::concrete_optimizer::dag::DagSolution const &solution, ::concrete_optimizer::Dag const &dag) noexcept;
 
+::rust::Box<::concrete_optimizer::CancellationToken> cancellation_token() noexcept;
+} // namespace utils
+
+namespace restriction {
+::rust::Box<::concrete_optimizer::PreparedRestriction> prepare_keyset_restriction(::concrete_optimizer::restriction::KeysetRestriction const &restriction);
+} // namespace restriction
+
+namespace utils {
+::rust::Box<::concrete_optimizer::ProgressCounter> progress_counter() noexcept;
+
 ::rust::Box<::concrete_optimizer::Location> location_unknown() noexcept;
 
 ::rust::Box<::concrete_optimizer::Location> location_from_string(::rust::Str string) noexcept;
 
+::rust::String cache_directory() noexcept;
+
+void clear_cache();
+
+void warm_cache(::concrete_optimizer::Options const &options) noexcept;
+
+::std::uint64_t estimate_search_space_size(::concrete_optimizer::Options const &options) noexcept;
+
 ::CircuitKeys generate_virtual_keyset_info(::rust::Vec<::concrete_optimizer::utils::PartitionDefinition> partitions, bool generate_fks, ::concrete_optimizer::Options const &options) noexcept;
 
-::rust::Box<::concrete_optimizer::ExternalPartition> get_external_partition(::rust::String name, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t internal_dim, double max_variance, double variance) noexcept;
+::CircuitKeys generate_virtual_keyset_info_with_conversions(::rust::Vec<::concrete_optimizer::utils::PartitionDefinition> partitions, ::rust::Vec<::concrete_optimizer::utils::PartitionConversion> conversions, ::concrete_optimizer::Options const &options);
+
+::rust::Box<::concrete_optimizer::ExternalPartition> get_external_partition(::rust::String name, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t internal_dim, double max_variance, double variance);
+
+double get_noise_br(::concrete_optimizer::Options const &options, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t lwe_dim, ::std::uint64_t pbs_level, ::std::uint64_t pbs_log2_base);
+
+double get_noise_ks(::concrete_optimizer::Options const &options, ::std::uint64_t input_lwe_dim, ::std::uint64_t output_lwe_dim, ::std::uint64_t ks_level, ::std::uint64_t ks_log2_base);
 
-double get_noise_br(::concrete_optimizer::Options const &options, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t lwe_dim, ::std::uint64_t pbs_level, ::std::uint64_t pbs_log2_base) noexcept;
+double get_noise_modulus_switch(::concrete_optimizer::Options const &options, ::std::uint64_t lwe_dim, ::std::uint64_t log2_polynomial_size) noexcept;
+
+::rust::Vec<::concrete_optimizer::utils::DecompositionCandidate> enumerate_pbs_decompositions(::concrete_optimizer::Options const &options, ::std::uint64_t log2_polynomial_size, ::std::uint64_t glwe_dimension, ::std::uint64_t lwe_dim) noexcept;
 } // namespace utils
 
 namespace dag {
 ::rust::Box<::concrete_optimizer::Dag> empty() noexcept;
+
+::rust::Box<::concrete_optimizer::Dag> dag_from_json(::rust::Str input);
+
+::rust::Vec<::concrete_optimizer::dag::DagSolution> optimize_many(::rust::Slice<::rust::Box<::concrete_optimizer::Dag> const> dags, ::concrete_optimizer::Options const &options) noexcept;
+
+void clear_solution_cache() noexcept;
+
+::concrete_optimizer::dag::DagSolution evaluate_solution(::concrete_optimizer::Dag const &dag, ::concrete_optimizer::dag::DagSolution const &solution, ::concrete_optimizer::Options const &options) noexcept;
+
+::concrete_optimizer::dag::CircuitSolution circuit_solution_from_json(::rust::Str input);
+
+::concrete_optimizer::dag::CircuitSolution circuit_solution_from_bytes(::rust::Slice<::std::uint8_t const> bytes);
+
+::concrete_optimizer::dag::DagSolution dag_solution_from_json(::rust::Str input);
 } // namespace dag
 
 namespace weights {
 ::rust::Box<::concrete_optimizer::Weights> vector(::rust::Slice<::std::int64_t const> weights) noexcept;
 
 ::rust::Box<::concrete_optimizer::Weights> number(::std::int64_t weight) noexcept;
+
+::rust::Box<::concrete_optimizer::Weights> try_vector(::rust::Slice<::std::int64_t const> weights);
+
+::rust::Box<::concrete_optimizer::Weights> try_number(::std::int64_t weight);
+
+::rust::Box<::concrete_optimizer::Weights> float_vector(::rust::Slice<double const> weights) noexcept;
 } // namespace weights
 
 ::std::uint64_t NO_KEY_ID() noexcept;
 
 namespace restriction {
-::concrete_optimizer::restriction::RangeRestriction range_restriction_from_json(::rust::Str input) noexcept;
+::concrete_optimizer::restriction::RangeRestriction range_restriction_from_json(::rust::Str input);
 
-::concrete_optimizer::restriction::KeysetRestriction keyset_restriction_from_json(::rust::Str input) noexcept;
+::concrete_optimizer::restriction::KeysetRestriction keyset_restriction_from_json(::rust::Str input);
 } // namespace restriction
 } // namespace concrete_optimizer
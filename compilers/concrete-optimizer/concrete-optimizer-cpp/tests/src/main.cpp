@@ -1,5 +1,11 @@
 #include "concrete-optimizer.hpp"
 #include <cassert>
+#include <cmath>
+#include <exception>
+#include <filesystem>
+#include <limits>
+#include <memory>
+#include <string>
 #include <vector>
 
 template <typename T>
@@ -31,7 +37,28 @@ concrete_optimizer::Options default_options() {
       .ciphertext_modulus_log = CIPHERTEXT_MODULUS_LOG,
       .fft_precision = 53,
       .range_restriction = {},
-      .keyset_restriction = {}
+      .keyset_restriction = {},
+      .gpu_pbs_type = concrete_optimizer::GpuPbsType::Amortized,
+      .gpu_number_of_sm = 0,
+      .maximum_acceptable_complexity = std::numeric_limits<double>::infinity(),
+      .crt_decomposition_hint = {},
+      .security_curve_json = "",
+      .complexity_to_seconds = 0.0,
+      .allow_fast_keyswitch = true,
+      .objective = concrete_optimizer::Objective::MinComplexity,
+      .objective_complexity_weight = 1.0,
+      .objective_key_size_weight = 0.0,
+      .objective_batch_size = 0,
+      .pinned_glwe_dimension = {},
+      .pinned_polynomial_size = {},
+      .remote_pbs_latency = 0.0,
+      .remote_pbs_bandwidth = 0.0,
+      .quiet = false,
+      .gpu_batch_size = 0,
+      .secret_key_variance = 0.0,
+      .key_sharing_groups = {},
+      .error_on_infeasible = false,
+      .search_space = {},
   };
 }
 
@@ -46,6 +73,190 @@ TEST test_v0() {
   assert(solution.glwe_polynomial_size == 256);
 }
 
+TEST test_noise_margin() {
+  auto options = default_options();
+  auto solution = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  assert(solution.p_error < 1.0);
+  assert(solution.noise_margin >= 1.0);
+
+  auto dag_solution = concrete_optimizer::utils::convert_to_dag_solution(solution);
+  assert(dag_solution.noise_margin == solution.noise_margin);
+}
+
+TEST test_v0_atomic_pattern_sum_size() {
+  auto options = default_options();
+  auto bootstrap = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  auto single = concrete_optimizer::v0::optimize_atomic_pattern(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, 1, options);
+  assert(single.complexity == bootstrap.complexity);
+  assert(single.input_lwe_dimension == bootstrap.input_lwe_dimension);
+
+  auto accumulated = concrete_optimizer::v0::optimize_atomic_pattern(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, 1000, options);
+  assert(accumulated.p_error < 1.0);
+  assert(accumulated.complexity > single.complexity);
+}
+
+TEST test_v0_gpu_pbs_type() {
+  auto options = default_options();
+  options.use_gpu_constraints = true;
+  options.gpu_pbs_type = concrete_optimizer::GpuPbsType::Amortized;
+  concrete_optimizer::v0::Solution amortized =
+      concrete_optimizer::v0::optimize_bootstrap(
+          PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  options.gpu_pbs_type = concrete_optimizer::GpuPbsType::LowLatency;
+  concrete_optimizer::v0::Solution lowlat =
+      concrete_optimizer::v0::optimize_bootstrap(
+          PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+
+  assert(amortized.p_error < 1.0);
+  assert(lowlat.p_error < 1.0);
+}
+
+TEST test_v0_gpu_batch_size() {
+  auto options = default_options();
+  options.use_gpu_constraints = true;
+  options.gpu_number_of_sm = 108;
+  options.gpu_pbs_type = concrete_optimizer::GpuPbsType::LowLatency;
+  auto lowlat = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  options.gpu_pbs_type = concrete_optimizer::GpuPbsType::Amortized;
+  options.gpu_batch_size = 1;
+  auto single = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  options.gpu_batch_size = 64;
+  auto batched = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+
+  assert(single.p_error < 1.0);
+  assert(single.complexity == lowlat.complexity);
+  assert(batched.p_error < 1.0);
+  assert(batched.complexity < single.complexity);
+}
+
+TEST test_log2_p_error() {
+  auto options = default_options();
+  auto solution = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_1B, NOISE_DEVIATION_COEFF, options);
+  assert(solution.log2_p_error() == -std::log2(solution.p_error));
+  auto dag_solution = concrete_optimizer::utils::convert_to_dag_solution(solution);
+  assert(dag_solution.log2_p_error() == solution.log2_p_error());
+
+  solution.p_error = 0.0;
+  assert(std::isinf(solution.log2_p_error()));
+  solution.p_error = 1.0;
+  assert(solution.log2_p_error() == 0.0);
+}
+
+TEST test_error_on_infeasible() {
+  auto options = default_options();
+  options.maximum_acceptable_complexity = 1.0;
+  auto sentinel = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  assert(sentinel.p_error == 1.0);
+
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+  assert(dag->optimize(options).p_error == 1.0);
+
+  options.error_on_infeasible = true;
+  bool thrown = false;
+  try {
+    concrete_optimizer::v0::optimize_bootstrap(PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  } catch (const std::exception &e) {
+    thrown = std::string(e.what()).find("8-bit bootstrap") != std::string::npos;
+  }
+  assert(thrown);
+  thrown = false;
+  try {
+    dag->optimize(options);
+  } catch (const std::exception &e) {
+    thrown = std::string(e.what()).find("No feasible parameters") != std::string::npos;
+  }
+  assert(thrown);
+  thrown = false;
+  try {
+    concrete_optimizer::v0::optimize_atomic_pattern(PRECISION_8B, NOISE_DEVIATION_COEFF, 16,
+                                                    options);
+  } catch (const std::exception &e) {
+    thrown = std::string(e.what()).find("summing 16 ciphertexts") != std::string::npos;
+  }
+  assert(thrown);
+  auto progress = concrete_optimizer::utils::progress_counter();
+  auto throws = [&](auto optimize) {
+    try {
+      optimize();
+    } catch (const std::exception &e) {
+      return std::string(e.what()).find("No feasible parameters") != std::string::npos;
+    }
+    return false;
+  };
+  assert(throws([&] { dag->optimize_with_progress(options, *progress); }));
+  assert(throws([&] { dag->optimize_with_noise_map(options); }));
+  assert(throws([&] { dag->optimize_with_budget_report(options); }));
+  // Several solutions keep the sentinel
+  assert(dag->optimize_pareto(options).empty());
+  std::vector<uint64_t> security_levels = {128};
+  assert(dag->optimize_multi_security(slice(security_levels), options)[0].p_error == 1.0);
+
+  options.maximum_acceptable_complexity = std::numeric_limits<double>::infinity();
+  assert(dag->optimize(options).p_error < 1.0);
+}
+
+TEST test_v0_max_throughput() {
+  auto options = default_options();
+  options.use_gpu_constraints = true;
+  options.gpu_number_of_sm = 108;
+  options.gpu_pbs_type = concrete_optimizer::GpuPbsType::LowLatency;
+  auto latency = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  options.objective = concrete_optimizer::Objective::MaxThroughput;
+  options.objective_batch_size = 64;
+  auto throughput = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  // same operating point as the amortized model at this batch size
+  options.objective = concrete_optimizer::Objective::MinComplexity;
+  options.gpu_pbs_type = concrete_optimizer::GpuPbsType::Amortized;
+  options.gpu_batch_size = 64;
+  auto amortized = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+
+  assert(throughput.p_error < 1.0);
+  assert(throughput.complexity < latency.complexity);
+  assert(throughput.complexity == amortized.complexity);
+
+  // on cpu nothing is amortized, the batch size is ignored
+  auto cpu = default_options();
+  auto cpu_latency = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, cpu);
+  cpu.objective = concrete_optimizer::Objective::MaxThroughput;
+  cpu.objective_batch_size = 64;
+  auto cpu_throughput = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, cpu);
+  assert(cpu_throughput.complexity == cpu_latency.complexity);
+}
+
+TEST test_v0_secret_key_variance() {
+  auto options = default_options();
+  auto binary = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  options.secret_key_variance = 2.0 / 3.0;
+  auto ternary = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+
+  assert(binary.p_error < 1.0);
+  assert(ternary.p_error < 1.0);
+  assert(ternary.complexity >= binary.complexity);
+}
+
 TEST test_dag_no_lut() {
   auto dag = concrete_optimizer::dag::empty();
   auto builder = dag->builder("test");
@@ -70,6 +281,680 @@ TEST test_dag_no_lut() {
   assert(solution.glwe_dimension == 562);
 }
 
+TEST test_dag_sub() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> lhs_shape = {4, 1};
+  std::vector<uint64_t> rhs_shape = {1, 8};
+
+  auto lhs = builder->add_input(PRECISION_8B, slice(lhs_shape),
+                                *concrete_optimizer::utils::location_unknown());
+  auto rhs = builder->add_input(PRECISION_8B, slice(rhs_shape),
+                                *concrete_optimizer::utils::location_unknown());
+  auto id = builder->add_sub(lhs, rhs,
+                             *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(id);
+
+  auto solution = dag->optimize(default_options());
+  assert(solution.glwe_polynomial_size == 1);
+
+  std::vector<uint64_t> other_shape = {2, 8};
+  auto other = builder->add_input(PRECISION_8B, slice(other_shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  bool thrown = false;
+  try {
+    builder->add_sub(lhs, other, *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_float_dot() {
+  auto make_dag = [](rust::Box<concrete_optimizer::Weights> weights) {
+    auto dag = concrete_optimizer::dag::empty();
+    auto builder = dag->builder("test");
+
+    std::vector<uint64_t> shape = {};
+    std::vector<u_int64_t> table = {};
+    auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                    *concrete_optimizer::utils::location_unknown());
+    auto lut1 = builder->add_lut(input, slice(table), PRECISION_8B,
+                                 *concrete_optimizer::utils::location_unknown());
+    auto lut2 = builder->add_lut(input, slice(table), PRECISION_8B,
+                                 *concrete_optimizer::utils::location_unknown());
+    std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {lut1, lut2};
+    auto dot = builder->add_dot(slice(inputs), std::move(weights),
+                                *concrete_optimizer::utils::location_unknown());
+    auto lut3 = builder->add_lut(dot, slice(table), PRECISION_8B,
+                                 *concrete_optimizer::utils::location_unknown());
+    builder->tag_operator_as_output(lut3);
+    return dag;
+  };
+
+  std::vector<int64_t> integer_weights = {1, 1};
+  auto integer_dag =
+      make_dag(concrete_optimizer::weights::vector(slice(integer_weights)));
+  std::vector<double> float_weights = {0.5, 0.5};
+  auto float_dag =
+      make_dag(concrete_optimizer::weights::float_vector(slice(float_weights)));
+
+  auto options = default_options();
+  auto integer_solution = integer_dag->optimize(options);
+  auto float_solution = float_dag->optimize(options);
+  assert(integer_solution.p_error < 1.0);
+  assert(float_solution.p_error < 1.0);
+  // smaller weights, less noise
+  assert(float_solution.complexity <= integer_solution.complexity);
+  assert(float_dag->optimize_multi(options).is_feasible);
+
+  std::vector<double> nan_weights = {0.5, std::nan("")};
+  bool thrown = false;
+  try {
+    make_dag(concrete_optimizer::weights::float_vector(slice(nan_weights)));
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_checked_weights() {
+  std::vector<int64_t> weights = {3, -4};
+  // do not throw
+  concrete_optimizer::weights::try_vector(slice(weights));
+  concrete_optimizer::weights::try_number(-3);
+
+  std::vector<int64_t> huge_weights = {1, INT64_MAX};
+  bool thrown = false;
+  try {
+    concrete_optimizer::weights::try_vector(slice(huge_weights));
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  thrown = false;
+  try {
+    concrete_optimizer::weights::try_number(INT64_MIN);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_tree_max() {
+  auto make_dag = [](bool tree) {
+    auto dag = concrete_optimizer::dag::empty();
+    auto builder = dag->builder("test");
+
+    std::vector<uint64_t> shape = {3};
+    std::vector<concrete_optimizer::dag::OperatorIndex> inputs;
+    for (int i = 0; i < 4; i++) {
+      inputs.push_back(builder->add_input(
+          PRECISION_8B, slice(shape), *concrete_optimizer::utils::location_unknown()));
+    }
+    auto max = tree ? builder->add_tree_max(slice(inputs), slice(shape), PRECISION_8B,
+                                            *concrete_optimizer::utils::location_unknown())
+                    : builder->add_max_noise(slice(inputs), slice(shape),
+                                             *concrete_optimizer::utils::location_unknown());
+    builder->tag_operator_as_output(max);
+    return dag;
+  };
+
+  auto options = default_options();
+  auto flat = make_dag(false)->optimize(options);
+  auto tree = make_dag(true)->optimize(options);
+  assert(tree.p_error < 1.0);
+  // 3 pbs against none
+  assert(tree.complexity > flat.complexity);
+
+  auto single = concrete_optimizer::dag::empty();
+  auto builder = single->builder("test");
+  std::vector<uint64_t> shape = {3};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {input};
+  auto max = builder->add_tree_max(slice(inputs), slice(shape), PRECISION_8B,
+                                   *concrete_optimizer::utils::location_unknown());
+  assert(max.index == input.index);
+
+  bool thrown = false;
+  std::vector<concrete_optimizer::dag::OperatorIndex> no_inputs = {};
+  try {
+    builder->add_tree_max(slice(no_inputs), slice(shape), PRECISION_8B,
+                          *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_tree_pbs() {
+  auto make_dag = [](uint8_t decomposition_base) {
+    auto dag = concrete_optimizer::dag::empty();
+    auto builder = dag->builder("test");
+    std::vector<uint64_t> shape = {};
+    std::vector<u_int64_t> table = {};
+    auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                    *concrete_optimizer::utils::location_unknown());
+    auto lut = builder->add_tree_pbs(input, slice(table), PRECISION_8B,
+                                     decomposition_base,
+                                     *concrete_optimizer::utils::location_unknown());
+    builder->tag_operator_as_output(lut);
+    return dag;
+  };
+
+  auto options = default_options();
+  auto single = make_dag(PRECISION_8B)->optimize_multi(options);
+  auto tree = make_dag(4)->optimize_multi(options);
+  assert(single.is_feasible);
+  assert(tree.is_feasible);
+  // 4-bit pbs need smaller glwe keys than a 8-bit one
+  auto max_glwe_size = [](const concrete_optimizer::dag::CircuitSolution &solution) {
+    uint64_t size = 0;
+    for (auto &key : solution.circuit_keys.bootstrap_keys) {
+      size = std::max(size, key.output_key.polynomial_size * key.output_key.glwe_dimension);
+    }
+    return size;
+  };
+  assert(max_glwe_size(tree) < max_glwe_size(single));
+
+  bool thrown = false;
+  try {
+    make_dag(0);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_broadcast_linear_noise() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> lhs_shape = {4, 1};
+  std::vector<uint64_t> rhs_shape = {1, 8};
+
+  auto lhs = builder->add_input(PRECISION_8B, slice(lhs_shape),
+                                *concrete_optimizer::utils::location_unknown());
+  auto rhs = builder->add_input(PRECISION_8B, slice(rhs_shape),
+                                *concrete_optimizer::utils::location_unknown());
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {lhs, rhs};
+  std::vector<double> weights = {1.0, 1.0};
+  auto id = builder->add_broadcast_linear_noise(
+      slice(inputs), 1.0, 0.0, slice(weights), "add",
+      *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(id);
+
+  assert(std::string(builder->dump()).find("[4, 8]") != std::string::npos);
+
+  std::vector<double> missing_weights = {1.0};
+  bool thrown = false;
+  try {
+    builder->add_broadcast_linear_noise(
+        slice(inputs), 1.0, 0.0, slice(missing_weights), "add",
+        *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  std::vector<uint64_t> other_shape = {2, 8};
+  auto other = builder->add_input(PRECISION_8B, slice(other_shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<concrete_optimizer::dag::OperatorIndex> incompatible = {lhs, other};
+  thrown = false;
+  try {
+    builder->add_broadcast_max_noise(
+        slice(incompatible), *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_negate() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  auto id =
+      builder->add_negate(input, *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(id);
+
+  assert(std::string(builder->dump()).find("negate") != std::string::npos);
+
+  bool thrown = false;
+  try {
+    builder->add_negate(concrete_optimizer::dag::OperatorIndex{42},
+                        *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_identity() {
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+
+  auto build = [&](bool with_identity) {
+    auto dag = concrete_optimizer::dag::empty();
+    auto builder = dag->builder("test");
+    auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                    *concrete_optimizer::utils::location_unknown());
+    if (with_identity) {
+      input = builder->add_identity(input, *concrete_optimizer::utils::location_unknown());
+      assert(dag->get_operator_precision(input) == PRECISION_8B);
+    }
+    builder->add_lut(input, slice(table), PRECISION_8B,
+                     *concrete_optimizer::utils::location_unknown());
+    return dag;
+  };
+
+  auto options = default_options();
+  auto plain = build(false)->optimize(options);
+  auto with_identity = build(true)->optimize(options);
+  assert(with_identity.complexity == plain.complexity);
+  assert(with_identity.p_error == plain.p_error);
+
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  bool thrown = false;
+  try {
+    builder->add_identity(concrete_optimizer::dag::OperatorIndex{0},
+                          *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_edit() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                              *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> new_table(256, 1);
+  dag->replace_lut_table(lut, slice(new_table));
+
+  bool thrown_size = false;
+  std::vector<u_int64_t> wrong_table = {1, 0};
+  try {
+    dag->replace_lut_table(lut, slice(wrong_table));
+  } catch (const std::exception &) {
+    thrown_size = true;
+  }
+  assert(thrown_size);
+
+  bool thrown = false;
+  try {
+    dag->remove_operator(input);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  dag->remove_operator(lut);
+  assert(dag->get_output_indices().size() == 1);
+}
+
+TEST test_dag_merge() {
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+
+  auto dag = concrete_optimizer::dag::empty();
+  {
+    auto builder = dag->builder("main");
+    auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                    *concrete_optimizer::utils::location_unknown());
+    builder->add_lut(input, slice(table), PRECISION_8B,
+                     *concrete_optimizer::utils::location_unknown());
+  }
+
+  auto other = concrete_optimizer::dag::empty();
+  {
+    auto builder = other->builder("other");
+    auto input = builder->add_input(PRECISION_1B, slice(shape),
+                                    *concrete_optimizer::utils::location_unknown());
+    builder->add_lut(input, slice(table), PRECISION_1B,
+                     *concrete_optimizer::utils::location_unknown());
+  }
+
+  auto mapping = dag->merge(*other);
+  auto names = dag->get_circuit_names();
+  assert(names.size() == 2);
+  assert(std::string(names[0]) == "main");
+  assert(std::string(names[1]) == "other");
+  assert(mapping.size() == 2);
+  assert(mapping[0].index == 2);
+  assert(mapping[1].index == 3);
+  assert(dag->get_operator_precision(mapping[1]) == PRECISION_1B);
+  assert(dag->get_output_indices().size() == 2);
+
+  bool thrown = false;
+  try {
+    dag->merge(*other);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_optimize_many() {
+  std::vector<rust::Box<concrete_optimizer::Dag>> dags;
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+
+  dags.push_back(concrete_optimizer::dag::empty());
+  {
+    auto builder = dags[0]->builder("test");
+    auto input = builder->add_input(
+        PRECISION_8B, slice(shape), *concrete_optimizer::utils::location_unknown());
+    builder->add_lut(input, slice(table), PRECISION_8B,
+                     *concrete_optimizer::utils::location_unknown());
+  }
+
+  dags.push_back(concrete_optimizer::dag::empty());
+  {
+    auto builder = dags[1]->builder("test");
+    auto input = builder->add_input(
+        PRECISION_8B, slice(shape), *concrete_optimizer::utils::location_unknown());
+    builder->add_lut(input, slice(table), PRECISION_8B,
+                     *concrete_optimizer::utils::location_unknown());
+  }
+  dags[1]->add_all_compositions();
+
+  auto solutions =
+      concrete_optimizer::dag::optimize_many(slice(dags), default_options());
+  assert(solutions.size() == 2);
+
+  auto expected = dags[0]->optimize(default_options());
+  assert(solutions[0].glwe_dimension == expected.glwe_dimension);
+  assert(solutions[0].glwe_polynomial_size == expected.glwe_polynomial_size);
+  assert(solutions[0].p_error == expected.p_error);
+  assert(solutions[1].p_error == 1.0);
+}
+
+TEST test_dag_sweep_fft_precision() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto options = default_options();
+  std::vector<uint32_t> candidates = {53, 40};
+  auto solutions = dag->optimize_sweep_fft_precision(options, slice(candidates));
+  assert(solutions.size() == 2);
+
+  auto expected = dag->optimize(options);
+  assert(solutions[0].complexity == expected.complexity);
+  assert(solutions[0].p_error == expected.p_error);
+  // a less precise fft adds noise
+  assert(solutions[1].p_error < 1.0);
+  assert(solutions[1].complexity >= solutions[0].complexity);
+
+  assert(dag->optimize_sweep_fft_precision(options, {}).empty());
+}
+
+TEST test_dag_optimize_multi_security() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto options = default_options();
+  std::vector<uint64_t> security_levels = {132, 128};
+  auto solutions =
+      dag->optimize_multi_security(slice(security_levels), options);
+  assert(solutions.size() == 2);
+
+  auto expected = dag->optimize(options);
+  assert(solutions[1].complexity == expected.complexity);
+  assert(solutions[1].p_error == expected.p_error);
+  // a higher security requires larger parameters
+  assert(solutions[0].p_error < 1.0);
+  assert(solutions[0].complexity > solutions[1].complexity);
+
+  assert(dag->optimize_multi_security({}, options).empty());
+}
+
+TEST test_dag_solution_cache() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  concrete_optimizer::dag::clear_solution_cache();
+  auto options = default_options();
+  auto first = dag->optimize(options);
+  auto cached = dag->optimize(options);
+  assert(cached.complexity == first.complexity);
+  assert(cached.p_error == first.p_error);
+
+  // a constrained complexity is another cache entry
+  options.maximum_acceptable_complexity = first.complexity / 2.0;
+  assert(dag->optimize(options).p_error == 1.0);
+  options = default_options();
+
+  // a modified dag is another cache entry
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+  auto bigger = dag->optimize(options);
+  assert(bigger.complexity > first.complexity);
+
+  concrete_optimizer::dag::clear_solution_cache();
+  assert(dag->optimize(options).complexity == bigger.complexity);
+}
+
+TEST test_dag_operator_introspection() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {2, 3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+
+  assert(dag->get_operator_precision(input) == PRECISION_8B);
+  auto input_shape = dag->get_operator_shape(input);
+  assert(input_shape.size() == 2);
+  assert(input_shape[0] == 2 && input_shape[1] == 3);
+
+  bool thrown = false;
+  try {
+    dag->get_operator_precision(concrete_optimizer::dag::OperatorIndex{1});
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_operator_comments() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+
+  auto input = builder->add_input_with_comment(PRECISION_8B, slice(shape), "x = input()",
+                                               *concrete_optimizer::utils::location_unknown());
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {input};
+  std::vector<int64_t> weight_vec = {2};
+  auto dot = builder->add_dot_with_comment(
+      slice(inputs), concrete_optimizer::weights::vector(slice(weight_vec)), "y = 2 * x",
+      *concrete_optimizer::utils::location_unknown());
+  auto lut = builder->add_lut_with_comment(dot, slice(table), PRECISION_8B, "z = f(y)",
+                                           *concrete_optimizer::utils::location_unknown());
+  auto plain = builder->add_lut(lut, slice(table), PRECISION_8B,
+                                *concrete_optimizer::utils::location_unknown());
+
+  assert(std::string(dag->get_operator_comment(input)) == "x = input()");
+  assert(std::string(dag->get_operator_comment(dot)) == "y = 2 * x");
+  assert(std::string(dag->get_operator_comment(lut)) == "z = f(y)");
+  assert(dag->get_operator_comment(plain).empty());
+  assert(std::string(dag->dump()).find("z = f(y)") != std::string::npos);
+
+  bool thrown = false;
+  try {
+    dag->get_operator_comment(concrete_optimizer::dag::OperatorIndex{4});
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_to_dot() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto dot = std::string(dag->to_dot());
+  assert(dot.rfind("digraph dag {", 0) == 0);
+  assert(dot.find("%1 = Lut\\nprecision: 8\\nshape: [3]\", shape=box") != std::string::npos);
+  assert(dot.find("0 -> 1;") != std::string::npos);
+}
+
+TEST test_dag_operator_norm2() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  auto lut1 = builder->add_lut(input, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  auto lut2 = builder->add_lut(lut1, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {lut2};
+  std::vector<int64_t> weight_vec = {1 << 16};
+  auto dot = builder->add_dot(slice(inputs),
+                              concrete_optimizer::weights::vector(slice(weight_vec)),
+                              *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(dot);
+
+  assert(dag->get_operator_norm2(lut1) == 0.0);
+  assert(dag->get_operator_norm2(lut2) == 16.0);
+
+  bool thrown = false;
+  try {
+    dag->get_operator_norm2(input);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_add_dot_with_norm() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  auto lut1 = builder->add_lut(input, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  auto lut2 = builder->add_lut(lut1, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs1 = {lut1};
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs2 = {lut2};
+  std::vector<int64_t> weight_vec = {1 << 16};
+  auto dot1 = builder->add_dot_with_norm(slice(inputs1),
+                                         concrete_optimizer::weights::vector(slice(weight_vec)),
+                                         1 << 10, *concrete_optimizer::utils::location_unknown());
+  auto dot2 = builder->add_dot_with_norm(slice(inputs2),
+                                         concrete_optimizer::weights::vector(slice(weight_vec)),
+                                         -1.0, *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(dot1);
+  builder->tag_operator_as_output(dot2);
+
+  assert(dag->get_operator_norm2(lut1) == 10.0);
+  assert(dag->get_operator_norm2(lut2) == 16.0);
+}
+
+TEST test_dag_operator_kind_histogram() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  uint64_t luts = 0;
+  uint64_t total = 0;
+  for (auto kind : dag->operator_kind_histogram()) {
+    if (std::string(kind.name) == "Lut") {
+      luts = kind.count;
+    }
+    total += kind.count;
+  }
+  assert(luts == 1);
+  assert(total == 2);
+}
+
+TEST test_dag_tag_operators_as_output() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                              *concrete_optimizer::utils::location_unknown());
+
+  std::vector<concrete_optimizer::dag::OperatorIndex> invalid = {
+      input, concrete_optimizer::dag::OperatorIndex{2}};
+  bool thrown = false;
+  try {
+    builder->tag_operators_as_output(slice(invalid));
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+  assert(dag->get_output_indices().size() == 1);
+
+  std::vector<concrete_optimizer::dag::OperatorIndex> outputs = {input, lut};
+  builder->tag_operators_as_output(slice(outputs));
+  assert(dag->get_output_indices().size() == 2);
+}
+
 TEST test_dag_lut() {
   auto dag = concrete_optimizer::dag::empty();
   auto builder = dag->builder("test");
@@ -89,43 +974,1195 @@ TEST test_dag_lut() {
   assert(!solution.use_wop_pbs);
 }
 
-TEST test_dag_lut_wop() {
+TEST test_dag_lut_table_size() {
   auto dag = concrete_optimizer::dag::empty();
   auto builder = dag->builder("test");
 
   std::vector<uint64_t> shape = {3};
 
-  concrete_optimizer::dag::OperatorIndex input =
-      builder->add_input(PRECISION_16B, slice(shape), *concrete_optimizer::utils::location_unknown());
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
 
-  std::vector<u_int64_t> table = {};
-  auto id = builder->add_lut(input, slice(table), PRECISION_16B, *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> invalid_table(255, 0);
+  bool thrown = false;
+  try {
+    builder->add_lut(input, slice(invalid_table), PRECISION_8B,
+                     *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  std::vector<concrete_optimizer::dag::LutTable> invalid_tables(1);
+  for (auto value : invalid_table) {
+    invalid_tables[0].values.push_back(value);
+  }
+  thrown = false;
+  try {
+    builder->add_multi_lut(input, slice(invalid_tables), PRECISION_8B,
+                           *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  std::vector<u_int64_t> table(256, 0);
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+  assert(dag->get_output_indices().size() == 1);
+}
+
+TEST test_dag_dot_shapes() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> row_shape = {2};
+  std::vector<uint64_t> column_shape = {2, 1};
+  auto row = builder->add_input(PRECISION_8B, slice(row_shape),
+                                *concrete_optimizer::utils::location_unknown());
+  auto column = builder->add_input(PRECISION_8B, slice(column_shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  std::vector<int64_t> weights = {1, 2};
+
+  std::vector<concrete_optimizer::dag::OperatorIndex> same = {row, row};
+  builder->add_dot(slice(same), concrete_optimizer::weights::vector(slice(weights)),
+                   *concrete_optimizer::utils::location_unknown());
+
+  std::vector<concrete_optimizer::dag::OperatorIndex> transposed = {row, column};
+  bool thrown = false;
+  try {
+    builder->add_dot(slice(transposed), concrete_optimizer::weights::vector(slice(weights)),
+                     *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &e) {
+    thrown = std::string(e.what()).find("shape") != std::string::npos;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_signed_lut() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> invalid_table(255, 0);
+  bool thrown = false;
+  try {
+    builder->add_signed_lut(input, slice(invalid_table), PRECISION_8B,
+                            *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  std::vector<u_int64_t> table(256, 0);
+  auto id = builder->add_signed_lut(input, slice(table), PRECISION_8B,
+                                    *concrete_optimizer::utils::location_unknown());
   builder->tag_operator_as_output(id);
 
-  auto solution = dag->optimize(default_options());
-  assert(solution.glwe_dimension == 2);
-  assert(solution.glwe_polynomial_size == 1024);
-  assert(solution.use_wop_pbs);
+  auto solution = dag->optimize(default_options());
+  assert(solution.glwe_polynomial_size == 8192);
+}
+
+TEST test_dag_estimated_seconds() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto options = default_options();
+  auto solution = dag->optimize(options);
+  assert(std::isnan(solution.estimated_seconds(options)));
+
+  options.complexity_to_seconds = 1e-9;
+  assert(solution.estimated_seconds(options) == solution.complexity * 1e-9);
+}
+
+TEST test_dag_optimize_pareto() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  auto best = dag->optimize(options);
+  auto front = dag->optimize_pareto(options);
+  assert(front.size() > 0);
+  assert(front[0].complexity == best.complexity);
+  for (size_t i = 1; i < front.size(); i++) {
+    assert(front[i - 1].complexity <= front[i].complexity);
+    assert(front[i - 1].p_error > front[i].p_error);
+  }
+}
+
+TEST test_dag_maximum_acceptable_complexity() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto options = default_options();
+  auto unconstrained = dag->optimize(options);
+  assert(unconstrained.p_error < 1.0);
+
+  options.maximum_acceptable_complexity = unconstrained.complexity;
+  assert(dag->optimize(options).p_error < 1.0);
+
+  options.maximum_acceptable_complexity = unconstrained.complexity / 2.0;
+  auto infeasible = dag->optimize(options);
+  assert(infeasible.p_error == 1.0);
+  assert(infeasible.bootstrap_key_bytes() == 0);
+  assert(infeasible.keyswitch_key_bytes() == 0);
+  assert(!dag->optimize_multi(options).is_feasible);
+}
+
+TEST test_dag_weighted_objective() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto key_size = [](const concrete_optimizer::dag::DagSolution &solution) {
+    return solution.bootstrap_key_bytes() + solution.keyswitch_key_bytes();
+  };
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  auto min_complexity = dag->optimize(options);
+  // the objective is part of the solution cache key
+  options.objective = concrete_optimizer::Objective::Weighted;
+  options.objective_complexity_weight = 0.0;
+  options.objective_key_size_weight = 1.0;
+  auto min_key_size = dag->optimize(options);
+  assert(min_key_size.p_error < 1.0);
+  assert(key_size(min_key_size) < key_size(min_complexity));
+  assert(min_key_size.complexity > min_complexity.complexity);
+
+  options.objective_complexity_weight = 1.0;
+  options.objective_key_size_weight = 0.0;
+  assert(dag->optimize(options).complexity == min_complexity.complexity);
+}
+
+TEST test_dag_input_with_variance() {
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+  auto make_dag = [&](double input_variance) {
+    auto dag = concrete_optimizer::dag::empty();
+    auto builder = dag->builder("test");
+    auto input = builder->add_input_with_variance(
+        PRECISION_8B, slice(shape), input_variance,
+        *concrete_optimizer::utils::location_unknown());
+    auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                                *concrete_optimizer::utils::location_unknown());
+    builder->tag_operator_as_output(lut);
+    return dag;
+  };
+
+  auto options = default_options();
+
+  auto quiet = make_dag(0.0);
+  assert(quiet->optimize(options).p_error < 1.0);
+  assert(quiet->optimize_multi(options).is_feasible);
+
+  // A variance larger than any precision budget can never be decrypted
+  auto noisy = make_dag(1.0);
+  assert(noisy->optimize(options).p_error == 1.0);
+  assert(!noisy->optimize_multi(options).is_feasible);
+
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  bool thrown = false;
+  try {
+    builder->add_input_with_variance(
+        PRECISION_8B, slice(shape), -1.0,
+        *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_add_inputs() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<concrete_optimizer::dag::InputSpec> specs = {
+      {8, {3}},
+      {1, {}},
+      {4, {2, 5}},
+  };
+  auto inputs = builder->add_inputs(slice(specs), *concrete_optimizer::utils::location_unknown());
+  assert(inputs.size() == specs.size());
+  for (size_t i = 0; i < specs.size(); i++) {
+    assert(dag->get_operator_precision(inputs[i]) == specs[i].precision);
+    auto shape = dag->get_operator_shape(inputs[i]);
+    assert(std::equal(shape.begin(), shape.end(), specs[i].shape.begin(), specs[i].shape.end()));
+  }
+  assert(dag->get_input_indices().size() == specs.size());
+}
+
+TEST test_dag_input_in_partition() {
+  double variance = 6.14e-14;
+  auto partition = concrete_optimizer::utils::get_external_partition(
+      "tfhers", 11, 1, 841, 4.0 * variance, variance);
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto options = default_options();
+
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  auto input = builder->add_input(4, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  auto change_partition = builder->add_change_partition_with_src(
+      input, *partition, *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(change_partition, slice(table), 4,
+                   *concrete_optimizer::utils::location_unknown());
+  auto with_change_partition = dag->optimize_multi(options);
+  assert(with_change_partition.is_feasible);
+
+  auto other_dag = concrete_optimizer::dag::empty();
+  auto other_builder = other_dag->builder("test");
+  auto other_input = other_builder->add_input_in_partition(
+      4, slice(shape), *partition,
+      *concrete_optimizer::utils::location_unknown());
+  other_builder->add_lut(other_input, slice(table), 4,
+                         *concrete_optimizer::utils::location_unknown());
+  assert(other_dag->get_input_indices().size() == 1);
+  auto in_partition = other_dag->optimize_multi(options);
+  assert(in_partition.is_feasible);
+  assert(in_partition.complexity == with_change_partition.complexity);
+  assert(in_partition.circuit_keys.secret_keys.size() ==
+         with_change_partition.circuit_keys.secret_keys.size());
+  assert(in_partition.used_external_partitions.size() == 1);
+  assert(in_partition.used_external_partitions[0] == rust::String("tfhers"));
+}
+
+TEST test_multi_parameters_used_external_partitions() {
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  auto input = builder->add_input(4, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), 4,
+                   *concrete_optimizer::utils::location_unknown());
+  auto solution = dag->optimize_multi(default_options());
+  assert(solution.is_feasible);
+  assert(solution.used_external_partitions.empty());
+}
+
+TEST test_multi_parameters_key_sharing_groups() {
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  auto input1 = builder->add_input(4, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto input2 = builder->add_input(5, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  std::vector<concrete_optimizer::dag::OperatorIndex> dot_inputs = {input2};
+  std::vector<int64_t> weight_vec = {128};
+  auto dot = builder->add_dot(slice(dot_inputs),
+                              concrete_optimizer::weights::vector(slice(weight_vec)),
+                              *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input1, slice(table), 5,
+                   *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(dot, slice(table), 5,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto options = default_options();
+  auto independent = dag->optimize_multi(options);
+  options.key_sharing_groups.push_back({.partitions = {0, 1}});
+  auto grouped = dag->optimize_multi(options);
+
+  assert(independent.is_feasible);
+  assert(grouped.is_feasible);
+  assert(independent.circuit_keys.secret_keys.size() == 4);
+  assert(grouped.circuit_keys.secret_keys.size() == 3);
+  assert(independent.unshared_secret_key_count == 4);
+  assert(grouped.unshared_secret_key_count == 4);
+  assert(grouped.circuit_keys.bootstrap_keys[0].output_key.identifier ==
+         grouped.circuit_keys.bootstrap_keys[1].output_key.identifier);
+}
+
+TEST test_dag_change_partition_src_and_dst() {
+  double variance = 6.14e-14;
+  auto partition_in = concrete_optimizer::utils::get_external_partition(
+      "tfhers_in", 11, 1, 841, 4.0 * variance, variance);
+  auto partition_out = concrete_optimizer::utils::get_external_partition(
+      "tfhers_out", 11, 1, 841, 4.0 * variance, variance);
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  auto input = builder->add_input(4, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  auto change_partition = builder->add_change_partition(
+      input, *partition_in, *partition_out,
+      *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(change_partition, slice(table), 4,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto solution = dag->optimize_multi(default_options());
+  assert(solution.is_feasible);
+  // The input is converted directly between the two external partitions
+  assert(!solution.circuit_keys.conversion_keyswitch_keys.empty());
+}
+
+TEST test_dag_force_bootstrap() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {};
+
+  auto input = builder->add_input(PRECISION_1B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {input};
+  std::vector<int64_t> weight_vec = {1 << 8};
+  auto dot = builder->add_dot(slice(inputs), concrete_optimizer::weights::vector(slice(weight_vec)),
+                              *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(dot);
+  auto refreshed = builder->force_bootstrap(dot, *concrete_optimizer::utils::location_unknown());
+  assert(refreshed.index == dot.index + 1);
+
+  auto solution = dag->optimize(default_options());
+  assert(solution.p_error < 1.0);
+  // The bootstrap is a lut, it took the output tag of the dot
+  auto outputs = dag->get_output_indices();
+  assert(outputs.size() == 1);
+  assert(outputs[0].index == refreshed.index);
+  assert(dag->get_operator_norm2(refreshed) == 0.0);
+
+  // The dot is now used by the bootstrap, a new one would shift the indices
+  bool thrown = false;
+  try {
+    builder->force_bootstrap(dot, *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+  assert(dag->get_output_indices().size() == 1);
+}
+
+TEST test_dag_keyswitch() {
+  double variance = 6.14e-14;
+  auto partition = concrete_optimizer::utils::get_external_partition(
+      "tfhers", 11, 1, 841, 4.0 * variance, variance);
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  auto input = builder->add_input(4, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  auto lut = builder->add_lut(input, slice(table), 4,
+                              *concrete_optimizer::utils::location_unknown());
+  auto keyswitch =
+      builder->add_keyswitch(lut, *partition, *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(keyswitch);
+
+  auto solution = dag->optimize_multi(default_options());
+  assert(solution.is_feasible);
+  assert(solution.circuit_keys.conversion_keyswitch_keys.size() == 1);
+  assert(solution.used_external_partitions.size() == 1);
+  assert(std::string(solution.used_external_partitions[0]) == "tfhers");
+}
+
+TEST test_dag_rounded_lut() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> invalid_table = {0, 1, 2};
+  bool thrown = false;
+  try {
+    builder->add_rounded_lut(input, slice(invalid_table), 4, PRECISION_8B,
+                             *concrete_optimizer::utils::location_unknown());
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  std::vector<u_int64_t> table(16, 0);
+  auto throws = [&](auto add) {
+    try {
+      add();
+    } catch (const std::exception &) {
+      return true;
+    }
+    return false;
+  };
+  // Rounding to more bits than the input has, or an unknown input
+  assert(throws([&] {
+    builder->add_rounded_lut(input, slice(table), 64, PRECISION_8B,
+                             *concrete_optimizer::utils::location_unknown());
+  }));
+  assert(throws([&] {
+    builder->add_rounded_lut(concrete_optimizer::dag::OperatorIndex{42}, slice(table), 4, PRECISION_8B,
+                             *concrete_optimizer::utils::location_unknown());
+  }));
+
+  auto id =
+      builder->add_rounded_lut(input, slice(table), 4, PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(id);
+
+  auto solution = dag->optimize(default_options());
+  assert(solution.p_error < 1.0);
+
+  // The same lut after a separate round operator
+  auto two_nodes_dag = concrete_optimizer::dag::empty();
+  auto two_nodes_builder = two_nodes_dag->builder("test");
+  auto two_nodes_input = two_nodes_builder->add_input(
+      PRECISION_8B, slice(shape), *concrete_optimizer::utils::location_unknown());
+  auto rounded = two_nodes_builder->add_round_op(
+      two_nodes_input, 4, *concrete_optimizer::utils::location_unknown());
+  two_nodes_builder->add_lut(rounded, slice(table), PRECISION_8B,
+                             *concrete_optimizer::utils::location_unknown());
+  auto two_nodes_solution = two_nodes_dag->optimize(default_options());
+  assert(solution.complexity < two_nodes_solution.complexity);
+}
+
+TEST test_dag_multi_lut() {
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table(256, 0);
+  std::vector<concrete_optimizer::dag::LutTable> tables(3);
+  for (auto &lut_table : tables) {
+    for (auto value : table) {
+      lut_table.values.push_back(value);
+    }
+  }
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+
+  auto separate = concrete_optimizer::dag::empty();
+  {
+    auto builder = separate->builder("test");
+    auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                    *concrete_optimizer::utils::location_unknown());
+    for (size_t i = 0; i < tables.size(); i++) {
+      auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                                  *concrete_optimizer::utils::location_unknown());
+      builder->tag_operator_as_output(lut);
+    }
+  }
+
+  auto shared = concrete_optimizer::dag::empty();
+  {
+    auto builder = shared->builder("test");
+    auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                    *concrete_optimizer::utils::location_unknown());
+    auto outputs = builder->add_multi_lut(
+        input, slice(tables), PRECISION_8B,
+        *concrete_optimizer::utils::location_unknown());
+    assert(outputs.size() == tables.size());
+    for (auto output : outputs) {
+      assert(shared->get_operator_precision(output) == PRECISION_8B);
+      builder->tag_operator_as_output(output);
+    }
+
+    tables[1].values.push_back(0);
+    bool thrown = false;
+    try {
+      builder->add_multi_lut(input, slice(tables), PRECISION_8B,
+                             *concrete_optimizer::utils::location_unknown());
+    } catch (const std::exception &) {
+      thrown = true;
+    }
+    assert(thrown);
+  }
+
+  auto separate_solution = separate->optimize(options);
+  auto shared_solution = shared->optimize(options);
+  assert(separate_solution.p_error < 1.0);
+  assert(shared_solution.p_error < 1.0);
+  assert(shared_solution.complexity < separate_solution.complexity);
+}
+
+TEST test_dag_check_compositions() {
+  auto dag = concrete_optimizer::dag::empty();
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+
+  auto producer = dag->builder("producer");
+  auto input = producer->add_input(PRECISION_8B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto lut = producer->add_lut(input, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  producer->tag_operator_as_output(lut);
+
+  auto consumer = dag->builder("consumer");
+  consumer->add_input(PRECISION_8B, slice(shape),
+                      *concrete_optimizer::utils::location_unknown());
+  auto mismatched = consumer->add_input(
+      PRECISION_1B, slice(shape), *concrete_optimizer::utils::location_unknown());
+
+  std::string from = "producer";
+  std::string to = "consumer";
+  dag->add_composition(from, 0, to, 0);
+  assert(dag->check_compositions().empty());
+
+  dag->add_composition(from, 0, to, 1);
+  auto errors = dag->check_compositions();
+  assert(errors.size() == 1);
+  assert(std::string(errors[0].from_circuit) == from);
+  assert(errors[0].from.index == lut.index);
+  assert(errors[0].from_precision == PRECISION_8B);
+  assert(std::string(errors[0].to_circuit) == to);
+  assert(errors[0].to.index == mismatched.index);
+  assert(errors[0].to_precision == PRECISION_1B);
+  assert(!errors[0].message.empty());
+}
+
+TEST test_dag_add_composition_by_index() {
+  auto dag = concrete_optimizer::dag::empty();
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+
+  auto builder = dag->builder("test");
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                              *concrete_optimizer::utils::location_unknown());
+
+  bool thrown = false;
+  try {
+    dag->add_composition_by_index(input, input);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  thrown = false;
+  try {
+    dag->add_composition_by_index(lut, lut);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+  assert(!dag->is_composed());
+
+  dag->add_composition_by_index(lut, input);
+  assert(dag->check_compositions().empty());
+  assert(dag->is_composed());
+  // only the multi parameters optimization supports composition
+  auto options = default_options();
+  assert(dag->optimize(options).p_error == 1.0);
+  assert(dag->optimize_multi(options).is_feasible);
+}
+
+TEST test_dag_add_all_compositions_matching() {
+  std::vector<uint64_t> shape = {3};
+  std::vector<uint64_t> other_shape = {4};
+  std::vector<u_int64_t> table = {};
+
+  auto build = [&]() {
+    auto dag = concrete_optimizer::dag::empty();
+    auto builder = dag->builder("test");
+    auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                    *concrete_optimizer::utils::location_unknown());
+    builder->add_lut(input, slice(table), PRECISION_8B,
+                     *concrete_optimizer::utils::location_unknown());
+    auto low_input = builder->add_input(PRECISION_1B, slice(shape),
+                                        *concrete_optimizer::utils::location_unknown());
+    builder->add_lut(low_input, slice(table), PRECISION_1B,
+                     *concrete_optimizer::utils::location_unknown());
+    auto reshaped_input = builder->add_input(
+        PRECISION_8B, slice(other_shape), *concrete_optimizer::utils::location_unknown());
+    builder->add_lut(reshaped_input, slice(table), PRECISION_8B,
+                     *concrete_optimizer::utils::location_unknown());
+    return dag;
+  };
+
+  auto matching = build();
+  matching->add_all_compositions_matching();
+  assert(matching->check_compositions().empty());
+
+  auto all = build();
+  all->add_all_compositions();
+  assert(!all->check_compositions().empty());
+}
+
+TEST test_dag_noise_map() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                              *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut);
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  auto result = dag->optimize_with_noise_map(options);
+  assert(result.solution.p_error < 1.0);
+  assert(result.solution.complexity == dag->optimize(options).complexity);
+  assert(result.operators.size() == 2);
+  for (auto noise : result.operators) {
+    assert(noise.variance > 0.0);
+    assert(noise.margin >= 0.0);
+  }
+  // The input enters the lut, after keyswitch and modulus switching
+  assert(result.operators[input.index].variance >
+         result.operators[lut.index].variance);
+
+  options.maximum_acceptable_complexity = 0.0;
+  result = dag->optimize_with_noise_map(options);
+  assert(result.solution.p_error == 1.0);
+  assert(result.operators.empty());
+}
+
+TEST test_dag_budget_report() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  auto lut1 = builder->add_lut(input, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  auto lut2 = builder->add_lut(lut1, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut2);
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  auto result = dag->optimize_with_budget_report(options);
+  assert(result.solution.p_error < 1.0);
+  assert(result.solution.complexity == dag->optimize(options).complexity);
+  assert(result.operators.size() == 3);
+  // The lut inputs take most of the budget
+  assert(result.operators[input.index].p_error > 0.0);
+  assert(result.operators[lut1.index].p_error > 0.0);
+  double p_success = 1.0;
+  for (auto budget : result.operators) {
+    assert(budget.p_error <= result.solution.global_p_error);
+    p_success *= 1.0 - budget.p_error;
+  }
+  assert(std::abs(1.0 - p_success - result.solution.global_p_error) <=
+         1e-6 * result.solution.global_p_error);
+
+  options.maximum_acceptable_complexity = 0.0;
+  result = dag->optimize_with_budget_report(options);
+  assert(result.solution.p_error == 1.0);
+  assert(result.operators.empty());
+}
+
+TEST test_dag_security_curve() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {3};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                              *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut);
+
+  // a non-standard security level, only usable with a custom curve
+  auto options = default_options();
+  options.security_level = 100;
+  options.security_curve_json =
+      "[{\"lwe_dimension\": 512, \"minimal_variance\": 2.384185791015625e-07},"
+      " {\"lwe_dimension\": 1024, \"minimal_variance\": 8.881784197001252e-16},"
+      " {\"lwe_dimension\": 2048, \"minimal_variance\": 4.930380657631324e-32},"
+      " {\"lwe_dimension\": 262144, \"minimal_variance\": 7.52316384526264e-37}]";
+  concrete_optimizer::check_security_curve_json(options.security_curve_json);
+  auto solution = dag->optimize(options);
+  assert(solution.p_error < 1.0);
+  assert(solution.internal_ks_output_lwe_dimension >= 512);
+  assert(dag->optimize_multi(options).is_feasible);
+
+  // no internal dimension is covered
+  options.security_curve_json =
+      "[{\"lwe_dimension\": 4096, \"minimal_variance\": 1e-36},"
+      " {\"lwe_dimension\": 262144, \"minimal_variance\": 1e-37}]";
+  assert(dag->optimize(options).p_error == 1.0);
+  assert(!dag->optimize_multi(options).is_feasible);
+
+  for (auto invalid : {"[]", "{", "[{\"lwe_dimension\": 512, \"minimal_variance\": -1.0}]"}) {
+    bool thrown = false;
+    try {
+      concrete_optimizer::check_security_curve_json(invalid);
+    } catch (const std::exception &) {
+      thrown = true;
+    }
+    assert(thrown);
+  }
+}
+
+TEST test_validate_options() {
+  auto options = default_options();
+  concrete_optimizer::validate_options(options);
+  options.ciphertext_modulus_log = 32;
+  options.fft_precision = 32;
+  concrete_optimizer::validate_options(options);
+
+  options.fft_precision = 53;
+  bool thrown = false;
+  try {
+    concrete_optimizer::validate_options(options);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  auto invalid_curve = default_options();
+  invalid_curve.security_curve_json = "[";
+  bool thrown_curve = false;
+  try {
+    concrete_optimizer::validate_options(invalid_curve);
+  } catch (const std::exception &) {
+    thrown_curve = true;
+  }
+  assert(thrown_curve);
+
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_1B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), PRECISION_1B,
+                   *concrete_optimizer::utils::location_unknown());
+  assert(dag->optimize(options).p_error == 1.0);
+  auto solution = dag->optimize_multi(options);
+  assert(!solution.is_feasible);
+  assert(std::string(solution.error_msg).find("fft_precision") !=
+         std::string::npos);
+
+  auto weighted = default_options();
+  weighted.objective = concrete_optimizer::Objective::Weighted;
+  for (auto weights : std::vector<std::pair<double, double>>{
+           {-1.0, 1.0}, {1.0, std::nan("")}, {0.0, 0.0}}) {
+    weighted.objective_complexity_weight = weights.first;
+    weighted.objective_key_size_weight = weights.second;
+    bool thrown_weights = false;
+    try {
+      concrete_optimizer::validate_options(weighted);
+    } catch (const std::exception &) {
+      thrown_weights = true;
+    }
+    assert(thrown_weights);
+    assert(dag->optimize(weighted).p_error == 1.0);
+  }
+}
+
+TEST test_dag_lut_wop() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  concrete_optimizer::dag::OperatorIndex input =
+      builder->add_input(PRECISION_16B, slice(shape), *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> table = {};
+  auto id = builder->add_lut(input, slice(table), PRECISION_16B, *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(id);
+
+  auto solution = dag->optimize(default_options());
+  assert(solution.glwe_dimension == 2);
+  assert(solution.glwe_polynomial_size == 1024);
+  assert(solution.use_wop_pbs);
+}
+
+TEST test_dag_lut_force_wop() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  concrete_optimizer::dag::OperatorIndex input =
+      builder->add_input(PRECISION_8B, slice(shape), *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> table = {};
+  auto id = builder->add_lut(input, slice(table), PRECISION_8B, *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(id);
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Crt;
+  auto solution = dag->optimize(options);
+  assert(solution.use_wop_pbs);
+  assert(!solution.crt_decomposition.empty());
+}
+
+TEST test_dag_solution_required_keys() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  auto solution = dag->optimize(options);
+  auto keys = solution.required_keys();
+  assert(keys.secret_keys.size() == 2);
+  assert(keys.secret_keys[0].glwe_dimension == solution.glwe_dimension);
+  assert(keys.secret_keys[1].polynomial_size == solution.internal_ks_output_lwe_dimension);
+  assert(keys.keyswitch_keys.size() == 1);
+  assert(keys.bootstrap_keys.size() == 1);
+  assert(keys.bootstrap_keys[0].br_decomposition_parameter.level ==
+         solution.br_decomposition_level_count);
+  assert(keys.circuit_bootstrap_keys.empty());
+  assert(keys.private_functional_packing_keys.empty());
+
+  uint64_t glwe_size = solution.glwe_dimension + 1;
+  assert(solution.bootstrap_key_bytes() ==
+         solution.internal_ks_output_lwe_dimension * solution.br_decomposition_level_count *
+             glwe_size * glwe_size * solution.glwe_polynomial_size * 8);
+  assert(solution.keyswitch_key_bytes() ==
+         solution.input_lwe_dimension * solution.ks_decomposition_level_count *
+             (solution.internal_ks_output_lwe_dimension + 1) * 8);
+
+  options.encoding = concrete_optimizer::Encoding::Crt;
+  auto wop_solution = dag->optimize(options);
+  assert(wop_solution.use_wop_pbs);
+  auto wop_keys = wop_solution.required_keys();
+  assert(wop_keys.circuit_bootstrap_keys.size() == 1);
+  assert(wop_keys.private_functional_packing_keys.size() == 1);
+
+  auto circuit_solution = concrete_optimizer::utils::convert_to_circuit_solution(wop_solution, *dag);
+  assert(circuit_solution.circuit_keys.circuit_bootstrap_keys.size() == 1);
+  assert(circuit_solution.instructions_keys[0].tlu_circuit_bootstrap_key ==
+         wop_keys.circuit_bootstrap_keys[0].identifier);
+}
+
+TEST test_dag_crt_decomposition_hint() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  auto id = builder->add_lut(input, slice(table), PRECISION_8B,
+                             *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(id);
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Crt;
+  for (auto modulus : {8, 5, 7, 11}) {
+    options.crt_decomposition_hint.push_back(modulus);
+  }
+  auto solution = dag->optimize(options);
+  assert(solution.use_wop_pbs);
+  std::vector<uint64_t> expected = {5, 7, 8};
+  assert(std::vector<uint64_t>(solution.crt_decomposition.begin(),
+                               solution.crt_decomposition.end()) == expected);
+
+  options.crt_decomposition_hint.clear();
+  options.crt_decomposition_hint.push_back(2);
+  options.crt_decomposition_hint.push_back(3);
+  assert(dag->optimize(options).p_error == 1.0);
+}
+
+TEST test_dag_remote_pbs() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {3};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto options = default_options();
+  auto local = dag->optimize(options);
+  assert(local.p_error < 1.0);
+
+  // a fixed latency does not change the best parameters
+  options.remote_pbs_latency = 1e8;
+  auto latency = dag->optimize(options);
+  assert(latency.internal_ks_output_lwe_dimension ==
+         local.internal_ks_output_lwe_dimension);
+  assert(std::abs(latency.complexity - (local.complexity + 3 * 1e8)) <
+         1e-6 * latency.complexity);
+
+  options.remote_pbs_bandwidth = 64.0;
+  auto transfer = dag->optimize(options);
+  assert(transfer.p_error < 1.0);
+  assert(transfer.complexity > latency.complexity);
+}
+
+TEST test_dag_pinned_macro_parameters() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {3};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> table = {};
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  auto free = dag->optimize(options);
+  assert(free.p_error < 1.0);
+
+  uint64_t glwe_dimension = free.glwe_dimension + 1;
+  options.pinned_glwe_dimension.push_back(glwe_dimension);
+  options.pinned_polynomial_size.push_back(free.glwe_polynomial_size);
+  auto pinned = dag->optimize(options);
+  assert(pinned.p_error < 1.0);
+  assert(pinned.glwe_dimension == glwe_dimension);
+  assert(pinned.glwe_polynomial_size == free.glwe_polynomial_size);
+  assert(pinned.complexity > free.complexity);
+
+  auto multi = dag->optimize_multi(options);
+  assert(multi.is_feasible);
+  for (auto &key : multi.circuit_keys.bootstrap_keys) {
+    assert(key.output_key.glwe_dimension == glwe_dimension);
+    assert(key.output_key.polynomial_size == free.glwe_polynomial_size);
+  }
+
+  // not a power of two
+  options.pinned_polynomial_size.clear();
+  options.pinned_polynomial_size.push_back(3000);
+  assert(dag->optimize(options).p_error == 1.0);
+}
+
+TEST test_dag_evaluate_solution() {
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+  auto make_dag = [&](uint8_t precision) {
+    auto dag = concrete_optimizer::dag::empty();
+    auto builder = dag->builder("test");
+    auto input = builder->add_input(
+        precision, slice(shape), *concrete_optimizer::utils::location_unknown());
+    builder->add_lut(input, slice(table), precision,
+                     *concrete_optimizer::utils::location_unknown());
+    return dag;
+  };
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+
+  auto dag = make_dag(8);
+  auto solution = dag->optimize(options);
+  assert(solution.p_error < 1.0);
+  auto evaluated =
+      concrete_optimizer::dag::evaluate_solution(*dag, solution, options);
+  assert(evaluated.glwe_dimension == solution.glwe_dimension);
+  assert(evaluated.complexity == solution.complexity);
+  assert(evaluated.p_error == solution.p_error);
+  assert(evaluated.global_p_error == solution.global_p_error);
+  assert(evaluated.noise_max == solution.noise_max);
+  assert(evaluated.noise_margin == solution.noise_margin);
+  assert(solution.noise_margin >= 1.0);
+
+  // 1 bit parameters are too noisy for 8 bits
+  auto small_solution = make_dag(1)->optimize(options);
+  assert(small_solution.p_error < 1.0);
+  assert(concrete_optimizer::dag::evaluate_solution(*dag, small_solution,
+                                                    options)
+             .p_error == 1.0);
+}
+
+TEST test_dag_chosen_encoding() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                              *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut);
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  assert(dag->optimize(options).chosen_encoding ==
+         concrete_optimizer::Encoding::Native);
+  options.encoding = concrete_optimizer::Encoding::Crt;
+  assert(dag->optimize(options).chosen_encoding ==
+         concrete_optimizer::Encoding::Crt);
+
+  options.encoding = concrete_optimizer::Encoding::Auto;
+  auto solution = dag->optimize(options);
+  assert(solution.p_error < 1.0);
+  assert(solution.chosen_encoding != concrete_optimizer::Encoding::Auto);
+  assert((solution.chosen_encoding == concrete_optimizer::Encoding::Crt) ==
+         !solution.crt_decomposition.empty());
 }
 
-TEST test_dag_lut_force_wop() {
+TEST test_dag_solution_json() {
   auto dag = concrete_optimizer::dag::empty();
   auto builder = dag->builder("test");
 
   std::vector<uint64_t> shape = {3};
 
-  concrete_optimizer::dag::OperatorIndex input =
-      builder->add_input(PRECISION_8B, slice(shape), *concrete_optimizer::utils::location_unknown());
-
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
   std::vector<u_int64_t> table = {};
-  auto id = builder->add_lut(input, slice(table), PRECISION_8B, *concrete_optimizer::utils::location_unknown());
+  auto id = builder->add_lut(input, slice(table), PRECISION_8B,
+                             *concrete_optimizer::utils::location_unknown());
   builder->tag_operator_as_output(id);
 
   auto options = default_options();
   options.encoding = concrete_optimizer::Encoding::Crt;
   auto solution = dag->optimize(options);
   assert(solution.use_wop_pbs);
-  assert(!solution.crt_decomposition.empty());
+
+  auto json = solution.to_json();
+  auto read = concrete_optimizer::dag::dag_solution_from_json(json);
+  assert(read.use_wop_pbs);
+  assert(read.chosen_encoding == concrete_optimizer::Encoding::Crt);
+  assert(read.cb_decomposition_level_count ==
+         solution.cb_decomposition_level_count);
+  assert(read.pp_decomposition_base_log == solution.pp_decomposition_base_log);
+  assert(read.crt_decomposition.size() == solution.crt_decomposition.size());
+  assert(read.complexity == solution.complexity);
+  assert(read.to_json() == json);
+
+  concrete_optimizer::dag::DagSolution non_finite{};
+  non_finite.noise_max = std::numeric_limits<double>::quiet_NaN();
+  non_finite.complexity = std::numeric_limits<double>::infinity();
+  auto non_finite_read =
+      concrete_optimizer::dag::dag_solution_from_json(non_finite.to_json());
+  assert(non_finite_read.noise_max != non_finite_read.noise_max);
+  assert(non_finite_read.complexity == non_finite.complexity);
+
+  auto circuit_solution = dag->optimize_multi(default_options());
+  assert(circuit_solution.is_feasible);
+  auto circuit_json = circuit_solution.to_json();
+  auto circuit_read =
+      concrete_optimizer::dag::circuit_solution_from_json(circuit_json);
+  assert(circuit_read.dump() == circuit_solution.dump());
+
+  auto bytes = circuit_solution.to_bytes();
+  std::vector<uint8_t> bytes_vec(bytes.begin(), bytes.end());
+  auto bytes_read =
+      concrete_optimizer::dag::circuit_solution_from_bytes(slice(bytes_vec));
+  assert(bytes_read.dump() == circuit_solution.dump());
+
+  // unknown format version
+  bytes_vec[4] += 1;
+  bool version_thrown = false;
+  try {
+    concrete_optimizer::dag::circuit_solution_from_bytes(slice(bytes_vec));
+  } catch (std::exception &e) {
+    version_thrown = std::string(e.what()).find("version") != std::string::npos;
+  }
+  assert(version_thrown);
+
+  bool thrown = false;
+  try {
+    concrete_optimizer::dag::circuit_solution_from_json("{}");
+  } catch (std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_dag_json() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {1, 0};
+  auto input = builder->add_input_with_comment(PRECISION_1B, slice(shape), "x",
+                                               *concrete_optimizer::utils::location_unknown());
+  auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                              *concrete_optimizer::utils::location_unknown());
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {lut, lut};
+  std::vector<double> float_weights = {0.5, 0.25};
+  auto dot = builder->add_dot(slice(inputs),
+                              concrete_optimizer::weights::float_vector(slice(float_weights)),
+                              *concrete_optimizer::utils::location_unknown());
+  std::vector<u_int64_t> unknown_table = {};
+  auto output = builder->add_lut(dot, slice(unknown_table), PRECISION_1B,
+                                 *concrete_optimizer::utils::location_unknown());
+  dag->add_composition_by_index(output, input);
+
+  auto json = dag->to_json();
+  auto read = concrete_optimizer::dag::dag_from_json(json);
+  assert(read->to_json() == json);
+  assert(read->dump() == dag->dump());
+  assert(read->is_composed());
+  assert(std::string(read->get_operator_comment(input)) == "x");
+
+  auto options = default_options();
+  auto solution = dag->optimize_multi(options);
+  auto read_solution = read->optimize_multi(options);
+  assert(solution.is_feasible);
+  assert(read_solution.complexity == solution.complexity);
+  assert(read_solution.p_error == solution.p_error);
+
+  bool thrown = false;
+  try {
+    concrete_optimizer::dag::dag_from_json("{");
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  // a composition from an operator which is not an output
+  std::string inconsistent(json);
+  auto compositions = inconsistent.find("\"compositions\":[[3,0]]");
+  assert(compositions != std::string::npos);
+  inconsistent.replace(compositions, 23, "\"compositions\":[[1,0]]");
+  thrown = false;
+  try {
+    concrete_optimizer::dag::dag_from_json(inconsistent);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  // a precision which disagrees with the lut
+  std::string corrupted(json);
+  auto precision = corrupted.find("\"out_precision\":8");
+  assert(precision != std::string::npos);
+  corrupted.replace(precision, 17, "\"out_precision\":7");
+  thrown = false;
+  try {
+    concrete_optimizer::dag::dag_from_json(corrupted);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
 }
 
 TEST test_multi_parameters_1_precision() {
@@ -196,6 +2233,218 @@ TEST test_multi_parameters_2_precision() {
   assert(actual == expected);
 }
 
+TEST test_circuit_solution_diff() {
+  auto build = [](std::vector<uint8_t> precisions) {
+    auto dag = concrete_optimizer::dag::empty();
+    auto builder = dag->builder("test");
+    std::vector<uint64_t> shape = {3};
+    std::vector<u_int64_t> table = {};
+    std::vector<concrete_optimizer::dag::OperatorIndex> luts;
+    for (auto precision : precisions) {
+      auto input = builder->add_input(precision, slice(shape),
+                                      *concrete_optimizer::utils::location_unknown());
+      luts.push_back(builder->add_lut(input, slice(table), PRECISION_8B,
+                                      *concrete_optimizer::utils::location_unknown()));
+    }
+    std::vector<int64_t> weight_vec(luts.size(), 1);
+    auto id = builder->add_dot(slice(luts), concrete_optimizer::weights::vector(slice(weight_vec)),
+                               *concrete_optimizer::utils::location_unknown());
+    builder->tag_operator_as_output(id);
+    return dag->optimize_multi(default_options());
+  };
+  auto two_partitions = build({8, 1});
+  auto one_partition = build({8});
+
+  assert(std::string(two_partitions.diff(two_partitions)).empty());
+  auto diff = std::string(two_partitions.diff(one_partition));
+  assert(diff.find("secret_keys: 4 -> 2 entries") != std::string::npos);
+  assert(diff.find("secret_keys[3]:") != std::string::npos);
+  assert(diff.find("-> none") != std::string::npos);
+  assert(diff.find("complexity: ") != std::string::npos);
+  assert(diff.find("is_feasible") == std::string::npos);
+}
+
+TEST test_multi_parameters_forbid_fast_keyswitch() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input1 = builder->add_input(PRECISION_8B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto input2 = builder->add_input(PRECISION_1B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> table = {};
+  auto lut1 = builder->add_lut(input1, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  auto lut2 = builder->add_lut(input2, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {lut1, lut2};
+  std::vector<int64_t> weight_vec = {1, 1};
+  auto id = builder->add_dot(slice(inputs), concrete_optimizer::weights::vector(slice(weight_vec)),
+                             *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(id);
+
+  auto options = default_options();
+  options.allow_fast_keyswitch = false;
+  auto circuit_solution = dag->optimize_multi(options);
+  assert(circuit_solution.is_feasible);
+  assert(!circuit_solution.circuit_keys.conversion_keyswitch_keys.empty());
+  for (auto &key : circuit_solution.circuit_keys.conversion_keyswitch_keys) {
+    assert(!key.fast_keyswitch);
+  }
+}
+
+TEST test_multi_parameters_min_key_size() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input1 = builder->add_input(PRECISION_8B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto input2 = builder->add_input(PRECISION_1B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> table = {};
+  auto lut1 = builder->add_lut(input1, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  auto lut2 = builder->add_lut(input2, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+
+  std::vector<concrete_optimizer::dag::OperatorIndex> inputs = {lut1, lut2};
+  std::vector<int64_t> weight_vec = {1, 1};
+  auto id = builder->add_dot(slice(inputs), concrete_optimizer::weights::vector(slice(weight_vec)),
+                             *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(id);
+
+  auto lwe_dimension = [](const concrete_optimizer::dag::SecretLweKey &key) {
+    return (double)key.glwe_dimension * (double)key.polynomial_size;
+  };
+  auto key_size = [&](const CircuitKeys &keys) {
+    double size = 0.0;
+    for (auto &key : keys.bootstrap_keys) {
+      double glwe_size = (double)key.output_key.glwe_dimension + 1;
+      size += lwe_dimension(key.input_key) * key.br_decomposition_parameter.level *
+              glwe_size * glwe_size * key.output_key.polynomial_size * 8;
+    }
+    for (auto &key : keys.keyswitch_keys) {
+      size += lwe_dimension(key.input_key) * key.ks_decomposition_parameter.level *
+              (lwe_dimension(key.output_key) + 1) * 8;
+    }
+    return size;
+  };
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  auto min_complexity = dag->optimize_multi(options);
+  options.objective = concrete_optimizer::Objective::MinKeySize;
+  auto min_key_size = dag->optimize_multi(options);
+  assert(min_complexity.is_feasible);
+  assert(min_key_size.is_feasible);
+  assert(key_size(min_key_size.circuit_keys) < key_size(min_complexity.circuit_keys));
+  assert(min_key_size.complexity > min_complexity.complexity);
+}
+
+TEST test_multi_parameters_is_feasible() {
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+  auto make_dag = [&](double input_variance) {
+    auto dag = concrete_optimizer::dag::empty();
+    auto builder = dag->builder("test");
+    auto input1 = builder->add_input_with_variance(
+        PRECISION_8B, slice(shape), input_variance,
+        *concrete_optimizer::utils::location_unknown());
+    auto input2 = builder->add_input(PRECISION_1B, slice(shape),
+                                     *concrete_optimizer::utils::location_unknown());
+    auto lut1 = builder->add_lut(input1, slice(table), PRECISION_8B,
+                                 *concrete_optimizer::utils::location_unknown());
+    auto lut2 = builder->add_lut(input2, slice(table), PRECISION_1B,
+                                 *concrete_optimizer::utils::location_unknown());
+    builder->tag_operator_as_output(lut1);
+    builder->tag_operator_as_output(lut2);
+    return dag;
+  };
+
+  auto options = default_options();
+
+  auto quiet = make_dag(0.0);
+  assert(quiet->is_feasible(options));
+  assert(quiet->optimize_multi(options).is_feasible);
+
+  auto noisy = make_dag(1.0);
+  assert(!noisy->is_feasible(options));
+  assert(!noisy->optimize_multi(options).is_feasible);
+
+  // no assignment yet
+  options.multi_param_strategy = concrete_optimizer::MultiParamStrategy::Explicit;
+  assert(!quiet->is_feasible(options));
+}
+
+TEST test_multi_parameters_cancellable() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+  auto input1 = builder->add_input(PRECISION_8B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto input2 = builder->add_input(PRECISION_1B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto lut1 = builder->add_lut(input1, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  auto lut2 = builder->add_lut(input2, slice(table), PRECISION_1B,
+                               *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut1);
+  builder->tag_operator_as_output(lut2);
+
+  auto options = default_options();
+
+  auto token = concrete_optimizer::utils::cancellation_token();
+  assert(!token->is_cancelled());
+  auto solution = dag->optimize_multi_cancellable(options, *token);
+  assert(solution.is_feasible);
+
+  token->cancel();
+  assert(token->is_cancelled());
+  auto cancelled = dag->optimize_multi_cancellable(options, *token);
+  assert(!cancelled.is_feasible);
+  assert(std::string(cancelled.error_msg) == "cancelled");
+}
+
+TEST test_optimize_with_progress() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+  std::vector<u_int64_t> table = {};
+  auto input1 = builder->add_input(PRECISION_8B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto input2 = builder->add_input(PRECISION_1B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto lut1 = builder->add_lut(input1, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  auto lut2 = builder->add_lut(input2, slice(table), PRECISION_1B,
+                               *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut1);
+  builder->tag_operator_as_output(lut2);
+
+  auto options = default_options();
+
+  auto mono_progress = concrete_optimizer::utils::progress_counter();
+  assert(mono_progress->steps() == 0);
+  auto mono = dag->optimize_with_progress(options, *mono_progress);
+  assert(mono.p_error == dag->optimize(options).p_error);
+  assert(mono_progress->steps() > 0);
+
+  auto multi_progress = concrete_optimizer::utils::progress_counter();
+  auto multi = dag->optimize_multi_with_progress(options, *multi_progress);
+  assert(multi.is_feasible);
+  assert(multi_progress->steps() > 0);
+}
+
 TEST test_multi_parameters_2_precision_crt() {
   auto dag = concrete_optimizer::dag::empty();
   auto builder = dag->builder("test");
@@ -233,16 +2482,729 @@ TEST test_multi_parameters_2_precision_crt() {
   assert(circuit_solution.circuit_keys.conversion_keyswitch_keys.size() == 0);
 }
 
+TEST test_multi_parameters_with_report() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input1 = builder->add_input(PRECISION_8B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto input2 = builder->add_input(PRECISION_1B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> table = {};
+  auto lut1 = builder->add_lut(input1, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  auto lut2 = builder->add_lut(input2, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut1);
+  builder->tag_operator_as_output(lut2);
+
+  auto options = default_options();
+  auto report = dag->optimize_multi_with_report(options);
+  assert(report.solution.is_feasible);
+  assert(report.partitions.size() == 2);
+  assert(report.partitions[0].min_precision == 1);
+  assert(report.partitions[1].max_precision == 8);
+  double total_complexity = 0.0;
+  for (auto &partition : report.partitions) {
+    assert(partition.has_lut);
+    assert(partition.has_macro_parameters);
+    assert(partition.feasibility ==
+           concrete_optimizer::dag::PartitionFeasibility::Feasible);
+    assert(partition.complexity > 0.0);
+    total_complexity += partition.complexity;
+  }
+  assert(std::abs(total_complexity - report.solution.complexity) <=
+         1e-9 * report.solution.complexity);
+}
+
+TEST test_multi_parameters_explicit_partitions() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input1 = builder->add_input(PRECISION_8B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto input2 = builder->add_input(PRECISION_1B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> table = {};
+  auto lut1 = builder->add_lut(input1, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  auto lut2 = builder->add_lut(input2, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut1);
+  builder->tag_operator_as_output(lut2);
+
+  auto options = default_options();
+  options.multi_param_strategy = concrete_optimizer::MultiParamStrategy::Explicit;
+  // no assignment yet
+  assert(!dag->optimize_multi(options).is_feasible);
+
+  bool thrown = false;
+  std::vector<uint64_t> too_short = {0, 0};
+  try {
+    dag->set_partition_assignment(slice(too_short));
+  } catch (std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  std::vector<uint64_t> single = {0, 0, 0, 0};
+  dag->set_partition_assignment(slice(single));
+  auto report = dag->optimize_multi_with_report(options);
+  assert(report.solution.is_feasible);
+  assert(report.partitions.size() == 1);
+
+  std::vector<uint64_t> split = {0, 0, 0, 1};
+  dag->set_partition_assignment(slice(split));
+  report = dag->optimize_multi_with_report(options);
+  assert(report.solution.is_feasible);
+  assert(report.partitions.size() == 2);
+
+  // the assignments follow the merged and removed operators
+  auto other = concrete_optimizer::dag::empty();
+  auto other_builder = other->builder("other");
+  auto other_input = other_builder->add_input(
+      PRECISION_1B, slice(shape), *concrete_optimizer::utils::location_unknown());
+  auto other_lut =
+      other_builder->add_lut(other_input, slice(table), PRECISION_1B,
+                             *concrete_optimizer::utils::location_unknown());
+  other_builder->tag_operator_as_output(other_lut);
+  std::vector<uint64_t> other_single = {1, 1};
+  other->set_partition_assignment(slice(other_single));
+  auto mapping = dag->merge(*other);
+  report = dag->optimize_multi_with_report(options);
+  assert(report.solution.is_feasible);
+  assert(report.partitions.size() == 2);
+
+  dag->remove_operator(mapping[1]);
+  report = dag->optimize_multi_with_report(options);
+  assert(report.solution.is_feasible);
+}
+
+TEST test_get_noise_br_out_of_range() {
+  auto options = default_options();
+  bool thrown = false;
+  try {
+    concrete_optimizer::utils::get_noise_br(options, 11, 1, 800, 100, 1);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_generate_virtual_keyset_info_with_conversions() {
+  auto options = default_options();
+  rust::Vec<concrete_optimizer::utils::PartitionDefinition> partitions;
+  partitions.push_back({.precision = 3, .norm2 = 1.0});
+  partitions.push_back({.precision = 4, .norm2 = 100.0});
+  partitions.push_back({.precision = 5, .norm2 = 1000.0});
+
+  auto all = concrete_optimizer::utils::generate_virtual_keyset_info(partitions, true, options);
+  rust::Vec<concrete_optimizer::utils::PartitionConversion> conversions;
+  conversions.push_back({.src = 0, .dst = 2});
+  auto keys = concrete_optimizer::utils::generate_virtual_keyset_info_with_conversions(
+      partitions, conversions, options);
+  assert(keys.keyswitch_keys.size() < all.keyswitch_keys.size());
+  assert(keys.conversion_keyswitch_keys.size() == 1);
+
+  rust::Vec<concrete_optimizer::utils::PartitionConversion> invalid;
+  invalid.push_back({.src = 0, .dst = 3});
+  bool thrown = false;
+  try {
+    concrete_optimizer::utils::generate_virtual_keyset_info_with_conversions(partitions, invalid,
+                                                                             options);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_external_partition_accessors() {
+  double variance = 6.14e-14;
+  auto partition = concrete_optimizer::utils::get_external_partition(
+      "tfhers", 11, 1, 841, 4.0 * variance, variance);
+  assert(std::string(partition->name()) == "tfhers");
+  assert(partition->max_variance() == 4.0 * variance);
+  assert(partition->variance() == variance);
+}
+
+TEST test_external_partition_validation() {
+  double variance = 6.14e-14;
+  auto throws = [](uint64_t log2_polynomial_size, uint64_t glwe_dimension,
+                   uint64_t internal_dim, double max_variance, double variance) {
+    try {
+      concrete_optimizer::utils::get_external_partition(
+          "tfhers", log2_polynomial_size, glwe_dimension, internal_dim,
+          max_variance, variance);
+    } catch (const std::exception &) {
+      return true;
+    }
+    return false;
+  };
+  assert(!throws(11, 1, 841, 4.0 * variance, variance));
+  assert(throws(0, 1, 841, 4.0 * variance, variance));
+  assert(throws(11, 0, 841, 4.0 * variance, variance));
+  assert(throws(11, 1, 0, 4.0 * variance, variance));
+  assert(throws(11, 1, 841, 4.0 * variance, 0.0));
+  assert(throws(11, 1, 841, variance, 4.0 * variance));
+}
+
+TEST test_get_noise_ks() {
+  auto options = default_options();
+  auto solution = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  auto noise = concrete_optimizer::utils::get_noise_ks(
+      options, solution.input_lwe_dimension,
+      solution.internal_ks_output_lwe_dimension,
+      solution.ks_decomposition_level_count,
+      solution.ks_decomposition_base_log);
+  assert(0.0 < noise);
+  assert(noise < solution.noise_max);
+
+  bool thrown = false;
+  try {
+    concrete_optimizer::utils::get_noise_ks(
+        options, solution.input_lwe_dimension,
+        solution.internal_ks_output_lwe_dimension, 100, 1);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_get_noise_modulus_switch() {
+  auto options = default_options();
+  auto solution = concrete_optimizer::v0::optimize_bootstrap(
+      PRECISION_8B, NOISE_DEVIATION_COEFF, options);
+  uint64_t log2_polynomial_size = 0;
+  while ((uint64_t{1} << log2_polynomial_size) < solution.glwe_polynomial_size) {
+    log2_polynomial_size++;
+  }
+  auto br = concrete_optimizer::utils::get_noise_br(
+      options, log2_polynomial_size, solution.glwe_dimension,
+      solution.internal_ks_output_lwe_dimension,
+      solution.br_decomposition_level_count,
+      solution.br_decomposition_base_log);
+  auto ks = concrete_optimizer::utils::get_noise_ks(
+      options, solution.input_lwe_dimension,
+      solution.internal_ks_output_lwe_dimension,
+      solution.ks_decomposition_level_count,
+      solution.ks_decomposition_base_log);
+  auto ms = concrete_optimizer::utils::get_noise_modulus_switch(
+      options, solution.internal_ks_output_lwe_dimension, log2_polynomial_size);
+  assert(0.0 < ms);
+  // the three sources explain the noise before the blind rotate
+  auto noise = br * NOISE_DEVIATION_COEFF * NOISE_DEVIATION_COEFF + ks + ms;
+  assert(std::abs(noise - solution.noise_max) <= 1e-9 * solution.noise_max);
+}
+
+TEST test_enumerate_pbs_decompositions() {
+  auto options = default_options();
+  auto candidates =
+      concrete_optimizer::utils::enumerate_pbs_decompositions(options, 11, 1, 800);
+  assert(!candidates.empty());
+  for (size_t i = 0; i < candidates.size(); i++) {
+    auto candidate = candidates[i];
+    assert(candidate.level * candidate.base_log <= 64);
+    assert(candidate.max_variance ==
+           concrete_optimizer::utils::get_noise_br(options, 11, 1, 800,
+                                                   candidate.level,
+                                                   candidate.base_log));
+    if (i > 0) {
+      assert(candidates[i - 1].complexity < candidate.complexity);
+      assert(candidates[i - 1].max_variance > candidate.max_variance);
+    }
+  }
+}
+
+TEST test_location_from_invalid_string() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  builder->add_input(PRECISION_8B, slice(shape),
+                     *concrete_optimizer::utils::location_from_string("file.py:abc"));
+  builder->add_input(PRECISION_8B, slice(shape),
+                     *concrete_optimizer::utils::location_from_string("file.py:3:abc"));
+}
+
+TEST test_multi_parameters_range_restriction() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input1 = builder->add_input(PRECISION_8B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+  auto input2 = builder->add_input(PRECISION_1B, slice(shape),
+                                   *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> table = {};
+  auto lut1 = builder->add_lut(input1, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  auto lut2 = builder->add_lut(input2, slice(table), PRECISION_8B,
+                               *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut1);
+  builder->tag_operator_as_output(lut2);
+
+  concrete_optimizer::restriction::RangeRestriction restriction{};
+  restriction.glwe_dimensions.push_back(1);
+  auto json = restriction.range_restriction_to_json();
+  auto read = concrete_optimizer::restriction::range_restriction_from_json(json);
+  assert(read.glwe_dimensions.size() == 1);
+
+  auto options = default_options();
+  options.range_restriction =
+      std::make_shared<concrete_optimizer::restriction::RangeRestriction>(read);
+  auto solution = dag->optimize_multi(options);
+  assert(solution.is_feasible);
+  for (auto &key : solution.circuit_keys.bootstrap_keys) {
+    assert(key.output_key.glwe_dimension == 1);
+  }
+}
+
+TEST test_multi_parameters_range_restriction_polynomial_sizes() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+
+  std::vector<uint64_t> shape = {3};
+
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+
+  std::vector<u_int64_t> table = {};
+  auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                              *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut);
+
+  concrete_optimizer::restriction::RangeRestriction restriction{};
+  // The absolute sizes take precedence over the log2 ones.
+  restriction.glwe_log_polynomial_sizes.push_back(8);
+  restriction.glwe_polynomial_sizes.push_back(8192);
+  auto json = restriction.range_restriction_to_json();
+  auto read = concrete_optimizer::restriction::range_restriction_from_json(json);
+  assert(read.glwe_polynomial_sizes.size() == 1);
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  options.range_restriction =
+      std::make_shared<concrete_optimizer::restriction::RangeRestriction>(read);
+  auto solution = dag->optimize_multi(options);
+  assert(solution.is_feasible);
+  for (auto &key : solution.circuit_keys.bootstrap_keys) {
+    assert(key.output_key.polynomial_size == 8192);
+  }
+}
+
+TEST test_multi_parameters_restriction_explanation() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  auto lut = builder->add_lut(input, slice(table), PRECISION_8B,
+                              *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut);
+
+  concrete_optimizer::restriction::RangeRestriction restriction{};
+  restriction.glwe_polynomial_sizes.push_back(1024);
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  options.range_restriction =
+      std::make_shared<concrete_optimizer::restriction::RangeRestriction>(
+          restriction);
+  auto solution = dag->optimize_multi(options);
+  assert(!solution.is_feasible);
+  auto error_msg = std::string(solution.error_msg);
+  assert(error_msg.find("(precision 8) requires polynomial_size") !=
+         std::string::npos);
+  assert(error_msg.find("caps it at 1024") != std::string::npos);
+}
+
+TEST test_multi_parameters_prepared_restriction() {
+  auto make_dag = [](uint8_t precision) {
+    auto dag = concrete_optimizer::dag::empty();
+    auto builder = dag->builder("test");
+    std::vector<uint64_t> shape = {3};
+    std::vector<u_int64_t> table = {};
+    auto input = builder->add_input(precision, slice(shape),
+                                    *concrete_optimizer::utils::location_unknown());
+    auto lut = builder->add_lut(input, slice(table), precision,
+                                *concrete_optimizer::utils::location_unknown());
+    builder->tag_operator_as_output(lut);
+    return dag;
+  };
+
+  auto options = default_options();
+  // the crt search does not use restrictions
+  options.encoding = concrete_optimizer::Encoding::Native;
+  auto reference = make_dag(PRECISION_8B)->optimize_multi(options);
+  assert(reference.is_feasible);
+
+  // a keyset made of the keys of the reference solution
+  concrete_optimizer::restriction::KeysetRestriction keyset{};
+  auto lwe_dimension = [](const concrete_optimizer::dag::SecretLweKey &key) {
+    return key.polynomial_size * key.glwe_dimension;
+  };
+  for (auto &key : reference.circuit_keys.bootstrap_keys) {
+    keyset.info.lwe_bootstrap_keys.push_back({
+        key.br_decomposition_parameter.level,
+        key.br_decomposition_parameter.log2_base,
+        key.output_key.glwe_dimension,
+        key.output_key.polynomial_size,
+        lwe_dimension(key.input_key),
+    });
+  }
+  for (auto &key : reference.circuit_keys.keyswitch_keys) {
+    keyset.info.lwe_keyswitch_keys.push_back({
+        key.ks_decomposition_parameter.level,
+        key.ks_decomposition_parameter.log2_base,
+        lwe_dimension(key.input_key),
+        lwe_dimension(key.output_key),
+    });
+  }
+  auto prepared = concrete_optimizer::restriction::prepare_keyset_restriction(keyset);
+
+  for (auto precision : {PRECISION_1B, PRECISION_8B}) {
+    auto solution = make_dag(precision)->optimize_multi_with_prepared(options, *prepared);
+    assert(solution.is_feasible);
+    for (auto &key : solution.circuit_keys.bootstrap_keys) {
+      assert(key.output_key.glwe_dimension ==
+             reference.circuit_keys.bootstrap_keys[0].output_key.glwe_dimension);
+      assert(key.output_key.polynomial_size ==
+             reference.circuit_keys.bootstrap_keys[0].output_key.polynomial_size);
+    }
+  }
+
+  bool thrown = false;
+  try {
+    concrete_optimizer::restriction::KeysetRestriction empty{};
+    concrete_optimizer::restriction::prepare_keyset_restriction(empty);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+
+  thrown = false;
+  try {
+    keyset.info.lwe_bootstrap_keys[0].polynomial_size = 1000;
+    concrete_optimizer::restriction::prepare_keyset_restriction(keyset);
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_range_restriction_from_invalid_json() {
+  bool thrown = false;
+  try {
+    concrete_optimizer::restriction::range_restriction_from_json("{not json");
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_range_restriction_from_inconsistent_json() {
+  auto from_json_throws =
+      [](const concrete_optimizer::restriction::RangeRestriction &restriction) {
+        try {
+          concrete_optimizer::restriction::range_restriction_from_json(
+              restriction.range_restriction_to_json());
+        } catch (const std::exception &) {
+          return true;
+        }
+        return false;
+      };
+
+  concrete_optimizer::restriction::RangeRestriction empty{};
+  assert(!from_json_throws(empty));
+
+  concrete_optimizer::restriction::RangeRestriction unsorted{};
+  unsorted.glwe_dimensions.push_back(2);
+  unsorted.glwe_dimensions.push_back(1);
+  assert(from_json_throws(unsorted));
+
+  concrete_optimizer::restriction::RangeRestriction duplicated{};
+  duplicated.ks_level_count.push_back(1);
+  duplicated.ks_level_count.push_back(1);
+  assert(from_json_throws(duplicated));
+
+  concrete_optimizer::restriction::RangeRestriction too_large_base_log{};
+  too_large_base_log.pbs_base_log.push_back(65);
+  assert(from_json_throws(too_large_base_log));
+
+  concrete_optimizer::restriction::RangeRestriction not_power_of_two{};
+  not_power_of_two.glwe_polynomial_sizes.push_back(1000);
+  assert(from_json_throws(not_power_of_two));
+}
+
+TEST test_keyset_restriction_from_invalid_json() {
+  bool thrown = false;
+  try {
+    concrete_optimizer::restriction::keyset_restriction_from_json("{not json");
+  } catch (const std::exception &) {
+    thrown = true;
+  }
+  assert(thrown);
+}
+
+TEST test_cache_directory() {
+  auto cache_dir = std::string(concrete_optimizer::utils::cache_directory());
+  assert(!cache_dir.empty());
+
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_1B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), PRECISION_1B,
+                   *concrete_optimizer::utils::location_unknown());
+  concrete_optimizer::dag::clear_solution_cache();
+  dag->optimize(default_options());
+  assert(std::filesystem::exists(cache_dir));
+
+  concrete_optimizer::utils::clear_cache();
+  assert(!std::filesystem::exists(cache_dir));
+  // clearing a missing cache is not an error
+  concrete_optimizer::utils::clear_cache();
+}
+
+TEST test_warm_cache() {
+  auto cache_dir = std::string(concrete_optimizer::utils::cache_directory());
+  auto options = default_options();
+  concrete_optimizer::utils::clear_cache();
+  options.cache_on_disk = false;
+  concrete_optimizer::utils::warm_cache(options);
+  assert(!std::filesystem::exists(cache_dir));
+
+  options.cache_on_disk = true;
+  concrete_optimizer::utils::warm_cache(options);
+  assert(std::filesystem::exists(cache_dir));
+
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_8B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), PRECISION_8B,
+                   *concrete_optimizer::utils::location_unknown());
+  assert(dag->optimize(options).p_error < 1.0);
+}
+
+TEST test_estimate_search_space_size() {
+  auto options = default_options();
+  auto full = concrete_optimizer::utils::estimate_search_space_size(options);
+  assert(full > 0);
+
+  options.pinned_glwe_dimension.push_back(1);
+  auto pinned = concrete_optimizer::utils::estimate_search_space_size(options);
+  assert(0 < pinned && pinned < full);
+
+  concrete_optimizer::restriction::RangeRestriction restriction{};
+  restriction.glwe_polynomial_sizes.push_back(2048);
+  options.range_restriction =
+      std::make_shared<concrete_optimizer::restriction::RangeRestriction>(
+          restriction);
+  auto restricted =
+      concrete_optimizer::utils::estimate_search_space_size(options);
+  assert(0 < restricted && restricted < pinned);
+}
+
+TEST test_search_space_options() {
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_1B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  auto lut = builder->add_lut(input, slice(table), PRECISION_1B,
+                              *concrete_optimizer::utils::location_unknown());
+  builder->tag_operator_as_output(lut);
+
+  auto options = default_options();
+  options.encoding = concrete_optimizer::Encoding::Native;
+  auto full = concrete_optimizer::utils::estimate_search_space_size(options);
+  options.search_space.glwe_dimension_max = 1;
+  options.search_space.log2_polynomial_size_min = 11;
+  options.search_space.log2_polynomial_size_max = 11;
+  assert(concrete_optimizer::utils::estimate_search_space_size(options) <
+         full);
+
+  auto solution = dag->optimize(options);
+  assert(solution.p_error < 1.0);
+  assert(solution.glwe_dimension == 1);
+  assert(solution.glwe_polynomial_size == 2048);
+
+  options.search_space.decomposition_level_max = 2;
+  auto circuit_solution = dag->optimize_multi(options);
+  assert(circuit_solution.is_feasible);
+  for (auto &key : circuit_solution.circuit_keys.bootstrap_keys) {
+    assert(key.output_key.glwe_dimension == 1);
+    assert(key.br_decomposition_parameter.level <= 2);
+  }
+  for (auto &key : circuit_solution.circuit_keys.keyswitch_keys) {
+    assert(key.ks_decomposition_parameter.level <= 2);
+  }
+}
+
+std::vector<std::pair<uint8_t, std::string>> logged_messages;
+
+void log_to_vector(uint8_t level, const char *msg, size_t len) {
+  logged_messages.push_back({level, std::string(msg, len)});
+}
+
+TEST test_log_callback() {
+  assert(concrete_optimizer_set_log_callback(log_to_vector));
+
+  auto dag = concrete_optimizer::dag::empty();
+  auto builder = dag->builder("test");
+  std::vector<uint64_t> shape = {};
+  std::vector<u_int64_t> table = {};
+  auto input = builder->add_input(PRECISION_1B, slice(shape),
+                                  *concrete_optimizer::utils::location_unknown());
+  builder->add_lut(input, slice(table), PRECISION_1B,
+                   *concrete_optimizer::utils::location_unknown());
+  auto options = default_options();
+  options.cache_on_disk = false;
+  dag->optimize(options);
+  assert(logged_messages.size() == 2);
+  assert(logged_messages[0].first == 2);
+  assert(logged_messages[0].second == "optimizer: Using stateless cache.");
+
+  options.quiet = true;
+  concrete_optimizer::dag::clear_solution_cache();
+  dag->optimize(options);
+  assert(logged_messages.size() == 2);
+
+  // the invalid curve warning also respects quiet
+  options.security_curve_json = "[";
+  concrete_optimizer::dag::clear_solution_cache();
+  dag->optimize(options);
+  assert(logged_messages.size() == 2);
+
+  options.quiet = false;
+  concrete_optimizer::dag::clear_solution_cache();
+  dag->optimize(options);
+  bool curve_warning = false;
+  for (auto &message : logged_messages) {
+    curve_warning |= message.first == 1 &&
+                     message.second.find("Invalid security curve") !=
+                         std::string::npos;
+  }
+  assert(curve_warning);
+
+  assert(!concrete_optimizer_set_log_callback(log_to_vector));
+}
+
 int main() {
 
   test_v0();
+  test_noise_margin();
+  test_v0_atomic_pattern_sum_size();
+  test_v0_gpu_pbs_type();
+  test_v0_gpu_batch_size();
+  test_v0_max_throughput();
+  test_error_on_infeasible();
+  test_log2_p_error();
+  test_v0_secret_key_variance();
   test_dag_no_lut();
+  test_dag_sub();
+  test_dag_float_dot();
+  test_checked_weights();
+  test_dag_tree_max();
+  test_dag_tree_pbs();
+  test_dag_broadcast_linear_noise();
+  test_dag_negate();
+  test_dag_identity();
+  test_dag_edit();
+  test_dag_merge();
+  test_dag_optimize_many();
+  test_dag_sweep_fft_precision();
+  test_dag_optimize_multi_security();
+  test_dag_solution_cache();
+  test_dag_operator_introspection();
+  test_dag_operator_comments();
+  test_dag_to_dot();
+  test_dag_operator_norm2();
+  test_dag_add_dot_with_norm();
+  test_dag_operator_kind_histogram();
+  test_dag_tag_operators_as_output();
   test_dag_lut();
+  test_dag_lut_table_size();
+  test_dag_dot_shapes();
+  test_dag_signed_lut();
+  test_dag_estimated_seconds();
+  test_dag_optimize_pareto();
+  test_dag_maximum_acceptable_complexity();
+  test_dag_weighted_objective();
+  test_dag_input_with_variance();
+  test_dag_add_inputs();
+  test_dag_input_in_partition();
+  test_multi_parameters_key_sharing_groups();
+  test_dag_change_partition_src_and_dst();
+  test_dag_keyswitch();
+  test_dag_force_bootstrap();
+  test_multi_parameters_used_external_partitions();
+  test_dag_rounded_lut();
+  test_dag_multi_lut();
+  test_dag_check_compositions();
+  test_dag_add_composition_by_index();
+  test_dag_add_all_compositions_matching();
+  test_dag_noise_map();
+  test_dag_budget_report();
+  test_dag_security_curve();
+  test_validate_options();
   test_dag_lut_wop();
   test_dag_lut_force_wop();
+  test_dag_solution_required_keys();
+  test_dag_crt_decomposition_hint();
+  test_dag_remote_pbs();
+  test_dag_pinned_macro_parameters();
+  test_dag_evaluate_solution();
+  test_dag_chosen_encoding();
+  test_dag_solution_json();
+  test_dag_json();
   test_multi_parameters_1_precision();
   test_multi_parameters_2_precision();
+  test_circuit_solution_diff();
+  test_multi_parameters_forbid_fast_keyswitch();
+  test_multi_parameters_min_key_size();
+  test_multi_parameters_with_report();
+  test_multi_parameters_explicit_partitions();
+  test_multi_parameters_is_feasible();
+  test_multi_parameters_cancellable();
+  test_optimize_with_progress();
   test_multi_parameters_2_precision_crt();
+  test_get_noise_br_out_of_range();
+  test_generate_virtual_keyset_info_with_conversions();
+  test_external_partition_accessors();
+  test_external_partition_validation();
+  test_get_noise_ks();
+  test_get_noise_modulus_switch();
+  test_enumerate_pbs_decompositions();
+  test_location_from_invalid_string();
+  test_multi_parameters_range_restriction();
+  test_multi_parameters_range_restriction_polynomial_sizes();
+  test_range_restriction_from_invalid_json();
+  test_range_restriction_from_inconsistent_json();
+  test_keyset_restriction_from_invalid_json();
+  test_multi_parameters_restriction_explanation();
+  test_multi_parameters_prepared_restriction();
+  test_log_callback();
+  test_cache_directory();
+  test_warm_cache();
+  test_estimate_search_space_size();
+  test_search_space_options();
 
   return 0;
 }
@@ -1,9 +1,17 @@
 use super::complexity::Complexity;
-use crate::parameters::{CmuxParameters, KeyswitchParameters, LweDimension, PbsParameters};
+use crate::parameters::{
+    CmuxParameters, GlweParameters, KeyswitchParameters, LweDimension, PbsParameters,
+};
 
 pub trait ComplexityModel: Send + Sync {
     fn pbs_complexity(&self, params: PbsParameters, ciphertext_modulus_log: u32) -> Complexity;
     fn cmux_complexity(&self, params: CmuxParameters, ciphertext_modulus_log: u32) -> Complexity;
+    // Paid once per PBS on top of its cmuxes
+    fn pbs_overhead_complexity(
+        &self,
+        output_glwe_params: GlweParameters,
+        ciphertext_modulus_log: u32,
+    ) -> Complexity;
     fn ks_complexity(&self, params: KeyswitchParameters, ciphertext_modulus_log: u32)
         -> Complexity;
     fn fft_complexity(&self, glwe_polynomial_size: f64, ciphertext_modulus_log: u32) -> Complexity;
@@ -3,7 +3,9 @@ use super::complexity_model::ComplexityModel;
 use super::operators::keyswitch_lwe::KsComplexity;
 use super::operators::{keyswitch_lwe, multi_bit_pbs, pbs};
 use crate::computing_cost::operators::multi_bit_pbs::MultiBitPbsComplexity;
-use crate::parameters::{CmuxParameters, KeyswitchParameters, LweDimension, PbsParameters};
+use crate::parameters::{
+    CmuxParameters, GlweParameters, KeyswitchParameters, LweDimension, PbsParameters,
+};
 
 #[derive(Clone)]
 pub struct CpuComplexity {
@@ -31,6 +33,14 @@ impl ComplexityModel for CpuComplexity {
         self.pbs.cmux.complexity(params, ciphertext_modulus_log)
     }
 
+    fn pbs_overhead_complexity(
+        &self,
+        _output_glwe_params: GlweParameters,
+        _ciphertext_modulus_log: u32,
+    ) -> Complexity {
+        0.
+    }
+
     fn ks_complexity(
         &self,
         params: KeyswitchParameters,
@@ -1,6 +1,9 @@
 use super::complexity::Complexity;
 use super::complexity_model::ComplexityModel;
-use crate::parameters::{CmuxParameters, KeyswitchParameters, LweDimension, PbsParameters};
+use super::cpu::CpuComplexity;
+use crate::parameters::{
+    CmuxParameters, GlweParameters, KeyswitchParameters, LweDimension, PbsParameters,
+};
 use crate::utils::square;
 
 #[derive(Clone, Copy)]
@@ -12,11 +15,15 @@ pub enum GpuPbsComplexity {
 #[derive(Clone, Copy)]
 pub struct GpuKsComplexity;
 
-#[derive(Clone, Copy)]
+/// Gpu complexities are the cpu ones, divided for amortized PBS by the number of PBS running
+/// concurrently, i.e. the batch size up to the number of streaming multiprocessors.
+#[derive(Clone)]
 pub struct GpuComplexity {
     pub ks: GpuKsComplexity,
     pub pbs: GpuPbsComplexity,
     pub number_of_sm: u64,
+    pub batch_size: u64,
+    pub cpu: CpuComplexity,
 }
 
 impl GpuComplexity {
@@ -25,43 +32,59 @@ impl GpuComplexity {
             ks: GpuKsComplexity,
             pbs: GpuPbsComplexity::Lowlat,
             number_of_sm,
+            batch_size: 1,
+            cpu: CpuComplexity::default(),
         }
     }
 
-    pub fn default_amortized_u64(number_of_sm: u64) -> Self {
+    pub fn default_amortized_u64(number_of_sm: u64, batch_size: u64) -> Self {
         Self {
             ks: GpuKsComplexity,
             pbs: GpuPbsComplexity::Amortized,
             number_of_sm,
+            batch_size,
+            cpu: CpuComplexity::default(),
+        }
+    }
+
+    fn concurrent_pbs(&self) -> f64 {
+        match self.pbs {
+            GpuPbsComplexity::Lowlat => 1.0,
+            GpuPbsComplexity::Amortized => {
+                self.batch_size.clamp(1, self.number_of_sm.max(1)) as f64
+            }
         }
     }
 }
 
 impl ComplexityModel for GpuComplexity {
-    #[allow(clippy::let_and_return, non_snake_case)]
-    fn pbs_complexity(&self, _params: PbsParameters, _ciphertext_modulus_log: u32) -> Complexity {
-        todo!()
+    fn pbs_complexity(&self, params: PbsParameters, ciphertext_modulus_log: u32) -> Complexity {
+        self.cpu.pbs_complexity(params, ciphertext_modulus_log) / self.concurrent_pbs()
     }
 
-    fn cmux_complexity(&self, _params: CmuxParameters, _ciphertext_modulus_log: u32) -> Complexity {
-        todo!()
+    fn cmux_complexity(&self, params: CmuxParameters, ciphertext_modulus_log: u32) -> Complexity {
+        self.cpu.cmux_complexity(params, ciphertext_modulus_log) / self.concurrent_pbs()
     }
 
-    #[allow(clippy::let_and_return)]
-    fn ks_complexity(
+    fn pbs_overhead_complexity(
         &self,
-        _params: KeyswitchParameters,
+        _output_glwe_params: GlweParameters,
         _ciphertext_modulus_log: u32,
     ) -> Complexity {
-        todo!()
+        0.
     }
 
-    fn fft_complexity(
+    fn ks_complexity(
         &self,
-        _glwe_polynomial_size: f64,
-        _ciphertext_modulus_log: u32,
+        params: KeyswitchParameters,
+        ciphertext_modulus_log: u32,
     ) -> Complexity {
-        todo!()
+        self.cpu.ks_complexity(params, ciphertext_modulus_log)
+    }
+
+    fn fft_complexity(&self, glwe_polynomial_size: f64, ciphertext_modulus_log: u32) -> Complexity {
+        self.cpu
+            .fft_complexity(glwe_polynomial_size, ciphertext_modulus_log)
     }
 
     fn levelled_complexity(
@@ -75,12 +98,14 @@ impl ComplexityModel for GpuComplexity {
 
     fn multi_bit_pbs_complexity(
         &self,
-        _params: PbsParameters,
-        _ciphertext_modulus_log: u32,
-        _grouping_factor: u32,
-        _jit_fft: bool,
+        params: PbsParameters,
+        ciphertext_modulus_log: u32,
+        grouping_factor: u32,
+        jit_fft: bool,
     ) -> Complexity {
-        todo!()
+        self.cpu
+            .multi_bit_pbs_complexity(params, ciphertext_modulus_log, grouping_factor, jit_fft)
+            / self.concurrent_pbs()
     }
 }
 
@@ -97,3 +122,30 @@ fn algorithmic_complexity_pbs(n: f64, k: f64, N: f64, ell: f64) -> f64 {
 fn algorithmic_complexity_ks(na: f64, nb: f64, ell: f64, log2_q: f64) -> f64 {
     na * nb * ell * log2_q
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::BrDecompositionParameters;
+
+    #[test]
+    fn test_amortized_batch_size() {
+        let params = CmuxParameters {
+            br_decomposition_parameter: BrDecompositionParameters {
+                level: 2,
+                log2_base: 10,
+            },
+            output_glwe_params: GlweParameters {
+                log2_polynomial_size: 11,
+                glwe_dimension: 1,
+            },
+        };
+        let lowlat = GpuComplexity::default_lowlat_u64(108).cmux_complexity(params, 64);
+        let single = GpuComplexity::default_amortized_u64(108, 1).cmux_complexity(params, 64);
+        let batched = GpuComplexity::default_amortized_u64(108, 64).cmux_complexity(params, 64);
+        let saturated = GpuComplexity::default_amortized_u64(108, 1000).cmux_complexity(params, 64);
+        assert_eq!(single, lowlat);
+        assert!((batched * 64. - lowlat).abs() < 1e-6 * lowlat);
+        assert!((saturated * 108. - lowlat).abs() < 1e-6 * lowlat);
+    }
+}
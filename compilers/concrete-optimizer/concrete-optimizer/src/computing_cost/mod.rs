@@ -5,3 +5,4 @@ pub mod cpu;
 mod fft;
 pub mod gpu;
 pub mod operators;
+pub mod remote;
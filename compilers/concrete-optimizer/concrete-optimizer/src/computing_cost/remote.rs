@@ -0,0 +1,137 @@
+use super::complexity::Complexity;
+use super::complexity_model::ComplexityModel;
+use super::cpu::CpuComplexity;
+use crate::parameters::{
+    CmuxParameters, GlweParameters, KeyswitchParameters, LweDimension, PbsParameters,
+};
+
+/// Complexity of PBS offloaded to a remote unit over a network.
+///
+/// Each PBS costs its local cpu complexity, plus a fixed latency and the transfer of its input and
+/// output ciphertexts. Keys are uploaded once per keyset, so their transfer is not charged per PBS.
+/// Keyswitches and levelled operations stay local.
+#[derive(Clone)]
+pub struct RemoteComplexity {
+    pub local: CpuComplexity,
+    /// Fixed cost of one remote PBS call, in complexity units.
+    pub per_pbs_latency: f64,
+    /// Transfer speed, in bits per complexity unit.
+    pub bandwidth: f64,
+}
+
+impl RemoteComplexity {
+    pub fn new(per_pbs_latency: f64, bandwidth: f64) -> Self {
+        Self {
+            local: CpuComplexity::default(),
+            per_pbs_latency,
+            bandwidth,
+        }
+    }
+
+    fn transfer_complexity(&self, nb_coefficients: f64, ciphertext_modulus_log: u32) -> Complexity {
+        nb_coefficients * ciphertext_modulus_log as f64 / self.bandwidth
+    }
+}
+
+impl ComplexityModel for RemoteComplexity {
+    fn pbs_complexity(&self, params: PbsParameters, ciphertext_modulus_log: u32) -> Complexity {
+        let cmux_complexity =
+            self.cmux_complexity(params.cmux_parameters(), ciphertext_modulus_log);
+        params.internal_lwe_dimension.0 as f64 * cmux_complexity
+            + self.pbs_overhead_complexity(params.output_glwe_params, ciphertext_modulus_log)
+    }
+
+    fn multi_bit_pbs_complexity(
+        &self,
+        params: PbsParameters,
+        ciphertext_modulus_log: u32,
+        grouping_factor: u32,
+        jit_fft: bool,
+    ) -> Complexity {
+        self.local.multi_bit_pbs_complexity(
+            params,
+            ciphertext_modulus_log,
+            grouping_factor,
+            jit_fft,
+        ) + self.transfer_complexity(
+            params.internal_lwe_dimension.0 as f64,
+            ciphertext_modulus_log,
+        ) + self.pbs_overhead_complexity(params.output_glwe_params, ciphertext_modulus_log)
+    }
+
+    // Each cmux consumes one mask coefficient of the input ciphertext, which has to be sent.
+    fn cmux_complexity(&self, params: CmuxParameters, ciphertext_modulus_log: u32) -> Complexity {
+        self.local.cmux_complexity(params, ciphertext_modulus_log)
+            + self.transfer_complexity(1.0, ciphertext_modulus_log)
+    }
+
+    // The input body is sent and the output ciphertext is received once per PBS.
+    fn pbs_overhead_complexity(
+        &self,
+        output_glwe_params: GlweParameters,
+        ciphertext_modulus_log: u32,
+    ) -> Complexity {
+        let output_lwe_dimension = output_glwe_params.sample_extract_lwe_dimension();
+        self.per_pbs_latency
+            + self.transfer_complexity(
+                1.0 + (output_lwe_dimension + 1) as f64,
+                ciphertext_modulus_log,
+            )
+    }
+
+    fn ks_complexity(
+        &self,
+        params: KeyswitchParameters,
+        ciphertext_modulus_log: u32,
+    ) -> Complexity {
+        self.local.ks_complexity(params, ciphertext_modulus_log)
+    }
+
+    fn fft_complexity(&self, glwe_polynomial_size: f64, ciphertext_modulus_log: u32) -> Complexity {
+        self.local
+            .fft_complexity(glwe_polynomial_size, ciphertext_modulus_log)
+    }
+
+    fn levelled_complexity(
+        &self,
+        sum_size: u64,
+        lwe_dimension: LweDimension,
+        ciphertext_modulus_log: u32,
+    ) -> Complexity {
+        self.local
+            .levelled_complexity(sum_size, lwe_dimension, ciphertext_modulus_log)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parameters::BrDecompositionParameters;
+
+    const PBS: PbsParameters = PbsParameters {
+        internal_lwe_dimension: LweDimension(800),
+        br_decomposition_parameter: BrDecompositionParameters {
+            level: 1,
+            log2_base: 23,
+        },
+        output_glwe_params: GlweParameters {
+            log2_polynomial_size: 11,
+            glwe_dimension: 1,
+        },
+    };
+
+    #[test]
+    fn test_remote_pbs_complexity() {
+        let local = CpuComplexity::default().pbs_complexity(PBS, 64);
+
+        let latency_only = RemoteComplexity::new(1e6, f64::INFINITY);
+        approx::assert_relative_eq!(latency_only.pbs_complexity(PBS, 64), local + 1e6);
+
+        // 800 + 1 coefficients sent, 2048 + 1 coefficients received
+        let bandwidth_only = RemoteComplexity::new(0.0, 64.0);
+        approx::assert_relative_eq!(
+            bandwidth_only.pbs_complexity(PBS, 64),
+            local + (801 + 2049) as f64
+        );
+    }
+}
@@ -3,6 +3,8 @@ use std::sync::Arc;
 use crate::computing_cost::complexity_model::ComplexityModel;
 use crate::computing_cost::cpu::CpuComplexity;
 use crate::computing_cost::gpu::GpuComplexity;
+use crate::computing_cost::remote::RemoteComplexity;
+use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_key;
 
 #[derive(Clone, Copy)]
 pub enum ProcessingUnit {
@@ -10,6 +12,13 @@ pub enum ProcessingUnit {
     Gpu {
         pbs_type: GpuPbsType,
         number_of_sm: u64,
+        // number of PBS evaluated together, only used by the amortized model
+        batch_size: u64,
+    },
+    // PBS offloaded to a remote unit, latency in complexity units, bandwidth in bits per complexity unit
+    Remote {
+        per_pbs_latency: f64,
+        bandwidth: f64,
     },
 }
 
@@ -22,21 +31,27 @@ pub enum GpuPbsType {
 impl ProcessingUnit {
     pub fn ks_to_string(self) -> &'static str {
         match self {
-            Self::Cpu => "cpu",
+            Self::Cpu | Self::Remote { .. } => "cpu",
             Self::Gpu { .. } => "gpu",
         }
     }
-    pub fn br_to_string(self) -> &'static str {
+    pub fn br_to_string(self) -> String {
         match self {
-            Self::Cpu => "cpu",
+            Self::Cpu => "cpu".into(),
             Self::Gpu {
                 pbs_type: GpuPbsType::Lowlat,
                 ..
-            } => "gpu_lowlat",
+            } => "gpu_lowlat".into(),
+            // the amortized cost depends on the batch size clamped to the number of SMs
             Self::Gpu {
                 pbs_type: GpuPbsType::Amortized,
-                ..
-            } => "gpu_amortized",
+                number_of_sm,
+                batch_size,
+            } => format!("gpu_amortized_{number_of_sm}_{batch_size}"),
+            Self::Remote {
+                per_pbs_latency,
+                bandwidth,
+            } => format!("remote_{per_pbs_latency}_{bandwidth}"),
         }
     }
     pub fn complexity_model(self) -> Arc<dyn ComplexityModel> {
@@ -45,11 +60,71 @@ impl ProcessingUnit {
             Self::Gpu {
                 pbs_type: GpuPbsType::Amortized,
                 number_of_sm,
-            } => Arc::new(GpuComplexity::default_amortized_u64(number_of_sm)),
+                batch_size,
+            } => Arc::new(GpuComplexity::default_amortized_u64(
+                number_of_sm,
+                batch_size,
+            )),
             Self::Gpu {
                 pbs_type: GpuPbsType::Lowlat,
                 number_of_sm,
+                ..
             } => Arc::new(GpuComplexity::default_lowlat_u64(number_of_sm)),
+            Self::Remote {
+                per_pbs_latency,
+                bandwidth,
+            } => Arc::new(RemoteComplexity::new(per_pbs_latency, bandwidth)),
         }
     }
 }
+
+/// Distribution of the secret keys coefficients, used by the keyswitch, blind rotate and modulus
+/// switching noise formulas.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum KeyDistribution {
+    /// Uniform in {0, 1}.
+    #[default]
+    Binary,
+    /// Uniform in {-1, 0, 1}.
+    Ternary,
+    /// Centered distribution of the given variance, e.g. a small gaussian.
+    Centered { variance: f64 },
+}
+
+impl KeyDistribution {
+    pub fn variance(self) -> f64 {
+        match self {
+            Self::Binary => 1. / 4.,
+            Self::Ternary => 2. / 3.,
+            Self::Centered { variance } => variance,
+        }
+    }
+    pub fn square_expectation(self) -> f64 {
+        match self {
+            Self::Binary => 1. / 4.,
+            Self::Ternary | Self::Centered { .. } => 0.,
+        }
+    }
+    // Distinguishes the decomposition caches on disk, empty for binary keys to keep existing caches
+    pub fn to_cache_suffix(self) -> String {
+        match self {
+            Self::Binary => String::new(),
+            Self::Ternary => "-ternary".into(),
+            Self::Centered { variance } => format!("-centered_{variance}"),
+        }
+    }
+    pub fn modulus_switching_noise(
+        self,
+        internal_ks_output_lwe_dimension: u64,
+        glwe_log2_polynomial_size: u64,
+        ciphertext_modulus_log: u32,
+    ) -> f64 {
+        estimate_modulus_switching_noise_with_key(
+            internal_ks_output_lwe_dimension,
+            glwe_log2_polynomial_size,
+            ciphertext_modulus_log,
+            self.variance(),
+            self.square_expectation(),
+        )
+    }
+}
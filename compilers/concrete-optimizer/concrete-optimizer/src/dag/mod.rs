@@ -1,3 +1,4 @@
 pub mod operator;
 pub mod rewrite;
+pub mod serialization;
 pub mod unparametrized;
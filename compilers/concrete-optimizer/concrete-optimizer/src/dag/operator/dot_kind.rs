@@ -1,6 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 use super::{ClearTensor, Shape};
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
 pub enum DotKind {
     // inputs = [x,y,z], weights = [a,b,c], = x*a + y*b + z*c
     Simple,
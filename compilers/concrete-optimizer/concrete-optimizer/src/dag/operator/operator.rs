@@ -1,24 +1,35 @@
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::iter::{empty, once};
 use std::ops::Deref;
 
+use serde::{Deserialize, Serialize};
+
 use crate::dag::operator::tensor::{ClearTensor, Shape};
 use crate::optimization::dag::multi_parameters::partition_cut::ExternalPartition;
 
 use super::DotKind;
 
 pub type Weights = ClearTensor<i64>;
+pub type FloatWeights = ClearTensor<f64>;
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub struct FunctionTable {
     pub values: Vec<u64>,
 }
 
 impl FunctionTable {
     pub const UNKWOWN: Self = Self { values: vec![] };
+
+    /// The table mapping each value of `precision` bits to itself.
+    pub fn identity(precision: Precision) -> Self {
+        Self {
+            values: (0..1_u64 << precision).collect(),
+        }
+    }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug)]
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
 pub struct LevelledComplexity {
     pub lwe_dim_cost_factor: f64,
     pub fixed_cost: f64,
@@ -68,14 +79,20 @@ impl std::ops::Mul<u64> for LevelledComplexity {
         }
     }
 }
+
 pub type Precision = u8;
 pub const MIN_PRECISION: Precision = 1;
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum Operator {
     Input {
         out_precision: Precision,
         out_shape: Shape,
+        // None means fresh encryption noise
+        input_variance: Option<f64>,
+        // None means the input is in an internal partition
+        #[serde(default)]
+        partition: Option<ExternalPartition>,
     },
     ZeroNoise {
         out_precision: Precision,
@@ -85,6 +102,14 @@ pub enum Operator {
         input: OperatorIndex,
         table: FunctionTable,
         out_precision: Precision,
+        // Some when the input is rounded to this precision by the same bootstrap, the table then
+        // has one entry per rounded value
+        #[serde(default)]
+        rounded_precision: Option<Precision>,
+        // The blind rotation of the closest previous lut of the same input is reused, so this lut
+        // only costs a sample extraction
+        #[serde(default)]
+        shared_rotation: bool,
     },
     Dot {
         inputs: Vec<OperatorIndex>,
@@ -118,9 +143,145 @@ pub enum Operator {
         src_partition: Option<ExternalPartition>,
         dst_partition: Option<ExternalPartition>,
     },
+    // Standalone keyswitch of the input to the destination partition, without any bootstrap
+    Keyswitch {
+        input: OperatorIndex,
+        dst_partition: ExternalPartition,
+    },
 }
 
 impl Operator {
+    // Names of the operator kinds, in variant order.
+    pub const KIND_NAMES: [&'static str; 10] = [
+        "Input",
+        "ZeroNoise",
+        "Lut",
+        "Dot",
+        "LinearNoise",
+        "MaxNoise",
+        "UnsafeCast",
+        "Round",
+        "ChangePartition",
+        "Keyswitch",
+    ];
+
+    // Returns the name of the operator kind.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::Input { .. } => "Input",
+            Self::ZeroNoise { .. } => "ZeroNoise",
+            Self::Lut { .. } => "Lut",
+            Self::Dot { .. } => "Dot",
+            Self::LinearNoise { .. } => "LinearNoise",
+            Self::MaxNoise { .. } => "MaxNoise",
+            Self::UnsafeCast { .. } => "UnsafeCast",
+            Self::Round { .. } => "Round",
+            Self::ChangePartition { .. } => "ChangePartition",
+            Self::Keyswitch { .. } => "Keyswitch",
+        }
+    }
+
+    // Hashes everything the optimization depends on, i.e. all but the comments.
+    pub(crate) fn hash_structure<H: Hasher>(&self, state: &mut H) {
+        fn hash_partition<H: Hasher>(partition: Option<&ExternalPartition>, state: &mut H) {
+            partition.hash(state);
+            if let Some(partition) = partition {
+                partition.max_variance.to_bits().hash(state);
+                partition.variance.to_bits().hash(state);
+            }
+        }
+        self.kind_name().hash(state);
+        match self {
+            Self::Input {
+                out_precision,
+                out_shape,
+                input_variance,
+                partition,
+            } => {
+                out_precision.hash(state);
+                out_shape.hash(state);
+                input_variance.map(f64::to_bits).hash(state);
+                hash_partition(partition.as_ref(), state);
+            }
+            Self::ZeroNoise {
+                out_precision,
+                out_shape,
+            } => {
+                out_precision.hash(state);
+                out_shape.hash(state);
+            }
+            Self::Lut {
+                input,
+                table,
+                out_precision,
+                rounded_precision,
+                shared_rotation,
+            } => {
+                input.hash(state);
+                table.hash(state);
+                out_precision.hash(state);
+                rounded_precision.hash(state);
+                shared_rotation.hash(state);
+            }
+            Self::Dot {
+                inputs,
+                weights,
+                kind,
+            } => {
+                inputs.hash(state);
+                weights.hash(state);
+                kind.hash(state);
+            }
+            Self::LinearNoise {
+                inputs,
+                complexity,
+                weights,
+                out_shape,
+                comment: _,
+            } => {
+                inputs.hash(state);
+                complexity.lwe_dim_cost_factor.to_bits().hash(state);
+                complexity.fixed_cost.to_bits().hash(state);
+                weights.len().hash(state);
+                for weight in weights {
+                    weight.to_bits().hash(state);
+                }
+                out_shape.hash(state);
+            }
+            Self::MaxNoise { inputs, out_shape } => {
+                inputs.hash(state);
+                out_shape.hash(state);
+            }
+            Self::UnsafeCast {
+                input,
+                out_precision,
+            }
+            | Self::Round {
+                input,
+                out_precision,
+            } => {
+                input.hash(state);
+                out_precision.hash(state);
+            }
+            Self::ChangePartition {
+                input,
+                src_partition,
+                dst_partition,
+            } => {
+                input.hash(state);
+                hash_partition(src_partition.as_ref(), state);
+                hash_partition(dst_partition.as_ref(), state);
+            }
+            Self::Keyswitch {
+                input,
+                dst_partition,
+            } => {
+                input.hash(state);
+                hash_partition(Some(dst_partition), state);
+            }
+        }
+    }
+
     // Returns an iterator on the indices of the operator inputs.
     pub(crate) fn get_inputs_iter(&self) -> Box<dyn Iterator<Item = &OperatorIndex> + '_> {
         match self {
@@ -131,12 +292,13 @@ impl Operator {
             Self::UnsafeCast { input, .. }
             | Self::Lut { input, .. }
             | Self::Round { input, .. }
-            | Self::ChangePartition { input, .. } => Box::new(once(input)),
+            | Self::ChangePartition { input, .. }
+            | Self::Keyswitch { input, .. } => Box::new(once(input)),
         }
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub struct OperatorIndex(pub usize);
 
 impl Deref for OperatorIndex {
@@ -160,8 +322,16 @@ impl fmt::Display for Operator {
             Self::Input {
                 out_precision,
                 out_shape,
+                input_variance,
+                partition,
             } => {
                 write!(f, "Input : u{out_precision} x {out_shape:?}")?;
+                if let Some(variance) = input_variance {
+                    write!(f, " (variance: {variance:e})")?;
+                }
+                if let Some(partition) = partition {
+                    write!(f, " (partition: {})", partition.name)?;
+                }
             }
             Self::ZeroNoise {
                 out_precision,
@@ -188,9 +358,21 @@ impl fmt::Display for Operator {
             Self::Lut {
                 input,
                 out_precision,
+                rounded_precision,
+                shared_rotation,
                 ..
             } => {
-                write!(f, "LUT[%{}] : u{out_precision}", input.0)?;
+                match rounded_precision {
+                    Some(rounded_precision) => write!(
+                        f,
+                        "LUT[ROUND[%{}] : u{rounded_precision}] : u{out_precision}",
+                        input.0
+                    )?,
+                    None => write!(f, "LUT[%{}] : u{out_precision}", input.0)?,
+                }
+                if *shared_rotation {
+                    write!(f, " (shared rotation)")?;
+                }
             }
             Self::LinearNoise {
                 inputs,
@@ -242,6 +424,12 @@ impl fmt::Display for Operator {
                 }
                 write!(f, "}}")?;
             }
+            Self::Keyswitch {
+                input,
+                dst_partition,
+            } => {
+                write!(f, "KEYSWITCH[%{}] : {}", input.0, dst_partition.name)?;
+            }
         }
         Ok(())
     }
@@ -1,9 +1,12 @@
+use std::fmt;
 use std::iter::Sum;
 use std::ops::Mul;
 
+use serde::{Deserialize, Serialize};
+
 use crate::utils::square_ref;
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub struct Shape {
     pub dimensions_size: Vec<u64>,
 }
@@ -57,9 +60,31 @@ impl Shape {
             dimensions_size: self.dimensions_size[1..].to_vec(),
         }
     }
+
+    /// Returns the NumPy-style broadcast of two shapes, or `None` if they are incompatible.
+    pub fn broadcast(&self, other: &Self) -> Option<Self> {
+        let rank = self.rank().max(other.rank());
+        let dim_size = |shape: &Self, i: usize| {
+            let offset = rank - shape.rank();
+            if i < offset {
+                1
+            } else {
+                shape.dimensions_size[i - offset]
+            }
+        };
+        let dimensions_size = (0..rank)
+            .map(|i| match (dim_size(self, i), dim_size(other, i)) {
+                (a, b) if a == b => Some(a),
+                (1, b) => Some(b),
+                (a, 1) => Some(a),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { dimensions_size })
+    }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub struct ClearTensor<W> {
     pub shape: Shape,
     pub values: Vec<W>,
@@ -105,6 +130,52 @@ where
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WeightsError {
+    /// The squared L2 norm of the weights does not fit an `i64`, starting at this weight.
+    SquareNorm2Overflow { index: usize, weight: i64 },
+}
+
+impl fmt::Display for WeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SquareNorm2Overflow { index, weight } => write!(
+                f,
+                "Weights squared L2 norm overflows at index {index} (weight {weight}), \
+                 such weights would make the noise meaningless"
+            ),
+        }
+    }
+}
+
+impl ClearTensor<i64> {
+    /// Same as `number`, but rejects a weight whose square overflows.
+    pub fn try_number(value: i64) -> Result<Self, WeightsError> {
+        let weights = Self::number(value);
+        let _ = weights.checked_square_norm2()?;
+        Ok(weights)
+    }
+
+    /// Same as `vector`, but rejects weights whose squared L2 norm overflows.
+    pub fn try_vector(values: impl Into<Vec<i64>>) -> Result<Self, WeightsError> {
+        let weights = Self::vector(values);
+        let _ = weights.checked_square_norm2()?;
+        Ok(weights)
+    }
+
+    pub fn checked_square_norm2(&self) -> Result<i64, WeightsError> {
+        self.values
+            .iter()
+            .enumerate()
+            .try_fold(0_i64, |acc, (index, &weight)| {
+                weight
+                    .checked_mul(weight)
+                    .and_then(|square| acc.checked_add(square))
+                    .ok_or(WeightsError::SquareNorm2Overflow { index, weight })
+            })
+    }
+}
+
 // helps using shared shapes
 impl From<&Self> for Shape {
     fn from(item: &Self) -> Self {
@@ -131,3 +202,35 @@ where
         Self::vector(items)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_weights() {
+        assert_eq!(
+            ClearTensor::try_vector([3, -4]).unwrap().values,
+            vec![3, -4]
+        );
+        assert_eq!(
+            ClearTensor::try_number(-3).unwrap().checked_square_norm2(),
+            Ok(9)
+        );
+        assert_eq!(
+            ClearTensor::try_number(i64::MAX),
+            Err(WeightsError::SquareNorm2Overflow {
+                index: 0,
+                weight: i64::MAX
+            })
+        );
+        let half = 1_i64 << 31;
+        assert_eq!(
+            ClearTensor::try_vector([half, half]),
+            Err(WeightsError::SquareNorm2Overflow {
+                index: 1,
+                weight: half
+            })
+        );
+    }
+}
@@ -2,14 +2,15 @@ use crate::dag::operator::operator::Operator;
 use crate::dag::operator::OperatorIndex;
 use crate::dag::unparametrized::{Dag, DagBuilder, DagOperator};
 
-fn reindex_op_inputs(op: &Operator, old_index_to_new: &[usize]) -> Operator {
+pub(crate) fn reindex_op_inputs(op: &Operator, old_index_to_new: &[usize]) -> Operator {
     let mut op = op.clone();
     match &mut op {
         Operator::Input { .. } | Operator::ZeroNoise { .. } => (),
         Operator::Lut { input, .. }
         | Operator::UnsafeCast { input, .. }
         | Operator::Round { input, .. }
-        | Operator::ChangePartition { input, .. } => input.0 = old_index_to_new[input.0],
+        | Operator::ChangePartition { input, .. }
+        | Operator::Keyswitch { input, .. } => input.0 = old_index_to_new[input.0],
         Operator::Dot { inputs, .. }
         | Operator::LinearNoise { inputs, .. }
         | Operator::MaxNoise { inputs, .. } => {
@@ -32,6 +33,10 @@ where
         let size = regen_dag.operators.len();
         let mut builder = regen_dag.builder(dag_op.circuit_tag);
         if let Some(op_i) = f(new_op.clone(), &dag_op, &mut builder) {
+            // the comment follows the operator if it has been rewritten to a new one
+            if op_i.0 >= size && !dag_op.comment.is_empty() {
+                builder.set_operator_comment(op_i, dag_op.comment.clone());
+            }
             old_index_to_new.push(op_i.0);
         } else {
             assert!(size == regen_dag.operators.len());
@@ -42,6 +47,7 @@ where
             regen_dag.output_state.push(*dag_op.output_state);
             regen_dag.circuit_tags.push(dag_op.circuit_tag.to_owned());
             regen_dag.locations.push(dag_op.location.to_owned());
+            regen_dag.comments.push(dag_op.comment.to_owned());
             new_op
                 .get_inputs_iter()
                 .for_each(|n| regen_dag.output_state[n.0].transition_use());
@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dag::operator::{dot_kind, Location, Operator, OperatorIndex, Precision, Shape};
+use crate::dag::unparametrized::{Dag, DagEditError, OutputState};
+
+/// An operator of a serialized dag, with everything the dag associates to it.
+#[derive(Serialize, Deserialize)]
+struct SerializedOperator {
+    circuit: String,
+    operator: Operator,
+    out_precision: Precision,
+    out_shape: Shape,
+    output_state: OutputState,
+    location: Location,
+    #[serde(default)]
+    comment: String,
+}
+
+/// The serialized form of a [`Dag`], one entry per operator in index order.
+///
+/// Composition rules are listed as sorted `(from, to)` pairs so that the serialization of a dag
+/// does not depend on the iteration order of its internal maps.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SerializedDag {
+    operators: Vec<SerializedOperator>,
+    compositions: Vec<(OperatorIndex, OperatorIndex)>,
+}
+
+impl From<Dag> for SerializedDag {
+    fn from(dag: Dag) -> Self {
+        let mut compositions: Vec<_> = dag
+            .composition
+            .clone()
+            .into_iter()
+            .flat_map(|(to, froms)| froms.into_iter().map(move |from| (from, to)))
+            .collect();
+        compositions.sort_by_key(|(from, to)| (from.0, to.0));
+        let operators = dag
+            .get_operators_iter()
+            .map(|op| SerializedOperator {
+                circuit: op.circuit_tag.clone(),
+                operator: op.operator.clone(),
+                out_precision: *op.precision,
+                out_shape: op.shape.clone(),
+                output_state: *op.output_state,
+                location: op.location.clone(),
+                comment: op.comment.clone(),
+            })
+            .collect();
+        Self {
+            operators,
+            compositions,
+        }
+    }
+}
+
+impl TryFrom<SerializedDag> for Dag {
+    type Error = DagEditError;
+
+    /// Rebuilds the dag, checking each operator as the builder would and that its recorded
+    /// precision, shape and output state are the ones the builder gives it. Composition rules must
+    /// link outputs to inputs.
+    fn try_from(serialized: SerializedDag) -> Result<Self, Self::Error> {
+        let mut dag = Self::new();
+        for op in serialized.operators {
+            check_operator(&mut dag, &op)?;
+            dag.operators.push(op.operator);
+            dag.out_precisions.push(op.out_precision);
+            dag.out_shapes.push(op.out_shape);
+            dag.output_state.push(op.output_state);
+            dag.circuit_tags.push(op.circuit);
+            dag.locations.push(op.location);
+            dag.comments.push(op.comment);
+        }
+        check_output_states(&dag)?;
+        for (from, to) in serialized.compositions {
+            dag.try_add_composition(from, to)?;
+        }
+        Ok(dag)
+    }
+}
+
+// Checks an operator to be added at the end of `dag`.
+fn check_operator(dag: &mut Dag, op: &SerializedOperator) -> Result<(), DagEditError> {
+    let index = OperatorIndex(dag.len());
+    if let Some(&input) = op
+        .operator
+        .get_inputs_iter()
+        .find(|input| input.0 >= index.0)
+    {
+        return Err(DagEditError::UnknownOperator(input));
+    }
+    match &op.operator {
+        Operator::Lut {
+            input,
+            table,
+            rounded_precision,
+            ..
+        } => dag.check_rounded_lut_table(*input, table, *rounded_precision)?,
+        Operator::Dot {
+            inputs,
+            weights,
+            kind,
+        } => {
+            dag.check_dot(inputs, &weights.shape)?;
+            let input_shape = &dag.out_shapes[inputs[0].0];
+            if weights.values.len() as u64 != weights.shape.flat_size()
+                || *kind != dot_kind(inputs.len() as u64, input_shape, weights)
+            {
+                return Err(DagEditError::InconsistentOperator(index));
+            }
+        }
+        Operator::LinearNoise {
+            inputs, weights, ..
+        } => {
+            if inputs.is_empty() {
+                return Err(DagEditError::InconsistentOperator(index));
+            }
+            if weights.len() != inputs.len() {
+                return Err(DagEditError::LinearNoiseWeights {
+                    len: weights.len(),
+                    expected: inputs.len(),
+                });
+            }
+        }
+        Operator::MaxNoise { inputs, .. } if inputs.is_empty() => {
+            return Err(DagEditError::InconsistentOperator(index));
+        }
+        _ => {}
+    }
+    let builder = dag.builder(&op.circuit);
+    if builder.infer_out_precision(&op.operator) != op.out_precision
+        || builder.infer_out_shape(&op.operator) != op.out_shape
+    {
+        return Err(DagEditError::InconsistentOperator(index));
+    }
+    Ok(())
+}
+
+// Checks that exactly the operators used as input are in the used state, unless tagged.
+fn check_output_states(dag: &Dag) -> Result<(), DagEditError> {
+    let mut used = vec![false; dag.len()];
+    for op in &dag.operators {
+        for input in op.get_inputs_iter() {
+            used[input.0] = true;
+        }
+    }
+    for (i, (&state, used)) in dag.output_state.iter().zip(used).enumerate() {
+        let expected = match state {
+            OutputState::Tagged => OutputState::Tagged,
+            _ if used => OutputState::Used,
+            _ => OutputState::Unused,
+        };
+        if state != expected {
+            return Err(DagEditError::InconsistentOperator(OperatorIndex(i)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dag::operator::{FunctionTable, LevelledComplexity};
+
+    fn composed_dag() -> Dag {
+        let mut dag = Dag::new();
+        let mut builder = dag.builder("main");
+        let input = builder.add_input(3, Shape::vector(2), Location::Unknown);
+        let dot = builder.add_dot([input], [3], Location::Line("main.py".into(), 4));
+        let lut = builder.add_lut(
+            dot,
+            FunctionTable { values: vec![1; 8] },
+            3,
+            Location::Unknown,
+        );
+        let _ = builder.add_linear_noise(
+            [lut],
+            LevelledComplexity::ADDITION,
+            [0.1],
+            Shape::vector(2),
+            "noise",
+            Location::Unknown,
+        );
+        builder.tag_operator_as_output(lut);
+        builder.set_operator_comment(input, "x");
+        let _ = dag.builder("other").add_input_with_variance(
+            5,
+            Shape::number(),
+            1e-20,
+            Location::Unknown,
+        );
+        dag.add_composition(lut, input);
+        dag
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dag = composed_dag();
+        let serialized = SerializedDag::from(dag.clone());
+        let deserialized = Dag::try_from(serialized).unwrap();
+        assert_eq!(deserialized, dag);
+        assert_eq!(deserialized.structural_hash(), dag.structural_hash());
+    }
+
+    #[test]
+    fn test_invalid_references() {
+        let mut serialized = SerializedDag::from(composed_dag());
+        serialized
+            .compositions
+            .push((OperatorIndex(0), OperatorIndex(2)));
+        assert_eq!(
+            Dag::try_from(serialized).unwrap_err(),
+            DagEditError::NotAnOutput(OperatorIndex(0))
+        );
+
+        let mut serialized = SerializedDag::from(composed_dag());
+        serialized.operators.swap(1, 2);
+        assert_eq!(
+            Dag::try_from(serialized).unwrap_err(),
+            DagEditError::UnknownOperator(OperatorIndex(1))
+        );
+    }
+
+    #[test]
+    fn test_corrupted_operators() {
+        let corrupted = |corrupt: fn(&mut Vec<SerializedOperator>)| {
+            let mut serialized = SerializedDag::from(composed_dag());
+            corrupt(&mut serialized.operators);
+            Dag::try_from(serialized).unwrap_err()
+        };
+        assert_eq!(
+            corrupted(|operators| operators[1].out_precision = 4),
+            DagEditError::InconsistentOperator(OperatorIndex(1))
+        );
+        assert_eq!(
+            corrupted(|operators| operators[2].out_shape = Shape::number()),
+            DagEditError::InconsistentOperator(OperatorIndex(2))
+        );
+        assert_eq!(
+            corrupted(|operators| operators[0].output_state = OutputState::Unused),
+            DagEditError::InconsistentOperator(OperatorIndex(0))
+        );
+        assert_eq!(
+            corrupted(|operators| operators[3].output_state = OutputState::Used),
+            DagEditError::InconsistentOperator(OperatorIndex(3))
+        );
+        assert_eq!(
+            corrupted(|operators| {
+                if let Operator::Lut { table, .. } = &mut operators[2].operator {
+                    let _ = table.values.pop();
+                }
+            }),
+            DagEditError::LutTableSize {
+                input: OperatorIndex(1),
+                len: 7,
+                expected: 8
+            }
+        );
+        assert_eq!(
+            corrupted(|operators| {
+                if let Operator::Dot { weights, .. } = &mut operators[1].operator {
+                    weights.values.push(1);
+                }
+            }),
+            DagEditError::InconsistentOperator(OperatorIndex(1))
+        );
+        assert_eq!(
+            corrupted(|operators| {
+                if let Operator::LinearNoise { weights, .. } = &mut operators[3].operator {
+                    weights.push(1.0);
+                }
+            }),
+            DagEditError::LinearNoiseWeights {
+                len: 2,
+                expected: 1
+            }
+        );
+    }
+}
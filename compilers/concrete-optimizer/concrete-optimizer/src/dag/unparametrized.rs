@@ -1,12 +1,18 @@
 use crate::dag::operator::{
-    FunctionTable, LevelledComplexity, Operator, OperatorIndex, Precision, Shape, Weights,
+    ClearTensor, FloatWeights, FunctionTable, LevelledComplexity, Operator, OperatorIndex,
+    Precision, Shape, Weights,
 };
 use crate::optimization::dag::multi_parameters::partition_cut::ExternalPartition;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
-    fmt,
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fmt::{self, Write as _},
+    hash::{Hash, Hasher},
 };
 
+use super::rewrite::regen::reindex_op_inputs;
+use super::serialization::SerializedDag;
+
 use super::operator::{
     dot_kind::{dot_kind, DotKind},
     Location,
@@ -17,7 +23,7 @@ use super::operator::{
 const DEFAULT_CIRCUIT: &str = "_";
 
 /// A state machine to define if an operator is used as output to a circuit.
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, Serialize, Deserialize)]
 pub enum OutputState {
     /// The operator was created and neither used as input to another operator, nor tagged as output
     /// explicitly. It is considered an output.
@@ -56,6 +62,177 @@ impl OutputState {
     }
 }
 
+/// Errors raised when editing the operators of an existing dag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DagEditError {
+    /// The operator does not exist in the dag.
+    UnknownOperator(OperatorIndex),
+    /// The operator is still used as input by other operators.
+    HasDependents(OperatorIndex),
+    /// The operator is not a lookup table.
+    NotALut(OperatorIndex),
+    /// The operator is not an output of its circuit.
+    NotAnOutput(OperatorIndex),
+    /// The operator is not an input of its circuit.
+    NotAnInput(OperatorIndex),
+    /// The lookup table size does not match the precision read from the lut input.
+    LutTableSize {
+        input: OperatorIndex,
+        len: usize,
+        expected: usize,
+    },
+    /// A multi lut has no table.
+    EmptyMultiLut,
+    /// A multi lut table does not have the size of the first table.
+    MultiLutTableSize {
+        table: usize,
+        len: usize,
+        expected: usize,
+    },
+    /// A rounded lut rounds its input to more bits than the input has.
+    RoundedPrecision {
+        input: OperatorIndex,
+        rounded_precision: Precision,
+    },
+    /// The tree PBS digits must have at least one bit.
+    NullDecompositionBase,
+    /// A dot input does not have the shape of the first dot input.
+    DotInputShape {
+        input: OperatorIndex,
+        first: OperatorIndex,
+    },
+    /// The dot weights shape does not match any supported dot of the inputs.
+    DotWeightsShape(OperatorIndex),
+    /// The dot has no input.
+    EmptyDot,
+    /// A real dot weight is NaN or infinite.
+    NonFiniteWeights,
+    /// The manual norm2 of a dot is NaN or infinite.
+    NonFiniteNorm2,
+    /// The max has no input.
+    EmptyMax,
+    /// An operand shape cannot be broadcast with the shapes of the previous operands.
+    BroadcastShape(OperatorIndex),
+    /// The linear noise does not have one weight per input.
+    LinearNoiseWeights { len: usize, expected: usize },
+    /// A loaded operator does not match its inputs, e.g. a precision or shape the builder would
+    /// not have inferred.
+    InconsistentOperator(OperatorIndex),
+    /// A max input does not have the max output precision.
+    MaxInputPrecision {
+        input: OperatorIndex,
+        expected: Precision,
+    },
+}
+
+impl fmt::Display for DagEditError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::UnknownOperator(index) => write!(f, "Operator %{index} does not exist"),
+            Self::HasDependents(index) => {
+                write!(f, "Operator %{index} is used as input by other operators")
+            }
+            Self::NotALut(index) => write!(f, "Operator %{index} is not a lookup table"),
+            Self::NotAnOutput(index) => write!(f, "Operator %{index} is not an output"),
+            Self::NotAnInput(index) => write!(f, "Operator %{index} is not an input"),
+            Self::LutTableSize {
+                input,
+                len,
+                expected,
+            } => write!(
+                f,
+                "Lut table has {len} entries, {expected} expected for the precision read from operator %{input}"
+            ),
+            Self::EmptyMultiLut => write!(f, "Multi lut has no table"),
+            Self::MultiLutTableSize {
+                table,
+                len,
+                expected,
+            } => write!(
+                f,
+                "Multi lut table {table} has {len} entries, {expected} expected as for table 0"
+            ),
+            Self::RoundedPrecision {
+                input,
+                rounded_precision,
+            } => write!(
+                f,
+                "Cannot round operator %{input} to {rounded_precision} bits, more than its precision"
+            ),
+            Self::NullDecompositionBase => write!(f, "Tree PBS decomposition base must be positive"),
+            Self::DotInputShape { input, first } => write!(
+                f,
+                "Dot input %{input} does not have the shape of the first dot input %{first}"
+            ),
+            Self::DotWeightsShape(first) => write!(
+                f,
+                "Dot weights shape is incompatible with the shape of dot input %{first}"
+            ),
+            Self::EmptyDot => write!(f, "Dot has no input"),
+            Self::NonFiniteWeights => write!(f, "Dot weights must be finite"),
+            Self::NonFiniteNorm2 => write!(f, "Dot manual norm2 must be finite"),
+            Self::EmptyMax => write!(f, "Max has no input"),
+            Self::BroadcastShape(input) => write!(
+                f,
+                "Operand %{input} shape cannot be broadcast with the previous operand shapes"
+            ),
+            Self::LinearNoiseWeights { len, expected } => write!(
+                f,
+                "Linear noise has {len} weights, {expected} expected for its inputs"
+            ),
+            Self::InconsistentOperator(index) => write!(
+                f,
+                "Operator %{index} is inconsistent with its inputs or its recorded precision, shape or output state"
+            ),
+            Self::MaxInputPrecision { input, expected } => write!(
+                f,
+                "Max input %{input} does not have the output precision {expected}"
+            ),
+        }
+    }
+}
+
+/// Errors raised when merging a dag into another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DagMergeError {
+    /// A circuit of the merged dag already exists in the receiving dag.
+    CircuitNameCollision(String),
+}
+
+impl fmt::Display for DagMergeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::CircuitNameCollision(name) => write!(f, "Circuit {name} already exists"),
+        }
+    }
+}
+
+/// A composition rule linking an output to an input of a different precision.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompositionError {
+    pub from_circuit: String,
+    pub from: OperatorIndex,
+    pub from_precision: Precision,
+    pub to_circuit: String,
+    pub to: OperatorIndex,
+    pub to_precision: Precision,
+}
+
+impl fmt::Display for CompositionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Composition from {}:%{} (u{}) to {}:%{} (u{}) changes precision",
+            self.from_circuit,
+            self.from,
+            self.from_precision,
+            self.to_circuit,
+            self.to,
+            self.to_precision
+        )
+    }
+}
+
 /// A type referencing every informations related to an operator of the dag.
 #[derive(Debug, Clone)]
 #[allow(unused)]
@@ -68,6 +245,7 @@ pub struct DagOperator<'dag> {
     pub output_state: &'dag OutputState,
     pub circuit_tag: &'dag String,
     pub location: &'dag Location,
+    pub comment: &'dag String,
 }
 
 impl<'dag> DagOperator<'dag> {
@@ -163,6 +341,10 @@ impl DagBuilder<'_> {
         self.dag.output_state.push(OutputState::new());
         self.dag.circuit_tags.push(self.circuit.clone());
         self.dag.locations.push(location);
+        self.dag.comments.push(match self.dag.operators.last() {
+            Some(Operator::LinearNoise { comment, .. }) => comment.clone(),
+            _ => String::new(),
+        });
         OperatorIndex(i)
     }
 
@@ -193,11 +375,55 @@ impl DagBuilder<'_> {
             Operator::Input {
                 out_precision,
                 out_shape,
+                input_variance: None,
+                partition: None,
+            },
+            location,
+        )
+    }
+
+    /// Adds an input whose ciphertexts already carry a known noise variance,
+    /// e.g. the outputs of a separately-compiled circuit.
+    pub fn add_input_with_variance(
+        &mut self,
+        out_precision: Precision,
+        out_shape: impl Into<Shape>,
+        input_variance: f64,
+        location: Location,
+    ) -> OperatorIndex {
+        let out_shape = out_shape.into();
+        self.add_operator(
+            Operator::Input {
+                out_precision,
+                out_shape,
+                input_variance: Some(input_variance),
+                partition: None,
+            },
+            location,
+        )
+    }
+
+    /// Adds an input whose ciphertexts are encrypted under the keys of an external partition.
+    pub fn add_input_in_partition(
+        &mut self,
+        out_precision: Precision,
+        out_shape: impl Into<Shape>,
+        partition: ExternalPartition,
+        location: Location,
+    ) -> OperatorIndex {
+        let out_shape = out_shape.into();
+        self.add_operator(
+            Operator::Input {
+                out_precision,
+                out_shape,
+                input_variance: None,
+                partition: Some(partition),
             },
             location,
         )
     }
 
+    /// Adds a lut, panicking if the table size does not match the input precision.
     pub fn add_lut(
         &mut self,
         input: OperatorIndex,
@@ -205,35 +431,292 @@ impl DagBuilder<'_> {
         out_precision: Precision,
         location: Location,
     ) -> OperatorIndex {
-        self.add_operator(
+        self.try_add_lut(input, table, out_precision, location)
+            .unwrap_or_else(|err| panic!("add_lut: {err}"))
+    }
+
+    /// Adds a lut, checking that the table has one entry per value of the input precision.
+    ///
+    /// An empty table stands for an unknown function and is always accepted.
+    pub fn try_add_lut(
+        &mut self,
+        input: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        self.check_lut_table(input, &table)?;
+        Ok(self.add_operator(
             Operator::Lut {
                 input,
                 table,
                 out_precision,
+                rounded_precision: None,
+                shared_rotation: false,
             },
             location,
-        )
+        ))
+    }
+
+    /// Adds a lut on a signed input, `table` listing the outputs for inputs from `-2^(p-1)` to
+    /// `2^(p-1) - 1`.
+    ///
+    /// Signed messages are encoded in two's complement on the same `p` bits as unsigned ones, so
+    /// this is a regular lut whose table is rotated to put the negative inputs last.
+    pub fn add_signed_lut(
+        &mut self,
+        input: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+        location: Location,
+    ) -> OperatorIndex {
+        let mut values = table.values;
+        let nb_negatives = values.len() / 2;
+        values.rotate_left(nb_negatives);
+        self.add_lut(input, FunctionTable { values }, out_precision, location)
+    }
+
+    /// Checks that `table` can be applied to `input`, see [`Self::try_add_lut`].
+    pub fn check_lut_table(
+        &self,
+        input: OperatorIndex,
+        table: &FunctionTable,
+    ) -> Result<(), DagEditError> {
+        self.dag.check_lut_table(input, table)
+    }
+
+    /// Adds several luts of the same input sharing a single blind rotation, one output per table.
+    ///
+    /// Each table is a lut on its own operator. The first one evaluates the blind rotation, the
+    /// others reuse it and only cost a sample extraction. All tables must have the same size, and
+    /// are checked as in [`Self::try_add_lut`].
+    pub fn add_multi_lut(
+        &mut self,
+        input: OperatorIndex,
+        tables: &[FunctionTable],
+        out_precision: Precision,
+        location: Location,
+    ) -> Result<Vec<OperatorIndex>, DagEditError> {
+        let Some(first) = tables.first() else {
+            return Err(DagEditError::EmptyMultiLut);
+        };
+        if let Some((table, other)) = tables
+            .iter()
+            .enumerate()
+            .find(|(_, table)| table.values.len() != first.values.len())
+        {
+            return Err(DagEditError::MultiLutTableSize {
+                table,
+                len: other.values.len(),
+                expected: first.values.len(),
+            });
+        }
+        self.check_lut_table(input, first)?;
+        Ok(tables
+            .iter()
+            .enumerate()
+            .map(|(i, table)| {
+                self.add_operator(
+                    Operator::Lut {
+                        input,
+                        table: table.clone(),
+                        out_precision,
+                        rounded_precision: None,
+                        shared_rotation: i > 0,
+                    },
+                    location.clone(),
+                )
+            })
+            .collect())
     }
 
+    /// Adds a dot, panicking if the inputs and weights shapes are incompatible.
     pub fn add_dot(
         &mut self,
         inputs: impl Into<Vec<OperatorIndex>>,
         weights: impl Into<Weights>,
         location: Location,
     ) -> OperatorIndex {
+        self.try_add_dot(inputs, weights, location)
+            .unwrap_or_else(|err| panic!("add_dot: {err}"))
+    }
+
+    /// Adds a dot, checking that all the inputs have the same shape and that the weights shape
+    /// gives a supported dot of them.
+    pub fn try_add_dot(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<Weights>,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
         let inputs = inputs.into();
         let weights = weights.into();
+        self.check_dot(&inputs, &weights.shape)?;
         // We detect the kind of dot to simplify matching later on.
         let nb_inputs = inputs.len() as u64;
         let input_shape = self.dag.get_operator(inputs[0]).shape;
-        self.add_operator(
+        Ok(self.add_operator(
             Operator::Dot {
                 inputs,
                 kind: dot_kind(nb_inputs, input_shape, &weights),
                 weights,
             },
             location,
-        )
+        ))
+    }
+
+    /// Checks that a dot of `inputs` by weights of `weights_shape` is supported, see
+    /// [`Self::try_add_dot`].
+    pub fn check_dot(
+        &self,
+        inputs: &[OperatorIndex],
+        weights_shape: &Shape,
+    ) -> Result<(), DagEditError> {
+        self.dag.check_dot(inputs, weights_shape)
+    }
+
+    /// Adds a dot with real weights, panicking on invalid shapes or weights.
+    pub fn add_float_dot(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<FloatWeights>,
+        location: Location,
+    ) -> OperatorIndex {
+        self.try_add_float_dot(inputs, weights, location)
+            .unwrap_or_else(|err| panic!("add_float_dot: {err}"))
+    }
+
+    /// Adds a dot with real weights, e.g. a non-integer scaling.
+    ///
+    /// It has the output shape and complexity of the equivalent integer dot, but is represented by
+    /// a linear noise operator so the noise uses the real weights magnitudes.
+    /// The shapes are checked as in [`Self::try_add_dot`] and the weights must be finite.
+    pub fn try_add_float_dot(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<FloatWeights>,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        self.try_add_float_dot_with_comment(inputs, weights, "float_dot", location)
+    }
+
+    /// Adds a dot with a manual norm2, panicking on invalid shapes or norm2.
+    pub fn add_dot_with_norm(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<Weights>,
+        manual_norm2: f64,
+        location: Location,
+    ) -> OperatorIndex {
+        self.try_add_dot_with_norm(inputs, weights, manual_norm2, location)
+            .unwrap_or_else(|err| panic!("add_dot_with_norm: {err}"))
+    }
+
+    /// Adds a dot whose noise norm2 is `manual_norm2` instead of the one derived from the weights,
+    /// e.g. when the actual weights are only known to be bounded.
+    ///
+    /// A negative `manual_norm2` falls back to [`Self::try_add_dot`], a NaN or infinite one is
+    /// rejected.
+    pub fn try_add_dot_with_norm(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<Weights>,
+        manual_norm2: f64,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        let weights = weights.into();
+        if manual_norm2 < 0.0 {
+            return self.try_add_dot(inputs, weights, location);
+        }
+        let weights = FloatWeights {
+            shape: weights.shape,
+            values: weights.values.iter().map(|&w| w as f64).collect(),
+        };
+        self.try_add_float_dot_with_norm(inputs, weights, manual_norm2, location)
+    }
+
+    /// Adds a real dot with a manual norm2, panicking on invalid shapes, weights or norm2.
+    pub fn add_float_dot_with_norm(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<FloatWeights>,
+        manual_norm2: f64,
+        location: Location,
+    ) -> OperatorIndex {
+        self.try_add_float_dot_with_norm(inputs, weights, manual_norm2, location)
+            .unwrap_or_else(|err| panic!("add_float_dot_with_norm: {err}"))
+    }
+
+    /// Same as [`Self::try_add_dot_with_norm`] for real weights, falling back to
+    /// [`Self::try_add_float_dot`].
+    pub fn try_add_float_dot_with_norm(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<FloatWeights>,
+        manual_norm2: f64,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        let weights = weights.into();
+        if manual_norm2.is_nan() || manual_norm2.is_infinite() {
+            return Err(DagEditError::NonFiniteNorm2);
+        }
+        if manual_norm2 < 0.0 {
+            return self.try_add_float_dot(inputs, weights, location);
+        }
+        let norm2 = weights.values.iter().map(|w| w * w).sum::<f64>().sqrt();
+        let nb_weights = weights.values.len();
+        // Null weights give no direction to rescale, the norm is spread evenly.
+        let values = if norm2 == 0.0 {
+            vec![manual_norm2 / (nb_weights as f64).sqrt(); nb_weights]
+        } else {
+            weights
+                .values
+                .iter()
+                .map(|w| w * manual_norm2 / norm2)
+                .collect()
+        };
+        let weights = FloatWeights {
+            shape: weights.shape,
+            values,
+        };
+        self.try_add_float_dot_with_comment(inputs, weights, "dot_with_norm", location)
+    }
+
+    fn try_add_float_dot_with_comment(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<FloatWeights>,
+        comment: &str,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        let inputs = inputs.into();
+        let weights = weights.into();
+        self.check_dot(&inputs, &weights.shape)?;
+        if !weights.values.iter().all(|weight| weight.is_finite()) {
+            return Err(DagEditError::NonFiniteWeights);
+        }
+        let nb_inputs = inputs.len() as u64;
+        let input_shape = self.dag.get_operator(inputs[0]).shape.clone();
+        // A single input is multiplied by all the weights, as in the integer dot variance.
+        let norm2 = || vec![weights.values.iter().map(|w| w * w).sum::<f64>().sqrt()];
+        let (out_shape, noise_weights) = match dot_kind(nb_inputs, &input_shape, &weights) {
+            DotKind::Simple | DotKind::CompatibleTensor => {
+                (Shape::number(), weights.values.clone())
+            }
+            DotKind::Tensor => (Shape::number(), norm2()),
+            DotKind::Broadcast { shape } if nb_inputs == 1 => (shape, norm2()),
+            DotKind::Broadcast { shape } => (shape, weights.values.clone()),
+            DotKind::Unsupported => unreachable!("unsupported dots are rejected by check_dot"),
+        };
+        let complexity = LevelledComplexity::ADDITION * nb_inputs * input_shape.flat_size();
+        Ok(self.add_linear_noise(
+            inputs,
+            complexity,
+            noise_weights,
+            out_shape,
+            comment,
+            location,
+        ))
     }
 
     pub fn add_linear_noise(
@@ -260,103 +743,455 @@ impl DagBuilder<'_> {
         self.add_operator(op, location)
     }
 
-    pub fn add_max_noise(
+    /// Adds a linear noise operator whose output shape is the NumPy-style broadcast of the
+    /// input shapes, panicking on incompatible shapes or weights.
+    pub fn add_broadcast_linear_noise(
         &mut self,
         inputs: impl Into<Vec<OperatorIndex>>,
-        out_shape: impl Into<Shape>,
+        complexity: LevelledComplexity,
+        weights: impl Into<Vec<f64>>,
+        comment: impl Into<String>,
         location: Location,
     ) -> OperatorIndex {
-        let inputs = inputs.into();
-        let out_shape = out_shape.into();
-        let op = Operator::MaxNoise { inputs, out_shape };
-        self.add_operator(op, location)
+        self.try_add_broadcast_linear_noise(inputs, complexity, weights, comment, location)
+            .unwrap_or_else(|err| panic!("add_broadcast_linear_noise: {err}"))
     }
 
-    pub fn add_unsafe_cast(
+    /// Adds a linear noise operator whose output shape is the NumPy-style broadcast of the
+    /// input shapes, checking that the shapes are compatible and that there is one weight per
+    /// input.
+    pub fn try_add_broadcast_linear_noise(
         &mut self,
-        input: OperatorIndex,
-        out_precision: Precision,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        complexity: LevelledComplexity,
+        weights: impl Into<Vec<f64>>,
+        comment: impl Into<String>,
         location: Location,
-    ) -> OperatorIndex {
-        let input_precision = self.dag.out_precisions[input.0];
-        if input_precision == out_precision {
-            return input;
+    ) -> Result<OperatorIndex, DagEditError> {
+        let inputs = inputs.into();
+        let weights = weights.into();
+        if weights.len() != inputs.len() {
+            return Err(DagEditError::LinearNoiseWeights {
+                len: weights.len(),
+                expected: inputs.len(),
+            });
         }
-        self.add_operator(
-            Operator::UnsafeCast {
-                input,
-                out_precision,
-            },
-            location,
-        )
+        let out_shape = self.try_broadcast_out_shape(&inputs)?;
+        Ok(self.add_linear_noise(inputs, complexity, weights, out_shape, comment, location))
     }
 
-    pub fn add_change_partition(
+    /// Adds a max noise operator whose output shape is the NumPy-style broadcast of the input
+    /// shapes, panicking on incompatible shapes.
+    pub fn add_broadcast_max_noise(
         &mut self,
-        input: OperatorIndex,
-        src_partition: Option<ExternalPartition>,
-        dst_partition: Option<ExternalPartition>,
+        inputs: impl Into<Vec<OperatorIndex>>,
         location: Location,
     ) -> OperatorIndex {
-        assert!(
-            src_partition.is_some() || dst_partition.is_some(),
-            "change_partition: src or dest partition need to be set"
-        );
-        self.add_operator(
-            Operator::ChangePartition {
-                input,
-                src_partition,
-                dst_partition,
-            },
-            location,
-        )
+        self.try_add_broadcast_max_noise(inputs, location)
+            .unwrap_or_else(|err| panic!("add_broadcast_max_noise: {err}"))
     }
 
-    pub fn add_round_op(
+    /// Adds a max noise operator whose output shape is the NumPy-style broadcast of the input
+    /// shapes, checking that the shapes are compatible.
+    pub fn try_add_broadcast_max_noise(
         &mut self,
-        input: OperatorIndex,
-        rounded_precision: Precision,
+        inputs: impl Into<Vec<OperatorIndex>>,
         location: Location,
-    ) -> OperatorIndex {
-        let in_precision = self.dag.out_precisions[input.0];
-        assert!(rounded_precision <= in_precision);
-        self.add_operator(
-            Operator::Round {
-                input,
-                out_precision: rounded_precision,
-            },
-            location,
-        )
+    ) -> Result<OperatorIndex, DagEditError> {
+        let inputs = inputs.into();
+        let out_shape = self.try_broadcast_out_shape(&inputs)?;
+        Ok(self.add_max_noise(inputs, out_shape, location))
     }
 
-    fn add_shift_left_lsb_to_msb_no_padding(
+    /// Adds `lhs - rhs`, panicking if the operand shapes cannot be broadcast together.
+    pub fn add_sub(
         &mut self,
-        input: OperatorIndex,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
         location: Location,
     ) -> OperatorIndex {
-        // Convert any input to simple 1bit msb replacing the padding
-        // For now encoding is not explicit, so 1 bit content without padding <=> 0 bit content with padding.
-        let in_precision = self.dag.out_precisions[input.0];
-        let shift_factor = Weights::number(1 << (in_precision as i64));
-        let lsb_as_msb = self.add_dot([input], shift_factor, location.clone());
-        self.add_unsafe_cast(lsb_as_msb, 0 as Precision, location)
+        self.try_add_sub(lhs, rhs, location)
+            .unwrap_or_else(|err| panic!("add_sub: {err}"))
     }
 
-    fn add_lut_1bit_no_padding(
+    /// Adds `lhs - rhs`, whose output shape is the NumPy-style broadcast of the operand shapes.
+    pub fn try_add_sub(
         &mut self,
-        input: OperatorIndex,
-        table: FunctionTable,
-        out_precision: Precision,
+        lhs: OperatorIndex,
+        rhs: OperatorIndex,
         location: Location,
-    ) -> OperatorIndex {
-        // For now encoding is not explicit, so 1 bit content without padding <=> 0 bit content with padding.
-        let in_precision = self.dag.out_precisions[input.0];
-        assert!(in_precision == 0);
-        // An add after with a clear constant is skipped here as it doesn't change noise handling.
-        self.add_lut(input, table, out_precision, location)
+    ) -> Result<OperatorIndex, DagEditError> {
+        let out_shape = self.try_broadcast_out_shape(&[lhs, rhs])?;
+        let complexity = LevelledComplexity::ADDITION * out_shape.flat_size();
+        Ok(self.add_linear_noise(
+            [lhs, rhs],
+            complexity,
+            [1.0, -1.0],
+            out_shape,
+            "sub",
+            location,
+        ))
     }
 
-    fn add_shift_right_msb_no_padding_to_lsb(
+    /// Adds `-input`, panicking if the input does not exist.
+    pub fn add_negate(&mut self, input: OperatorIndex, location: Location) -> OperatorIndex {
+        self.try_add_negate(input, location)
+            .unwrap_or_else(|err| panic!("add_negate: {err}"))
+    }
+
+    /// Adds `-input`, checking that the input exists.
+    pub fn try_add_negate(
+        &mut self,
+        input: OperatorIndex,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        let Some(out_shape) = self.dag.out_shapes.get(input.0).cloned() else {
+            return Err(DagEditError::UnknownOperator(input));
+        };
+        let complexity = LevelledComplexity::ADDITION * out_shape.flat_size();
+        Ok(self.add_linear_noise([input], complexity, [-1.0], out_shape, "negate", location))
+    }
+
+    /// Adds a no-op node with the shape and precision of `input`, adding neither noise nor cost,
+    /// panicking if the input does not exist.
+    pub fn add_identity(&mut self, input: OperatorIndex, location: Location) -> OperatorIndex {
+        self.try_add_identity(input, location)
+            .unwrap_or_else(|err| panic!("add_identity: {err}"))
+    }
+
+    /// Adds a no-op node with the shape and precision of `input`, checking that the input exists.
+    pub fn try_add_identity(
+        &mut self,
+        input: OperatorIndex,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        let Some(out_shape) = self.dag.out_shapes.get(input.0).cloned() else {
+            return Err(DagEditError::UnknownOperator(input));
+        };
+        Ok(self.add_linear_noise(
+            [input],
+            LevelledComplexity::ZERO,
+            [1.0],
+            out_shape,
+            "identity",
+            location,
+        ))
+    }
+
+    pub fn add_max_noise(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        out_shape: impl Into<Shape>,
+        location: Location,
+    ) -> OperatorIndex {
+        let inputs = inputs.into();
+        let out_shape = out_shape.into();
+        let op = Operator::MaxNoise { inputs, out_shape };
+        self.add_operator(op, location)
+    }
+
+    /// Adds a max over `inputs`, panicking if the inputs are empty or of another precision.
+    pub fn add_tree_max(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        out_shape: impl Into<Shape>,
+        out_precision: Precision,
+        location: Location,
+    ) -> OperatorIndex {
+        self.try_add_tree_max(inputs, out_shape, out_precision, location)
+            .unwrap_or_else(|err| panic!("add_tree_max: {err}"))
+    }
+
+    /// Adds a max over `inputs`, modelled as the tree of pairwise maxes run on ciphertexts.
+    ///
+    /// Each pairwise max is `rhs + relu(lhs - rhs)`: a subtraction, a PBS and an addition.
+    /// The signed difference needs one more bit than the output, so the relu is a lut on
+    /// `out_precision + 1` bits.
+    /// So the cost and noise grow with the log2(N) depth of the tree, unlike `add_max_noise`.
+    /// A single input is returned as is.
+    pub fn try_add_tree_max(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        out_shape: impl Into<Shape>,
+        out_precision: Precision,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        let mut level: Vec<OperatorIndex> = inputs.into();
+        if level.is_empty() {
+            return Err(DagEditError::EmptyMax);
+        }
+        for &input in &level {
+            match self.dag.out_precisions.get(input.0) {
+                None => return Err(DagEditError::UnknownOperator(input)),
+                Some(&precision) if precision != out_precision => {
+                    return Err(DagEditError::MaxInputPrecision {
+                        input,
+                        expected: out_precision,
+                    })
+                }
+                Some(_) => (),
+            }
+        }
+        let out_shape = out_shape.into();
+        let complexity = LevelledComplexity::ADDITION * out_shape.flat_size();
+        while level.len() > 1 {
+            level = level
+                .chunks(2)
+                .map(|pair| match *pair {
+                    [lhs, rhs] => {
+                        let diff = self.add_linear_noise(
+                            [lhs, rhs],
+                            complexity,
+                            [1.0, -1.0],
+                            out_shape.clone(),
+                            "max_sub",
+                            location.clone(),
+                        );
+                        let signed_diff =
+                            self.add_unsafe_cast(diff, out_precision + 1, location.clone());
+                        let relu = self.add_lut(
+                            signed_diff,
+                            FunctionTable::UNKWOWN,
+                            out_precision,
+                            location.clone(),
+                        );
+                        self.add_linear_noise(
+                            [rhs, relu],
+                            complexity,
+                            [1.0, 1.0],
+                            out_shape.clone(),
+                            "max_select",
+                            location.clone(),
+                        )
+                    }
+                    // odd one out, goes to the next level
+                    [single] => single,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+        Ok(level[0])
+    }
+
+    /// Adds a tree-based PBS, evaluating a table too large for a single PBS, panicking if the
+    /// table size does not match the input precision.
+    pub fn add_tree_pbs(
+        &mut self,
+        input: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+        decomposition_base: Precision,
+        location: Location,
+    ) -> OperatorIndex {
+        self.try_add_tree_pbs(input, table, out_precision, decomposition_base, location)
+            .unwrap_or_else(|err| panic!("add_tree_pbs: {err}"))
+    }
+
+    /// Adds a tree-based PBS, checking the table as [`Self::try_add_lut`].
+    ///
+    /// The input is split in digits of `decomposition_base` bits, extracted from the lowest one as
+    /// in [`Self::add_expanded_round`]. The first level evaluates all the clear sub-tables on the
+    /// lowest digit, then each level packs the previous outputs in encrypted sub-tables evaluated
+    /// on the next digit, until a single output remains.
+    /// The rotations of a level are modelled as one lut on a tensor with one row per sub-table,
+    /// and the packing keyswitches as levelled operations.
+    /// An input with at most `decomposition_base` bits is a single lut.
+    pub fn try_add_tree_pbs(
+        &mut self,
+        input: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+        decomposition_base: Precision,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        if decomposition_base == 0 {
+            return Err(DagEditError::NullDecompositionBase);
+        }
+        self.check_lut_table(input, &table)?;
+        let in_precision = self.dag.out_precisions[input.0];
+        if in_precision <= decomposition_base {
+            return self.try_add_lut(input, table, out_precision, location);
+        }
+        let in_shape = self.dag.out_shapes[input.0].clone();
+        let level_shape = |nb_sub_tables: u64| {
+            if nb_sub_tables == 1 {
+                return in_shape.clone();
+            }
+            let mut dimensions_size = vec![nb_sub_tables];
+            dimensions_size.extend(&in_shape.dimensions_size);
+            Shape { dimensions_size }
+        };
+        let digit_complexity = LevelledComplexity::ADDITION * in_shape.flat_size();
+        let mut digits = vec![];
+        let mut high = input;
+        let mut high_precision = in_precision;
+        while high_precision > decomposition_base {
+            let rest = high;
+            high_precision -= decomposition_base;
+            high = self.add_expanded_round(rest, high_precision, location.clone());
+            let digit = self.add_linear_noise(
+                [rest, high],
+                digit_complexity,
+                [1.0, -1.0],
+                in_shape.clone(),
+                "tree_pbs_digit",
+                location.clone(),
+            );
+            digits.push(self.add_unsafe_cast(digit, decomposition_base, location.clone()));
+        }
+        digits.push(high);
+
+        let mut remaining_precision = in_precision;
+        let mut outputs: Option<OperatorIndex> = None;
+        for digit in digits {
+            remaining_precision -= self.dag.out_precisions[digit.0];
+            let shape = level_shape(1 << remaining_precision);
+            let spread = self.add_linear_noise(
+                [digit],
+                LevelledComplexity::ZERO,
+                [1.0],
+                shape.clone(),
+                "tree_pbs_spread",
+                location.clone(),
+            );
+            let rotations = self.add_lut(
+                spread,
+                FunctionTable::UNKWOWN,
+                out_precision,
+                location.clone(),
+            );
+            outputs = Some(match outputs {
+                // the first level sub-tables are in clear
+                None => rotations,
+                Some(previous) => {
+                    let previous_size = self.dag.out_shapes[previous.0].flat_size();
+                    let sub_tables = self.add_linear_noise(
+                        [previous],
+                        LevelledComplexity::ADDITION * previous_size,
+                        [1.0],
+                        shape.clone(),
+                        "tree_pbs_packing",
+                        location.clone(),
+                    );
+                    // the rotation selects one encrypted entry, keeping its noise
+                    self.add_linear_noise(
+                        [rotations, sub_tables],
+                        LevelledComplexity::ADDITION * shape.flat_size(),
+                        [1.0, 1.0],
+                        shape,
+                        "tree_pbs_select",
+                        location.clone(),
+                    )
+                }
+            });
+        }
+        Ok(outputs.unwrap())
+    }
+
+    pub fn add_unsafe_cast(
+        &mut self,
+        input: OperatorIndex,
+        out_precision: Precision,
+        location: Location,
+    ) -> OperatorIndex {
+        let input_precision = self.dag.out_precisions[input.0];
+        if input_precision == out_precision {
+            return input;
+        }
+        self.add_operator(
+            Operator::UnsafeCast {
+                input,
+                out_precision,
+            },
+            location,
+        )
+    }
+
+    pub fn add_change_partition(
+        &mut self,
+        input: OperatorIndex,
+        src_partition: Option<ExternalPartition>,
+        dst_partition: Option<ExternalPartition>,
+        location: Location,
+    ) -> OperatorIndex {
+        assert!(
+            src_partition.is_some() || dst_partition.is_some(),
+            "change_partition: src or dest partition need to be set"
+        );
+        self.add_operator(
+            Operator::ChangePartition {
+                input,
+                src_partition,
+                dst_partition,
+            },
+            location,
+        )
+    }
+
+    /// Adds a standalone keyswitch of `input` to `dst_partition`, not followed by a bootstrap.
+    ///
+    /// Unlike [`Self::add_change_partition`], only this operator is moved to the destination
+    /// partition, and it accounts for the conversion keyswitch noise and complexity.
+    pub fn add_keyswitch(
+        &mut self,
+        input: OperatorIndex,
+        dst_partition: ExternalPartition,
+        location: Location,
+    ) -> OperatorIndex {
+        self.add_operator(
+            Operator::Keyswitch {
+                input,
+                dst_partition,
+            },
+            location,
+        )
+    }
+
+    pub fn add_round_op(
+        &mut self,
+        input: OperatorIndex,
+        rounded_precision: Precision,
+        location: Location,
+    ) -> OperatorIndex {
+        let in_precision = self.dag.out_precisions[input.0];
+        assert!(rounded_precision <= in_precision);
+        self.add_operator(
+            Operator::Round {
+                input,
+                out_precision: rounded_precision,
+            },
+            location,
+        )
+    }
+
+    fn add_shift_left_lsb_to_msb_no_padding(
+        &mut self,
+        input: OperatorIndex,
+        location: Location,
+    ) -> OperatorIndex {
+        // Convert any input to simple 1bit msb replacing the padding
+        // For now encoding is not explicit, so 1 bit content without padding <=> 0 bit content with padding.
+        let in_precision = self.dag.out_precisions[input.0];
+        let shift_factor = Weights::number(1 << (in_precision as i64));
+        let lsb_as_msb = self.add_dot([input], shift_factor, location.clone());
+        self.add_unsafe_cast(lsb_as_msb, 0 as Precision, location)
+    }
+
+    fn add_lut_1bit_no_padding(
+        &mut self,
+        input: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+        location: Location,
+    ) -> OperatorIndex {
+        // For now encoding is not explicit, so 1 bit content without padding <=> 0 bit content with padding.
+        let in_precision = self.dag.out_precisions[input.0];
+        assert!(in_precision == 0);
+        // An add after with a clear constant is skipped here as it doesn't change noise handling.
+        self.add_lut(input, table, out_precision, location)
+    }
+
+    fn add_shift_right_msb_no_padding_to_lsb(
         &mut self,
         input: OperatorIndex,
         out_precision: Precision,
@@ -433,6 +1268,7 @@ impl DagBuilder<'_> {
         self.add_lut(rounded, table, out_precision, location)
     }
 
+    /// Adds a rounded lut, panicking on an invalid rounded precision or table size.
     pub fn add_rounded_lut(
         &mut self,
         input: OperatorIndex,
@@ -441,8 +1277,39 @@ impl DagBuilder<'_> {
         out_precision: Precision,
         location: Location,
     ) -> OperatorIndex {
-        let rounded = self.add_round_op(input, rounded_precision, location.clone());
-        self.add_lut(rounded, table, out_precision, location)
+        self.try_add_rounded_lut(input, table, rounded_precision, out_precision, location)
+            .unwrap_or_else(|err| panic!("add_rounded_lut: {err}"))
+    }
+
+    /// Adds a lut on `input` rounded to `rounded_precision`, the rounding being fused in the
+    /// bootstrap of the lut.
+    ///
+    /// Unlike [`Self::add_expanded_rounded_lut`], no intermediate rounded ciphertext is computed:
+    /// the lut input only needs to be correct on its `rounded_precision` high bits. The table has
+    /// one entry per rounded value, an empty table standing for an unknown function.
+    pub fn try_add_rounded_lut(
+        &mut self,
+        input: OperatorIndex,
+        table: FunctionTable,
+        rounded_precision: Precision,
+        out_precision: Precision,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        self.dag
+            .check_rounded_lut_table(input, &table, Some(rounded_precision))?;
+        // Rounding to the input precision is a regular lut
+        let rounded_precision =
+            (rounded_precision < self.dag.out_precisions[input.0]).then_some(rounded_precision);
+        Ok(self.add_operator(
+            Operator::Lut {
+                input,
+                table,
+                out_precision,
+                rounded_precision,
+                shared_rotation: false,
+            },
+            location,
+        ))
     }
 
     /// Marks an operator as being an output of the circuit.
@@ -458,11 +1325,100 @@ impl DagBuilder<'_> {
         self.dag.output_state[operator.0].transition_tag();
     }
 
+    /// Attaches a free-form comment to an operator, e.g. its source information.
+    pub fn set_operator_comment(&mut self, operator: OperatorIndex, comment: impl Into<String>) {
+        assert!(operator.0 < self.dag.len());
+        debug_assert!(self.dag.circuit_tags[operator.0] == self.circuit);
+        self.dag.comments[operator.0] = comment.into();
+    }
+
+    /// Marks several operators as being outputs of the circuit.
+    ///
+    /// Every index is checked before any operator is tagged, so an invalid index leaves the dag
+    /// untouched.
+    pub fn tag_operators_as_output(
+        &mut self,
+        operators: &[OperatorIndex],
+    ) -> Result<(), DagEditError> {
+        if let Some(&invalid) = operators.iter().find(|op| op.0 >= self.dag.len()) {
+            return Err(DagEditError::UnknownOperator(invalid));
+        }
+        for &operator in operators {
+            self.tag_operator_as_output(operator);
+        }
+        Ok(())
+    }
+
+    /// Forces a bootstrap on the output of an operator, so that the following operators see a
+    /// freshly refreshed noise.
+    ///
+    /// The bootstrap is an identity lut of the same precision added at the end of the dag, whose
+    /// index is returned, so no existing index changes. The operator must not have users yet: force
+    /// the bootstrap right after adding the operator and build its users on the bootstrap. The
+    /// output tag and compositions of the operator move to the bootstrap.
+    /// A lut is already a bootstrap and is returned unchanged.
+    pub fn force_bootstrap(
+        &mut self,
+        operator: OperatorIndex,
+        location: Location,
+    ) -> Result<OperatorIndex, DagEditError> {
+        let Some(op) = self.dag.operators.get(operator.0) else {
+            return Err(DagEditError::UnknownOperator(operator));
+        };
+        if matches!(op, Operator::Lut { .. }) {
+            return Ok(operator);
+        }
+        if self
+            .dag
+            .operators
+            .iter()
+            .any(|op| op.get_inputs_iter().any(|input| *input == operator))
+        {
+            return Err(DagEditError::HasDependents(operator));
+        }
+        let out_precision = self.dag.out_precisions[operator.0];
+        let output_state = self.dag.output_state[operator.0];
+        let bootstrap = self.add_operator(
+            Operator::Lut {
+                input: operator,
+                table: FunctionTable::identity(out_precision),
+                out_precision,
+                rounded_precision: None,
+                shared_rotation: false,
+            },
+            location,
+        );
+        self.dag.output_state[operator.0] = OutputState::Used;
+        self.dag.output_state[bootstrap.0] = output_state;
+        for froms in self.dag.composition.0.values_mut() {
+            for from in froms.iter_mut().filter(|from| **from == operator) {
+                *from = bootstrap;
+            }
+        }
+        Ok(bootstrap)
+    }
+
     pub fn get_circuit(&self) -> DagCircuit<'_> {
         self.dag.get_circuit(&self.circuit)
     }
 
-    fn infer_out_shape(&self, op: &Operator) -> Shape {
+    /// Returns the NumPy-style broadcast of the shapes of `inputs`.
+    fn try_broadcast_out_shape(&self, inputs: &[OperatorIndex]) -> Result<Shape, DagEditError> {
+        inputs
+            .iter()
+            .try_fold(Shape::number(), |broadcast, &input| {
+                let shape = self
+                    .dag
+                    .out_shapes
+                    .get(input.0)
+                    .ok_or(DagEditError::UnknownOperator(input))?;
+                broadcast
+                    .broadcast(shape)
+                    .ok_or(DagEditError::BroadcastShape(input))
+            })
+    }
+
+    pub(crate) fn infer_out_shape(&self, op: &Operator) -> Shape {
         match op {
             Operator::Input { out_shape, .. }
             | Operator::LinearNoise { out_shape, .. }
@@ -471,7 +1427,8 @@ impl DagBuilder<'_> {
             Operator::Lut { input, .. }
             | Operator::UnsafeCast { input, .. }
             | Operator::Round { input, .. }
-            | Operator::ChangePartition { input, .. } => self.dag.out_shapes[input.0].clone(),
+            | Operator::ChangePartition { input, .. }
+            | Operator::Keyswitch { input, .. } => self.dag.out_shapes[input.0].clone(),
             Operator::Dot {
                 kind: DotKind::Simple | DotKind::Tensor | DotKind::CompatibleTensor,
                 ..
@@ -482,22 +1439,12 @@ impl DagBuilder<'_> {
             } => shape.clone(),
             Operator::Dot {
                 kind: DotKind::Unsupported,
-                weights,
-                inputs,
-            } => {
-                let weights_shape = &weights.shape;
-                let input_shape = self.dag.get_operator(inputs[0]).shape;
-                println!();
-                println!();
-                println!("Error diagnostic on dot operation:");
-                println!("Incompatible operands: <{input_shape:?}> DOT <{weights_shape:?}>");
-                println!();
-                panic!("Unsupported or invalid dot operation")
-            }
+                ..
+            } => unreachable!("unsupported dots are rejected by check_dot"),
         }
     }
 
-    fn infer_out_precision(&self, op: &Operator) -> Precision {
+    pub(crate) fn infer_out_precision(&self, op: &Operator) -> Precision {
         match op {
             Operator::Input { out_precision, .. }
             | Operator::ZeroNoise { out_precision, .. }
@@ -507,7 +1454,9 @@ impl DagBuilder<'_> {
             Operator::Dot { inputs, .. }
             | Operator::LinearNoise { inputs, .. }
             | Operator::MaxNoise { inputs, .. } => self.dag.out_precisions[inputs[0].0],
-            Operator::ChangePartition { input, .. } => self.dag.out_precisions[input.0],
+            Operator::ChangePartition { input, .. } | Operator::Keyswitch { input, .. } => {
+                self.dag.out_precisions[input.0]
+            }
         }
     }
 }
@@ -524,6 +1473,14 @@ impl CompositionRules {
             .or_insert_with(|| [from].into());
     }
 
+    pub(crate) fn remove(&mut self, index: OperatorIndex) {
+        let _ = self.0.remove(&index);
+        self.0.retain(|_, froms| {
+            froms.retain(|from| *from != index);
+            !froms.is_empty()
+        });
+    }
+
     pub(crate) fn update_index(&mut self, old_to_new_map: &[usize]) {
         let mut old_map = HashMap::with_capacity(self.0.capacity());
         std::mem::swap(&mut old_map, &mut self.0);
@@ -560,7 +1517,8 @@ impl IntoIterator for CompositionRules {
 ///
 /// For ease of use in tests, it is also possible to add operators on an anonymous circuit (`_`)
 /// directly on a [`Dag`] object itself, using the `Dag::add_*` methods.
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(into = "SerializedDag", try_from = "SerializedDag")]
 #[must_use]
 pub struct Dag {
     pub(crate) operators: Vec<Operator>,
@@ -574,6 +1532,8 @@ pub struct Dag {
     pub(crate) circuit_tags: Vec<String>,
     // Collect the operator locations
     pub(crate) locations: Vec<Location>,
+    // Collect the operator comments, empty if none
+    pub(crate) comments: Vec<String>,
     // Composition rules
     pub(crate) composition: CompositionRules,
 }
@@ -603,12 +1563,13 @@ impl Dag {
             output_state: vec![],
             circuit_tags: vec![],
             locations: vec![],
+            comments: vec![],
             composition: CompositionRules::default(),
         }
     }
 
     /// Returns a builder for the circuit named `circuit`.
-    pub fn builder<A: AsRef<str>>(&mut self, circuit: A) -> DagBuilder {
+    pub fn builder<A: AsRef<str>>(&mut self, circuit: A) -> DagBuilder<'_> {
         DagBuilder {
             dag: self,
             circuit: circuit.as_ref().into(),
@@ -624,6 +1585,34 @@ impl Dag {
             .add_input(out_precision, out_shape, Location::Unknown)
     }
 
+    pub fn add_input_with_variance(
+        &mut self,
+        out_precision: Precision,
+        out_shape: impl Into<Shape>,
+        input_variance: f64,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_input_with_variance(
+            out_precision,
+            out_shape,
+            input_variance,
+            Location::Unknown,
+        )
+    }
+
+    pub fn add_input_in_partition(
+        &mut self,
+        out_precision: Precision,
+        out_shape: impl Into<Shape>,
+        partition: ExternalPartition,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_input_in_partition(
+            out_precision,
+            out_shape,
+            partition,
+            Location::Unknown,
+        )
+    }
+
     pub fn add_zero_noise(
         &mut self,
         out_precision: Precision,
@@ -643,12 +1632,40 @@ impl Dag {
             .add_lut(input, table, out_precision, Location::Unknown)
     }
 
-    pub fn add_change_partition(
+    pub fn force_bootstrap(
+        &mut self,
+        operator: OperatorIndex,
+    ) -> Result<OperatorIndex, DagEditError> {
+        self.builder(DEFAULT_CIRCUIT)
+            .force_bootstrap(operator, Location::Unknown)
+    }
+
+    pub fn add_signed_lut(
         &mut self,
         input: OperatorIndex,
-        src_partition: Option<ExternalPartition>,
-        dst_partition: Option<ExternalPartition>,
-    ) -> OperatorIndex {
+        table: FunctionTable,
+        out_precision: Precision,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_signed_lut(input, table, out_precision, Location::Unknown)
+    }
+
+    pub fn add_multi_lut(
+        &mut self,
+        input: OperatorIndex,
+        tables: &[FunctionTable],
+        out_precision: Precision,
+    ) -> Result<Vec<OperatorIndex>, DagEditError> {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_multi_lut(input, tables, out_precision, Location::Unknown)
+    }
+
+    pub fn add_change_partition(
+        &mut self,
+        input: OperatorIndex,
+        src_partition: Option<ExternalPartition>,
+        dst_partition: Option<ExternalPartition>,
+    ) -> OperatorIndex {
         self.builder(DEFAULT_CIRCUIT).add_change_partition(
             input,
             src_partition,
@@ -657,6 +1674,15 @@ impl Dag {
         )
     }
 
+    pub fn add_keyswitch(
+        &mut self,
+        input: OperatorIndex,
+        dst_partition: ExternalPartition,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_keyswitch(input, dst_partition, Location::Unknown)
+    }
+
     pub fn add_dot(
         &mut self,
         inputs: impl Into<Vec<OperatorIndex>>,
@@ -666,6 +1692,29 @@ impl Dag {
             .add_dot(inputs, weights, Location::Unknown)
     }
 
+    pub fn add_float_dot(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<FloatWeights>,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_float_dot(inputs, weights, Location::Unknown)
+    }
+
+    pub fn add_dot_with_norm(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        weights: impl Into<Weights>,
+        manual_norm2: f64,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_dot_with_norm(
+            inputs,
+            weights,
+            manual_norm2,
+            Location::Unknown,
+        )
+    }
+
     pub fn add_linear_noise(
         &mut self,
         inputs: impl Into<Vec<OperatorIndex>>,
@@ -684,6 +1733,45 @@ impl Dag {
         )
     }
 
+    pub fn add_broadcast_linear_noise(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        complexity: LevelledComplexity,
+        weights: impl Into<Vec<f64>>,
+        comment: impl Into<String>,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_broadcast_linear_noise(
+            inputs,
+            complexity,
+            weights,
+            comment,
+            Location::Unknown,
+        )
+    }
+
+    pub fn add_broadcast_max_noise(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_broadcast_max_noise(inputs, Location::Unknown)
+    }
+
+    pub fn add_sub(&mut self, lhs: OperatorIndex, rhs: OperatorIndex) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_sub(lhs, rhs, Location::Unknown)
+    }
+
+    pub fn add_negate(&mut self, input: OperatorIndex) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_negate(input, Location::Unknown)
+    }
+
+    pub fn add_identity(&mut self, input: OperatorIndex) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT)
+            .add_identity(input, Location::Unknown)
+    }
+
     pub fn add_max_noise(
         &mut self,
         inputs: impl Into<Vec<OperatorIndex>>,
@@ -693,6 +1781,36 @@ impl Dag {
             .add_max_noise(inputs, out_shape, Location::Unknown)
     }
 
+    pub fn add_tree_max(
+        &mut self,
+        inputs: impl Into<Vec<OperatorIndex>>,
+        out_shape: impl Into<Shape>,
+        out_precision: Precision,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_tree_max(
+            inputs,
+            out_shape,
+            out_precision,
+            Location::Unknown,
+        )
+    }
+
+    pub fn add_tree_pbs(
+        &mut self,
+        input: OperatorIndex,
+        table: FunctionTable,
+        out_precision: Precision,
+        decomposition_base: Precision,
+    ) -> OperatorIndex {
+        self.builder(DEFAULT_CIRCUIT).add_tree_pbs(
+            input,
+            table,
+            out_precision,
+            decomposition_base,
+            Location::Unknown,
+        )
+    }
+
     pub fn add_unsafe_cast(
         &mut self,
         input: OperatorIndex,
@@ -788,7 +1906,7 @@ impl Dag {
         rounded_precision: Precision,
         out_precision: Precision,
     ) -> OperatorIndex {
-        self.builder(DEFAULT_CIRCUIT).add_expanded_rounded_lut(
+        self.builder(DEFAULT_CIRCUIT).add_rounded_lut(
             input,
             table,
             rounded_precision,
@@ -804,6 +1922,25 @@ impl Dag {
         self.composition.add(from, to);
     }
 
+    /// Adds a composition rule, checking that `from` is an output and `to` is an input.
+    pub fn try_add_composition(
+        &mut self,
+        from: OperatorIndex,
+        to: OperatorIndex,
+    ) -> Result<(), DagEditError> {
+        if let Some(&invalid) = [from, to].iter().find(|op| op.0 >= self.len()) {
+            return Err(DagEditError::UnknownOperator(invalid));
+        }
+        if !self.get_operator(from).is_output() {
+            return Err(DagEditError::NotAnOutput(from));
+        }
+        if !self.get_operator(to).is_input() {
+            return Err(DagEditError::NotAnInput(to));
+        }
+        self.add_composition(from, to);
+        Ok(())
+    }
+
     /// Adds a composition rule between every elements of from and every elements of to.
     pub fn add_compositions<A: AsRef<[OperatorIndex]>, B: AsRef<[OperatorIndex]>>(
         &mut self,
@@ -822,6 +1959,62 @@ impl Dag {
         !self.composition.0.is_empty()
     }
 
+    /// Returns the composition rules whose output and input precisions differ.
+    pub fn check_compositions(&self) -> Vec<CompositionError> {
+        let mut errors: Vec<CompositionError> = self
+            .composition
+            .0
+            .iter()
+            .flat_map(|(to, froms)| froms.iter().map(move |from| (*from, *to)))
+            .filter(|(from, to)| self.out_precisions[from.0] != self.out_precisions[to.0])
+            .map(|(from, to)| CompositionError {
+                from_circuit: self.circuit_tags[from.0].clone(),
+                from,
+                from_precision: self.out_precisions[from.0],
+                to_circuit: self.circuit_tags[to.0].clone(),
+                to,
+                to_precision: self.out_precisions[to.0],
+            })
+            .collect();
+        errors.sort_by_key(|error| (error.to.0, error.from.0));
+        errors
+    }
+
+    /// Returns a hash of everything the optimization depends on, i.e. all but the locations and
+    /// comments.
+    ///
+    /// The hash is only meant to be compared within a single process.
+    pub fn structural_hash(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash_structure(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Feeds everything the optimization depends on to `hasher`, see [`Self::structural_hash`].
+    pub fn hash_structure<H: Hasher>(&self, hasher: &mut H) {
+        for operator in &self.operators {
+            operator.hash_structure(hasher);
+        }
+        for shape in &self.out_shapes {
+            shape.dimensions_size.hash(hasher);
+        }
+        self.out_precisions.hash(hasher);
+        self.output_state.hash(hasher);
+        self.circuit_tags.hash(hasher);
+        let mut composition: Vec<_> = self
+            .composition
+            .0
+            .iter()
+            .map(|(to, froms)| {
+                let mut froms = froms.clone();
+                froms.sort_by_key(|from| from.0);
+                (*to, froms)
+            })
+            .collect();
+        composition.sort_by_key(|(to, _)| to.0);
+        composition.hash(hasher);
+    }
+
     /// Returns an iterator over the operator indices.
     pub fn get_indices_iter(&self) -> impl Iterator<Item = OperatorIndex> {
         (0..self.len()).map(OperatorIndex)
@@ -843,7 +2036,7 @@ impl Dag {
     /// # Note:
     ///
     /// Panics if no circuit with the given name exist in the dag.
-    pub fn get_circuit<A: AsRef<str>>(&self, circuit: A) -> DagCircuit {
+    pub fn get_circuit<A: AsRef<str>>(&self, circuit: A) -> DagCircuit<'_> {
         let circuit = circuit.as_ref().to_string();
         assert!(self.circuit_tags.contains(&circuit));
         let ids = self
@@ -895,9 +2088,15 @@ impl Dag {
             output_state: self.output_state.get(id.0).unwrap(),
             circuit_tag: self.circuit_tags.get(id.0).unwrap(),
             location: self.locations.get(id.0).unwrap(),
+            comment: self.comments.get(id.0).unwrap(),
         }
     }
 
+    /// Try to return an operator from its index. Returns `None` if the operator index is invalid.
+    pub fn try_get_operator(&self, id: OperatorIndex) -> Option<DagOperator<'_>> {
+        (id.0 < self.len()).then(|| self.get_operator(id))
+    }
+
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.operators.len()
@@ -915,16 +2114,296 @@ impl Dag {
         self.output_state[operator.0].transition_tag();
     }
 
+    /// Copies the circuits and operators of `other` at the end of this dag.
+    ///
+    /// Returns, for each operator of `other`, its index in this dag. Fails without modifying the
+    /// dag if a circuit of `other` has the same name as a circuit of this dag.
+    pub fn merge(&mut self, other: &Self) -> Result<Vec<OperatorIndex>, DagMergeError> {
+        let names: HashSet<&String> = self.circuit_tags.iter().collect();
+        if let Some(name) = other.circuit_tags.iter().find(|name| names.contains(name)) {
+            return Err(DagMergeError::CircuitNameCollision(name.clone()));
+        }
+        let offset = self.len();
+        let old_index_to_new: Vec<usize> = (offset..offset + other.len()).collect();
+        self.operators.extend(
+            other
+                .operators
+                .iter()
+                .map(|op| reindex_op_inputs(op, &old_index_to_new)),
+        );
+        self.out_shapes.extend_from_slice(&other.out_shapes);
+        self.out_precisions.extend_from_slice(&other.out_precisions);
+        self.output_state.extend_from_slice(&other.output_state);
+        self.circuit_tags.extend_from_slice(&other.circuit_tags);
+        self.locations.extend_from_slice(&other.locations);
+        self.comments.extend_from_slice(&other.comments);
+        let mut composition = other.composition.clone();
+        composition.update_index(&old_index_to_new);
+        for (to, froms) in composition.0 {
+            for from in froms {
+                self.composition.add(from, to);
+            }
+        }
+        Ok(old_index_to_new.into_iter().map(OperatorIndex).collect())
+    }
+
+    /// Removes an operator from the dag.
+    ///
+    /// Operators added after the removed one are shifted down by one index. Fails if the operator
+    /// is still used as input by another operator.
+    pub fn remove_operator(&mut self, index: OperatorIndex) -> Result<(), DagEditError> {
+        if index.0 >= self.len() {
+            return Err(DagEditError::UnknownOperator(index));
+        }
+        if self
+            .operators
+            .iter()
+            .any(|op| op.get_inputs_iter().any(|input| *input == index))
+        {
+            return Err(DagEditError::HasDependents(index));
+        }
+        let removed = self.operators.remove(index.0);
+        let _ = self.out_shapes.remove(index.0);
+        let _ = self.out_precisions.remove(index.0);
+        let _ = self.output_state.remove(index.0);
+        let _ = self.circuit_tags.remove(index.0);
+        let _ = self.locations.remove(index.0);
+        let _ = self.comments.remove(index.0);
+        // Inputs are always added before their consumers, so their indices are unchanged.
+        for input in removed.get_inputs_iter() {
+            let still_used = self
+                .operators
+                .iter()
+                .any(|op| op.get_inputs_iter().any(|id| id == input));
+            if !still_used && self.output_state[input.0] == OutputState::Used {
+                self.output_state[input.0] = OutputState::Unused;
+            }
+        }
+        let old_index_to_new: Vec<usize> = (0..=self.len())
+            .map(|i| if i > index.0 { i - 1 } else { i })
+            .collect();
+        for op in &mut self.operators {
+            *op = reindex_op_inputs(op, &old_index_to_new);
+        }
+        self.composition.remove(index);
+        self.composition.update_index(&old_index_to_new);
+        Ok(())
+    }
+
+    fn check_lut_table(
+        &self,
+        input: OperatorIndex,
+        table: &FunctionTable,
+    ) -> Result<(), DagEditError> {
+        self.check_rounded_lut_table(input, table, None)
+    }
+
+    // The table has one entry per value of the rounded precision, if any, else of the input one
+    pub(crate) fn check_rounded_lut_table(
+        &self,
+        input: OperatorIndex,
+        table: &FunctionTable,
+        rounded_precision: Option<Precision>,
+    ) -> Result<(), DagEditError> {
+        let Some(&in_precision) = self.out_precisions.get(input.0) else {
+            return Err(DagEditError::UnknownOperator(input));
+        };
+        let precision = match rounded_precision {
+            Some(rounded_precision) if rounded_precision > in_precision => {
+                return Err(DagEditError::RoundedPrecision {
+                    input,
+                    rounded_precision,
+                })
+            }
+            Some(rounded_precision) => rounded_precision,
+            None => in_precision,
+        };
+        // No table is large enough for a precision beyond the usize bits
+        let expected = 1_usize.checked_shl(precision.into()).unwrap_or(usize::MAX);
+        if !table.values.is_empty() && table.values.len() != expected {
+            return Err(DagEditError::LutTableSize {
+                input,
+                len: table.values.len(),
+                expected,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_dot(
+        &self,
+        inputs: &[OperatorIndex],
+        weights_shape: &Shape,
+    ) -> Result<(), DagEditError> {
+        if let Some(&unknown) = inputs.iter().find(|input| input.0 >= self.len()) {
+            return Err(DagEditError::UnknownOperator(unknown));
+        }
+        let Some(&first) = inputs.first() else {
+            return Err(DagEditError::EmptyDot);
+        };
+        let first_shape = &self.out_shapes[first.0];
+        if let Some(&input) = inputs
+            .iter()
+            .find(|input| self.out_shapes[input.0] != *first_shape)
+        {
+            return Err(DagEditError::DotInputShape { input, first });
+        }
+        let weights = ClearTensor::<i64> {
+            shape: weights_shape.clone(),
+            values: vec![],
+        };
+        if dot_kind(inputs.len() as u64, first_shape, &weights) == DotKind::Unsupported {
+            return Err(DagEditError::DotWeightsShape(first));
+        }
+        Ok(())
+    }
+
+    /// Replaces the table of a lookup table operator.
+    pub fn replace_lut_table(
+        &mut self,
+        index: OperatorIndex,
+        new_table: FunctionTable,
+    ) -> Result<(), DagEditError> {
+        let (input, rounded_precision) = match self.operators.get(index.0) {
+            Some(Operator::Lut {
+                input,
+                rounded_precision,
+                ..
+            }) => (*input, *rounded_precision),
+            Some(_) => return Err(DagEditError::NotALut(index)),
+            None => return Err(DagEditError::UnknownOperator(index)),
+        };
+        self.check_rounded_lut_table(input, &new_table, rounded_precision)?;
+        if let Operator::Lut { table, .. } = &mut self.operators[index.0] {
+            *table = new_table;
+        }
+        Ok(())
+    }
+
+    /// Returns the precision a lut reads from its input, i.e. its rounded precision if the rounding
+    /// is fused in the lut.
+    pub(crate) fn lut_in_precision(&self, lut: OperatorIndex) -> Precision {
+        match self.operators[lut.0] {
+            Operator::Lut {
+                rounded_precision: Some(rounded_precision),
+                ..
+            } => rounded_precision,
+            Operator::Lut { input, .. } => self.out_precisions[input.0],
+            _ => unreachable!("%{lut} is not a lut"),
+        }
+    }
+
+    /// Returns the lut evaluating the blind rotation of `lut`, i.e. `lut` itself unless it shares
+    /// the rotation of a previous lut of the same input and precision.
+    pub(crate) fn rotation_lut(&self, lut: OperatorIndex) -> OperatorIndex {
+        let Operator::Lut {
+            input,
+            shared_rotation: true,
+            ..
+        } = self.operators[lut.0]
+        else {
+            return lut;
+        };
+        let out_precision = self.out_precisions[lut.0];
+        (0..lut.0)
+            .rev()
+            .map(OperatorIndex)
+            .find(|previous| {
+                self.out_precisions[previous.0] == out_precision
+                    && matches!(
+                        self.operators[previous.0],
+                        Operator::Lut {
+                            input: previous_input,
+                            shared_rotation: false,
+                            ..
+                        } if previous_input == input
+                    )
+            })
+            .unwrap_or(lut)
+    }
+
+    /// Returns the number of operators of each kind, in [`Operator::KIND_NAMES`] order.
+    pub fn operator_kind_histogram(&self) -> Vec<(&'static str, usize)> {
+        Operator::KIND_NAMES
+            .iter()
+            .map(|&name| {
+                let count = self
+                    .operators
+                    .iter()
+                    .filter(|op| op.kind_name() == name)
+                    .count();
+                (name, count)
+            })
+            .collect()
+    }
+
     /// Returns the number of circuits in the dag.
     pub fn get_circuit_count(&self) -> usize {
         self.get_circuits_iter().count()
     }
+
+    /// Returns the names of the circuits in the dag, in order of first appearance.
+    pub fn get_circuit_names(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.circuit_tags
+            .iter()
+            .filter(|name| seen.insert(name.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns a graphviz dot graph of the dag, with one cluster per circuit.
+    ///
+    /// Nodes are labelled with the operator kind, precision and shape. Inputs and outputs are
+    /// drawn as boxes, composition rules as dashed edges.
+    pub fn to_dot(&self) -> String {
+        let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"");
+        let mut dot = String::from("digraph dag {\n");
+        for (i, circuit) in self.get_circuit_names().iter().enumerate() {
+            let _ = writeln!(dot, "  subgraph cluster_{i} {{");
+            let _ = writeln!(dot, "    label=\"{}\";", escape(circuit));
+            for op in self.get_circuit(circuit).get_operators_iter() {
+                let index = op.id.0;
+                let node_shape = if op.is_input() || op.is_output() {
+                    "box"
+                } else {
+                    "ellipse"
+                };
+                let _ = writeln!(
+                    dot,
+                    "    {index} [label=\"%{index} = {}\\nprecision: {}\\nshape: {:?}\", shape={node_shape}];",
+                    op.operator.kind_name(),
+                    op.precision,
+                    op.shape.dimensions_size,
+                );
+            }
+            dot.push_str("  }\n");
+        }
+        for op in self.get_operators_iter() {
+            for input in op.operator.get_inputs_iter() {
+                let _ = writeln!(dot, "  {} -> {};", input.0, op.id.0);
+            }
+        }
+        let mut compositions: Vec<_> = self
+            .composition
+            .0
+            .iter()
+            .flat_map(|(to, froms)| froms.iter().map(move |from| (from.0, to.0)))
+            .collect();
+        compositions.sort_unstable();
+        for (from, to) in compositions {
+            let _ = writeln!(dot, "  {from} -> {to} [style=dashed];");
+        }
+        dot.push_str("}\n");
+        dot
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         optimization::dag::multi_parameters::optimize::MacroParameters, parameters::GlweParameters,
+        utils::viz::Viz,
     };
 
     use super::*;
@@ -1027,10 +2506,14 @@ mod tests {
                 Operator::Input {
                     out_precision: 1,
                     out_shape: Shape::number(),
+                    input_variance: None,
+                    partition: None,
                 },
                 Operator::Input {
                     out_precision: 2,
                     out_shape: Shape::number(),
+                    input_variance: None,
+                    partition: None,
                 },
                 Operator::LinearNoise {
                     inputs: vec![input1, input2],
@@ -1043,6 +2526,8 @@ mod tests {
                     input: sum1,
                     table: FunctionTable::UNKWOWN,
                     out_precision: 1,
+                    rounded_precision: None,
+                    shared_rotation: false,
                 },
                 Operator::LinearNoise {
                     inputs: vec![input1, lut1],
@@ -1063,6 +2548,8 @@ mod tests {
                     input: dot,
                     table: FunctionTable::UNKWOWN,
                     out_precision: 2,
+                    rounded_precision: None,
+                    shared_rotation: false,
                 },
                 Operator::ChangePartition {
                     input: lut2,
@@ -1089,6 +2576,8 @@ mod tests {
             Operator::Input {
                 out_precision,
                 out_shape: Shape::number(),
+                input_variance: None,
+                partition: None,
             },
             // The rounding addition skipped, it's a no-op wrt crypto parameter
             // Clear: cleared = input - bit0
@@ -1107,6 +2596,8 @@ mod tests {
                 input: OperatorIndex(2),
                 table: FunctionTable::UNKWOWN,
                 out_precision: 5,
+                rounded_precision: None,
+                shared_rotation: false,
             },
             //// Erase bit
             Operator::Dot {
@@ -1134,6 +2625,8 @@ mod tests {
                 input: OperatorIndex(7),
                 table: FunctionTable::UNKWOWN,
                 out_precision: 4,
+                rounded_precision: None,
+                shared_rotation: false,
             },
             //// Erase bit
             Operator::Dot {
@@ -1161,6 +2654,8 @@ mod tests {
                 input: OperatorIndex(12),
                 table: FunctionTable::UNKWOWN,
                 out_precision: 3,
+                rounded_precision: None,
+                shared_rotation: false,
             },
             //// Erase bit
             Operator::Dot {
@@ -1177,6 +2672,8 @@ mod tests {
                 input: OperatorIndex(15),
                 table: FunctionTable::UNKWOWN,
                 out_precision: 5,
+                rounded_precision: None,
+                shared_rotation: false,
             },
         ];
         assert_eq!(expecteds.len(), graph.operators.len());
@@ -1184,4 +2681,940 @@ mod tests {
             assert_eq!(expected, actual, "{i}-th operation");
         }
     }
+
+    #[test]
+    fn test_sub() {
+        let mut graph = Dag::new();
+        let lhs = graph.add_input(3, Shape::vector(4));
+        let rhs = graph.add_input(3, Shape::number());
+        let sub = graph.add_sub(lhs, rhs);
+        assert_eq!(
+            graph.operators[sub.0],
+            Operator::LinearNoise {
+                inputs: vec![lhs, rhs],
+                complexity: LevelledComplexity::ADDITION * 4,
+                weights: vec![1.0, -1.0],
+                out_shape: Shape::vector(4),
+                comment: "sub".to_string(),
+            }
+        );
+        assert_eq!(graph.out_precisions[sub.0], 3);
+    }
+
+    #[test]
+    fn test_sub_incompatible_shapes() {
+        let mut graph = Dag::new();
+        let lhs = graph.add_input(3, Shape::vector(4));
+        let rhs = graph.add_input(3, Shape::vector(3));
+        let mut builder = graph.builder(DEFAULT_CIRCUIT);
+        assert_eq!(
+            builder.try_add_sub(lhs, rhs, Location::Unknown),
+            Err(DagEditError::BroadcastShape(rhs))
+        );
+        assert_eq!(
+            builder.try_add_sub(lhs, OperatorIndex(5), Location::Unknown),
+            Err(DagEditError::UnknownOperator(OperatorIndex(5)))
+        );
+        assert_eq!(graph.len(), 2);
+    }
+
+    #[test]
+    fn test_negate() {
+        let mut graph = Dag::new();
+        let input = graph.add_input(3, Shape::vector(4));
+        let negate = graph.add_negate(input);
+        assert_eq!(graph.out_shapes[negate.0], Shape::vector(4));
+        assert_eq!(graph.out_precisions[negate.0], 3);
+        assert_eq!(
+            graph.operators[negate.0],
+            Operator::LinearNoise {
+                inputs: vec![input],
+                complexity: LevelledComplexity::ADDITION * 4,
+                weights: vec![-1.0],
+                out_shape: Shape::vector(4),
+                comment: "negate".to_string(),
+            }
+        );
+        assert_eq!(
+            graph
+                .builder(DEFAULT_CIRCUIT)
+                .try_add_negate(OperatorIndex(5), Location::Unknown),
+            Err(DagEditError::UnknownOperator(OperatorIndex(5)))
+        );
+        assert_eq!(graph.len(), 2);
+    }
+
+    #[test]
+    fn test_identity() {
+        let mut graph = Dag::new();
+        let input = graph.add_input(3, Shape::vector(4));
+        let identity = graph.add_identity(input);
+        assert_eq!(graph.out_shapes[identity.0], Shape::vector(4));
+        assert_eq!(graph.out_precisions[identity.0], 3);
+        assert_eq!(
+            graph.operators[identity.0],
+            Operator::LinearNoise {
+                inputs: vec![input],
+                complexity: LevelledComplexity::ZERO,
+                weights: vec![1.0],
+                out_shape: Shape::vector(4),
+                comment: "identity".to_string(),
+            }
+        );
+        assert_eq!(
+            graph
+                .builder(DEFAULT_CIRCUIT)
+                .try_add_identity(OperatorIndex(5), Location::Unknown),
+            Err(DagEditError::UnknownOperator(OperatorIndex(5)))
+        );
+        assert_eq!(graph.len(), 2);
+    }
+
+    #[test]
+    fn test_float_dot() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(3, Shape::number());
+        let b = graph.add_input(3, Shape::number());
+        let dot = graph.add_float_dot([a, b], FloatWeights::vector([0.5, -1.5]));
+        assert_eq!(
+            graph.operators[dot.0],
+            Operator::LinearNoise {
+                inputs: vec![a, b],
+                complexity: LevelledComplexity::ADDITION * 2,
+                weights: vec![0.5, -1.5],
+                out_shape: Shape::number(),
+                comment: "float_dot".to_string(),
+            }
+        );
+        assert_eq!(graph.out_precisions[dot.0], 3);
+
+        let tensor = graph.add_input(3, Shape::vector(2));
+        let dot = graph.add_float_dot([tensor], FloatWeights::vector([3.0, 4.0]));
+        assert_eq!(graph.out_shapes[dot.0], Shape::number());
+        assert!(matches!(
+            &graph.operators[dot.0],
+            Operator::LinearNoise { weights, .. } if weights == &vec![5.0]
+        ));
+
+        let scaled = graph.add_float_dot([tensor], FloatWeights::number(0.25));
+        assert_eq!(graph.out_shapes[scaled.0], Shape::vector(2));
+
+        let mut builder = graph.builder(DEFAULT_CIRCUIT);
+        assert_eq!(
+            builder.try_add_float_dot([], FloatWeights::vector([0.5]), Location::Unknown),
+            Err(DagEditError::EmptyDot)
+        );
+        assert_eq!(
+            builder.try_add_float_dot(
+                [a, b],
+                FloatWeights::vector([0.5, f64::NAN]),
+                Location::Unknown
+            ),
+            Err(DagEditError::NonFiniteWeights)
+        );
+        assert_eq!(
+            builder.try_add_float_dot_with_norm(
+                [a, b],
+                FloatWeights::vector([0.5, 1.0]),
+                f64::NAN,
+                Location::Unknown
+            ),
+            Err(DagEditError::NonFiniteNorm2)
+        );
+    }
+
+    #[test]
+    fn test_dot_with_norm() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(3, Shape::number());
+        let b = graph.add_input(3, Shape::number());
+        let dot = graph.add_dot_with_norm([a, b], Weights::vector([3, 4]), 10.0);
+        assert!(matches!(
+            &graph.operators[dot.0],
+            Operator::LinearNoise { weights, comment, .. }
+                if weights == &vec![6.0, 8.0] && comment == "dot_with_norm"
+        ));
+        assert_eq!(graph.out_shapes[dot.0], Shape::number());
+
+        let tensor = graph.add_input(3, Shape::vector(4));
+        let dot = graph.add_dot_with_norm([tensor], Weights::vector([0, 0, 0, 0]), 2.0);
+        assert!(matches!(
+            &graph.operators[dot.0],
+            Operator::LinearNoise { weights, .. } if weights == &vec![2.0]
+        ));
+
+        let dot = graph.add_dot_with_norm([a, b], Weights::vector([3, 4]), -1.0);
+        assert!(matches!(
+            &graph.operators[dot.0],
+            Operator::Dot { weights, .. } if weights == &Weights::vector([3, 4])
+        ));
+    }
+
+    #[test]
+    fn test_tree_max() {
+        let mut graph = Dag::new();
+        let inputs: Vec<_> = (0..5)
+            .map(|_| graph.add_input(4, Shape::vector(3)))
+            .collect();
+        assert_eq!(
+            graph.add_tree_max([inputs[0]], Shape::vector(3), 4),
+            inputs[0]
+        );
+
+        let max = graph.add_tree_max(inputs.clone(), Shape::vector(3), 4);
+        let luts: Vec<_> = graph
+            .get_operators_iter()
+            .filter(|op| matches!(op.operator, Operator::Lut { .. }))
+            .map(|op| op.id)
+            .collect();
+        // one pbs per pairwise max
+        assert_eq!(luts.len(), 4);
+        assert_eq!(graph.out_shapes[max.0], Shape::vector(3));
+        assert_eq!(graph.out_precisions[max.0], 4);
+        // the relu is evaluated on the signed difference
+        assert!(luts.iter().all(|lut| matches!(
+            &graph.operators[lut.0],
+            Operator::Lut { input, .. } if graph.out_precisions[input.0] == 5
+        )));
+        // the last pairwise max selects the result of the 3 levels tree
+        assert!(matches!(
+            &graph.operators[max.0],
+            Operator::LinearNoise { inputs, comment, .. }
+                if comment == "max_select" && inputs[1] == *luts.last().unwrap()
+        ));
+
+        let mut builder = graph.builder(DEFAULT_CIRCUIT);
+        assert_eq!(
+            builder.try_add_tree_max([], Shape::vector(3), 4, Location::Unknown),
+            Err(DagEditError::EmptyMax)
+        );
+        assert_eq!(
+            builder.try_add_tree_max(inputs, Shape::vector(3), 5, Location::Unknown),
+            Err(DagEditError::MaxInputPrecision {
+                input: OperatorIndex(0),
+                expected: 5
+            })
+        );
+    }
+
+    #[test]
+    fn test_dot_shapes() {
+        let mut graph = Dag::new();
+        let row = graph.add_input(3, Shape::vector(2));
+        let column = graph.add_input(
+            3,
+            Shape {
+                dimensions_size: vec![2, 1],
+            },
+        );
+        let mut builder = graph.builder(DEFAULT_CIRCUIT);
+        let dot = builder
+            .try_add_dot([row, row], [1, 2], Location::Unknown)
+            .unwrap();
+        assert_eq!(builder.dag.out_shapes[dot.0], Shape::vector(2));
+        assert_eq!(
+            builder.try_add_dot([row, column], [1, 2], Location::Unknown),
+            Err(DagEditError::DotInputShape {
+                input: column,
+                first: row
+            })
+        );
+        assert_eq!(
+            builder.try_add_dot([row], [1, 2, 3], Location::Unknown),
+            Err(DagEditError::DotWeightsShape(row))
+        );
+        assert_eq!(
+            builder.try_add_dot([], [1], Location::Unknown),
+            Err(DagEditError::EmptyDot)
+        );
+        assert_eq!(
+            builder.try_add_dot([OperatorIndex(7)], [1], Location::Unknown),
+            Err(DagEditError::UnknownOperator(OperatorIndex(7)))
+        );
+    }
+
+    #[test]
+    fn test_tree_pbs() {
+        let mut graph = Dag::new();
+        let small = graph.add_input(4, Shape::number());
+        let lut = graph.add_tree_pbs(small, FunctionTable::UNKWOWN, 4, 4);
+        assert!(matches!(graph.operators[lut.0], Operator::Lut { .. }));
+        assert_eq!(
+            graph.builder(DEFAULT_CIRCUIT).try_add_tree_pbs(
+                small,
+                FunctionTable::UNKWOWN,
+                4,
+                0,
+                Location::Unknown
+            ),
+            Err(DagEditError::NullDecompositionBase)
+        );
+        let nb_operators = graph.len();
+        assert_eq!(
+            graph.builder(DEFAULT_CIRCUIT).try_add_tree_pbs(
+                OperatorIndex(nb_operators),
+                FunctionTable::UNKWOWN,
+                4,
+                2,
+                Location::Unknown
+            ),
+            Err(DagEditError::UnknownOperator(OperatorIndex(nb_operators)))
+        );
+        assert_eq!(graph.len(), nb_operators);
+
+        let large = graph.add_input(10, Shape::vector(2));
+        let nb_operators = graph.len();
+        let tree = graph.add_tree_pbs(large, FunctionTable::UNKWOWN, 10, 4);
+        // digits of 4, 4 and 2 bits
+        let rotations: Vec<_> = graph.operators[nb_operators..]
+            .iter()
+            .enumerate()
+            .filter_map(|(i, op)| match op {
+                Operator::Lut { input, .. } if graph.out_precisions[input.0] != 0 => {
+                    Some(nb_operators + i)
+                }
+                _ => None,
+            })
+            .collect();
+        let rotation_shapes: Vec<_> = rotations
+            .iter()
+            .map(|&i| graph.out_shapes[i].clone())
+            .collect();
+        assert_eq!(
+            rotation_shapes,
+            [
+                Shape {
+                    dimensions_size: vec![64, 2]
+                },
+                Shape {
+                    dimensions_size: vec![4, 2]
+                },
+                Shape::vector(2),
+            ]
+        );
+        assert_eq!(graph.out_shapes[tree.0], Shape::vector(2));
+        assert_eq!(graph.out_precisions[tree.0], 10);
+        assert!(matches!(
+            &graph.operators[tree.0],
+            Operator::LinearNoise { inputs, comment, .. }
+                if comment == "tree_pbs_select" && inputs[0].0 == *rotations.last().unwrap()
+        ));
+    }
+
+    #[test]
+    fn test_broadcast_noise() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(
+            3,
+            Shape {
+                dimensions_size: vec![4, 1],
+            },
+        );
+        let b = graph.add_input(
+            3,
+            Shape {
+                dimensions_size: vec![1, 8],
+            },
+        );
+        let c = graph.add_input(3, Shape::number());
+        let expected = Shape {
+            dimensions_size: vec![4, 8],
+        };
+        let sum = graph.add_broadcast_linear_noise(
+            [a, b, c],
+            LevelledComplexity::ADDITION,
+            [1.0, 1.0, 1.0],
+            "sum",
+        );
+        assert_eq!(graph.out_shapes[sum.0], expected);
+        let max = graph.add_broadcast_max_noise([a, b]);
+        assert_eq!(graph.out_shapes[max.0], expected);
+    }
+
+    #[test]
+    fn test_broadcast_noise_incompatible_shapes() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(3, Shape::vector(4));
+        let b = graph.add_input(3, Shape::vector(8));
+        let mut builder = graph.builder(DEFAULT_CIRCUIT);
+        assert_eq!(
+            builder.try_add_broadcast_max_noise([a, b], Location::Unknown),
+            Err(DagEditError::BroadcastShape(b))
+        );
+        assert_eq!(
+            builder.try_add_broadcast_linear_noise(
+                [a, b],
+                LevelledComplexity::ADDITION,
+                [1.0, 1.0],
+                "sum",
+                Location::Unknown
+            ),
+            Err(DagEditError::BroadcastShape(b))
+        );
+        assert_eq!(
+            builder.try_add_broadcast_linear_noise(
+                [a, a],
+                LevelledComplexity::ADDITION,
+                [1.0],
+                "sum",
+                Location::Unknown
+            ),
+            Err(DagEditError::LinearNoiseWeights {
+                len: 1,
+                expected: 2
+            })
+        );
+        assert_eq!(graph.len(), 2);
+    }
+
+    #[test]
+    fn test_remove_operator() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(1, Shape::number());
+        let b = graph.add_input(1, Shape::number());
+        let c = graph.add_dot([a, b], [1, 1]);
+        let d = graph.add_lut(c, FunctionTable::UNKWOWN, 1);
+        let e = graph.add_lut(b, FunctionTable::UNKWOWN, 1);
+        assert!(!graph.get_operator(c).is_output());
+        assert_eq!(
+            graph.remove_operator(c),
+            Err(DagEditError::HasDependents(c))
+        );
+        graph.remove_operator(d).unwrap();
+        assert!(graph.get_operator(c).is_output());
+        graph.remove_operator(c).unwrap();
+        assert!(graph.get_operator(a).is_output());
+        assert!(!graph.get_operator(b).is_output());
+        assert_eq!(graph.len(), 3);
+        // e was shifted down and still points to b
+        let e = OperatorIndex(e.0 - 2);
+        assert_eq!(
+            graph.operators[e.0],
+            Operator::Lut {
+                input: b,
+                table: FunctionTable::UNKWOWN,
+                out_precision: 1,
+                rounded_precision: None,
+                shared_rotation: false,
+            }
+        );
+        assert_eq!(
+            graph.remove_operator(OperatorIndex(3)),
+            Err(DagEditError::UnknownOperator(OperatorIndex(3)))
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut graph = Dag::new();
+        let a = graph
+            .builder("main")
+            .add_input(1, Shape::number(), Location::Unknown);
+        let _ = graph
+            .builder("main")
+            .add_lut(a, FunctionTable::UNKWOWN, 1, Location::Unknown);
+        let mut other = Dag::new();
+        let mut builder = other.builder("other");
+        let b = builder.add_input(2, Shape::number(), Location::Unknown);
+        let c = builder.add_lut(b, FunctionTable::UNKWOWN, 2, Location::Unknown);
+        other.add_composition(c, b);
+        let mapping = graph.merge(&other).unwrap();
+        assert_eq!(mapping, vec![OperatorIndex(2), OperatorIndex(3)]);
+        assert_eq!(graph.len(), 4);
+        assert_eq!(
+            graph.operators[3],
+            Operator::Lut {
+                input: OperatorIndex(2),
+                table: FunctionTable::UNKWOWN,
+                out_precision: 2,
+                rounded_precision: None,
+                shared_rotation: false,
+            }
+        );
+        assert_eq!(graph.circuit_tags[3], "other");
+        assert!(graph.get_operator(mapping[1]).is_output());
+        assert_eq!(
+            graph.composition.0.get(&OperatorIndex(2)),
+            Some(&vec![OperatorIndex(3)])
+        );
+        assert_eq!(
+            graph.merge(&other),
+            Err(DagMergeError::CircuitNameCollision("other".into()))
+        );
+        assert_eq!(graph.len(), 4);
+    }
+
+    #[test]
+    fn test_get_circuit_names() {
+        let mut graph = Dag::new();
+        assert!(graph.get_circuit_names().is_empty());
+        let a = graph
+            .builder("main")
+            .add_input(1, Shape::number(), Location::Unknown);
+        let _ = graph
+            .builder("other")
+            .add_input(1, Shape::number(), Location::Unknown);
+        let _ = graph
+            .builder("main")
+            .add_lut(a, FunctionTable::UNKWOWN, 1, Location::Unknown);
+        assert_eq!(graph.get_circuit_names(), vec!["main", "other"]);
+        assert_eq!(graph.get_circuit_count(), 2);
+    }
+
+    #[test]
+    fn test_try_add_composition() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(1, Shape::number());
+        let b = graph.add_lut(a, FunctionTable::UNKWOWN, 1);
+        assert_eq!(
+            graph.try_add_composition(a, a),
+            Err(DagEditError::NotAnOutput(a))
+        );
+        assert_eq!(
+            graph.try_add_composition(b, b),
+            Err(DagEditError::NotAnInput(b))
+        );
+        assert_eq!(
+            graph.try_add_composition(b, OperatorIndex(2)),
+            Err(DagEditError::UnknownOperator(OperatorIndex(2)))
+        );
+        assert!(!graph.is_composed());
+        graph.try_add_composition(b, a).unwrap();
+        assert!(graph.is_composed());
+    }
+
+    #[test]
+    fn test_replace_lut_table() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(1, Shape::number());
+        let b = graph.add_lut(a, FunctionTable::UNKWOWN, 1);
+        let table = FunctionTable { values: vec![1, 0] };
+        graph.replace_lut_table(b, table.clone()).unwrap();
+        assert!(matches!(
+            &graph.operators[b.0],
+            Operator::Lut { table: t, .. } if *t == table
+        ));
+        assert_eq!(
+            graph.replace_lut_table(a, table),
+            Err(DagEditError::NotALut(a))
+        );
+        assert_eq!(
+            graph.replace_lut_table(b, FunctionTable { values: vec![0; 3] }),
+            Err(DagEditError::LutTableSize {
+                input: a,
+                len: 3,
+                expected: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_lut_table_size() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(2, Shape::number());
+        let mut builder = graph.builder(DEFAULT_CIRCUIT);
+        let table = FunctionTable {
+            values: vec![0, 1, 2, 3],
+        };
+        assert!(builder.try_add_lut(a, table, 2, Location::Unknown).is_ok());
+        assert!(builder
+            .try_add_lut(a, FunctionTable::UNKWOWN, 2, Location::Unknown)
+            .is_ok());
+        assert_eq!(
+            builder.try_add_lut(
+                a,
+                FunctionTable {
+                    values: vec![0, 1, 2]
+                },
+                2,
+                Location::Unknown
+            ),
+            Err(DagEditError::LutTableSize {
+                input: a,
+                len: 3,
+                expected: 4
+            })
+        );
+        assert_eq!(graph.len(), 3);
+    }
+
+    #[test]
+    fn test_multi_lut() {
+        let mut graph = Dag::new();
+        let input = graph.add_input(2, Shape::vector(3));
+        let tables: Vec<_> = (0..3)
+            .map(|i| FunctionTable {
+                values: vec![i, i + 1, i + 2, i + 3],
+            })
+            .collect();
+        let outputs = graph.add_multi_lut(input, &tables, 4).unwrap();
+        assert_eq!(outputs.len(), tables.len());
+        for (i, (output, expected)) in outputs.iter().zip(&tables).enumerate() {
+            let Operator::Lut {
+                input: lut_input,
+                table,
+                shared_rotation,
+                ..
+            } = &graph.operators[output.0]
+            else {
+                panic!("%{output} is not a lut");
+            };
+            assert_eq!(*lut_input, input);
+            assert_eq!(table, expected);
+            assert_eq!(*shared_rotation, i > 0);
+            assert_eq!(graph.rotation_lut(*output), outputs[0]);
+            assert_eq!(graph.out_shapes[output.0], Shape::vector(3));
+            assert_eq!(graph.out_precisions[output.0], 4);
+            assert_eq!(graph.output_state[output.0], OutputState::Unused);
+            assert!(graph.get_operator(*output).is_output());
+        }
+        assert_eq!(graph.output_state[input.0], OutputState::Used);
+
+        assert_eq!(
+            graph.add_multi_lut(input, &[], 4),
+            Err(DagEditError::EmptyMultiLut)
+        );
+        let uneven = [tables[0].clone(), FunctionTable { values: vec![0; 2] }];
+        assert_eq!(
+            graph.add_multi_lut(input, &uneven, 4),
+            Err(DagEditError::MultiLutTableSize {
+                table: 1,
+                len: 2,
+                expected: 4
+            })
+        );
+        assert_eq!(graph.len(), 4);
+    }
+
+    #[test]
+    fn test_fused_rounded_lut() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(5, Shape::number());
+        let mut builder = graph.builder(DEFAULT_CIRCUIT);
+        let table = FunctionTable {
+            values: vec![0, 1, 2, 3],
+        };
+        let lut = builder
+            .try_add_rounded_lut(a, table.clone(), 2, 5, Location::Unknown)
+            .unwrap();
+        assert_eq!(
+            builder.try_add_rounded_lut(a, table.clone(), 3, 5, Location::Unknown),
+            Err(DagEditError::LutTableSize {
+                input: a,
+                len: 4,
+                expected: 8
+            })
+        );
+        assert_eq!(
+            builder.try_add_rounded_lut(a, FunctionTable::UNKWOWN, 6, 5, Location::Unknown),
+            Err(DagEditError::RoundedPrecision {
+                input: a,
+                rounded_precision: 6
+            })
+        );
+        assert_eq!(
+            builder.try_add_rounded_lut(OperatorIndex(9), table, 2, 5, Location::Unknown),
+            Err(DagEditError::UnknownOperator(OperatorIndex(9)))
+        );
+        // A single operator, the rounding is part of the lut
+        assert_eq!(graph.len(), 2);
+        assert_eq!(
+            graph.operators[lut.0],
+            Operator::Lut {
+                input: a,
+                table: FunctionTable {
+                    values: vec![0, 1, 2, 3]
+                },
+                out_precision: 5,
+                rounded_precision: Some(2),
+                shared_rotation: false,
+            }
+        );
+        assert_eq!(graph.lut_in_precision(lut), 2);
+        assert_eq!(
+            graph.replace_lut_table(lut, FunctionTable::identity(5)),
+            Err(DagEditError::LutTableSize {
+                input: a,
+                len: 32,
+                expected: 4
+            })
+        );
+        // Rounding to the input precision is a regular lut
+        let lut = graph.add_rounded_lut(a, FunctionTable::UNKWOWN, 5, 5);
+        assert!(matches!(
+            graph.operators[lut.0],
+            Operator::Lut {
+                rounded_precision: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_signed_lut() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(2, Shape::number());
+        // outputs for -2, -1, 0, 1
+        let table = FunctionTable {
+            values: vec![10, 11, 12, 13],
+        };
+        let lut = graph.add_signed_lut(a, table, 2);
+        assert_eq!(
+            graph.operators[lut.0],
+            Operator::Lut {
+                input: a,
+                table: FunctionTable {
+                    values: vec![12, 13, 10, 11]
+                },
+                out_precision: 2,
+                rounded_precision: None,
+                shared_rotation: false,
+            }
+        );
+        let unknown = graph.add_signed_lut(a, FunctionTable::UNKWOWN, 2);
+        assert_eq!(
+            graph.operators[unknown.0],
+            Operator::Lut {
+                input: a,
+                table: FunctionTable::UNKWOWN,
+                out_precision: 2,
+                rounded_precision: None,
+                shared_rotation: false,
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "add_lut: Lut table has 3 entries")]
+    fn test_add_lut_table_size_panics() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(2, Shape::number());
+        let _ = graph.add_lut(
+            a,
+            FunctionTable {
+                values: vec![0, 1, 2],
+            },
+            2,
+        );
+    }
+
+    #[test]
+    fn test_try_get_operator() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(3, Shape::vector(4));
+        let op = graph.try_get_operator(a).unwrap();
+        assert_eq!(*op.precision, 3);
+        assert_eq!(*op.shape, Shape::vector(4));
+        assert!(graph.try_get_operator(OperatorIndex(1)).is_none());
+    }
+
+    #[test]
+    fn test_force_bootstrap() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(3, Shape::number());
+        let dot = graph.add_dot([a], [4]);
+        let refreshed = graph.force_bootstrap(dot).unwrap();
+        assert_eq!(
+            graph.operators[refreshed.0],
+            Operator::Lut {
+                input: dot,
+                table: FunctionTable::identity(3),
+                out_precision: 3,
+                rounded_precision: None,
+                shared_rotation: false,
+            }
+        );
+        assert_eq!(graph.force_bootstrap(refreshed), Ok(refreshed));
+        assert_eq!(
+            graph.force_bootstrap(OperatorIndex(3)),
+            Err(DagEditError::UnknownOperator(OperatorIndex(3)))
+        );
+
+        // the output tag moves to the bootstrap, added after the existing operators
+        let mut graph = Dag::new();
+        let a = graph.add_input(3, Shape::number());
+        let dot = graph.add_dot([a], [4]);
+        let other = graph.add_input(3, Shape::number());
+        graph.tag_operator_as_output(dot);
+        let refreshed = graph.force_bootstrap(dot).unwrap();
+        assert_eq!(refreshed, OperatorIndex(3));
+        assert_eq!(graph.output_state[dot.0], OutputState::Used);
+        assert_eq!(graph.output_state[refreshed.0], OutputState::Tagged);
+        assert_eq!(graph.output_state[other.0], OutputState::Unused);
+        let user = graph.add_dot([refreshed], [2]);
+        assert_eq!(graph.output_state[refreshed.0], OutputState::Tagged);
+        assert_eq!(graph.get_output_operators_iter().count(), 3);
+
+        // an operator with users cannot be bootstrapped without shifting the indices
+        assert_eq!(graph.force_bootstrap(user), Ok(OperatorIndex(user.0 + 1)));
+        assert_eq!(
+            graph.force_bootstrap(dot),
+            Err(DagEditError::HasDependents(dot))
+        );
+        assert_eq!(graph.len(), 6);
+    }
+
+    #[test]
+    fn test_operator_kind_histogram() {
+        let mut graph = Dag::new();
+        let a = graph.add_input(1, Shape::number());
+        let b = graph.add_input(1, Shape::number());
+        let c = graph.add_dot([a, b], [1, 1]);
+        let _ = graph.add_lut(c, FunctionTable::UNKWOWN, 1);
+        let histogram = graph.operator_kind_histogram();
+        assert_eq!(histogram.len(), Operator::KIND_NAMES.len());
+        assert!(histogram.contains(&("Input", 2)));
+        assert!(histogram.contains(&("Dot", 1)));
+        assert!(histogram.contains(&("Lut", 1)));
+        assert!(histogram.contains(&("LinearNoise", 0)));
+    }
+
+    #[test]
+    fn test_tag_operators_as_output() {
+        let mut graph = Dag::new();
+        let mut builder = graph.builder("main");
+        let a = builder.add_input(1, Shape::number(), Location::Unknown);
+        let b = builder.add_input(1, Shape::number(), Location::Unknown);
+        let _ = builder.add_dot([a, b], [1, 1], Location::Unknown);
+        assert_eq!(
+            builder.tag_operators_as_output(&[a, OperatorIndex(3)]),
+            Err(DagEditError::UnknownOperator(OperatorIndex(3)))
+        );
+        assert!(!graph.get_operator(a).is_output());
+        let mut builder = graph.builder("main");
+        builder.tag_operators_as_output(&[a, b]).unwrap();
+        assert!(graph.get_operator(a).is_output());
+        assert!(graph.get_operator(b).is_output());
+    }
+
+    #[test]
+    fn test_check_compositions() {
+        let mut graph = Dag::new();
+        let mut builder = graph.builder("producer");
+        let input = builder.add_input(3, Shape::number(), Location::Unknown);
+        let output = builder.add_lut(input, FunctionTable::UNKWOWN, 4, Location::Unknown);
+        builder.tag_operator_as_output(output);
+        let mut builder = graph.builder("consumer");
+        let same = builder.add_input(4, Shape::number(), Location::Unknown);
+        let other = builder.add_input(5, Shape::number(), Location::Unknown);
+        graph.add_compositions([output], [same, other]);
+        assert_eq!(
+            graph.check_compositions(),
+            vec![CompositionError {
+                from_circuit: "producer".to_string(),
+                from: output,
+                from_precision: 4,
+                to_circuit: "consumer".to_string(),
+                to: other,
+                to_precision: 5,
+            }]
+        );
+        assert_eq!(
+            graph.check_compositions()[0].to_string(),
+            "Composition from producer:%1 (u4) to consumer:%3 (u5) changes precision"
+        );
+    }
+
+    #[test]
+    fn test_structural_hash() {
+        let build = |weight: i64, location: Location| {
+            let mut graph = Dag::new();
+            let mut builder = graph.builder("main");
+            let input = builder.add_input(3, Shape::number(), location.clone());
+            let dot = builder.add_dot([input], [weight], location.clone());
+            let _ = builder.add_lut(dot, FunctionTable::UNKWOWN, 3, location);
+            graph
+        };
+        let graph = build(2, Location::Unknown);
+        assert_eq!(graph.structural_hash(), graph.clone().structural_hash());
+        let located = build(2, Location::Line("main.py".into(), 3));
+        assert_eq!(graph.structural_hash(), located.structural_hash());
+        assert_ne!(
+            graph.structural_hash(),
+            build(3, Location::Unknown).structural_hash()
+        );
+        let mut composed = graph.clone();
+        composed.add_composition(OperatorIndex(2), OperatorIndex(0));
+        assert_ne!(graph.structural_hash(), composed.structural_hash());
+        let build_noise = |weight: f64, comment: &str| {
+            let mut graph = Dag::new();
+            let mut builder = graph.builder("main");
+            let input = builder.add_input(3, Shape::number(), Location::Unknown);
+            let _ = builder.add_linear_noise(
+                [input],
+                LevelledComplexity::ADDITION,
+                [weight],
+                Shape::number(),
+                comment,
+                Location::Unknown,
+            );
+            graph
+        };
+        assert_eq!(
+            build_noise(1.5, "a").structural_hash(),
+            build_noise(1.5, "b").structural_hash()
+        );
+        assert_ne!(
+            build_noise(1.5, "a").structural_hash(),
+            build_noise(2.5, "a").structural_hash()
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let mut graph = Dag::new();
+        let mut builder = graph.builder("main \"1\"");
+        let a = builder.add_input(3, Shape::vector(2), Location::Unknown);
+        let b = builder.add_dot([a], [2], Location::Unknown);
+        let c = builder.add_lut(b, FunctionTable::UNKWOWN, 4, Location::Unknown);
+        graph.add_composition(c, a);
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph dag {"));
+        assert!(dot.contains("label=\"main \\\"1\\\"\";"));
+        assert!(dot.contains("0 [label=\"%0 = Input\\nprecision: 3\\nshape: [2]\", shape=box];"));
+        assert!(dot.contains("1 [label=\"%1 = Dot\\nprecision: 3\\nshape: [2]\", shape=ellipse];"));
+        assert!(dot.contains("2 [label=\"%2 = Lut\\nprecision: 4\\nshape: [2]\", shape=box];"));
+        assert!(dot.contains("  0 -> 1;\n  1 -> 2;\n"));
+        assert!(dot.contains("  2 -> 0 [style=dashed];"));
+        assert!(dot.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_operator_comments() {
+        let mut graph = Dag::new();
+        let mut builder = graph.builder("main");
+        let a = builder.add_input(1, Shape::number(), Location::Unknown);
+        let b = builder.add_input(1, Shape::number(), Location::Unknown);
+        let c = builder.add_lut(b, FunctionTable::UNKWOWN, 1, Location::Unknown);
+        let d = builder.add_linear_noise(
+            [c],
+            LevelledComplexity::ZERO,
+            [1.0],
+            Shape::number(),
+            "noise",
+            Location::Unknown,
+        );
+        let hash = graph.structural_hash();
+        graph
+            .builder("main")
+            .set_operator_comment(b, "b from main.py");
+        assert_eq!(graph.structural_hash(), hash);
+        assert_eq!(graph.get_operator(a).comment, "");
+        assert_eq!(graph.get_operator(b).comment, "b from main.py");
+        assert_eq!(graph.get_operator(d).comment, "noise");
+        assert!(graph.viz_string().contains("b from main.py"));
+        graph.remove_operator(d).unwrap();
+        graph.remove_operator(a).unwrap();
+        assert_eq!(
+            graph.get_operator(OperatorIndex(0)).comment,
+            "b from main.py"
+        );
+        let mut merged = Dag::new();
+        let _ = merged
+            .builder("other")
+            .add_input(1, Shape::number(), Location::Unknown);
+        let mapping = merged.merge(&graph).unwrap();
+        assert_eq!(merged.get_operator(mapping[0]).comment, "b from main.py");
+        assert_eq!(merged.get_operator(mapping[1]).comment, "");
+    }
 }
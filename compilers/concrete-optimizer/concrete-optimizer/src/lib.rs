@@ -11,6 +11,7 @@ pub mod global_parameters;
 pub mod noise_estimator;
 pub mod optimization;
 pub mod parameters;
+pub mod security;
 pub mod utils;
 pub mod weight;
 
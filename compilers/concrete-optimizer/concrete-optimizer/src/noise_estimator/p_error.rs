@@ -3,6 +3,16 @@ pub fn combine_errors(p_error1: f64, p_error2: f64) -> f64 {
     p_error1 + p_error2 - p_error1 * p_error2
 }
 
+// ln(1 - p_error), without rounding 1 - p_error to 1 for tiny p_error
+pub fn log_success(p_error: f64) -> f64 {
+    (-p_error).ln_1p()
+}
+
+// Inverse of log_success
+pub fn p_error_of_log_success(log_success: f64) -> f64 {
+    -log_success.exp_m1()
+}
+
 pub fn repeat_p_error(p_error: f64, count: u64) -> f64 {
     if p_error * count as f64 > 1. {
         iterative_repeat_p_error(p_error, count)
@@ -76,4 +86,22 @@ mod tests {
 
         assert_eq_both_repeat_p_error(0.000_000_000_01, 100);
     }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn test_log_success() {
+        let tiny = 1e-44;
+        // The naive product loses everything
+        let naive = 1.0 - (0..10_000).fold(1.0, |acc, _| acc * (1.0 - tiny));
+        assert!(naive == 0.0);
+        let log_success_sum: f64 = (0..10_000).map(|_| log_success(tiny)).sum();
+        let global_p_error = p_error_of_log_success(log_success_sum);
+        assert!(((global_p_error - 1e-40) / 1e-40).abs() < 1e-12);
+
+        assert!(p_error_of_log_success(0.0) == 0.0);
+        assert!(p_error_of_log_success(log_success(1.0)) == 1.0);
+        let expected = repeat_p_error(0.001, 100);
+        let global_p_error = p_error_of_log_success(100.0 * log_success(0.001));
+        assert!(((global_p_error - expected) / expected).abs() < 1e-12);
+    }
 }
@@ -1,9 +1,9 @@
-use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_binary_key;
-
 use super::config::{Config, SearchSpace};
 use super::decomposition::cmux::CmuxComplexityNoise;
+use super::decomposition::common::{bootstrap_key_size, keyswitch_key_size};
 use super::decomposition::keyswitch::KsComplexityNoise;
 use super::wop_atomic_pattern::optimize::find_p_error;
+use crate::config::KeyDistribution;
 use crate::noise_estimator::error;
 use crate::parameters::{BrDecompositionParameters, GlweParameters, KsDecompositionParameters};
 use crate::utils::square;
@@ -25,10 +25,30 @@ pub struct Solution {
     pub br_decomposition_base_log: u64,        //b(BR)
     pub complexity: f64,
     pub noise_max: f64,
-    pub p_error: f64, // error probability
+    pub noise_margin: f64, // failure bound variance / noise_max
+    pub p_error: f64,      // error probability
     pub global_p_error: f64,
 }
 
+impl Solution {
+    // Size in bytes of the bootstrap and keyswitch keys
+    pub fn key_size(&self) -> f64 {
+        let glwe_params = GlweParameters {
+            log2_polynomial_size: self.glwe_polynomial_size.ilog2() as u64,
+            glwe_dimension: self.glwe_dimension,
+        };
+        bootstrap_key_size(
+            self.internal_ks_output_lwe_dimension,
+            self.br_decomposition_level_count,
+            glwe_params,
+        ) + keyswitch_key_size(
+            self.input_lwe_dimension,
+            self.ks_decomposition_level_count,
+            self.internal_ks_output_lwe_dimension,
+        )
+    }
+}
+
 // Constants during optimisation of decompositions
 pub(crate) struct OptimizationDecompositionsConsts<'a> {
     pub config: Config<'a>,
@@ -57,9 +77,10 @@ fn update_state_with_best_decompositions(
     glwe_params: GlweParameters,
     cmux_quantities: &[CmuxComplexityNoise],
     ks_quantities: &[KsComplexityNoise],
+    key_distribution: KeyDistribution,
 ) {
     let input_lwe_dimension = glwe_params.sample_extract_lwe_dimension();
-    let noise_modulus_switching = estimate_modulus_switching_noise_with_binary_key(
+    let noise_modulus_switching = key_distribution.modulus_switching_noise(
         internal_dim,
         glwe_params.log2_polynomial_size,
         consts.config.ciphertext_modulus_log,
@@ -126,6 +147,7 @@ fn update_state_with_best_decompositions(
                     br_decomposition_level_count: br_l,
                     br_decomposition_base_log: br_b,
                     noise_max,
+                    noise_margin: safe_variance / noise_max,
                     complexity,
                     p_error,
                     global_p_error: f64::NAN,
@@ -180,7 +202,7 @@ pub fn optimize_one(
     let min_internal_lwe_dimensions = search_space.internal_lwe_dimensions[0];
     let lower_bound_cut = |glwe_log_poly_size| {
         // TODO: cut if min complexity is higher than current best
-        CUTS && estimate_modulus_switching_noise_with_binary_key(
+        CUTS && persistent_caches.key_distribution.modulus_switching_noise(
             min_internal_lwe_dimensions,
             glwe_log_poly_size,
             ciphertext_modulus_log,
@@ -216,6 +238,7 @@ pub fn optimize_one(
                     glwe_params,
                     cmux_quantities,
                     ks_quantities,
+                    caches.key_distribution,
                 );
             }
         }
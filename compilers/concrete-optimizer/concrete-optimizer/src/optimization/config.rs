@@ -18,6 +18,142 @@ pub struct Config<'a> {
     pub ciphertext_modulus_log: u32,
     pub fft_precision: u32,
     pub complexity_model: &'a dyn ComplexityModel,
+    pub objective: Objective,
+}
+
+/// Why a [`Config`] cannot produce sound parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConfigError {
+    CiphertextModulusLog(u32),
+    FftPrecision {
+        fft_precision: u32,
+        ciphertext_modulus_log: u32,
+    },
+    ObjectiveWeights {
+        complexity_weight: f64,
+        key_size_weight: f64,
+    },
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::CiphertextModulusLog(ciphertext_modulus_log) => write!(
+                f,
+                "ciphertext_modulus_log {ciphertext_modulus_log} is not between 1 and 64"
+            ),
+            Self::FftPrecision {
+                fft_precision,
+                ciphertext_modulus_log,
+            } => write!(
+                f,
+                "fft_precision {fft_precision} is not between 1 and ciphertext_modulus_log {ciphertext_modulus_log}"
+            ),
+            Self::ObjectiveWeights {
+                complexity_weight,
+                key_size_weight,
+            } => write!(
+                f,
+                "objective weights {complexity_weight} (complexity) and {key_size_weight} (key size) must be non negative numbers, not both zero"
+            ),
+        }
+    }
+}
+
+impl Config<'_> {
+    /// Checks that the modulus fits in 64 bits ciphertexts, that the fft precision is supported
+    /// by the noise model, i.e. does not exceed the modulus, and that the objective is well formed.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let ciphertext_modulus_log = self.ciphertext_modulus_log;
+        if !(1..=64).contains(&ciphertext_modulus_log) {
+            return Err(ConfigError::CiphertextModulusLog(ciphertext_modulus_log));
+        }
+        if !(1..=ciphertext_modulus_log).contains(&self.fft_precision) {
+            return Err(ConfigError::FftPrecision {
+                fft_precision: self.fft_precision,
+                ciphertext_modulus_log,
+            });
+        }
+        self.objective.validate()
+    }
+}
+
+/// What the solo key and multi parameters optimizations minimize, under the error probability constraint.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Objective {
+    /// The circuit complexity.
+    #[default]
+    MinComplexity,
+    /// The total size in bytes of the bootstrap and keyswitch keys.
+    MinKeySize,
+    /// A weighted sum of the circuit complexity and of the total key size in bytes.
+    /// Weights must be non negative and not both zero, see [`Objective::validate`].
+    Weighted {
+        complexity_weight: f64,
+        key_size_weight: f64,
+    },
+    /// The amortized complexity per ciphertext when evaluating batches of `batch_size` ciphertexts,
+    /// i.e. the inverse of the throughput. Only gpus amortize the batch, see
+    /// [`Objective::processing_unit`]: on cpu the batch size is ignored and this is the same as
+    /// [`Objective::MinComplexity`].
+    MaxThroughput { batch_size: u64 },
+}
+
+impl Objective {
+    /// Checks that the weights of a weighted objective are non negative and not both zero.
+    pub fn validate(self) -> Result<(), ConfigError> {
+        if let Self::Weighted {
+            complexity_weight,
+            key_size_weight,
+        } = self
+        {
+            let valid = |weight: f64| weight.is_finite() && weight >= 0.0;
+            if !valid(complexity_weight)
+                || !valid(key_size_weight)
+                || complexity_weight + key_size_weight == 0.0
+            {
+                return Err(ConfigError::ObjectiveWeights {
+                    complexity_weight,
+                    key_size_weight,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Cost to minimize for a solution of the given complexity and key size.
+    pub fn cost(self, complexity: f64, key_size: f64) -> f64 {
+        match self {
+            Self::MinComplexity => complexity,
+            Self::MinKeySize => key_size,
+            Self::Weighted {
+                complexity_weight,
+                key_size_weight,
+            } => complexity_weight * complexity + key_size_weight * key_size,
+            // the complexity model of the processing unit already amortizes the batch
+            Self::MaxThroughput { .. } => complexity,
+        }
+    }
+
+    /// The processing unit whose complexity model matches the objective: maximizing the
+    /// throughput evaluates gpu pbs with the amortized model at the objective batch size.
+    /// Other processing units are returned unchanged, whatever the objective.
+    pub fn processing_unit(
+        self,
+        processing_unit: config::ProcessingUnit,
+    ) -> config::ProcessingUnit {
+        match (self, processing_unit) {
+            (
+                Self::MaxThroughput { batch_size },
+                config::ProcessingUnit::Gpu { number_of_sm, .. },
+            ) => config::ProcessingUnit::Gpu {
+                pbs_type: GpuPbsType::Amortized,
+                number_of_sm,
+                batch_size: batch_size.max(1),
+            },
+            _ => processing_unit,
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -26,6 +162,21 @@ pub struct ParameterRestrictions {
     pub log2_polynomial_size_max: u64,
     pub glwe_dimension_min: u64,
     pub glwe_dimension_max: u64,
+    pub internal_lwe_dimension_min: u64,
+    pub internal_lwe_dimension_max: u64,
+}
+
+impl Default for ParameterRestrictions {
+    fn default() -> Self {
+        Self {
+            log2_polynomial_size_min: 0,
+            log2_polynomial_size_max: u64::MAX,
+            glwe_dimension_min: 0,
+            glwe_dimension_max: u64::MAX,
+            internal_lwe_dimension_min: 0,
+            internal_lwe_dimension_max: u64::MAX,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -34,6 +185,12 @@ pub struct SearchSpace {
     pub glwe_dimensions: Vec<u64>,
     pub internal_lwe_dimensions: Vec<u64>,
     pub levelled_only_lwe_dimensions: Range,
+    // Moduli the crt decomposition is restricted to, empty to use the default heuristic
+    pub crt_decomposition_hint: Vec<u64>,
+    // Whether conversion keyswitches may use the fast keyswitch variant
+    pub allow_fast_keyswitch: bool,
+    // Groups of partitions which must share their big secret key, i.e. use the same glwe parameters
+    pub key_sharing_groups: Vec<Vec<u64>>,
 }
 
 impl SearchSpace {
@@ -53,6 +210,9 @@ impl SearchSpace {
             glwe_dimensions,
             internal_lwe_dimensions,
             levelled_only_lwe_dimensions,
+            crt_decomposition_hint: vec![],
+            allow_fast_keyswitch: true,
+            key_sharing_groups: vec![],
         }
     }
 
@@ -72,6 +232,9 @@ impl SearchSpace {
             glwe_dimensions,
             internal_lwe_dimensions,
             levelled_only_lwe_dimensions,
+            crt_decomposition_hint: vec![],
+            allow_fast_keyswitch: true,
+            key_sharing_groups: vec![],
         }
     }
 
@@ -91,11 +254,44 @@ impl SearchSpace {
             glwe_dimensions,
             internal_lwe_dimensions,
             levelled_only_lwe_dimensions,
+            crt_decomposition_hint: vec![],
+            allow_fast_keyswitch: true,
+            key_sharing_groups: vec![],
         }
     }
+    // The other partitions which must share their big secret key with the given partition
+    pub fn key_sharing_mates(&self, partition: usize) -> impl Iterator<Item = usize> + '_ {
+        self.key_sharing_groups
+            .iter()
+            .filter(move |group| group.contains(&(partition as u64)))
+            .flatten()
+            .map(|&mate| mate as usize)
+            .filter(move |&mate| mate != partition)
+    }
+
+    // Keeps only the parameters within the (inclusive) bounds
+    pub fn restrict(&mut self, restrictions: &ParameterRestrictions) {
+        self.glwe_log_polynomial_sizes
+            .retain(|log2_polynomial_size| {
+                (restrictions.log2_polynomial_size_min..=restrictions.log2_polynomial_size_max)
+                    .contains(log2_polynomial_size)
+            });
+        self.glwe_dimensions.retain(|glwe_dimension| {
+            (restrictions.glwe_dimension_min..=restrictions.glwe_dimension_max)
+                .contains(glwe_dimension)
+        });
+        self.internal_lwe_dimensions
+            .retain(|internal_lwe_dimension| {
+                (restrictions.internal_lwe_dimension_min..=restrictions.internal_lwe_dimension_max)
+                    .contains(internal_lwe_dimension)
+            });
+    }
+
     pub fn default(processing_unit: config::ProcessingUnit) -> Self {
         match processing_unit {
-            config::ProcessingUnit::Cpu => Self::default_cpu(),
+            config::ProcessingUnit::Cpu | config::ProcessingUnit::Remote { .. } => {
+                Self::default_cpu()
+            }
             config::ProcessingUnit::Gpu {
                 pbs_type: GpuPbsType::Amortized,
                 ..
@@ -107,3 +303,116 @@ impl SearchSpace {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::computing_cost::cpu::CpuComplexity;
+
+    #[test]
+    fn test_validate() {
+        let complexity_model = CpuComplexity::default();
+        let config = |ciphertext_modulus_log, fft_precision| Config {
+            security_level: 128,
+            maximum_acceptable_error_probability: 0.01,
+            key_sharing: true,
+            ciphertext_modulus_log,
+            fft_precision,
+            complexity_model: &complexity_model,
+            objective: Objective::MinComplexity,
+        };
+        assert_eq!(config(64, 53).validate(), Ok(()));
+        assert_eq!(config(32, 32).validate(), Ok(()));
+        assert_eq!(
+            config(32, 53).validate(),
+            Err(ConfigError::FftPrecision {
+                fft_precision: 53,
+                ciphertext_modulus_log: 32
+            })
+        );
+        assert!(config(64, 0).validate().is_err());
+        assert_eq!(
+            config(128, 53).validate(),
+            Err(ConfigError::CiphertextModulusLog(128))
+        );
+    }
+
+    #[test]
+    fn test_validate_objective_weights() {
+        let weighted = |complexity_weight, key_size_weight| {
+            Objective::Weighted {
+                complexity_weight,
+                key_size_weight,
+            }
+            .validate()
+        };
+        assert_eq!(weighted(1.0, 0.0), Ok(()));
+        assert_eq!(weighted(0.5, 2.0), Ok(()));
+        assert!(weighted(0.0, 0.0).is_err());
+        assert!(weighted(-1.0, 1.0).is_err());
+        assert!(weighted(1.0, f64::NAN).is_err());
+        assert!(weighted(f64::INFINITY, 1.0).is_err());
+        assert_eq!(Objective::MinKeySize.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_max_throughput_processing_unit() {
+        let objective = Objective::MaxThroughput { batch_size: 64 };
+        assert_eq!(objective.cost(10.0, 1e6), 10.0);
+        let lowlat = config::ProcessingUnit::Gpu {
+            pbs_type: GpuPbsType::Lowlat,
+            number_of_sm: 108,
+            batch_size: 1,
+        };
+        assert_eq!(
+            objective.processing_unit(lowlat).br_to_string(),
+            "gpu_amortized_108_64"
+        );
+        let small_gpu = config::ProcessingUnit::Gpu {
+            pbs_type: GpuPbsType::Lowlat,
+            number_of_sm: 16,
+            batch_size: 1,
+        };
+        assert_ne!(
+            objective.processing_unit(small_gpu).br_to_string(),
+            objective.processing_unit(lowlat).br_to_string()
+        );
+        assert_eq!(
+            Objective::MinComplexity
+                .processing_unit(lowlat)
+                .br_to_string(),
+            "gpu_lowlat"
+        );
+        assert_eq!(
+            objective
+                .processing_unit(config::ProcessingUnit::Cpu)
+                .br_to_string(),
+            "cpu"
+        );
+    }
+
+    #[test]
+    fn test_restrict() {
+        let mut search_space = SearchSpace::default_cpu();
+        search_space.restrict(&ParameterRestrictions::default());
+        assert_eq!(
+            search_space.internal_lwe_dimensions,
+            SearchSpace::default_cpu().internal_lwe_dimensions
+        );
+        search_space.restrict(&ParameterRestrictions {
+            log2_polynomial_size_min: 10,
+            log2_polynomial_size_max: 11,
+            glwe_dimension_max: 2,
+            internal_lwe_dimension_min: 700,
+            internal_lwe_dimension_max: 900,
+            ..ParameterRestrictions::default()
+        });
+        assert_eq!(search_space.glwe_log_polynomial_sizes, [10, 11]);
+        assert_eq!(search_space.glwe_dimensions, [1, 2]);
+        assert!(!search_space.internal_lwe_dimensions.is_empty());
+        assert!(search_space
+            .internal_lwe_dimensions
+            .iter()
+            .all(|dim| (700..=900).contains(dim)));
+    }
+}
@@ -15,8 +15,8 @@ use crate::optimization::{Err, Result};
 use super::complexity::OperationsCount;
 use super::keys_spec;
 use super::noise_expression::{
-    bootstrap_noise, fast_keyswitch_noise, input_noise, keyswitch_noise, modulus_switching_noise,
-    NoiseExpression,
+    bootstrap_noise, constant_noise, fast_keyswitch_noise, input_noise, keyswitch_noise,
+    modulus_switching_noise, NoiseExpression,
 };
 use super::partitions::Partitions;
 use super::symbolic::{bootstrap, fast_keyswitch, keyswitch, SymbolMap};
@@ -37,10 +37,17 @@ impl PartitionedDag {
             .dag
             .get_operators_iter()
             .map(|op| match op.operator {
-                Operator::Input { .. } => {
+                Operator::Input { input_variance, .. } => {
                     let mut output = OperatorVariance::zero(self.partitions.nb_partitions);
                     let op_partition = self.partitions[op.id].instruction_partition;
-                    output[op_partition] += 1.0 * input_noise(op_partition);
+                    match input_variance {
+                        Some(variance) => output[op_partition] += *variance * constant_noise(),
+                        None => output[op_partition] += 1.0 * input_noise(op_partition),
+                    }
+                    for &index in &self.partitions[op.id].alternative_output_representation {
+                        output[index] = output[op_partition].clone()
+                            + 1.0 * fast_keyswitch_noise(op_partition, index);
+                    }
                     output
                 }
                 _ => OperatorVariance::zero(self.partitions.nb_partitions),
@@ -214,6 +221,7 @@ impl VariancedDag {
             if let Operator::Input {
                 out_precision,
                 out_shape,
+                ..
             } = op.operator
             {
                 let partition_index =
@@ -339,6 +347,16 @@ impl VariancedDag {
                     operator.get_inputs_iter().next().unwrap().variance()[operator_partition]
                         .clone()
                 }
+                Operator::Keyswitch { .. } => {
+                    let input = operator.get_inputs_iter().next().unwrap();
+                    match operator.partition().inputs_transition[0] {
+                        Some(Transition::Internal { src_partition }) => {
+                            input.variance()[src_partition].clone()
+                                + 1.0 * fast_keyswitch_noise(src_partition, operator_partition)
+                        }
+                        _ => input.variance()[operator_partition].clone(),
+                    }
+                }
                 Operator::Input { .. } | Operator::ZeroNoise { .. } => unreachable!(),
 
                 Operator::Dot {
@@ -414,6 +432,35 @@ pub struct AnalyzedDag {
     pub p_cut: PartitionCut,
 }
 
+impl AnalyzedDag {
+    /// Returns the names of the external partitions used by at least one instruction, in partition
+    /// order.
+    pub fn used_external_partitions(&self) -> Vec<String> {
+        let mut used: Vec<PartitionIndex> = self
+            .instrs_partition
+            .iter()
+            .flat_map(|instr_partition| {
+                std::iter::once(instr_partition.instruction_partition).chain(
+                    instr_partition
+                        .alternative_output_representation
+                        .iter()
+                        .copied(),
+                )
+            })
+            .filter(|partition| self.p_cut.is_external_partition(partition))
+            .collect();
+        used.sort();
+        used.dedup();
+        used.iter()
+            .map(|&partition| {
+                self.p_cut.external_partitions[self.p_cut.external_partition_index(partition)]
+                    .name
+                    .clone()
+            })
+            .collect()
+    }
+}
+
 pub fn analyze(
     dag: &Dag,
     noise_config: &NoiseBoundConfig,
@@ -482,7 +529,6 @@ pub fn original_instrs_partition(
             if let Operator::Lut { .. } = dag.operators[new_instruction.0] {
                 let ks_dst = new_instr_part.instruction_partition;
                 partition = Some(ks_dst);
-                #[allow(clippy::match_on_vec_items)]
                 let ks_src = match new_instr_part.inputs_transition[0] {
                     Some(Transition::Internal { src_partition }) => src_partition,
                     None => ks_dst,
@@ -496,6 +542,20 @@ pub fn original_instrs_partition(
                 tlu_keyswitch_key = Some(ks_key);
                 tlu_bootstrap_key = Some(pbs_key);
             }
+            if let Operator::Keyswitch { .. } = dag.operators[new_instruction.0] {
+                if let Some(Transition::Internal { src_partition }) =
+                    new_instr_part.inputs_transition[0]
+                {
+                    let dst = new_instr_part.instruction_partition;
+                    input_partition = Some(src_partition);
+                    conversion_key = Some(
+                        fks_keys[src_partition.0][dst.0]
+                            .as_ref()
+                            .unwrap()
+                            .identifier,
+                    );
+                }
+            }
             if !new_instr_part.alternative_output_representation.is_empty() {
                 assert!(new_instr_part.alternative_output_representation.len() == 1);
                 let src = new_instr_part.instruction_partition;
@@ -655,7 +715,6 @@ fn variance_constraint(
 
 #[allow(unused)]
 #[allow(clippy::float_cmp)]
-#[allow(clippy::match_on_vec_items)]
 fn collect_all_variance_constraints(
     dag: &VariancedDag,
     noise_config: &NoiseBoundConfig,
@@ -669,8 +728,10 @@ fn collect_all_variance_constraints(
     let mut constraints = vec![];
     for op in dag.get_operators_iter() {
         let partition = partitions[op.id].instruction_partition;
-        if let Operator::Lut { input, .. } = op.operator {
-            let precision = dag.out_precisions[input.0];
+        // A shared rotation has the input constraint of its rotation lut
+        if let (Operator::Lut { input, .. }, true) = (op.operator, dag.rotation_lut(op.id) == op.id)
+        {
+            let precision = dag.lut_in_precision(op.id);
             let dst_partition = partition;
             let src_partition = match partitions[op.id].inputs_transition[0] {
                 None => dst_partition,
@@ -719,14 +780,14 @@ fn collect_all_variance_constraints(
 }
 
 #[allow(unused)]
-#[allow(clippy::match_on_vec_items)]
 fn operations_counts(
     dag: &Dag,
-    op: &Operator,
+    op_i: OperatorIndex,
     nb_partitions: usize,
     instr_partition: &InstructionPartition,
 ) -> OperationsCount {
     let mut counts = SymbolMap::new();
+    let op = &dag.operators[op_i.0];
     if let Operator::Lut { input, .. } = op {
         let partition = instr_partition.instruction_partition;
         let nb_lut = dag.out_shapes[input.0].flat_size() as usize;
@@ -734,27 +795,35 @@ fn operations_counts(
             Some(Transition::Internal { src_partition }) => src_partition,
             Some(Transition::Additional { .. }) | None => partition,
         };
-        counts.update(keyswitch(src_partition, partition), |a| a + nb_lut);
-        counts.update(bootstrap(partition), |a| a + nb_lut);
+        // A shared rotation only adds a sample extraction to its rotation lut
+        if dag.rotation_lut(op_i) == op_i {
+            counts.update(keyswitch(src_partition, partition), |a| a + nb_lut);
+            counts.update(bootstrap(partition), |a| a + nb_lut);
+        }
         for &conv_partition in &instr_partition.alternative_output_representation {
             counts.update(fast_keyswitch(partition, conv_partition), |a| a + nb_lut);
         }
     }
+    if let Operator::Keyswitch { input, .. } = op {
+        if let Some(Transition::Internal { src_partition }) = instr_partition.inputs_transition[0] {
+            let nb_ks = dag.out_shapes[input.0].flat_size() as usize;
+            let partition = instr_partition.instruction_partition;
+            counts.update(fast_keyswitch(src_partition, partition), |a| a + nb_ks);
+        }
+    }
     OperationsCount(counts)
 }
 
 #[allow(unused)]
 fn collect_operations_count(dag: &VariancedDag) -> Vec<OperationsCount> {
     dag.dag
-        .operators
-        .iter()
-        .enumerate()
-        .map(|(i, op)| {
+        .get_indices_iter()
+        .map(|op_i| {
             operations_counts(
                 &dag.dag,
-                op,
+                op_i,
                 dag.partitions.nb_partitions,
-                &dag.partitions[OperatorIndex(i)],
+                &dag.partitions[op_i],
             )
         })
         .collect()
@@ -1048,6 +1117,25 @@ pub mod tests {
         }
     }
 
+    #[allow(clippy::float_cmp)]
+    #[test]
+    fn test_input_with_variance() {
+        let mut dag = unparametrized::Dag::new();
+        let variance = 2.0_f64.powi(-60);
+        let input1 = dag.add_input_with_variance(8, Shape::number(), variance);
+        let dot = dag.add_dot([input1], [3]);
+        let lut1 = dag.add_lut(dot, FunctionTable::UNKWOWN, 8);
+        let dag = analyze(&dag);
+        assert!(dag.nb_partitions == 1);
+        let p = dag.instrs_partition[input1.0].instruction_partition;
+        assert_input_on(&dag, p, input1.0, 0.0);
+        let dot_variance = &dag.instrs_variances[dot.0][p];
+        assert!(dot_variance.coeff(constant_noise()) == 9.0 * variance);
+        assert!(dot_variance.coeff(input_noise(p)) == 0.0);
+        assert_pbs_on(&dag, p, lut1.0, 1.0);
+        assert!(dag.instrs_variances[lut1.0][p].coeff(constant_noise()) == 0.0);
+    }
+
     #[test]
     fn test_levelled_op() {
         let mut dag = unparametrized::Dag::new();
@@ -1,5 +1,7 @@
 use std::{fmt, ops::Add};
 
+use crate::optimization::config::Objective;
+
 use super::{
     partitions::PartitionIndex,
     symbolic::{fast_keyswitch, keyswitch, Symbol, SymbolArray, SymbolMap, SymbolScheme},
@@ -46,21 +48,42 @@ impl ComplexityValues {
 /// A complexity expression is a sum of complexity terms associating operation
 /// symbols with the number of time they gets executed in the circuit.
 #[derive(Clone, Debug)]
-pub struct ComplexityEvaluator(SymbolArray<usize>);
+pub struct ComplexityEvaluator(SymbolArray<usize>, Objective);
 
 impl ComplexityEvaluator {
-    /// Creates a complexity expression from a set of operation counts.
+    /// Creates a complexity expression from a set of operation counts,
+    /// whose total cost is the objective to minimize.
     pub fn from_scheme_and_counts(
         scheme: &SymbolScheme,
         counts: &OperationsCount,
+        objective: Objective,
     ) -> ComplexityEvaluator {
-        Self(SymbolArray::from_scheme_and_map(scheme, &counts.0))
+        Self(
+            SymbolArray::from_scheme_and_map(scheme, &counts.0),
+            objective,
+        )
     }
 
     pub fn scheme(&self) -> &SymbolScheme {
         self.0.scheme()
     }
 
+    /// Returns the cost of one execution of an fhe operation symbol, given its complexity and the
+    /// size of its key.
+    ///
+    /// A key is stored once whatever the number of executions, so its size is spread over them.
+    pub fn unit_cost(&self, symbol: Symbol, complexity: f64, key_size: f64) -> f64 {
+        if self.1 == Objective::MinComplexity {
+            return complexity;
+        }
+        let n_ops = *self.0.get(&symbol);
+        if n_ops == 0 {
+            return 0.0;
+        }
+        let n_ops = n_ops as f64;
+        self.1.cost(n_ops * complexity, key_size) / n_ops
+    }
+
     /// Evaluates the total cost expression on a set of cost values.
     pub fn evaluate_total_cost(&self, costs: &ComplexityValues) -> f64 {
         self.0
@@ -69,6 +92,27 @@ impl ComplexityEvaluator {
             .fold(0.0, |acc, (n_ops, cost)| acc + (*n_ops as f64) * *cost)
     }
 
+    /// Evaluates the total cost expression per partition on a set of cost values.
+    ///
+    /// Keyswitches are attributed to their destination partition.
+    pub fn evaluate_partition_costs(&self, costs: &ComplexityValues) -> Vec<f64> {
+        let mut partition_costs = vec![0.0; self.scheme().nb_partitions()];
+        for ((symbol, n_ops), cost) in self.0.iter_with_sym().zip(costs.0.iter()) {
+            let partition = match symbol {
+                Symbol::Input(partition)
+                | Symbol::Bootstrap(partition)
+                | Symbol::ModulusSwitch(partition)
+                | Symbol::Keyswitch(_, partition)
+                | Symbol::FastKeyswitch(_, partition) => partition,
+                Symbol::Constant => continue,
+            };
+            if *n_ops > 0 {
+                partition_costs[partition.0] += (*n_ops as f64) * *cost;
+            }
+        }
+        partition_costs
+    }
+
     /// Evaluates the max ks cost expression on a set of cost values.
     pub fn evaluate_ks_max_cost(
         &self,
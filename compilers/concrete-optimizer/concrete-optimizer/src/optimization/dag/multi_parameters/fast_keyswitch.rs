@@ -3,6 +3,7 @@
 use concrete_cpu_noise_model::gaussian_noise::conversion::modular_variance_to_variance;
 
 // TODO: move to concrete-cpu
+use crate::optimization::decomposition::common::KEY_COEFFICIENT_BYTES;
 use crate::optimization::decomposition::keyswitch::KsComplexityNoise;
 use crate::parameters::{GlweParameters, KsDecompositionParameters};
 
@@ -16,8 +17,11 @@ pub struct FksComplexityNoise {
     pub decomp: KsDecompositionParameters,
     pub noise: f64,
     pub complexity: f64,
+    pub key_size: f64,
     pub src_glwe_param: GlweParameters,
     pub dst_glwe_param: GlweParameters,
+    // false when a standard keyswitch is used for the conversion
+    pub fast_keyswitch: bool,
 }
 
 // Copy & paste from concrete-cpu
@@ -74,6 +78,15 @@ pub fn complexity(input_glwe: &GlweParameters, output_glwe: &GlweParameters, lev
     (add_count + mul_count) as f64
 }
 
+// Size in bytes of the fast keyswitch key
+#[allow(non_snake_case)]
+pub fn key_size(input_glwe: &GlweParameters, output_glwe: &GlweParameters, level: u64) -> f64 {
+    let k0 = output_glwe.glwe_dimension;
+    let N0 = output_glwe.polynomial_size();
+    let upper_k0 = upper_k0(input_glwe, output_glwe);
+    (upper_k0 * level * (k0 + 1) * N0 * KEY_COEFFICIENT_BYTES) as f64
+}
+
 #[allow(non_snake_case)]
 pub fn noise(
     ks: &KsComplexityNoise,
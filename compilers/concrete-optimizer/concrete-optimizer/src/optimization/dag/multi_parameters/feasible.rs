@@ -1,4 +1,4 @@
-use crate::noise_estimator::p_error::{combine_errors, repeat_p_error};
+use crate::noise_estimator::p_error::{log_success, p_error_of_log_success};
 use crate::optimization::dag::multi_parameters::variance_constraint::VarianceConstraint;
 use crate::optimization::dag::solo_key::analyze::p_error_from_relative_variance;
 
@@ -191,7 +191,7 @@ impl Feasible {
     }
 
     fn global_p_error_with_cut(&self, operations_variance: &NoiseValues, cut: f64) -> Option<f64> {
-        let mut global_p_error = 0.0;
+        let mut global_log_success = 0.0;
         for constraint in &self.constraints {
             let variance = constraint
                 .noise_evaluator
@@ -200,15 +200,12 @@ impl Feasible {
                 .evaluate(operations_variance);
             let relative_variance = variance / constraint.safe_variance_bound;
             let p_error = p_error_from_relative_variance(relative_variance, self.kappa);
-            global_p_error = combine_errors(
-                global_p_error,
-                repeat_p_error(p_error, constraint.nb_constraints),
-            );
-            if global_p_error > cut {
+            global_log_success += constraint.nb_constraints as f64 * log_success(p_error);
+            if p_error_of_log_success(global_log_success) > cut {
                 return None;
             }
         }
-        Some(global_p_error)
+        Some(p_error_of_log_success(global_log_success))
     }
 
     pub fn global_p_error(&self, operations_variance: &NoiseValues) -> f64 {
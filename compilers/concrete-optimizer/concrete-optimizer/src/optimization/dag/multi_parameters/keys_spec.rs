@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use crate::optimization::{atomic_pattern, wop_atomic_pattern};
 use crate::parameters::{BrDecompositionParameters, KsDecompositionParameters};
 
-use crate::optimization::dag::multi_parameters::optimize::{MacroParameters, REAL_FAST_KS};
+use crate::optimization::dag::multi_parameters::optimize::MacroParameters;
 
 pub type Id = u64;
 /* An Id is unique per key type. Starting from 0 for the first key ... */
@@ -117,6 +117,10 @@ pub struct CircuitSolution {
     pub crt_decomposition: Vec<u64>, // empty in native case
     pub is_feasible: bool,
     pub error_msg: String,
+    /* names of the external partitions present in the keyset */
+    pub used_external_partitions: Vec<String>,
+    /* number of secret keys without key sharing, key sharing helped if circuit_keys has less */
+    pub unshared_secret_key_count: u64,
 }
 
 impl CircuitSolution {
@@ -216,6 +220,8 @@ impl CircuitSolution {
             crt_decomposition: sol.crt_decomposition,
             is_feasible: true,
             error_msg,
+            used_external_partitions: vec![],
+            unshared_secret_key_count: 2,
         }
     }
 
@@ -260,6 +266,8 @@ impl CircuitSolution {
                 global_p_error: sol.global_p_error,
                 is_feasible,
                 error_msg,
+                used_external_partitions: vec![],
+                unshared_secret_key_count: 1,
             };
         }
         let small_key = SecretLweKey {
@@ -315,6 +323,8 @@ impl CircuitSolution {
             global_p_error: sol.global_p_error,
             is_feasible,
             error_msg,
+            used_external_partitions: vec![],
+            unshared_secret_key_count: 2,
         }
     }
 }
@@ -415,7 +425,7 @@ impl ExpandedCircuitKeys {
                         input_key: big_secret_keys[src].clone(),
                         output_key: big_secret_keys[dst].clone(),
                         ks_decomposition_parameter: fks.decomp,
-                        fast_keyswitch: REAL_FAST_KS,
+                        fast_keyswitch: fks.fast_keyswitch,
                         description: cross_key("fks"),
                     });
                     identifier_fks += 1;
@@ -574,6 +584,23 @@ impl ExpandedCircuitKeys {
 
     #[allow(clippy::too_many_lines)]
     pub fn shared_keys(self) -> (Self, KeySharing) {
+        self.shared_keys_if(|_, _| true)
+    }
+
+    // Only shares the big secret keys of partitions in a same group
+    pub fn shared_keys_in_groups(self, groups: &[Vec<u64>]) -> (Self, KeySharing) {
+        let nb_partitions = self.big_secret_keys.len() as Id;
+        self.shared_keys_if(|key0, key1| {
+            key0 < nb_partitions
+                && key1 < nb_partitions
+                && groups
+                    .iter()
+                    .any(|group| group.contains(&key0) && group.contains(&key1))
+        })
+    }
+
+    // Shares the keys of the same size for which can_share(initial key id, initial key id) holds
+    fn shared_keys_if(self, can_share: impl Fn(Id, Id) -> bool) -> (Self, KeySharing) {
         // initial key to common key
         let mut leader: HashMap<Id, &SecretLweKey> = HashMap::new();
         let mut groups: HashMap<Id, Vec<Id>> = HashMap::new();
@@ -597,7 +624,7 @@ impl ExpandedCircuitKeys {
                 for key1 in &secret_keys[i + 1..] {
                     let same_size = key0.polynomial_size == key1.polynomial_size
                         && key0.glwe_dimension == key1.glwe_dimension;
-                    if same_size {
+                    if same_size && can_share(key0.identifier, key1.identifier) {
                         _ = leader.insert(key1.identifier, key0);
                         groups
                             .get_mut(&key0.identifier)
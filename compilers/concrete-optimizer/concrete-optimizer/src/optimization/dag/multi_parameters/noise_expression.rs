@@ -13,9 +13,11 @@ use super::{
 pub struct NoiseValues(SymbolArray<f64>);
 
 impl NoiseValues {
-    /// Returns an empty set of noise values.
+    /// Returns an empty set of noise values, except for the unit constant noise.
     pub fn from_scheme(scheme: &SymbolScheme) -> NoiseValues {
-        NoiseValues(SymbolArray::from_scheme(scheme))
+        let mut values = SymbolArray::from_scheme(scheme);
+        values.set(&Symbol::Constant, 1.0);
+        NoiseValues(values)
     }
 
     /// Sets the noise variance associated with a noise source.
@@ -248,6 +250,11 @@ pub fn modulus_switching_noise(partition: PartitionIndex) -> NoiseSource {
     NoiseSource(Symbol::ModulusSwitch(partition))
 }
 
+/// Returns the constant noise source symbol, whose variance is always 1.
+pub fn constant_noise() -> NoiseSource {
+    NoiseSource(Symbol::Constant)
+}
+
 #[cfg(test)]
 mod test {
 
@@ -1,19 +1,27 @@
 // OPT: cache for fks and verified pareto
-use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_binary_key;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use crate::config::KeyDistribution;
+use crate::dag::operator::{Operator, OperatorIndex, Precision};
+use crate::dag::rewrite::round::expand_round_and_index_map;
 use crate::dag::unparametrized::Dag;
 use crate::noise_estimator::error;
 use crate::optimization;
-use crate::optimization::config::{Config, NoiseBoundConfig, SearchSpace};
+use crate::optimization::config::{Config, NoiseBoundConfig, Objective, SearchSpace};
 use crate::optimization::dag::multi_parameters::analyze::{analyze, AnalyzedDag};
 use crate::optimization::dag::multi_parameters::fast_keyswitch;
 use crate::optimization::dag::multi_parameters::fast_keyswitch::FksComplexityNoise;
 use crate::optimization::dag::solo_key::analyze::lut_count_from_dag;
-use crate::optimization::dag::solo_key::optimize::optimize as optimize_mono;
+use crate::optimization::dag::solo_key::optimize::{
+    optimize as optimize_mono, optimize_with_progress as optimize_mono_with_progress,
+};
 use crate::optimization::decomposition::cmux::CmuxComplexityNoise;
 use crate::optimization::decomposition::keyswitch::KsComplexityNoise;
 use crate::optimization::decomposition::{cmux, keyswitch, DecompCaches, PersistDecompCaches};
+use crate::optimization::progress::Progress;
 use crate::parameters::GlweParameters;
+use serde::{Deserialize, Serialize};
 
 use crate::optimization::dag::multi_parameters::complexity::ComplexityEvaluator;
 use crate::optimization::dag::multi_parameters::feasible::Feasible;
@@ -53,7 +61,7 @@ struct PartialMicroParameters {
     complexity: f64,
 }
 
-#[derive(Hash, Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Hash, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct MacroParameters {
     pub glwe_params: GlweParameters,
     pub internal_dim: u64,
@@ -70,6 +78,44 @@ pub struct Parameters {
     pub complexity: f64,
 }
 
+impl Parameters {
+    // Lexicographic order on the macro parameters, to choose between equally good solutions
+    fn tie_break_key(&self) -> Vec<Option<(u64, u64, u64)>> {
+        self.macro_params
+            .iter()
+            .map(|macro_params| {
+                macro_params.map(|p| {
+                    (
+                        p.glwe_params.glwe_dimension,
+                        p.glwe_params.log2_polynomial_size,
+                        p.internal_dim,
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartitionFeasibility {
+    // The optimization stopped before concluding on this partition
+    Unknown,
+    Feasible,
+    Unfeasible,
+}
+
+// Diagnostic of one partition of a multi-parameters optimization
+#[derive(Debug, Clone)]
+pub struct PartitionReport {
+    pub partition: PartitionIndex,
+    // Min and max lut input precisions, None for partitions without lut
+    pub precisions: Option<(Precision, Precision)>,
+    pub macro_params: Option<MacroParameters>,
+    pub feasibility: PartitionFeasibility,
+    // Part of the circuit complexity spent in the partition, conversions to it included
+    pub complexity: Option<f64>,
+}
+
 #[derive(Debug, Clone)]
 struct OperationsCV {
     variance: NoiseValues,
@@ -108,7 +154,11 @@ fn optimize_1_ks(
             continue;
         }
         // variance is decreasing, complexity is increasing
-        let ks_cost = ks_quantity.complexity(ks_input_lwe_dim);
+        let ks_cost = complexity.unit_cost(
+            keyswitch(ks_src, ks_dst),
+            ks_quantity.complexity(ks_input_lwe_dim),
+            ks_quantity.key_size(ks_input_lwe_dim, macro_parameters[ks_dst.0].internal_dim),
+        );
         let ks_variance = ks_quantity.noise(ks_input_lwe_dim);
         if ks_cost > ks_max_cost {
             return None;
@@ -189,6 +239,7 @@ fn optimize_1_fks_and_all_compatible_ks(
     cut_complexity: f64,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    allow_fast_keyswitch: bool,
 ) -> Option<(Best1FksAndManyKs, OperationsCV)> {
     // At this point every thing else is known apart fks and ks
     let input_glwe = macro_parameters[fks_src.0].glwe_params;
@@ -197,7 +248,9 @@ fn optimize_1_fks_and_all_compatible_ks(
     // OPT: have a separate cache for fks
     let ks_pareto = caches.pareto_quantities(output_lwe_dim).to_owned();
     // TODO: fast ks in the other direction as well
-    let use_fast_ks = REAL_FAST_KS && input_glwe.sample_extract_lwe_dimension() >= output_lwe_dim;
+    let use_fast_ks = REAL_FAST_KS
+        && allow_fast_keyswitch
+        && input_glwe.sample_extract_lwe_dimension() >= output_lwe_dim;
     let ks_src = fks_dst;
     let ks_input_dim = macro_parameters[fks_dst.0]
         .glwe_params
@@ -228,8 +281,10 @@ fn optimize_1_fks_and_all_compatible_ks(
                 decomp: ks_quantity.decomp,
                 noise: 0.0,
                 complexity: 0.0,
+                key_size: 0.0,
                 src_glwe_param: input_glwe,
                 dst_glwe_param: output_glwe,
+                fast_keyswitch: false,
             }
         } else if use_fast_ks {
             let noise = fast_keyswitch::noise(
@@ -241,26 +296,41 @@ fn optimize_1_fks_and_all_compatible_ks(
             );
             let complexity =
                 fast_keyswitch::complexity(&input_glwe, &output_glwe, ks_quantity.decomp.level);
+            let key_size =
+                fast_keyswitch::key_size(&input_glwe, &output_glwe, ks_quantity.decomp.level);
             FksComplexityNoise {
                 decomp: ks_quantity.decomp,
                 noise,
                 complexity,
+                key_size,
                 src_glwe_param: input_glwe,
                 dst_glwe_param: output_glwe,
+                fast_keyswitch: true,
             }
         } else {
             let noise = ks_quantity.noise(input_glwe.sample_extract_lwe_dimension());
             let complexity = ks_quantity.complexity(input_glwe.sample_extract_lwe_dimension());
+            let key_size = ks_quantity.key_size(
+                input_glwe.sample_extract_lwe_dimension(),
+                output_glwe.sample_extract_lwe_dimension(),
+            );
             FksComplexityNoise {
                 decomp: ks_quantity.decomp,
                 noise,
                 complexity,
+                key_size,
                 src_glwe_param: input_glwe,
                 dst_glwe_param: output_glwe,
+                fast_keyswitch: false,
             }
         };
 
-        if fks_quantity.complexity > fks_max_cost {
+        let fks_cost = complexity.unit_cost(
+            fast_keyswitch(fks_src, fks_dst),
+            fks_quantity.complexity,
+            fks_quantity.key_size,
+        );
+        if fks_cost > fks_max_cost {
             // complexity is strictly increasing by level
             // next complexity will be worse
             return best_sol;
@@ -272,7 +342,7 @@ fn optimize_1_fks_and_all_compatible_ks(
 
         operations
             .cost
-            .set_cost(fast_keyswitch(fks_src, fks_dst), fks_quantity.complexity);
+            .set_cost(fast_keyswitch(fks_src, fks_dst), fks_cost);
         operations
             .variance
             .set_variance(fast_keyswitch_noise(fks_src, fks_dst), fks_quantity.noise);
@@ -325,6 +395,7 @@ fn optimize_dst_exclusive_fks_subset_and_all_ks(
     cut_complexity: f64,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    allow_fast_keyswitch: bool,
 ) -> Option<(Vec<Best1FksAndManyKs>, OperationsCV)> {
     // All fks subgroup can be optimized independently
     let mut acc_operations = operations.clone();
@@ -349,6 +420,7 @@ fn optimize_dst_exclusive_fks_subset_and_all_ks(
                 cut_complexity,
                 ciphertext_modulus_log,
                 fft_precision,
+                allow_fast_keyswitch,
             )?;
             result.push(bests);
             _ = std::mem::replace(&mut acc_operations, operations);
@@ -389,6 +461,7 @@ fn optimize_1_cmux_and_dst_exclusive_fks_subset_and_all_ks(
     best_p_error: f64,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    allow_fast_keyswitch: bool,
 ) -> Option<PartialMicroParameters> {
     let mut operations = operations.clone();
     let mut best_sol = None;
@@ -411,7 +484,14 @@ fn optimize_1_cmux_and_dst_exclusive_fks_subset_and_all_ks(
         }
 
         // Lower bounds cuts
-        let pbs_cost = cmux_quantity.complexity_br(macro_param_partition.internal_dim);
+        let pbs_cost = complexity.unit_cost(
+            bootstrap(partition),
+            cmux_quantity.complexity_br(macro_param_partition.internal_dim),
+            cmux_quantity.key_size_br(
+                macro_param_partition.internal_dim,
+                macro_param_partition.glwe_params,
+            ),
+        );
         operations.cost.set_cost(bootstrap(partition), pbs_cost);
         let lower_cost = complexity.evaluate_total_cost(&operations.cost);
         if lower_cost > best_sol_complexity {
@@ -438,6 +518,7 @@ fn optimize_1_cmux_and_dst_exclusive_fks_subset_and_all_ks(
             best_sol_complexity,
             ciphertext_modulus_log,
             fft_precision,
+            allow_fast_keyswitch,
         );
         if sol.is_none() {
             continue;
@@ -488,6 +569,7 @@ fn apply_all_ks_lower_bound(
     nb_partitions: usize,
     macro_parameters: &[MacroParameters],
     used_tlu_keyswitch: &[Vec<bool>],
+    complexity: &ComplexityEvaluator,
     operations: &mut OperationsCV,
 ) {
     for (src, dst) in cross_partition(nb_partitions) {
@@ -504,7 +586,11 @@ fn apply_all_ks_lower_bound(
         );
         operations.cost.set_cost(
             keyswitch(src, dst),
-            keyswitch::lowest_complexity_ks(ks_pareto, in_lwe_dim),
+            complexity.unit_cost(
+                keyswitch(src, dst),
+                keyswitch::lowest_complexity_ks(ks_pareto, in_lwe_dim),
+                keyswitch::lowest_key_size_ks(ks_pareto, in_lwe_dim, out_internal_dim),
+            ),
         );
     }
 }
@@ -516,9 +602,11 @@ fn apply_fks_variance_and_cost_or_lower_bound(
     initial_fks: &[Vec<Option<FksComplexityNoise>>],
     fks_to_optimize: &[Option<FksSrc>],
     used_conversion_keyswitch: &[Vec<bool>],
+    complexity: &ComplexityEvaluator,
     operations: &mut OperationsCV,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    allow_fast_keyswitch: bool,
 ) {
     for (src, dst) in cross_partition(nb_partitions) {
         if !used_conversion_keyswitch[src.0][dst.0] {
@@ -543,22 +631,42 @@ fn apply_fks_variance_and_cost_or_lower_bound(
                     operations
                         .variance
                         .set_variance(fast_keyswitch_noise(src, dst), this_fks.noise);
-                    operations
-                        .cost
-                        .set_cost(fast_keyswitch(src, dst), this_fks.complexity);
+                    operations.cost.set_cost(
+                        fast_keyswitch(src, dst),
+                        complexity.unit_cost(
+                            fast_keyswitch(src, dst),
+                            this_fks.complexity,
+                            this_fks.key_size,
+                        ),
+                    );
                 }
                 continue;
             }
         }
         let ks_pareto = caches.pareto_quantities(output_glwe.sample_extract_lwe_dimension());
         let use_fast_ks = REAL_FAST_KS
+            && allow_fast_keyswitch
             && input_glwe.sample_extract_lwe_dimension()
                 >= output_glwe.sample_extract_lwe_dimension();
-        let cost = if use_fast_ks {
-            fast_keyswitch::complexity(input_glwe, output_glwe, ks_pareto[0].decomp.level)
+        let (lowest_complexity, lowest_key_size) = if use_fast_ks {
+            let level = ks_pareto[0].decomp.level;
+            (
+                fast_keyswitch::complexity(input_glwe, output_glwe, level),
+                fast_keyswitch::key_size(input_glwe, output_glwe, level),
+            )
         } else {
-            keyswitch::lowest_complexity_ks(ks_pareto, input_glwe.sample_extract_lwe_dimension())
+            let in_lwe_dim = input_glwe.sample_extract_lwe_dimension();
+            (
+                keyswitch::lowest_complexity_ks(ks_pareto, in_lwe_dim),
+                keyswitch::lowest_key_size_ks(
+                    ks_pareto,
+                    in_lwe_dim,
+                    output_glwe.sample_extract_lwe_dimension(),
+                ),
+            )
         };
+        let cost =
+            complexity.unit_cost(fast_keyswitch(src, dst), lowest_complexity, lowest_key_size);
         operations.cost.set_cost(fast_keyswitch(src, dst), cost);
         let mut variance_min = f64::INFINITY;
         // TODO: use a pareto front to avoid that loop
@@ -586,6 +694,7 @@ fn apply_fks_variance_and_cost_or_lower_bound(
 fn apply_partitions_input_and_modulus_variance_and_cost(
     ciphertext_modulus_log: u32,
     security_level: u64,
+    key_distribution: KeyDistribution,
     nb_partitions: usize,
     macro_parameters: &[MacroParameters],
     partition: PartitionIndex,
@@ -601,7 +710,7 @@ fn apply_partitions_input_and_modulus_variance_and_cost(
                 let input_variance = macro_parameters[i.0]
                     .glwe_params
                     .minimal_variance(ciphertext_modulus_log, security_level);
-                let variance_modulus_switching = estimate_modulus_switching_noise_with_binary_key(
+                let variance_modulus_switching = key_distribution.modulus_switching_noise(
                     macro_parameters[i.0].internal_dim,
                     macro_parameters[i.0].glwe_params.log2_polynomial_size,
                     ciphertext_modulus_log,
@@ -622,6 +731,7 @@ fn apply_pbs_variance_and_cost_or_lower_bounds(
     macro_parameters: &[MacroParameters],
     initial_pbs: &[Option<CmuxComplexityNoise>],
     partition: PartitionIndex,
+    complexity: &ComplexityEvaluator,
     operations: &mut OperationsCV,
 ) {
     // setting already chosen pbs and lower bounds
@@ -630,12 +740,18 @@ fn apply_pbs_variance_and_cost_or_lower_bounds(
         let this_pbs = if i == partition { &None } else { this_pbs };
         if let Some(this_pbs) = this_pbs {
             let internal_dim = macro_parameters[i.0].internal_dim;
+            let glwe_params = macro_parameters[i.0].glwe_params;
             operations
                 .variance
                 .set_variance(bootstrap_noise(i), this_pbs.noise_br(internal_dim));
-            operations
-                .cost
-                .set_cost(bootstrap(i), this_pbs.complexity_br(internal_dim));
+            operations.cost.set_cost(
+                bootstrap(i),
+                complexity.unit_cost(
+                    bootstrap(i),
+                    this_pbs.complexity_br(internal_dim),
+                    this_pbs.key_size_br(internal_dim, glwe_params),
+                ),
+            );
         } else {
             // OPT: Most values could be shared on first optimize_macro
             let in_internal_dim = macro_parameters[i.0].internal_dim;
@@ -716,12 +832,15 @@ fn optimize_macro(
     init_parameters: &Parameters,
     best_complexity: f64,
     best_p_error: f64,
+    cancel: &AtomicBool,
+    progress: &mut Progress<'_>,
 ) -> Parameters {
     let nb_partitions = init_parameters.macro_params.len();
     assert!(partition.0 < nb_partitions);
 
+    let key_distribution = caches.key_distribution;
     let variance_modulus_switching_of = |glwe_log2_poly_size, internal_lwe_dimensions| {
-        estimate_modulus_switching_noise_with_binary_key(
+        key_distribution.modulus_switching_noise(
             internal_lwe_dimensions,
             glwe_log2_poly_size,
             ciphertext_modulus_log,
@@ -746,6 +865,11 @@ fn optimize_macro(
             .iter()
             .map(|b| (*a, *b))
     });
+    // a partition sharing its big secret key follows the glwe parameters of its group
+    let shared_glwe_params = search_space
+        .key_sharing_mates(partition.0)
+        .find_map(|mate| init_parameters.macro_params.get(mate).copied().flatten())
+        .map(|macro_params| macro_params.glwe_params);
     let mut lb_message = None;
     for (glwe_dimension, log2_polynomial_size) in glwe_params_domain {
         if !search_space_restriction.is_available_glwe(
@@ -762,15 +886,23 @@ fn optimize_macro(
             log2_polynomial_size,
             glwe_dimension,
         };
+        if shared_glwe_params.is_some_and(|shared| shared != glwe_params) {
+            continue;
+        }
 
         let input_variance = glwe_params.minimal_variance(ciphertext_modulus_log, security_level);
-        if glwe_dimension == 1 && log2_polynomial_size == 8 {
-            // this is insecure and so minimal variance will be above 1
-            assert!(input_variance > 1.0);
+        if input_variance >= 1.0 {
+            // this is insecure, e.g. glwe_dimension 1 with log2_polynomial_size 8,
+            // or outside of a custom security curve
             continue;
         }
 
         for &internal_dim in &search_space.internal_lwe_dimensions {
+            if cancel.load(Ordering::Relaxed) {
+                // the caller discards the result
+                return best_parameters;
+            }
+            progress.step();
             if !search_space_restriction.is_available_macro(
                 partition,
                 MacroParameters {
@@ -806,6 +938,7 @@ fn optimize_macro(
             apply_partitions_input_and_modulus_variance_and_cost(
                 ciphertext_modulus_log,
                 security_level,
+                key_distribution,
                 nb_partitions,
                 &macros,
                 partition,
@@ -832,6 +965,7 @@ fn optimize_macro(
                 &macros,
                 &init_parameters.micro_params.pbs,
                 partition,
+                complexity,
                 &mut operations,
             );
 
@@ -841,6 +975,7 @@ fn optimize_macro(
                 nb_partitions,
                 &macros,
                 used_tlu_keyswitch,
+                complexity,
                 &mut operations,
             );
             // OPT: could be done once and than partially updated
@@ -851,9 +986,11 @@ fn optimize_macro(
                 &init_parameters.micro_params.fks,
                 &fks_to_optimize,
                 used_conversion_keyswitch,
+                complexity,
                 &mut operations,
                 ciphertext_modulus_log,
                 fft_precision,
+                search_space.allow_fast_keyswitch,
             );
 
             let non_feasible = !feasible.feasible(&operations.variance);
@@ -927,6 +1064,7 @@ fn optimize_macro(
                 best_p_error,
                 ciphertext_modulus_log,
                 fft_precision,
+                search_space.allow_fast_keyswitch,
             );
             if let Some(some_micro_params) = micro_opt {
                 // erase macros and all fks that can't be real
@@ -994,8 +1132,10 @@ fn optimize_macro(
             }
         }
     }
-    if DEBUG && lb_message.is_some() {
-        eprintln!("{}", lb_message.unwrap());
+    if DEBUG {
+        if let Some(lb_message) = lb_message {
+            eprintln!("{lb_message}");
+        }
     }
     best_parameters
 }
@@ -1005,7 +1145,42 @@ fn cross_partition(nb_partitions: usize) -> impl Iterator<Item = (PartitionIndex
         .flat_map(move |a| PartitionIndex::range(0, nb_partitions).map(move |b| (a, b)))
 }
 
-#[allow(clippy::too_many_lines, clippy::missing_errors_doc)]
+/// Number of macro parameters candidates explored for each partition,
+/// i.e. the glwe parameters and internal lwe dimensions allowed by the restriction.
+pub fn search_space_size(
+    search_space: &SearchSpace,
+    search_space_restriction: &impl SearchSpaceRestriction,
+) -> u64 {
+    let partition = PartitionIndex::FIRST;
+    let mut size = 0;
+    for &glwe_dimension in &search_space.glwe_dimensions {
+        for &log2_polynomial_size in &search_space.glwe_log_polynomial_sizes {
+            let glwe_params = GlweParameters {
+                log2_polynomial_size,
+                glwe_dimension,
+            };
+            if !search_space_restriction.is_available_glwe(partition, glwe_params) {
+                continue;
+            }
+            size += search_space
+                .internal_lwe_dimensions
+                .iter()
+                .filter(|&&internal_dim| {
+                    search_space_restriction.is_available_macro(
+                        partition,
+                        MacroParameters {
+                            glwe_params,
+                            internal_dim,
+                        },
+                    )
+                })
+                .count() as u64;
+        }
+    }
+    size
+}
+
+#[allow(clippy::missing_errors_doc)]
 pub fn optimize(
     dag: &Dag,
     config: Config,
@@ -1014,6 +1189,34 @@ pub fn optimize(
     persistent_caches: &PersistDecompCaches,
     p_cut: &Option<PartitionCut>,
     default_partition: PartitionIndex,
+) -> optimization::Result<(AnalyzedDag, Parameters)> {
+    optimize_cancellable(
+        dag,
+        config,
+        search_space,
+        search_space_restriction,
+        persistent_caches,
+        p_cut,
+        default_partition,
+        &AtomicBool::new(false),
+        &mut Progress::none(),
+    )
+}
+
+/// Same as `optimize`, but stops with `Err::Cancelled` once `cancel` is set
+/// and reports each macro parameters candidate to `progress`.
+/// Both are handled for each macro parameters candidate.
+#[allow(clippy::too_many_lines, clippy::missing_errors_doc)]
+pub fn optimize_cancellable(
+    dag: &Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    search_space_restriction: &impl SearchSpaceRestriction,
+    persistent_caches: &PersistDecompCaches,
+    p_cut: &Option<PartitionCut>,
+    default_partition: PartitionIndex,
+    cancel: &AtomicBool,
+    progress: &mut Progress<'_>,
 ) -> optimization::Result<(AnalyzedDag, Parameters)> {
     let ciphertext_modulus_log = config.ciphertext_modulus_log;
     let fft_precision = config.fft_precision;
@@ -1041,7 +1244,11 @@ pub fn optimize(
         .for_each(|c| c.init_evaluator(&scheme));
     let feasible = Feasible::of(&dag.variance_constraints, kappa, None);
 
-    let complexity = ComplexityEvaluator::from_scheme_and_counts(&scheme, &dag.operations_count);
+    let complexity = ComplexityEvaluator::from_scheme_and_counts(
+        &scheme,
+        &dag.operations_count,
+        config.objective,
+    );
     let used_tlu_keyswitch = used_tlu_keyswitch(&dag);
     let used_conversion_keyswitch = used_conversion_keyswitch(&dag);
 
@@ -1089,6 +1296,8 @@ pub fn optimize(
                         &params,
                         best_complexity,
                         best_p_error,
+                        cancel,
+                        progress,
                     )
                 }
                 None => optimize_macro(
@@ -1106,8 +1315,13 @@ pub fn optimize(
                     &params,
                     best_complexity,
                     best_p_error,
+                    cancel,
+                    progress,
                 ),
             };
+            if cancel.load(Ordering::Relaxed) {
+                return Err(optimization::Err::Cancelled);
+            }
 
             assert!(
                 new_params.is_feasible.is_feasible() || !params.is_feasible.is_feasible(),
@@ -1143,7 +1357,13 @@ pub fn optimize(
                     params.p_error, params.complexity, params.is_lower_bound
                 );
             }
-            if params.complexity < best_complexity {
+            #[allow(clippy::float_cmp)]
+            let same_cost = params.complexity == best_complexity && params.p_error == best_p_error;
+            let wins_tie = same_cost
+                && best_params
+                    .as_ref()
+                    .is_some_and(|best| params.tie_break_key() < best.tie_break_key());
+            if params.complexity < best_complexity || wins_tie {
                 best_complexity = params.complexity;
                 best_p_error = params.p_error;
                 best_params = Some(params.clone());
@@ -1178,19 +1398,333 @@ pub fn optimize(
             _ => unreachable!(),
         }
     }
-    let best_params = best_params.unwrap();
+    let mut best_params = best_params.unwrap();
     sanity_check(
         &best_params,
         &used_conversion_keyswitch,
         &used_tlu_keyswitch,
         ciphertext_modulus_log,
         security_level,
+        persistent_caches.key_distribution,
         &feasible,
         &complexity,
     );
+    if config.objective != Objective::MinComplexity {
+        // the search has minimized the objective cost, report the actual complexity
+        let complexity = ComplexityEvaluator::from_scheme_and_counts(
+            &scheme,
+            &dag.operations_count,
+            Objective::MinComplexity,
+        );
+        best_params.complexity =
+            complexity.evaluate_total_cost(&operations_cost(&best_params, &complexity));
+    }
     Ok((dag, best_params))
 }
 
+/// Decides whether some parameters can satisfy all the variance constraints of the dag,
+/// without searching for the cheapest ones.
+///
+/// The macro parameters grid is first explored with the lowest noise decompositions only:
+///  - if even the lowest noise of each operation over the whole grid violates a constraint,
+///    the dag is infeasible,
+///  - if a single set of macro parameters, shared by all partitions and taken on a coarse grid,
+///    satisfies the constraints, the dag is feasible.
+///
+/// The complete optimization is only run when these bounds are inconclusive.
+pub fn is_feasible(
+    dag: &Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    search_space_restriction: &impl SearchSpaceRestriction,
+    persistent_caches: &PersistDecompCaches,
+    p_cut: &Option<PartitionCut>,
+) -> bool {
+    if lut_count_from_dag(dag) == 0 && !dag.is_composed() {
+        return optimize_mono(dag, config, search_space, persistent_caches)
+            .best_solution
+            .is_some();
+    }
+    let default_partition = PartitionIndex::FIRST;
+    let bounds = match p_cut {
+        Some(p_cut) => bounds_feasibility(
+            dag,
+            config,
+            search_space,
+            &(
+                search_space_restriction,
+                ExternalPartitionRestriction(p_cut.clone()),
+            ),
+            persistent_caches,
+            p_cut,
+            default_partition,
+        ),
+        None => bounds_feasibility(
+            dag,
+            config,
+            search_space,
+            search_space_restriction,
+            persistent_caches,
+            &PartitionCut::for_each_precision(dag),
+            default_partition,
+        ),
+    };
+    bounds.unwrap_or_else(|| {
+        optimize(
+            dag,
+            config,
+            search_space,
+            search_space_restriction,
+            persistent_caches,
+            p_cut,
+            default_partition,
+        )
+        .is_ok()
+    })
+}
+
+const COARSE_INTERNAL_DIM_STEP: usize = 64;
+
+// Returns None when the lowest noise bounds cannot conclude on the feasibility
+fn bounds_feasibility(
+    dag: &Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    search_space_restriction: &impl SearchSpaceRestriction,
+    persistent_caches: &PersistDecompCaches,
+    p_cut: &PartitionCut,
+    default_partition: PartitionIndex,
+) -> Option<bool> {
+    let ciphertext_modulus_log = config.ciphertext_modulus_log;
+    let security_level = config.security_level;
+    let noise_config = NoiseBoundConfig {
+        security_level,
+        maximum_acceptable_error_probability: config.maximum_acceptable_error_probability,
+        ciphertext_modulus_log,
+    };
+    let Ok(mut dag) = analyze(dag, &noise_config, &Some(p_cut.clone()), default_partition) else {
+        return Some(false);
+    };
+    let kappa =
+        error::sigma_scale_of_error_probability(config.maximum_acceptable_error_probability);
+    let scheme = SymbolScheme::new(dag.nb_partitions);
+    dag.variance_constraints
+        .iter_mut()
+        .for_each(|c| c.init_evaluator(&scheme));
+    let feasible = Feasible::of(&dag.variance_constraints, kappa, None);
+    // only the variances matter here
+    let complexity = ComplexityEvaluator::from_scheme_and_counts(
+        &scheme,
+        &dag.operations_count,
+        config.objective,
+    );
+    let used_tlu_keyswitch = used_tlu_keyswitch(&dag);
+    let nb_partitions = dag.nb_partitions;
+    let mut caches = persistent_caches.caches();
+
+    let available_macros: Vec<Vec<MacroParameters>> = PartitionIndex::range(0, nb_partitions)
+        .map(|partition| {
+            available_macro_parameters(
+                ciphertext_modulus_log,
+                security_level,
+                search_space,
+                search_space_restriction,
+                partition,
+            )
+        })
+        .collect();
+    if available_macros.iter().any(Vec::is_empty) {
+        return Some(false);
+    }
+
+    // Lowest variance of each noise source, each one taking its best macro parameters.
+    // Fast keyswitches are left at zero, i.e. the same glwe parameters on both sides.
+    let mut lower_bounds = NoiseValues::from_scheme(&scheme);
+    for (i, macros) in available_macros.iter().enumerate() {
+        let i = PartitionIndex(i);
+        let mut input_variance = f64::INFINITY;
+        let mut variance_modulus_switching = f64::INFINITY;
+        let mut variance_br = f64::INFINITY;
+        for macro_params in macros {
+            let glwe_params = macro_params.glwe_params;
+            input_variance = input_variance
+                .min(glwe_params.minimal_variance(ciphertext_modulus_log, security_level));
+            variance_modulus_switching =
+                variance_modulus_switching.min(caches.key_distribution.modulus_switching_noise(
+                    macro_params.internal_dim,
+                    glwe_params.log2_polynomial_size,
+                    ciphertext_modulus_log,
+                ));
+            variance_br = variance_br.min(cmux::lowest_noise_br(
+                caches.cmux.pareto_quantities(glwe_params),
+                macro_params.internal_dim,
+            ));
+        }
+        lower_bounds.set_variance(input_noise(i), input_variance);
+        lower_bounds.set_variance(modulus_switching_noise(i), variance_modulus_switching);
+        lower_bounds.set_variance(bootstrap_noise(i), variance_br);
+    }
+    for (src, dst) in cross_partition(nb_partitions) {
+        if !used_tlu_keyswitch[src.0][dst.0] {
+            continue;
+        }
+        // the keyswitch noise decreases with the output dimension and increases with the input one
+        let out_internal_dim = available_macros[dst.0]
+            .iter()
+            .map(|m| m.internal_dim)
+            .max()
+            .unwrap();
+        let in_lwe_dim = available_macros[src.0]
+            .iter()
+            .map(|m| m.glwe_params.sample_extract_lwe_dimension())
+            .min()
+            .unwrap();
+        let ks_pareto = caches.keyswitch.pareto_quantities(out_internal_dim);
+        lower_bounds.set_variance(
+            keyswitch_noise(src, dst),
+            keyswitch::lowest_noise_ks(ks_pareto, in_lwe_dim),
+        );
+    }
+    if !feasible.feasible(&lower_bounds) {
+        return Some(false);
+    }
+
+    // Same macro parameters for all partitions, with the lowest noise decompositions,
+    // on a coarse grid of internal dimensions to limit the number of keyswitch paretos
+    let coarse_internal_dims: HashSet<u64> = search_space
+        .internal_lwe_dimensions
+        .iter()
+        .step_by(COARSE_INTERNAL_DIM_STEP)
+        .copied()
+        .collect();
+    let no_fks = vec![vec![None; nb_partitions]; nb_partitions];
+    let no_used_fks = vec![vec![false; nb_partitions]; nb_partitions];
+    let shared_macros = available_macros[1..].iter().fold(
+        available_macros[0].iter().copied().collect::<HashSet<_>>(),
+        |shared, macros| {
+            macros
+                .iter()
+                .copied()
+                .filter(|m| shared.contains(m))
+                .collect()
+        },
+    );
+    for &macro_params in available_macros[0]
+        .iter()
+        .filter(|m| coarse_internal_dims.contains(&m.internal_dim) && shared_macros.contains(m))
+    {
+        let glwe_params = macro_params.glwe_params;
+        let internal_dim = macro_params.internal_dim;
+        let lowest_pbs = cmux::lowest_noise(caches.cmux.pareto_quantities(glwe_params));
+        let ks_pareto = caches.keyswitch.pareto_quantities(internal_dim);
+        let lowest_ks = ks_pareto[ks_pareto.len() - 1];
+        let available_pbs = PartitionIndex::range(0, nb_partitions).all(|i| {
+            search_space_restriction.is_available_micro_pbs(i, macro_params, lowest_pbs.decomp)
+        });
+        let available_ks = cross_partition(nb_partitions).all(|(src, dst)| {
+            !used_tlu_keyswitch[src.0][dst.0]
+                || search_space_restriction.is_available_micro_ks(
+                    src,
+                    macro_params,
+                    dst,
+                    macro_params,
+                    lowest_ks.decomp,
+                )
+        });
+        if !available_pbs || !available_ks {
+            continue;
+        }
+        let macros = vec![macro_params; nb_partitions];
+        let mut operations = OperationsCV {
+            variance: NoiseValues::from_scheme(&scheme),
+            cost: ComplexityValues::from_scheme(&scheme),
+        };
+        apply_partitions_input_and_modulus_variance_and_cost(
+            ciphertext_modulus_log,
+            security_level,
+            caches.key_distribution,
+            nb_partitions,
+            &macros,
+            default_partition,
+            glwe_params.minimal_variance(ciphertext_modulus_log, security_level),
+            caches.key_distribution.modulus_switching_noise(
+                internal_dim,
+                glwe_params.log2_polynomial_size,
+                ciphertext_modulus_log,
+            ),
+            &mut operations,
+        );
+        apply_pbs_variance_and_cost_or_lower_bounds(
+            &mut caches.cmux,
+            &macros,
+            &vec![None; nb_partitions],
+            default_partition,
+            &complexity,
+            &mut operations,
+        );
+        apply_all_ks_lower_bound(
+            &mut caches.keyswitch,
+            nb_partitions,
+            &macros,
+            &used_tlu_keyswitch,
+            &complexity,
+            &mut operations,
+        );
+        apply_fks_variance_and_cost_or_lower_bound(
+            &mut caches.keyswitch,
+            nb_partitions,
+            &macros,
+            &no_fks,
+            &vec![None; nb_partitions],
+            &no_used_fks,
+            &complexity,
+            &mut operations,
+            ciphertext_modulus_log,
+            config.fft_precision,
+            search_space.allow_fast_keyswitch,
+        );
+        if feasible.feasible(&operations.variance) {
+            return Some(true);
+        }
+    }
+    None
+}
+
+fn available_macro_parameters(
+    ciphertext_modulus_log: u32,
+    security_level: u64,
+    search_space: &SearchSpace,
+    search_space_restriction: &impl SearchSpaceRestriction,
+    partition: PartitionIndex,
+) -> Vec<MacroParameters> {
+    let mut result = vec![];
+    for &glwe_dimension in &search_space.glwe_dimensions {
+        for &log2_polynomial_size in &search_space.glwe_log_polynomial_sizes {
+            let glwe_params = GlweParameters {
+                log2_polynomial_size,
+                glwe_dimension,
+            };
+            if glwe_params.minimal_variance(ciphertext_modulus_log, security_level) >= 1.0 {
+                // this is insecure, see optimize_macro
+                continue;
+            }
+            if !search_space_restriction.is_available_glwe(partition, glwe_params) {
+                continue;
+            }
+            for &internal_dim in &search_space.internal_lwe_dimensions {
+                let macro_params = MacroParameters {
+                    glwe_params,
+                    internal_dim,
+                };
+                if search_space_restriction.is_available_macro(partition, macro_params) {
+                    result.push(macro_params);
+                }
+            }
+        }
+    }
+    result
+}
+
 fn used_tlu_keyswitch(dag: &AnalyzedDag) -> Vec<Vec<bool>> {
     let mut result = vec![vec![false; dag.nb_partitions]; dag.nb_partitions];
     for (src_partition, dst_partition) in cross_partition(dag.nb_partitions) {
@@ -1225,6 +1759,54 @@ fn used_conversion_keyswitch(dag: &AnalyzedDag) -> Vec<Vec<bool>> {
     result
 }
 
+// Cost of each fhe operation with the given parameters, f64::MAX for the absent ones
+fn operations_cost(params: &Parameters, complexity: &ComplexityEvaluator) -> ComplexityValues {
+    let nb_partitions = params.macro_params.len();
+    let micro_params = &params.micro_params;
+    let mut cost = ComplexityValues::from_scheme(complexity.scheme());
+    for partition in PartitionIndex::range(0, nb_partitions) {
+        let macro_params = params.macro_params[partition.0].unwrap();
+        let internal_dim = macro_params.internal_dim;
+        cost.set_cost(
+            bootstrap(partition),
+            micro_params.pbs[partition.0].map_or(f64::MAX, |pbs| {
+                complexity.unit_cost(
+                    bootstrap(partition),
+                    pbs.complexity_br(internal_dim),
+                    pbs.key_size_br(internal_dim, macro_params.glwe_params),
+                )
+            }),
+        );
+        for src_partition in PartitionIndex::range(0, nb_partitions) {
+            let src_lwe_dim = params.macro_params[src_partition.0]
+                .unwrap()
+                .glwe_params
+                .sample_extract_lwe_dimension();
+            cost.set_cost(
+                keyswitch(src_partition, partition),
+                micro_params.ks[src_partition.0][partition.0].map_or(f64::MAX, |ks| {
+                    complexity.unit_cost(
+                        keyswitch(src_partition, partition),
+                        ks.complexity(src_lwe_dim),
+                        ks.key_size(src_lwe_dim, internal_dim),
+                    )
+                }),
+            );
+            cost.set_cost(
+                fast_keyswitch(src_partition, partition),
+                micro_params.fks[src_partition.0][partition.0].map_or(f64::MAX, |fks| {
+                    complexity.unit_cost(
+                        fast_keyswitch(src_partition, partition),
+                        fks.complexity,
+                        fks.key_size,
+                    )
+                }),
+            );
+        }
+    }
+    cost
+}
+
 #[allow(clippy::float_cmp)]
 fn sanity_check(
     params: &Parameters,
@@ -1232,6 +1814,7 @@ fn sanity_check(
     used_tlu_keyswitch: &[Vec<bool>],
     ciphertext_modulus_log: u32,
     security_level: u64,
+    key_distribution: KeyDistribution,
     feasible: &Feasible,
     complexity: &ComplexityEvaluator,
 ) {
@@ -1243,7 +1826,7 @@ fn sanity_check(
     let nb_partitions = params.macro_params.len();
     let mut operations = OperationsCV {
         variance: NoiseValues::from_scheme(complexity.scheme()),
-        cost: ComplexityValues::from_scheme(complexity.scheme()),
+        cost: operations_cost(params, complexity),
     };
     let micro_params = &params.micro_params;
     for partition in PartitionIndex::range(0, nb_partitions) {
@@ -1251,7 +1834,7 @@ fn sanity_check(
         let glwe_param = partition_macro.glwe_params;
         let internal_dim = partition_macro.internal_dim;
         let input_variance = glwe_param.minimal_variance(ciphertext_modulus_log, security_level);
-        let variance_modulus_switching = estimate_modulus_switching_noise_with_binary_key(
+        let variance_modulus_switching = key_distribution.modulus_switching_noise(
             internal_dim,
             glwe_param.log2_polynomial_size,
             ciphertext_modulus_log,
@@ -1267,14 +1850,10 @@ fn sanity_check(
             operations
                 .variance
                 .set_variance(bootstrap_noise(partition), this_pbs.noise_br(internal_dim));
-            operations
-                .cost
-                .set_cost(bootstrap(partition), this_pbs.complexity_br(internal_dim));
         } else {
             operations
                 .variance
                 .set_variance(bootstrap_noise(partition), f64::MAX);
-            operations.cost.set_cost(bootstrap(partition), f64::MAX);
         }
         for src_partition in PartitionIndex::range(0, nb_partitions) {
             let src_partition_macro = params.macro_params[src_partition.0].unwrap();
@@ -1289,10 +1868,6 @@ fn sanity_check(
                     keyswitch_noise(src_partition, partition),
                     this_ks.noise(src_lwe_dim),
                 );
-                operations.cost.set_cost(
-                    keyswitch(src_partition, partition),
-                    this_ks.complexity(src_lwe_dim),
-                );
             } else {
                 assert!(
                     !used_tlu_keyswitch[src_partition.0][partition.0],
@@ -1301,9 +1876,6 @@ fn sanity_check(
                 operations
                     .variance
                     .set_variance(keyswitch_noise(src_partition, partition), f64::MAX);
-                operations
-                    .cost
-                    .set_cost(keyswitch(src_partition, partition), f64::MAX);
             }
             if let Some(this_fks) = micro_params.fks[src_partition.0][partition.0] {
                 assert!(
@@ -1314,10 +1886,6 @@ fn sanity_check(
                     fast_keyswitch_noise(src_partition, partition),
                     this_fks.noise,
                 );
-                operations.cost.set_cost(
-                    fast_keyswitch(src_partition, partition),
-                    this_fks.complexity,
-                );
             } else {
                 assert!(
                     !used_conversion_keyswitch[src_partition.0][partition.0],
@@ -1326,9 +1894,6 @@ fn sanity_check(
                 operations
                     .variance
                     .set_variance(fast_keyswitch_noise(src_partition, partition), f64::MAX);
-                operations
-                    .cost
-                    .set_cost(fast_keyswitch(src_partition, partition), f64::MAX);
             }
         }
     }
@@ -1341,6 +1906,139 @@ fn sanity_check(
     }
 }
 
+// Explains a failure caused by the search space restriction, by comparing the polynomial sizes
+// found without restriction to the largest ones the restriction allows.
+// Only called when the optimization has failed.
+fn explain_restriction(
+    dag: &Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    search_space_restriction: &impl SearchSpaceRestriction,
+    persistent_caches: &PersistDecompCaches,
+    p_cut: &Option<PartitionCut>,
+    reports: &[PartitionReport],
+) -> Option<String> {
+    let (_, unrestricted) = optimize(
+        dag,
+        config,
+        search_space,
+        &NoSearchSpaceRestriction,
+        persistent_caches,
+        p_cut,
+        PartitionIndex::FIRST,
+    )
+    .ok()?;
+    let mut explanations = vec![];
+    for (i, macro_params) in unrestricted.macro_params.iter().enumerate() {
+        let Some(macro_params) = macro_params else {
+            continue;
+        };
+        let partition = PartitionIndex(i);
+        let max_log2_polynomial_size = search_space
+            .glwe_dimensions
+            .iter()
+            .flat_map(|&glwe_dimension| {
+                search_space
+                    .glwe_log_polynomial_sizes
+                    .iter()
+                    .map(move |&log2_polynomial_size| GlweParameters {
+                        log2_polynomial_size,
+                        glwe_dimension,
+                    })
+            })
+            .filter(|&glwe_params| {
+                search_space_restriction.is_available_glwe(partition, glwe_params)
+            })
+            .map(|glwe_params| glwe_params.log2_polynomial_size)
+            .max();
+        let precision = match reports.get(i).and_then(|report| report.precisions) {
+            Some((_, max_precision)) => format!(" (precision {max_precision})"),
+            None => String::new(),
+        };
+        let polynomial_size = macro_params.glwe_params.polynomial_size();
+        match max_log2_polynomial_size {
+            None => explanations.push(format!(
+                "partition {partition}{precision} requires polynomial_size {polynomial_size} \
+                 but the restriction allows no glwe parameters"
+            )),
+            Some(max_log2_polynomial_size)
+                if max_log2_polynomial_size < macro_params.glwe_params.log2_polynomial_size =>
+            {
+                explanations.push(format!(
+                    "partition {partition}{precision} requires polynomial_size {polynomial_size} \
+                     but the restriction caps it at {}",
+                    1_u64 << max_log2_polynomial_size
+                ));
+            }
+            Some(_) => (),
+        }
+    }
+    if explanations.is_empty() {
+        return Some(
+            "Parameters exist without the search space restriction, \
+             but the restriction excludes all of them"
+                .into(),
+        );
+    }
+    Some(explanations.join("\n"))
+}
+
+fn partition_reports(
+    dag: &Dag,
+    p_cut: &PartitionCut,
+    dag_and_params: &optimization::Result<(AnalyzedDag, Parameters)>,
+) -> Vec<PartitionReport> {
+    let (dag, _) = expand_round_and_index_map(dag);
+    let mut precisions: Vec<Option<(Precision, Precision)>> = vec![None; p_cut.n_partitions()];
+    for (op_i, op) in dag.operators.iter().enumerate() {
+        if let Operator::Lut { .. } = op {
+            let Some(partition) = p_cut.partition(&dag, OperatorIndex(op_i)) else {
+                continue;
+            };
+            let precision = dag.lut_in_precision(OperatorIndex(op_i));
+            let range = &mut precisions[partition.0];
+            *range = Some(range.map_or((precision, precision), |(min, max)| {
+                (min.min(precision), max.max(precision))
+            }));
+        }
+    }
+    let complexities = dag_and_params.as_ref().ok().map(|(dag, params)| {
+        let scheme = SymbolScheme::new(dag.nb_partitions);
+        let complexity = ComplexityEvaluator::from_scheme_and_counts(
+            &scheme,
+            &dag.operations_count,
+            Objective::MinComplexity,
+        );
+        complexity.evaluate_partition_costs(&operations_cost(params, &complexity))
+    });
+    precisions
+        .into_iter()
+        .enumerate()
+        .map(|(i, precisions)| {
+            let partition = PartitionIndex(i);
+            let (macro_params, feasibility) = match dag_and_params {
+                Ok((_, params)) => match params.macro_params.get(i).copied().flatten() {
+                    Some(macro_params) => (Some(macro_params), PartitionFeasibility::Feasible),
+                    None => (None, PartitionFeasibility::Unknown),
+                },
+                Err(optimization::Err::UnfeasibleVarianceConstraint(constraint))
+                    if constraint.partition == partition =>
+                {
+                    (None, PartitionFeasibility::Unfeasible)
+                }
+                Err(_) => (None, PartitionFeasibility::Unknown),
+            };
+            PartitionReport {
+                partition,
+                precisions,
+                macro_params,
+                feasibility,
+                complexity: complexities.as_ref().map(|complexities| complexities[i]),
+            }
+        })
+        .collect()
+}
+
 pub fn optimize_to_circuit_solution(
     dag: &Dag,
     config: Config,
@@ -1349,18 +2047,51 @@ pub fn optimize_to_circuit_solution(
     persistent_caches: &PersistDecompCaches,
     p_cut: &Option<PartitionCut>,
 ) -> keys_spec::CircuitSolution {
+    optimize_to_circuit_solution_with_report(
+        dag,
+        config,
+        search_space,
+        search_space_restriction,
+        persistent_caches,
+        p_cut,
+        &AtomicBool::new(false),
+        &mut Progress::none(),
+    )
+    .0
+}
+
+// Same as optimize_to_circuit_solution, with a report per partition, cancellable and with progress
+// The report is empty when the dag is optimized without partitions (no lut)
+pub fn optimize_to_circuit_solution_with_report(
+    dag: &Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    search_space_restriction: &impl SearchSpaceRestriction,
+    persistent_caches: &PersistDecompCaches,
+    p_cut: &Option<PartitionCut>,
+    cancel: &AtomicBool,
+    progress: &mut Progress<'_>,
+) -> (keys_spec::CircuitSolution, Vec<PartitionReport>) {
     if lut_count_from_dag(dag) == 0 && !dag.is_composed() {
         let nb_instr = dag.operators.len();
-        if let Some(sol) = optimize_mono(dag, config, search_space, persistent_caches).best_solution
+        if let Some(sol) =
+            optimize_mono_with_progress(dag, config, search_space, persistent_caches, progress)
+                .best_solution
         {
-            return keys_spec::CircuitSolution::from_native_solution(sol, nb_instr);
+            return (
+                keys_spec::CircuitSolution::from_native_solution(sol, nb_instr),
+                vec![],
+            );
         }
-        return keys_spec::CircuitSolution::no_solution(
-            optimization::Err::NoParametersFound.to_string(),
+        return (
+            keys_spec::CircuitSolution::no_solution(
+                optimization::Err::NoParametersFound.to_string(),
+            ),
+            vec![],
         );
     }
     let default_partition = PartitionIndex::FIRST;
-    let dag_and_params = optimize(
+    let dag_and_params = optimize_cancellable(
         dag,
         config,
         search_space,
@@ -1368,22 +2099,59 @@ pub fn optimize_to_circuit_solution(
         persistent_caches,
         p_cut,
         default_partition,
+        cancel,
+        progress,
     );
+    let report = match &dag_and_params {
+        Ok((analyzed_dag, _)) => partition_reports(dag, &analyzed_dag.p_cut, &dag_and_params),
+        Err(_) => {
+            let p_cut = p_cut
+                .clone()
+                .unwrap_or_else(|| PartitionCut::for_each_precision(dag));
+            partition_reports(dag, &p_cut, &dag_and_params)
+        }
+    };
     #[allow(clippy::option_if_let_else)]
-    match dag_and_params {
-        Err(e) => keys_spec::CircuitSolution::no_solution(e.to_string()),
+    let solution = match dag_and_params {
+        Err(e @ (optimization::Err::Cancelled | optimization::Err::NotComposable(_))) => {
+            keys_spec::CircuitSolution::no_solution(e.to_string())
+        }
+        Err(e) => {
+            let explanation = explain_restriction(
+                dag,
+                config,
+                search_space,
+                search_space_restriction,
+                persistent_caches,
+                p_cut,
+                &report,
+            );
+            keys_spec::CircuitSolution::no_solution(match explanation {
+                Some(explanation) => format!("{e}\n{explanation}"),
+                None => e.to_string(),
+            })
+        }
         Ok((dag, params)) => {
             let ext_keys = keys_spec::ExpandedCircuitKeys::of(&params);
+            let unshared_secret_key_count =
+                (ext_keys.big_secret_keys.len() + ext_keys.small_secret_keys.len()) as u64;
             let instructions_keys = analyze::original_instrs_partition(&dag, &ext_keys);
             let (ext_keys, instructions_keys) = if config.key_sharing {
                 let (ext_keys, key_sharing) = ext_keys.shared_keys();
                 let instructions_keys =
                     InstructionKeys::shared_keys(&instructions_keys, &key_sharing);
                 (ext_keys, instructions_keys)
+            } else if !search_space.key_sharing_groups.is_empty() {
+                let (ext_keys, key_sharing) =
+                    ext_keys.shared_keys_in_groups(&search_space.key_sharing_groups);
+                let instructions_keys =
+                    InstructionKeys::shared_keys(&instructions_keys, &key_sharing);
+                (ext_keys, instructions_keys)
             } else {
                 (ext_keys, instructions_keys)
             };
             let circuit_keys = ext_keys.compacted();
+            let used_external_partitions = dag.used_external_partitions();
             keys_spec::CircuitSolution {
                 circuit_keys,
                 instructions_keys,
@@ -1393,9 +2161,12 @@ pub fn optimize_to_circuit_solution(
                 global_p_error: params.global_p_error,
                 is_feasible: true,
                 error_msg: String::default(),
+                used_external_partitions,
+                unshared_secret_key_count,
             }
         }
-    }
+    };
+    (solution, report)
 }
 
 #[cfg(test)]
@@ -5,6 +5,7 @@ use crate::{
     parameters::{BrDecompositionParameters, GlweParameters, KsDecompositionParameters},
 };
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
 use super::MacroParameters;
 
@@ -228,6 +229,9 @@ impl SearchSpaceRestriction for NoSearchSpaceRestriction {
 #[derive(Serialize, Deserialize)]
 pub struct RangeRestriction {
     pub glwe_log_polynomial_sizes: Vec<u64>,
+    /// Absolute polynomial sizes, taking precedence over `glwe_log_polynomial_sizes` when non-empty.
+    #[serde(default)]
+    pub glwe_polynomial_sizes: Vec<u64>,
     pub glwe_dimensions: Vec<u64>,
     pub internal_lwe_dimensions: Vec<u64>,
     pub pbs_level_count: Vec<u64>,
@@ -236,14 +240,126 @@ pub struct RangeRestriction {
     pub ks_base_log: Vec<u64>,
 }
 
+/// Largest decomposition base log and level count, i.e. the bits of a 64 bits ciphertext modulus.
+const MAX_DECOMPOSITION_BITS: u64 = 64;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RangeRestrictionError {
+    NotSortedAndDeduplicated(&'static str),
+    OutOfBounds {
+        field: &'static str,
+        value: u64,
+        min: u64,
+        max: u64,
+    },
+    PolynomialSizeNotPowerOfTwo(u64),
+}
+
+impl fmt::Display for RangeRestrictionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotSortedAndDeduplicated(field) => write!(
+                f,
+                "Range restriction {field} is not sorted and deduplicated"
+            ),
+            Self::OutOfBounds {
+                field,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "Range restriction {field} has a value {value} outside of [{min}, {max}]"
+            ),
+            Self::PolynomialSizeNotPowerOfTwo(polynomial_size) => write!(
+                f,
+                "Range restriction has a polynomial size {polynomial_size} which is not a power of two"
+            ),
+        }
+    }
+}
+
+fn check_range(
+    field: &'static str,
+    values: &[u64],
+    min: u64,
+    max: u64,
+) -> Result<(), RangeRestrictionError> {
+    if !values.windows(2).all(|w| w[0] < w[1]) {
+        return Err(RangeRestrictionError::NotSortedAndDeduplicated(field));
+    }
+    if let Some(&value) = values.iter().find(|v| !(min..=max).contains(*v)) {
+        return Err(RangeRestrictionError::OutOfBounds {
+            field,
+            value,
+            min,
+            max,
+        });
+    }
+    Ok(())
+}
+
+impl RangeRestriction {
+    /// Checks the restriction is consistent, so that it cannot silently prune the whole search space.
+    /// An empty range is valid and does not restrict its parameter.
+    pub fn validate(&self) -> Result<(), RangeRestrictionError> {
+        check_range(
+            "glwe_log_polynomial_sizes",
+            &self.glwe_log_polynomial_sizes,
+            1,
+            63,
+        )?;
+        check_range(
+            "glwe_polynomial_sizes",
+            &self.glwe_polynomial_sizes,
+            2,
+            1 << 63,
+        )?;
+        if let Some(&polynomial_size) = self
+            .glwe_polynomial_sizes
+            .iter()
+            .find(|size| !size.is_power_of_two())
+        {
+            return Err(RangeRestrictionError::PolynomialSizeNotPowerOfTwo(
+                polynomial_size,
+            ));
+        }
+        check_range("glwe_dimensions", &self.glwe_dimensions, 1, u64::MAX)?;
+        check_range(
+            "internal_lwe_dimensions",
+            &self.internal_lwe_dimensions,
+            1,
+            u64::MAX,
+        )?;
+        for (field, values) in [
+            ("pbs_level_count", &self.pbs_level_count),
+            ("pbs_base_log", &self.pbs_base_log),
+            ("ks_level_count", &self.ks_level_count),
+            ("ks_base_log", &self.ks_base_log),
+        ] {
+            check_range(field, values, 1, MAX_DECOMPOSITION_BITS)?;
+        }
+        Ok(())
+    }
+
+    fn is_available_polynomial_size(&self, glwe_params: GlweParameters) -> bool {
+        if self.glwe_polynomial_sizes.is_empty() {
+            self.glwe_log_polynomial_sizes.is_empty()
+                || self
+                    .glwe_log_polynomial_sizes
+                    .contains(&glwe_params.log2_polynomial_size)
+        } else {
+            self.glwe_polynomial_sizes
+                .contains(&glwe_params.polynomial_size())
+        }
+    }
+}
+
 impl SearchSpaceRestriction for RangeRestriction {
     fn is_available_glwe(&self, _partition: PartitionIndex, glwe_params: GlweParameters) -> bool {
         (self.glwe_dimensions.is_empty()
             || self.glwe_dimensions.contains(&glwe_params.glwe_dimension))
-            && (self.glwe_log_polynomial_sizes.is_empty()
-                || self
-                    .glwe_log_polynomial_sizes
-                    .contains(&glwe_params.log2_polynomial_size))
+            && self.is_available_polynomial_size(glwe_params)
     }
 
     fn is_available_macro(
@@ -255,10 +371,7 @@ impl SearchSpaceRestriction for RangeRestriction {
             || self
                 .glwe_dimensions
                 .contains(&macro_parameters.glwe_params.glwe_dimension))
-            && (self.glwe_log_polynomial_sizes.is_empty()
-                || self
-                    .glwe_log_polynomial_sizes
-                    .contains(&macro_parameters.glwe_params.log2_polynomial_size))
+            && self.is_available_polynomial_size(macro_parameters.glwe_params)
             && (self.internal_lwe_dimensions.is_empty()
                 || self
                     .internal_lwe_dimensions
@@ -275,10 +388,7 @@ impl SearchSpaceRestriction for RangeRestriction {
             || self
                 .glwe_dimensions
                 .contains(&macro_parameters.glwe_params.glwe_dimension))
-            && (self.glwe_log_polynomial_sizes.is_empty()
-                || self
-                    .glwe_log_polynomial_sizes
-                    .contains(&macro_parameters.glwe_params.log2_polynomial_size))
+            && self.is_available_polynomial_size(macro_parameters.glwe_params)
             && (self.internal_lwe_dimensions.is_empty()
                 || self
                     .internal_lwe_dimensions
@@ -316,41 +426,104 @@ impl SearchSpaceRestriction for RangeRestriction {
     }
 }
 
-#[allow(unused)]
 #[derive(Serialize, Deserialize)]
 pub struct LweSecretKeyInfo {
-    lwe_dimension: u64,
+    pub lwe_dimension: u64,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct LweBootstrapKeyInfo {
-    level_count: u64,
-    base_log: u64,
-    glwe_dimension: u64,
-    polynomial_size: u64,
-    input_lwe_dimension: u64,
+    pub level_count: u64,
+    pub base_log: u64,
+    pub glwe_dimension: u64,
+    pub polynomial_size: u64,
+    pub input_lwe_dimension: u64,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct LweKeyswitchKeyInfo {
-    level_count: u64,
-    base_log: u64,
-    input_lwe_dimension: u64,
-    output_lwe_dimension: u64,
+    pub level_count: u64,
+    pub base_log: u64,
+    pub input_lwe_dimension: u64,
+    pub output_lwe_dimension: u64,
 }
 
-#[allow(unused)]
 #[derive(Serialize, Deserialize)]
 pub struct KeysetInfo {
-    lwe_secret_keys: Vec<LweSecretKeyInfo>,
-    lwe_bootstrap_keys: Vec<LweBootstrapKeyInfo>,
-    lwe_keyswitch_keys: Vec<LweKeyswitchKeyInfo>,
+    pub lwe_secret_keys: Vec<LweSecretKeyInfo>,
+    pub lwe_bootstrap_keys: Vec<LweBootstrapKeyInfo>,
+    pub lwe_keyswitch_keys: Vec<LweKeyswitchKeyInfo>,
 }
 
 /// An object restricting the search space based on a keyset.
 #[derive(Serialize, Deserialize)]
 pub struct KeysetRestriction {
-    info: KeysetInfo,
+    pub info: KeysetInfo,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeysetRestrictionError {
+    NoBootstrapKey,
+    PolynomialSizeNotPowerOfTwo(u64),
+    EmptyDecomposition { level_count: u64, base_log: u64 },
+}
+
+impl fmt::Display for KeysetRestrictionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoBootstrapKey => write!(f, "Keyset restriction has no bootstrap key"),
+            Self::PolynomialSizeNotPowerOfTwo(polynomial_size) => write!(
+                f,
+                "Keyset restriction has a polynomial size {polynomial_size} which is not a power of two"
+            ),
+            Self::EmptyDecomposition {
+                level_count,
+                base_log,
+            } => write!(
+                f,
+                "Keyset restriction has an empty decomposition (level count {level_count}, base log {base_log})"
+            ),
+        }
+    }
+}
+
+impl KeysetRestriction {
+    /// Checks the keyset can restrict a search, i.e. it has usable bootstrap keys.
+    pub fn check(&self) -> Result<(), KeysetRestrictionError> {
+        if self.info.lwe_bootstrap_keys.is_empty() {
+            return Err(KeysetRestrictionError::NoBootstrapKey);
+        }
+        let decompositions = self
+            .info
+            .lwe_bootstrap_keys
+            .iter()
+            .map(|k| (k.level_count, k.base_log))
+            .chain(
+                self.info
+                    .lwe_keyswitch_keys
+                    .iter()
+                    .map(|k| (k.level_count, k.base_log)),
+            );
+        for (level_count, base_log) in decompositions {
+            if level_count == 0 || base_log == 0 {
+                return Err(KeysetRestrictionError::EmptyDecomposition {
+                    level_count,
+                    base_log,
+                });
+            }
+        }
+        if let Some(k) = self
+            .info
+            .lwe_bootstrap_keys
+            .iter()
+            .find(|k| !k.polynomial_size.is_power_of_two())
+        {
+            return Err(KeysetRestrictionError::PolynomialSizeNotPowerOfTwo(
+                k.polynomial_size,
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl SearchSpaceRestriction for KeysetRestriction {
@@ -28,6 +28,7 @@ fn default_config() -> Config<'static> {
         ciphertext_modulus_log: 64,
         fft_precision: 53,
         complexity_model,
+        objective: Objective::MinComplexity,
     }
 }
 
@@ -610,6 +611,41 @@ fn test_multi_rounded_fks_coherency() {
     }
 }
 
+fn is_feasible(dag: &unparametrized::Dag) -> bool {
+    super::is_feasible(
+        dag,
+        default_config(),
+        &SearchSpace::default_cpu(),
+        &NoSearchSpaceRestriction,
+        &SHARED_CACHES,
+        &None,
+    )
+}
+
+#[test]
+fn test_is_feasible() {
+    for precision in [1, 4, 8] {
+        let dag = v0_dag(0, precision, 1.0);
+        assert!(is_feasible(&dag));
+    }
+    // only feasible with one set of parameters per partition
+    let mut dag = v0_dag(0, 1, MAX_WEIGHT[1] as f64);
+    for out_precision in 2..12 {
+        add_v0_dag(
+            &mut dag,
+            0,
+            out_precision,
+            MAX_WEIGHT[out_precision as usize] as f64,
+        );
+    }
+    assert!(is_feasible(&dag));
+    assert!(optimize(&dag, &None, PartitionIndex(0)).is_some());
+    // far beyond any feasible noise
+    let dag = v0_dag(0, 8, 1e20);
+    assert!(!is_feasible(&dag));
+    assert!(optimize(&dag, &None, PartitionIndex(0)).is_none());
+}
+
 #[test]
 fn test_levelled_only() {
     let mut dag = unparametrized::Dag::new();
@@ -631,6 +667,269 @@ fn test_levelled_only() {
     assert!(sol.circuit_keys.secret_keys[0].polynomial_size == sol_mono.glwe_polynomial_size);
 }
 
+fn key_size(params: &Parameters) -> f64 {
+    let macro_params: Vec<_> = params.macro_params.iter().map(|m| m.unwrap()).collect();
+    let micro_params = &params.micro_params;
+    let mut key_size = 0.0;
+    for (partition, macro_param) in macro_params.iter().enumerate() {
+        if let Some(pbs) = micro_params.pbs[partition] {
+            key_size += pbs.key_size_br(macro_param.internal_dim, macro_param.glwe_params);
+        }
+        for (src_partition, src_macro_param) in macro_params.iter().enumerate() {
+            if let Some(ks) = micro_params.ks[src_partition][partition] {
+                key_size += ks.key_size(
+                    src_macro_param.glwe_params.sample_extract_lwe_dimension(),
+                    macro_param.internal_dim,
+                );
+            }
+            if let Some(fks) = micro_params.fks[src_partition][partition] {
+                key_size += fks.key_size;
+            }
+        }
+    }
+    key_size
+}
+
+fn optimize_with_objective(dag: &unparametrized::Dag, objective: Objective) -> Parameters {
+    let config = Config {
+        objective,
+        ..default_config()
+    };
+    super::optimize(
+        dag,
+        config,
+        &SearchSpace::default_cpu(),
+        &NoSearchSpaceRestriction,
+        &SHARED_CACHES,
+        &None,
+        LOW_PARTITION,
+    )
+    .unwrap()
+    .1
+}
+
+#[test]
+fn test_optimize_min_key_size() {
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(4, Shape::number());
+    let input2 = dag.add_input(8, Shape::number());
+    let lut1 = dag.add_lut(input1, FunctionTable::UNKWOWN, 8);
+    let lut2 = dag.add_lut(input2, FunctionTable::UNKWOWN, 8);
+    let _ = dag.add_dot([lut1, lut2], [1, 1]);
+    let min_complexity = optimize(&dag, &None, LOW_PARTITION).unwrap();
+    let min_key_size = optimize_with_objective(&dag, Objective::MinKeySize);
+    assert!(min_key_size.p_error <= _4_SIGMA);
+    assert!(key_size(&min_key_size) < key_size(&min_complexity));
+    // the actual complexity is reported, not the key size
+    assert!(min_key_size.complexity > min_complexity.complexity);
+}
+
+#[test]
+fn test_optimize_weighted_objective() {
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(4, Shape::number());
+    let input2 = dag.add_input(8, Shape::number());
+    let lut1 = dag.add_lut(input1, FunctionTable::UNKWOWN, 8);
+    let lut2 = dag.add_lut(input2, FunctionTable::UNKWOWN, 8);
+    let _ = dag.add_dot([lut1, lut2], [1, 1]);
+    let min_complexity = optimize_with_objective(&dag, Objective::MinComplexity);
+    let min_key_size = optimize_with_objective(&dag, Objective::MinKeySize);
+    // both terms weigh the same for the min complexity solution
+    let weighted_objective = Objective::Weighted {
+        complexity_weight: 1.0,
+        key_size_weight: min_complexity.complexity / key_size(&min_complexity),
+    };
+    let weighted = optimize_with_objective(&dag, weighted_objective);
+    let cost = |params: &Parameters| weighted_objective.cost(params.complexity, key_size(params));
+    assert!(weighted.p_error <= _4_SIGMA);
+    assert!(cost(&weighted) <= cost(&min_complexity));
+    assert!(cost(&weighted) <= cost(&min_key_size));
+    // a null key size weight is the same as minimizing complexity
+    let complexity_only = optimize_with_objective(
+        &dag,
+        Objective::Weighted {
+            complexity_weight: 1.0,
+            key_size_weight: 0.0,
+        },
+    );
+    assert!(complexity_only.complexity == min_complexity.complexity);
+}
+
+#[test]
+fn test_partition_report() {
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(4, Shape::number());
+    let input2 = dag.add_input(8, Shape::number());
+    let _ = dag.add_lut(input1, FunctionTable::UNKWOWN, 4);
+    let _ = dag.add_lut(input2, FunctionTable::UNKWOWN, 8);
+    let config = default_config();
+    let search_space = SearchSpace::default_cpu();
+    let (sol, report) = super::optimize_to_circuit_solution_with_report(
+        &dag,
+        config,
+        &search_space,
+        &NoSearchSpaceRestriction,
+        &SHARED_CACHES,
+        &None,
+        &AtomicBool::new(false),
+        &mut Progress::none(),
+    );
+    assert!(sol.is_feasible);
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].precisions, Some((4, 4)));
+    assert_eq!(report[1].precisions, Some((8, 8)));
+    for partition in &report {
+        assert_eq!(partition.feasibility, PartitionFeasibility::Feasible);
+        assert!(partition.macro_params.is_some());
+        assert!(partition.complexity.unwrap() > 0.0);
+    }
+    let total_complexity: f64 = report.iter().map(|p| p.complexity.unwrap()).sum();
+    assert!((total_complexity - sol.complexity).abs() <= 1e-9 * sol.complexity);
+    assert!(report[1].complexity > report[0].complexity);
+
+    let dag = v0_dag(0, 8, 1e20);
+    let (sol, report) = super::optimize_to_circuit_solution_with_report(
+        &dag,
+        config,
+        &search_space,
+        &NoSearchSpaceRestriction,
+        &SHARED_CACHES,
+        &None,
+        &AtomicBool::new(false),
+        &mut Progress::none(),
+    );
+    assert!(!sol.is_feasible);
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].feasibility, PartitionFeasibility::Unfeasible);
+    assert!(report[0].macro_params.is_none());
+    assert!(report[0].complexity.is_none());
+}
+
+#[test]
+fn test_restriction_explanation() {
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(4, Shape::number());
+    let input2 = dag.add_input(8, Shape::number());
+    let _ = dag.add_lut(input1, FunctionTable::UNKWOWN, 4);
+    let _ = dag.add_lut(input2, FunctionTable::UNKWOWN, 8);
+    let restriction = RangeRestriction {
+        glwe_log_polynomial_sizes: vec![10],
+        glwe_polynomial_sizes: vec![],
+        glwe_dimensions: vec![],
+        internal_lwe_dimensions: vec![],
+        pbs_level_count: vec![],
+        pbs_base_log: vec![],
+        ks_level_count: vec![],
+        ks_base_log: vec![],
+    };
+    let (sol, _) = super::optimize_to_circuit_solution_with_report(
+        &dag,
+        default_config(),
+        &SearchSpace::default_cpu(),
+        &restriction,
+        &SHARED_CACHES,
+        &None,
+        &AtomicBool::new(false),
+        &mut Progress::none(),
+    );
+    assert!(!sol.is_feasible);
+    assert!(sol
+        .error_msg
+        .contains("partition 1 (precision 8) requires polynomial_size"));
+    assert!(sol
+        .error_msg
+        .contains("but the restriction caps it at 1024"));
+}
+
+#[test]
+fn test_optimize_cancelled() {
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(4, Shape::number());
+    let input2 = dag.add_input(8, Shape::number());
+    let _ = dag.add_lut(input1, FunctionTable::UNKWOWN, 4);
+    let _ = dag.add_lut(input2, FunctionTable::UNKWOWN, 8);
+    let config = default_config();
+    let search_space = SearchSpace::default_cpu();
+    let cancelled = optimize_cancellable(
+        &dag,
+        config,
+        &search_space,
+        &NoSearchSpaceRestriction,
+        &SHARED_CACHES,
+        &None,
+        PartitionIndex::FIRST,
+        &AtomicBool::new(true),
+        &mut Progress::none(),
+    );
+    assert!(matches!(cancelled, Err(optimization::Err::Cancelled)));
+    let (sol, _) = super::optimize_to_circuit_solution_with_report(
+        &dag,
+        config,
+        &search_space,
+        &NoSearchSpaceRestriction,
+        &SHARED_CACHES,
+        &None,
+        &AtomicBool::new(true),
+        &mut Progress::none(),
+    );
+    assert!(!sol.is_feasible);
+    assert_eq!(sol.error_msg, "cancelled");
+}
+
+#[test]
+fn test_optimize_progress() {
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(4, Shape::number());
+    let input2 = dag.add_input(8, Shape::number());
+    let _ = dag.add_lut(input1, FunctionTable::UNKWOWN, 4);
+    let _ = dag.add_lut(input2, FunctionTable::UNKWOWN, 8);
+    let config = default_config();
+    let search_space = SearchSpace::default_cpu();
+    let last_step = std::cell::Cell::new(0);
+    let callback = |step| {
+        assert_eq!(step, last_step.get() + 1);
+        last_step.set(step);
+    };
+    let mut progress = Progress::new(&callback);
+    let (sol, _) = super::optimize_to_circuit_solution_with_report(
+        &dag,
+        config,
+        &search_space,
+        &NoSearchSpaceRestriction,
+        &SHARED_CACHES,
+        &None,
+        &AtomicBool::new(false),
+        &mut progress,
+    );
+    assert!(sol.is_feasible);
+    assert!(last_step.get() > 0);
+    assert_eq!(progress.steps(), last_step.get());
+}
+
+#[test]
+fn test_explicit_partition_assignment() {
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(4, Shape::number());
+    let input2 = dag.add_input(8, Shape::number());
+    let _ = dag.add_lut(input1, FunctionTable::UNKWOWN, 4);
+    let _ = dag.add_lut(input2, FunctionTable::UNKWOWN, 8);
+    let assignment = |partitions: [usize; 4]| {
+        PartitionCut::from_assignment(&dag, &partitions.map(PartitionIndex)).unwrap()
+    };
+
+    let p_cut = assignment([0, 0, 5, 5]);
+    assert_eq!(p_cut.n_partitions(), 1);
+    let sol = optimize(&dag, &Some(p_cut), LOW_PARTITION).unwrap();
+    assert_eq!(sol.macro_params.len(), 1);
+
+    let p_cut = assignment([0, 0, 7, 3]);
+    assert_eq!(p_cut.n_partitions(), 2);
+    let sol = optimize(&dag, &Some(p_cut), LOW_PARTITION).unwrap();
+    assert_eq!(sol.macro_params.len(), 2);
+
+    assert!(PartitionCut::from_assignment(&dag, &[PartitionIndex(0)]).is_err());
+}
+
 #[test]
 fn test_big_secret_key_sharing() {
     let mut dag = unparametrized::Dag::new();
@@ -647,6 +946,7 @@ fn test_big_secret_key_sharing() {
         ciphertext_modulus_log: 64,
         fft_precision: 53,
         complexity_model: &CpuComplexity::default(),
+        objective: Objective::MinComplexity,
     };
     let config_no_sharing = Config {
         key_sharing: false,
@@ -672,6 +972,8 @@ fn test_big_secret_key_sharing() {
         &SHARED_CACHES,
         &None,
     );
+    assert!(sol_sharing.unshared_secret_key_count == 4);
+    assert!(sol_no_sharing.unshared_secret_key_count == 4);
     let keys_sharing = sol_sharing.circuit_keys;
     let keys_no_sharing = sol_no_sharing.circuit_keys;
     assert!(keys_sharing.secret_keys.len() == 3);
@@ -682,6 +984,55 @@ fn test_big_secret_key_sharing() {
     assert!(keys_sharing.keyswitch_keys.len() == keys_no_sharing.keyswitch_keys.len());
 }
 
+#[test]
+fn test_key_sharing_groups() {
+    let mut dag = unparametrized::Dag::new();
+    let input1 = dag.add_input(4, Shape::number());
+    let input2 = dag.add_input(5, Shape::number());
+    let input2 = dag.add_dot([input2], [128]);
+    let lut1 = dag.add_lut(input1, FunctionTable::UNKWOWN, 5);
+    let lut2 = dag.add_lut(input2, FunctionTable::UNKWOWN, 5);
+    let _ = dag.add_dot([lut1, lut2], [16, 1]);
+    let config = Config {
+        key_sharing: false,
+        ..default_config()
+    };
+    let mut search_space = SearchSpace::default_cpu();
+    let sol_no_group = super::optimize_to_circuit_solution(
+        &dag,
+        config,
+        &search_space,
+        &NoSearchSpaceRestriction,
+        &SHARED_CACHES,
+        &None,
+    );
+    search_space.key_sharing_groups = vec![vec![0, 1]];
+    let sol_group = super::optimize_to_circuit_solution(
+        &dag,
+        config,
+        &search_space,
+        &NoSearchSpaceRestriction,
+        &SHARED_CACHES,
+        &None,
+    );
+    assert!(sol_group.is_feasible);
+    let keys_no_group = sol_no_group.circuit_keys;
+    let keys_group = sol_group.circuit_keys;
+    assert_eq!(keys_no_group.secret_keys.len(), 4);
+    // only the big keys are shared
+    assert_eq!(keys_group.secret_keys.len(), 3);
+    assert_eq!(keys_group.bootstrap_keys.len(), 2);
+    assert_eq!(
+        keys_group.bootstrap_keys[0].output_key.identifier,
+        keys_group.bootstrap_keys[1].output_key.identifier
+    );
+    assert_ne!(
+        keys_group.bootstrap_keys[0].input_key.identifier,
+        keys_group.bootstrap_keys[1].input_key.identifier
+    );
+    assert!(sol_group.complexity >= sol_no_group.complexity);
+}
+
 #[test]
 fn test_big_and_small_secret_key() {
     let mut dag = unparametrized::Dag::new();
@@ -698,6 +1049,7 @@ fn test_big_and_small_secret_key() {
         ciphertext_modulus_log: 64,
         fft_precision: 53,
         complexity_model: &CpuComplexity::default(),
+        objective: Objective::MinComplexity,
     };
     let config_no_sharing = Config {
         key_sharing: false,
@@ -819,6 +1171,9 @@ fn test_maximal_multi() {
     let p_cut = PartitionCut::maximal_partitionning(&dag);
     let sol = optimize(&dag, &Some(p_cut.clone()), PartitionIndex(0)).unwrap();
     assert!(sol.macro_params.len() == 2);
+    assert_eq!(PartitionCut::lut_log_norm2(&dag, lut1), Some(0.0));
+    assert_eq!(PartitionCut::lut_log_norm2(&dag, lut2), Some(16.0));
+    assert_eq!(PartitionCut::lut_log_norm2(&dag, input), None);
 
     eprintln!("{:?}", sol.micro_params.pbs);
 
@@ -935,6 +1290,65 @@ fn test_optimize_tfhers_different_in_out_2lut_compute() {
     assert!(sol.is_some());
 }
 
+#[test]
+fn test_search_space_size() {
+    let search_space = SearchSpace::default_cpu();
+    let full = search_space.glwe_dimensions.len()
+        * search_space.glwe_log_polynomial_sizes.len()
+        * search_space.internal_lwe_dimensions.len();
+    assert_eq!(
+        search_space_size(&search_space, &NoSearchSpaceRestriction),
+        full as u64
+    );
+    let restriction = RangeRestriction {
+        glwe_log_polynomial_sizes: vec![],
+        glwe_polynomial_sizes: vec![],
+        glwe_dimensions: vec![1],
+        internal_lwe_dimensions: search_space.internal_lwe_dimensions[..2].to_vec(),
+        pbs_level_count: vec![],
+        pbs_base_log: vec![],
+        ks_level_count: vec![],
+        ks_base_log: vec![],
+    };
+    assert_eq!(
+        search_space_size(&search_space, &restriction),
+        2 * search_space.glwe_log_polynomial_sizes.len() as u64
+    );
+}
+
+#[test]
+fn test_optimize_tfhers_stable_partitions() {
+    let variance = get_tfhers_noise_br();
+    let tfhers_partition = |name: &str| ExternalPartition {
+        name: String::from(name),
+        macro_params: TFHERS_MACRO_PARAMS,
+        max_variance: variance * 6.0,
+        variance,
+    };
+    let mut dag = unparametrized::Dag::new();
+    let tfhers_precision = 8;
+    let input = dag.add_input(tfhers_precision, Shape::number());
+    let change_part1 = dag.add_change_partition(input, Some(tfhers_partition("tfhers_in")), None);
+    let lut1 = dag.add_lut(change_part1, FunctionTable::UNKWOWN, 4);
+    let lut2 = dag.add_lut(lut1, FunctionTable::UNKWOWN, tfhers_precision);
+    let _ = dag.add_change_partition(lut2, None, Some(tfhers_partition("tfhers_out")));
+
+    let first = optimize(&dag, &None, PartitionIndex(0)).unwrap();
+    for _ in 0..4 {
+        let p_cut = PartitionCut::for_each_precision(&dag);
+        let names: Vec<_> = p_cut
+            .external_partitions
+            .iter()
+            .map(|partition| partition.name.as_str())
+            .collect();
+        assert_eq!(names, ["tfhers_in", "tfhers_out"]);
+        let sol = optimize(&dag, &None, PartitionIndex(0)).unwrap();
+        assert_eq!(sol.macro_params, first.macro_params);
+        assert_eq!(sol.complexity.to_bits(), first.complexity.to_bits());
+        assert_eq!(sol.p_error.to_bits(), first.p_error.to_bits());
+    }
+}
+
 #[test]
 fn test_optimize_tfhers_input_constraints() {
     let variances = [1.0, 6.14e-14, 2.14e-16];
@@ -967,6 +1381,131 @@ fn test_optimize_tfhers_input_constraints() {
     }
 }
 
+#[test]
+fn test_optimize_tfhers_input_in_partition() {
+    let variance = 6.14e-14;
+    let tfhers_partition = ExternalPartition {
+        name: String::from("tfhers"),
+        macro_params: TFHERS_MACRO_PARAMS,
+        max_variance: variance * 4.0,
+        variance,
+    };
+    let tfhers_precision = 4;
+
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(tfhers_precision, Shape::number());
+    let change_part1 = dag.add_change_partition(input, Some(tfhers_partition.clone()), None);
+    let lut = dag.add_lut(change_part1, FunctionTable::UNKWOWN, tfhers_precision);
+    _ = dag.add_dot([lut], [128]);
+    let with_change_partition = optimize(&dag, &None, PartitionIndex(0)).unwrap();
+
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input_in_partition(tfhers_precision, Shape::number(), tfhers_partition);
+    let lut = dag.add_lut(input, FunctionTable::UNKWOWN, tfhers_precision);
+    _ = dag.add_dot([lut], [128]);
+    let in_partition = optimize(&dag, &None, PartitionIndex(0)).unwrap();
+
+    assert_eq!(in_partition.complexity, with_change_partition.complexity);
+    assert_eq!(in_partition.p_error, with_change_partition.p_error);
+}
+
+#[test]
+fn test_optimize_tfhers_change_partition_src_and_dst() {
+    let variance = get_tfhers_noise_br();
+    let tfhers_partition_in = ExternalPartition {
+        name: String::from("tfhers_in"),
+        macro_params: TFHERS_MACRO_PARAMS,
+        max_variance: variance * 4.0,
+        variance,
+    };
+    let tfhers_partition_out = ExternalPartition {
+        name: String::from("tfhers_out"),
+        macro_params: TFHERS_MACRO_PARAMS,
+        max_variance: variance * 4.0,
+        variance,
+    };
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(4, Shape::number());
+    let change_part = dag.add_change_partition(
+        input,
+        Some(tfhers_partition_in.clone()),
+        Some(tfhers_partition_out.clone()),
+    );
+    _ = dag.add_lut(change_part, FunctionTable::UNKWOWN, 4);
+
+    let p_cut = PartitionCut::maximal_partitionning(&dag);
+    let src = p_cut.external_partition(&tfhers_partition_in).unwrap();
+    let dst = p_cut.external_partition(&tfhers_partition_out).unwrap();
+    let sol = optimize(&dag, &Some(p_cut), LOW_PARTITION).unwrap();
+    // The conversion needs a keyswitch key from one external partition to the other
+    assert!(sol.micro_params.fks[src.0][dst.0].is_some());
+}
+
+#[test]
+fn test_optimize_keyswitch() {
+    let variance = get_tfhers_noise_br();
+    let tfhers_partition = ExternalPartition {
+        name: String::from("tfhers"),
+        macro_params: TFHERS_MACRO_PARAMS,
+        max_variance: variance * 4.0,
+        variance,
+    };
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(4, Shape::number());
+    let lut = dag.add_lut(input, FunctionTable::UNKWOWN, 4);
+    let without_keyswitch = optimize(&dag, &None, PartitionIndex(0)).unwrap();
+
+    let keyswitch = dag.add_keyswitch(lut, tfhers_partition.clone());
+    dag.tag_operator_as_output(keyswitch);
+    let p_cut = PartitionCut::maximal_partitionning(&dag);
+    let dst = p_cut.external_partition(&tfhers_partition).unwrap();
+    let src = PartitionIndex((dst.0 + 1) % 2);
+    let sol = optimize(&dag, &Some(p_cut), src).unwrap();
+    // Only the keyswitch is in the external partition, the lut keeps its own parameters
+    assert!(sol.micro_params.fks[src.0][dst.0].is_some());
+    assert!(sol.micro_params.ks[src.0][src.0].is_some());
+    assert!(sol.micro_params.ks[dst.0][dst.0].is_none());
+    assert!(sol.complexity > without_keyswitch.complexity);
+}
+
+#[test]
+fn test_used_external_partitions() {
+    let variance = get_tfhers_noise_br();
+    let tfhers_partition = ExternalPartition {
+        name: String::from("tfhers"),
+        macro_params: TFHERS_MACRO_PARAMS,
+        max_variance: variance * 4.0,
+        variance,
+    };
+    let config = default_config();
+    let search_space = SearchSpace::default_cpu();
+    let circuit_solution = |dag: &unparametrized::Dag| {
+        super::optimize_to_circuit_solution(
+            dag,
+            config,
+            &search_space,
+            &NoSearchSpaceRestriction,
+            &SHARED_CACHES,
+            &None,
+        )
+    };
+
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input(4, Shape::number());
+    _ = dag.add_lut(input, FunctionTable::UNKWOWN, 4);
+    let sol = circuit_solution(&dag);
+    assert!(sol.is_feasible);
+    assert!(sol.used_external_partitions.is_empty());
+
+    let mut dag = unparametrized::Dag::new();
+    let input = dag.add_input_in_partition(4, Shape::number(), tfhers_partition.clone());
+    let lut = dag.add_lut(input, FunctionTable::UNKWOWN, 4);
+    _ = dag.add_change_partition(lut, None, Some(tfhers_partition));
+    let sol = circuit_solution(&dag);
+    assert!(sol.is_feasible);
+    assert_eq!(sol.used_external_partitions, ["tfhers"]);
+}
+
 #[test]
 fn test_optimize_tfhers_output_constraints() {
     let variances = [1.0, 6.14e-14, 2.14e-16];
@@ -1028,3 +1567,65 @@ fn test_optimize_tfhers_to_concrete_and_back_example() {
     let sol = optimize(&dag, &None, PartitionIndex(0));
     assert!(sol.is_some());
 }
+
+#[test]
+fn test_optimize_32_bits_modulus() {
+    let modulus_log = 32;
+    let caches = crate::optimization::decomposition::cache(
+        128,
+        crate::config::ProcessingUnit::Cpu,
+        None,
+        true,
+        modulus_log,
+        modulus_log,
+        crate::config::KeyDistribution::Binary,
+    );
+    let config_64 = default_config();
+    let config = Config {
+        ciphertext_modulus_log: modulus_log,
+        fft_precision: modulus_log,
+        ..config_64
+    };
+    assert!(config.validate().is_ok());
+    let search_space = SearchSpace::default_cpu();
+    let dag = dag_lut_sum_of_2_partitions_2_layer(1, 4, true);
+    let sol = super::optimize_to_circuit_solution(
+        &dag,
+        config,
+        &search_space,
+        &NoSearchSpaceRestriction,
+        &caches,
+        &None,
+    );
+    assert!(sol.is_feasible);
+    assert!(sol.p_error <= _4_SIGMA);
+    let keys = &sol.circuit_keys;
+    assert!(keys.bootstrap_keys.len() == 2);
+    let modulus_log = modulus_log as u64;
+    for key in &keys.bootstrap_keys {
+        let decomp = key.br_decomposition_parameter;
+        assert!(decomp.level * decomp.log2_base <= modulus_log);
+    }
+    for decomp in keys
+        .keyswitch_keys
+        .iter()
+        .map(|key| key.ks_decomposition_parameter)
+        .chain(
+            keys.conversion_keyswitch_keys
+                .iter()
+                .map(|key| key.ks_decomposition_parameter),
+        )
+    {
+        assert!(decomp.level * decomp.log2_base <= modulus_log);
+    }
+    // less bits leave less room for the noise
+    let sol_64 = super::optimize_to_circuit_solution(
+        &dag,
+        config_64,
+        &search_space,
+        &NoSearchSpaceRestriction,
+        &SHARED_CACHES,
+        &None,
+    );
+    assert!(sol.complexity >= sol_64.complexity);
+}
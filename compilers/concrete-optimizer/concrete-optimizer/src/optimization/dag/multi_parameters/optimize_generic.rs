@@ -1,21 +1,31 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use crate::dag::unparametrized::Dag;
+use crate::optimization;
 use crate::optimization::config::{Config, SearchSpace};
 use crate::optimization::dag::multi_parameters::keys_spec::CircuitSolution;
-use crate::optimization::dag::multi_parameters::optimize::optimize_to_circuit_solution as native_optimize;
+use crate::optimization::dag::multi_parameters::optimize::{
+    is_feasible as native_is_feasible, optimize_to_circuit_solution_with_report as native_optimize,
+    PartitionReport,
+};
 use crate::optimization::dag::solo_key::analyze;
 use crate::optimization::dag::solo_key::optimize_generic::{max_precision, Encoding};
 use crate::optimization::decomposition::PersistDecompCaches;
+use crate::optimization::progress::Progress;
 use crate::optimization::wop_atomic_pattern::optimize::optimize_to_circuit_solution as crt_optimize_no_dag;
 
 use super::optimize::SearchSpaceRestriction;
 use super::partition_cut::PartitionCut;
 
-fn best_complexity_solution(native: CircuitSolution, crt: CircuitSolution) -> CircuitSolution {
-    match (&native.is_feasible, &crt.is_feasible) {
+fn best_complexity_solution<T>(
+    native: (CircuitSolution, T),
+    crt: (CircuitSolution, T),
+) -> (CircuitSolution, T) {
+    match (&native.0.is_feasible, &crt.0.is_feasible) {
         (true, true) => {
             // crt has 0 complexity in no lut case
             // so we always select native in this case
-            if native.complexity <= crt.complexity || crt.complexity == 0.0 {
+            if native.0.complexity <= crt.0.complexity || crt.0.complexity == 0.0 {
                 native
             } else {
                 crt
@@ -38,6 +48,9 @@ fn crt_optimize(
             "Crt does not support round/reinterpret_precision operator",
         );
     } // TODO: dag to params
+    if analyze::has_input_variance(dag) {
+        return CircuitSolution::no_solution("Crt does not support inputs with a known variance");
+    }
     let max_precision = max_precision(dag);
     let nb_luts = analyze::lut_count_from_dag(dag);
     let worst_log_norm = analyze::worst_log_norm_for_wop(dag);
@@ -64,8 +77,38 @@ pub fn optimize(
     caches: &PersistDecompCaches,
     p_cut: &Option<PartitionCut>,
 ) -> CircuitSolution {
+    optimize_with_report(
+        dag,
+        config,
+        search_space,
+        search_space_restriction,
+        encoding,
+        default_log_norm2_woppbs,
+        caches,
+        p_cut,
+        &AtomicBool::new(false),
+        &mut Progress::none(),
+    )
+    .0
+}
+
+// The partition report is empty when the crt solution is selected
+// Once `cancel` is set, the search stops and returns a "cancelled" error
+// The crt search does not report progress
+pub fn optimize_with_report(
+    dag: &Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    search_space_restriction: &impl SearchSpaceRestriction,
+    encoding: Encoding,
+    default_log_norm2_woppbs: f64,
+    caches: &PersistDecompCaches,
+    p_cut: &Option<PartitionCut>,
+    cancel: &AtomicBool,
+    progress: &mut Progress<'_>,
+) -> (CircuitSolution, Vec<PartitionReport>) {
     let dag = dag.clone();
-    let native = || {
+    let mut native = || {
         native_optimize(
             &dag,
             config,
@@ -73,11 +116,63 @@ pub fn optimize(
             search_space_restriction,
             caches,
             p_cut,
+            cancel,
+            progress,
+        )
+    };
+    let crt = || {
+        (
+            crt_optimize(&dag, config, search_space, default_log_norm2_woppbs, caches),
+            vec![],
+        )
+    };
+    let solution = match encoding {
+        Encoding::Auto => {
+            let native = native();
+            if cancel.load(Ordering::Relaxed) {
+                native
+            } else {
+                best_complexity_solution(native, crt())
+            }
+        }
+        Encoding::Native => native(),
+        Encoding::Crt => crt(),
+    };
+    if cancel.load(Ordering::Relaxed) {
+        return (
+            CircuitSolution::no_solution(optimization::Err::Cancelled.to_string()),
+            vec![],
+        );
+    }
+    solution
+}
+
+/// Fast check that `optimize` can find a solution, see `optimize::is_feasible`.
+/// The complexity of the solution is not taken into account.
+pub fn is_feasible(
+    dag: &Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    search_space_restriction: &impl SearchSpaceRestriction,
+    encoding: Encoding,
+    default_log_norm2_woppbs: f64,
+    caches: &PersistDecompCaches,
+    p_cut: &Option<PartitionCut>,
+) -> bool {
+    let native = || {
+        native_is_feasible(
+            dag,
+            config,
+            search_space,
+            search_space_restriction,
+            caches,
+            p_cut,
         )
     };
-    let crt = || crt_optimize(&dag, config, search_space, default_log_norm2_woppbs, caches);
+    let crt =
+        || crt_optimize(dag, config, search_space, default_log_norm2_woppbs, caches).is_feasible;
     match encoding {
-        Encoding::Auto => best_complexity_solution(native(), crt()),
+        Encoding::Auto => native() || crt(),
         Encoding::Native => native(),
         Encoding::Crt => crt(),
     }
@@ -1,10 +1,12 @@
 use std::collections::{HashMap, HashSet};
 
 use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
 
 use crate::dag::operator::{Operator, OperatorIndex, Precision};
 use crate::dag::rewrite::round::expand_round_and_index_map;
 use crate::dag::unparametrized;
+use crate::global_parameters::DEFAULT_DOMAINS;
 use crate::optimization::dag::multi_parameters::partitions::PartitionIndex;
 use crate::optimization::dag::solo_key::analyze::out_variances;
 use crate::optimization::dag::solo_key::symbolic_variance::SymbolicVariance;
@@ -14,7 +16,7 @@ use super::optimize::MacroParameters;
 const ROUND_INNER_MULTI_PARAMETER: bool = false;
 const ROUND_EXTERNAL_MULTI_PARAMETER: bool = !ROUND_INNER_MULTI_PARAMETER && true;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ExternalPartition {
     pub name: String,
     pub macro_params: MacroParameters,
@@ -46,6 +48,130 @@ impl std::fmt::Display for ExternalPartition {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum ExternalPartitionError {
+    PolynomialSizeTooSmall(u64),
+    PolynomialSizeTooLarge(u64),
+    NullGlweDimension,
+    GlweDimensionTooLarge(u64),
+    NullInternalDimension,
+    InternalDimensionTooLarge(u64),
+    NonPositiveVariance(f64),
+    NonFiniteVariance(f64),
+    VarianceAboveMax { variance: f64, max_variance: f64 },
+}
+
+impl std::fmt::Display for ExternalPartitionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PolynomialSizeTooSmall(log2_polynomial_size) => write!(
+                f,
+                "External partition has a log2 polynomial size {log2_polynomial_size} below {}",
+                MIN_EXTERNAL_LOG2_POLYNOMIAL_SIZE
+            ),
+            Self::PolynomialSizeTooLarge(log2_polynomial_size) => write!(
+                f,
+                "External partition has a log2 polynomial size {log2_polynomial_size} above {}",
+                MAX_EXTERNAL_LOG2_POLYNOMIAL_SIZE
+            ),
+            Self::NullGlweDimension => write!(f, "External partition has a null glwe dimension"),
+            Self::GlweDimensionTooLarge(glwe_dimension) => write!(
+                f,
+                "External partition has a glwe dimension {glwe_dimension} giving a big lwe dimension above {}",
+                MAX_EXTERNAL_LWE_DIMENSION
+            ),
+            Self::NullInternalDimension => {
+                write!(f, "External partition has a null internal dimension")
+            }
+            Self::InternalDimensionTooLarge(internal_dim) => write!(
+                f,
+                "External partition has an internal dimension {internal_dim} above {}",
+                MAX_EXTERNAL_LWE_DIMENSION
+            ),
+            Self::NonPositiveVariance(variance) => {
+                write!(
+                    f,
+                    "External partition has a non positive variance {variance}"
+                )
+            }
+            Self::NonFiniteVariance(variance) => {
+                write!(f, "External partition has a non finite variance {variance}")
+            }
+            Self::VarianceAboveMax {
+                variance,
+                max_variance,
+            } => write!(
+                f,
+                "External partition has a variance {variance} above its max variance {max_variance}"
+            ),
+        }
+    }
+}
+
+const MIN_EXTERNAL_LOG2_POLYNOMIAL_SIZE: u64 = DEFAULT_DOMAINS
+    .glwe_pbs_constrained_cpu
+    .log2_polynomial_size
+    .start;
+
+const MAX_EXTERNAL_LOG2_POLYNOMIAL_SIZE: u64 = DEFAULT_DOMAINS
+    .glwe_pbs_constrained_cpu
+    .log2_polynomial_size
+    .end
+    - 1;
+
+const MAX_EXTERNAL_LWE_DIMENSION: u64 = DEFAULT_DOMAINS.free_lwe.end;
+
+impl ExternalPartition {
+    /// Checks the partition parameters are usable by the optimizer.
+    pub fn check(&self) -> Result<(), ExternalPartitionError> {
+        let glwe_params = self.macro_params.glwe_params;
+        if glwe_params.log2_polynomial_size < MIN_EXTERNAL_LOG2_POLYNOMIAL_SIZE {
+            return Err(ExternalPartitionError::PolynomialSizeTooSmall(
+                glwe_params.log2_polynomial_size,
+            ));
+        }
+        if glwe_params.log2_polynomial_size > MAX_EXTERNAL_LOG2_POLYNOMIAL_SIZE {
+            return Err(ExternalPartitionError::PolynomialSizeTooLarge(
+                glwe_params.log2_polynomial_size,
+            ));
+        }
+        if glwe_params.glwe_dimension == 0 {
+            return Err(ExternalPartitionError::NullGlweDimension);
+        }
+        let big_lwe_dimension = glwe_params
+            .glwe_dimension
+            .checked_mul(glwe_params.polynomial_size());
+        if big_lwe_dimension.is_none_or(|dim| dim > MAX_EXTERNAL_LWE_DIMENSION) {
+            return Err(ExternalPartitionError::GlweDimensionTooLarge(
+                glwe_params.glwe_dimension,
+            ));
+        }
+        if self.macro_params.internal_dim == 0 {
+            return Err(ExternalPartitionError::NullInternalDimension);
+        }
+        if self.macro_params.internal_dim > MAX_EXTERNAL_LWE_DIMENSION {
+            return Err(ExternalPartitionError::InternalDimensionTooLarge(
+                self.macro_params.internal_dim,
+            ));
+        }
+        for variance in [self.variance, self.max_variance] {
+            if variance.is_nan() || variance <= 0.0 {
+                return Err(ExternalPartitionError::NonPositiveVariance(variance));
+            }
+            if variance.is_infinite() {
+                return Err(ExternalPartitionError::NonFiniteVariance(variance));
+            }
+        }
+        if self.variance > self.max_variance {
+            return Err(ExternalPartitionError::VarianceAboveMax {
+                variance: self.variance,
+                max_variance: self.max_variance,
+            });
+        }
+        Ok(())
+    }
+}
+
 // TODO: keep both precisions
 // TODO: rounding lut should have its own partition based on max norm2 and precisions
 #[derive(Clone, Debug)]
@@ -65,6 +191,10 @@ pub struct PartitionCut {
     pub rnorm2: Vec<f64>,
 
     pub external_partitions: Vec<ExternalPartition>,
+
+    // Explicit lut partitions, indexed by (round expanded) operators, empty when cut by precisions
+    pub lut_assignment: Vec<Option<PartitionIndex>>,
+    pub n_assigned_partitions: usize,
 }
 
 impl PartitionCut {
@@ -74,6 +204,8 @@ impl PartitionCut {
             rnorm2: vec![],
             external_partitions: vec![],
             has_internal_partitions: true,
+            lut_assignment: vec![],
+            n_assigned_partitions: 0,
         }
     }
 
@@ -82,9 +214,16 @@ impl PartitionCut {
     }
 
     pub fn n_internal_partitions(&self) -> usize {
+        if self.is_explicit() {
+            return self.n_assigned_partitions;
+        }
         self.p_cut.len() + self.has_internal_partitions as usize
     }
 
+    pub fn is_explicit(&self) -> bool {
+        !self.lut_assignment.is_empty()
+    }
+
     pub fn n_external_partitions(&self) -> usize {
         self.external_partitions.len()
     }
@@ -112,6 +251,8 @@ impl PartitionCut {
             rnorm2: vec![],
             external_partitions: vec![],
             has_internal_partitions,
+            lut_assignment: vec![],
+            n_assigned_partitions: 0,
         }
     }
 
@@ -129,6 +270,8 @@ impl PartitionCut {
             rnorm2: vec![],
             external_partitions: external_partitions.to_vec(),
             has_internal_partitions,
+            lut_assignment: vec![],
+            n_assigned_partitions: 0,
         }
     }
 
@@ -146,66 +289,150 @@ impl PartitionCut {
         op_i: OperatorIndex,
     ) -> Option<PartitionIndex> {
         let op = &dag.operators[op_i.0];
+        // A shared rotation is evaluated with the keys of its rotation lut
+        let rotation_lut = dag.rotation_lut(op_i);
+        if rotation_lut != op_i {
+            return self.partition(dag, rotation_lut);
+        }
         match op {
-            Operator::Lut { input, .. } => {
+            Operator::Lut { .. } if self.is_explicit() => self.lut_assignment[op_i.0],
+            Operator::Lut { .. } => {
                 assert!(self.has_internal_partitions);
                 for (partition, &(precision_cut, norm2_cut)) in self.p_cut.iter().enumerate() {
-                    if dag.out_precisions[input.0] <= precision_cut
-                        && self.rnorm2(op_i) <= norm2_cut
+                    if dag.lut_in_precision(op_i) <= precision_cut && self.rnorm2(op_i) <= norm2_cut
                     {
                         return Some(PartitionIndex(partition));
                     }
                 }
                 Some(PartitionIndex(self.p_cut.len()))
             }
-            Operator::ChangePartition {
+            Operator::Input {
+                partition: Some(partition),
+                ..
+            }
+            | Operator::ChangePartition {
                 src_partition: Some(partition),
                 dst_partition: None,
                 ..
             }
             | Operator::ChangePartition {
-                src_partition: None,
                 dst_partition: Some(partition),
                 ..
-            } => {
-                for (i, external_partition) in self.external_partitions.iter().enumerate() {
-                    if partition == external_partition {
-                        return Some(PartitionIndex(self.n_internal_partitions() + i));
-                    }
-                }
-                None
             }
+            | Operator::Keyswitch {
+                dst_partition: partition,
+                ..
+            } => self.external_partition(partition),
             _ => None,
         }
     }
 
+    /// The partition index of an external partition.
+    pub fn external_partition(&self, partition: &ExternalPartition) -> Option<PartitionIndex> {
+        self.external_partitions
+            .iter()
+            .position(|external_partition| external_partition == partition)
+            .map(|i| PartitionIndex(self.n_internal_partitions() + i))
+    }
+
     pub fn for_each_precision(dag: &unparametrized::Dag) -> Self {
         let (dag, _) = expand_round_and_index_map(dag);
         let mut lut_in_precisions: HashSet<_> = HashSet::default();
-        let mut partitions: HashSet<ExternalPartition> = HashSet::default();
-        for op in &dag.operators {
-            if let Operator::Lut { input, .. } = op {
-                _ = lut_in_precisions.insert(dag.out_precisions[input.0]);
+        for (op_i, op) in dag.operators.iter().enumerate() {
+            if let Operator::Lut { .. } = op {
+                _ = lut_in_precisions.insert(dag.lut_in_precision(OperatorIndex(op_i)));
             }
         }
+        let precisions: Vec<_> = lut_in_precisions.iter().copied().collect();
+        let external_partitions = Self::external_partitions_of(&dag);
+        Self::from_precisions_and_external_partitions(&precisions, &external_partitions)
+    }
+
+    // Luts are placed in the partition assigned to their operator (one per operator),
+    // the other operators follow the partition of their levelled block.
+    pub fn from_assignment(
+        original_dag: &unparametrized::Dag,
+        assignment: &[PartitionIndex],
+    ) -> Result<Self, String> {
+        if assignment.len() != original_dag.len() {
+            return Err(format!(
+                "Partition assignment has {} entries for {} operators",
+                assignment.len(),
+                original_dag.len()
+            ));
+        }
+        let (dag, rewrited) = expand_round_and_index_map(original_dag);
+        let mut expanded_assignment = vec![PartitionIndex::FIRST; dag.operators.len()];
+        for (original_i, expanded) in rewrited.iter().enumerate() {
+            for op_i in expanded {
+                expanded_assignment[op_i.0] = assignment[original_i];
+            }
+        }
+        let mut lut_partitions: Vec<_> = dag
+            .operators
+            .iter()
+            .enumerate()
+            .filter(|(_, op)| matches!(op, Operator::Lut { .. }))
+            .map(|(op_i, _)| expanded_assignment[op_i])
+            .collect();
+        lut_partitions.sort();
+        lut_partitions.dedup();
+        // Renumber the used partitions densely, keeping the user order
+        let lut_assignment = dag
+            .operators
+            .iter()
+            .enumerate()
+            .map(|(op_i, op)| {
+                matches!(op, Operator::Lut { .. }).then(|| {
+                    PartitionIndex(
+                        lut_partitions
+                            .binary_search(&expanded_assignment[op_i])
+                            .unwrap(),
+                    )
+                })
+            })
+            .collect();
+        Ok(Self {
+            p_cut: vec![],
+            rnorm2: vec![],
+            external_partitions: Self::external_partitions_of(&dag),
+            has_internal_partitions: !lut_partitions.is_empty(),
+            lut_assignment,
+            n_assigned_partitions: lut_partitions.len(),
+        })
+    }
+
+    // In order of first use, so that partition indices are stable across runs
+    fn external_partitions_of(dag: &unparametrized::Dag) -> Vec<ExternalPartition> {
+        let mut partitions: Vec<ExternalPartition> = vec![];
+        let mut insert = |partition: &ExternalPartition| {
+            if !partitions.contains(partition) {
+                partitions.push(partition.clone());
+            }
+        };
         for op in &dag.operators {
-            if let Operator::ChangePartition {
-                src_partition,
-                dst_partition,
-                ..
-            } = op
-            {
-                if let Some(partition) = src_partition {
-                    _ = partitions.insert(partition.clone());
-                }
-                if let Some(partition) = dst_partition {
-                    _ = partitions.insert(partition.clone());
+            match op {
+                Operator::Input {
+                    partition: Some(partition),
+                    ..
+                } => insert(partition),
+                Operator::ChangePartition {
+                    src_partition,
+                    dst_partition,
+                    ..
+                } => {
+                    if let Some(partition) = src_partition {
+                        insert(partition);
+                    }
+                    if let Some(partition) = dst_partition {
+                        insert(partition);
+                    }
                 }
+                Operator::Keyswitch { dst_partition, .. } => insert(dst_partition),
+                _ => (),
             }
         }
-        let precisions: Vec<_> = lut_in_precisions.iter().copied().collect();
-        let external_partitions = Vec::from_iter(partitions);
-        Self::from_precisions_and_external_partitions(&precisions, &external_partitions)
+        partitions
     }
 
     #[allow(clippy::too_many_lines)]
@@ -249,28 +476,37 @@ impl PartitionCut {
                 Operator::Lut { .. } => {
                     noise_origins[op_i] = std::iter::once(op_i).collect();
                 }
-                Operator::Input { .. } => {
+                Operator::Input { partition, .. } => {
                     max_output_norm2[op_i] = 1.0; // initial value that can be maxed
                     noise_origins[op_i] = std::iter::once(op_i).collect();
+                    if let Some(partition) = partition {
+                        external_partitions.push(partition.clone());
+                    }
                 }
                 Operator::ZeroNoise { .. } => {
                     max_output_norm2[op_i] = 0.0; // initial value that can be maxed
                     noise_origins[op_i] = std::iter::once(op_i).collect();
                 }
                 Operator::ChangePartition {
-                    src_partition: Some(partition),
-                    dst_partition: None,
-                    ..
-                }
-                | Operator::ChangePartition {
-                    src_partition: None,
-                    dst_partition: Some(partition),
+                    src_partition,
+                    dst_partition,
                     ..
                 } => {
-                    external_partitions.push(partition.clone());
+                    assert!(
+                        src_partition.is_some() || dst_partition.is_some(),
+                        "change_partition not supported when src and dest partition are both unset"
+                    );
+                    external_partitions.extend(src_partition.iter().cloned());
+                    external_partitions.extend(dst_partition.iter().cloned());
                 }
-                Operator::ChangePartition { .. } => {
-                    panic!("change_partition not supported when src and dest partition are both set or unset");
+                // the keyswitched value keeps its input noise
+                #[allow(clippy::assigning_clones)]
+                Operator::Keyswitch {
+                    input,
+                    dst_partition,
+                } => {
+                    noise_origins[op_i] = noise_origins[input.0].clone();
+                    external_partitions.push(dst_partition.clone());
                 }
                 // unreachable
                 Operator::Round { .. } => panic!("expand_round failed"),
@@ -297,8 +533,8 @@ impl PartitionCut {
         let mut round_done: HashMap<usize, u64> = HashMap::default();
         // reassociate all lut's output_norm2 and precisions
         for (op_i, output_norm2) in max_output_norm2.iter_mut().enumerate() {
-            if let Operator::Lut { input, .. } = dag.operators[op_i] {
-                let input_precision = dag.out_precisions[input.0];
+            if let Operator::Lut { .. } = dag.operators[op_i] {
+                let input_precision = dag.lut_in_precision(OperatorIndex(op_i));
                 let output_precision = dag.out_precisions[op_i] as i32;
                 let delta_precision = output_precision - input_precision as i32;
                 assert!(!output_norm2.is_nan());
@@ -335,10 +571,44 @@ impl PartitionCut {
             rnorm2: max_output_norm2,
             external_partitions,
             has_internal_partitions,
+            lut_assignment: vec![],
+            n_assigned_partitions: 0,
         }
     }
 
+    /// The log2 relative norm2 of a lut, as used by `maximal_partitionning` to cut partitions.
+    /// None when the operator is not a lut.
+    pub fn lut_log_norm2(dag: &unparametrized::Dag, op_i: OperatorIndex) -> Option<f64> {
+        let Some(Operator::Lut { .. }) = dag.operators.get(op_i.0) else {
+            return None;
+        };
+        let (_, rewrited) = expand_round_and_index_map(dag);
+        let expanded_op_i = *rewrited[op_i.0].last()?;
+        Some(Self::maximal_partitionning(dag).rnorm2[expanded_op_i.0])
+    }
+
     pub fn delete_unused_cut(&self, used: &HashSet<PartitionIndex>) -> Self {
+        if self.is_explicit() {
+            let mut used_internal: Vec<_> = used
+                .iter()
+                .filter(|p| self.is_internal_partition(p))
+                .copied()
+                .collect();
+            used_internal.sort();
+            let lut_assignment = self
+                .lut_assignment
+                .iter()
+                .map(|partition| {
+                    partition.map(|p| PartitionIndex(used_internal.binary_search(&p).unwrap()))
+                })
+                .collect();
+            return Self {
+                lut_assignment,
+                n_assigned_partitions: used_internal.len(),
+                has_internal_partitions: !used_internal.is_empty(),
+                ..self.clone()
+            };
+        }
         let mut p_cut = vec![];
         for (i, &cut) in self.p_cut.iter().enumerate() {
             if used.contains(&PartitionIndex(i)) {
@@ -354,12 +624,19 @@ impl PartitionCut {
             rnorm2: self.rnorm2.clone(),
             external_partitions: self.external_partitions.clone(),
             has_internal_partitions,
+            lut_assignment: vec![],
+            n_assigned_partitions: 0,
         }
     }
 }
 
 impl std::fmt::Display for PartitionCut {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_explicit() {
+            for partition in 0..self.n_assigned_partitions {
+                writeln!(f, "partition {partition}: explicit")?;
+            }
+        }
         let mut prev_precision_cut = 0;
         for (partition, &(precision_cut, norm2_cut)) in self.p_cut.iter().enumerate() {
             prev_precision_cut = prev_precision_cut.min(precision_cut);
@@ -378,11 +655,13 @@ impl std::fmt::Display for PartitionCut {
             }
             prev_precision_cut = precision_cut + 1;
         }
-        writeln!(
-            f,
-            "partition {}: {prev_precision_cut} bits and higher",
-            self.p_cut.len()
-        )?;
+        if !self.is_explicit() {
+            writeln!(
+                f,
+                "partition {}: {prev_precision_cut} bits and higher",
+                self.p_cut.len()
+            )?;
+        }
         for (i, e_partition) in self.external_partitions.iter().enumerate() {
             writeln!(
                 f,
@@ -54,6 +54,14 @@ fn extract_levelled_block(dag: &unparametrized::Dag) -> Blocks {
             Operator::Input { .. } | Operator::ZeroNoise { .. } => (),
             // Block entry point and pre-exit point
             Op::Lut { .. } => (),
+            // Block entry point, converting between two external partitions
+            Op::ChangePartition {
+                src_partition: Some(_),
+                dst_partition: Some(_),
+                ..
+            } => (),
+            // Block entry point, keyswitching to an external partition
+            Op::Keyswitch { .. } => (),
             // Connectors
             Op::UnsafeCast { input, .. } | Op::ChangePartition { input, .. } => {
                 uf.union(input.0, op_i);
@@ -100,6 +108,18 @@ fn levelled_blocks_constraints(
                     constraints_by_block[input_group].exit.extend([partition]);
                 }
             }
+            // The input of a conversion between two external partitions is in the source one
+            if let Op::ChangePartition {
+                input,
+                src_partition: Some(src_partition),
+                dst_partition: Some(_),
+            } = op
+            {
+                if let Some(partition) = p_cut.external_partition(src_partition) {
+                    let input_group = blocks.block_of[input.0];
+                    _ = constraints_by_block[input_group].forced.insert(partition);
+                }
+            }
         }
     }
     constraints_by_block
@@ -126,7 +146,10 @@ fn only_1_partition(dag: &unparametrized::Dag) -> Partitions {
             | Op::MaxNoise { inputs, .. } => {
                 instrs_partition[op_i].inputs_transition = vec![None; inputs.len()];
             }
-            Op::Lut { .. } | Op::UnsafeCast { .. } | Operator::ChangePartition { .. } => {
+            Op::Lut { .. }
+            | Op::UnsafeCast { .. }
+            | Operator::ChangePartition { .. }
+            | Operator::Keyswitch { .. } => {
                 instrs_partition[op_i].inputs_transition = vec![None];
             }
             Op::Input { .. } | Op::ZeroNoise { .. } => (),
@@ -243,8 +266,34 @@ fn resolve_by_levelled_block(
                     vec![Some(Transition::Additional {
                         src_partition: input_partition,
                     })]
+                };
+                // A conversion between two external partitions reads its input converted to the
+                // destination partition
+                if let Op::ChangePartition {
+                    src_partition: Some(_),
+                    dst_partition: Some(_),
+                    ..
+                } = op
+                {
+                    if group_partition != input_partition {
+                        _ = instrs_p[input.0]
+                            .alternative_output_representation
+                            .insert(group_partition);
+                    }
                 }
             }
+            Op::Keyswitch { input, .. } => {
+                let instruction_partition = p_cut.partition(dag, OperatorIndex(op_i)).unwrap();
+                instrs_p[op_i].instruction_partition = instruction_partition;
+                let input_partition = instrs_p[input.0].instruction_partition;
+                instrs_p[op_i].inputs_transition = if input_partition == instruction_partition {
+                    vec![None]
+                } else {
+                    vec![Some(Transition::Internal {
+                        src_partition: input_partition,
+                    })]
+                };
+            }
             Operator::Input { .. } | Operator::ZeroNoise { .. } => {
                 instrs_p[op_i].instruction_partition = group_partition
             }
@@ -288,7 +337,9 @@ pub mod tests {
     use crate::dag::operator::{FunctionTable, LevelledComplexity, Shape, Weights};
     use crate::dag::unparametrized;
     use crate::optimization::dag::multi_parameters::optimize::MacroParameters;
-    use crate::optimization::dag::multi_parameters::partition_cut::ExternalPartition;
+    use crate::optimization::dag::multi_parameters::partition_cut::{
+        ExternalPartition, ExternalPartitionError,
+    };
     use crate::optimization::decomposition::cmux::get_noise_br;
     use crate::optimization::decomposition::{self, PersistDecompCaches};
     use crate::parameters::GlweParameters;
@@ -305,6 +356,7 @@ pub mod tests {
             true,
             CIPHERTEXT_MODULUS_LOG,
             FFT_PRECISION,
+            config::KeyDistribution::Binary,
         )
     });
 
@@ -413,6 +465,82 @@ pub mod tests {
         .unwrap()
     }
 
+    #[test]
+    fn test_external_partition_check() {
+        let variance = get_tfhers_noise_br();
+        let tfhers_partition = ExternalPartition {
+            name: String::from("tfhers"),
+            macro_params: TFHERS_MACRO_PARAMS,
+            max_variance: variance * 4.0,
+            variance,
+        };
+        assert_eq!(tfhers_partition.check(), Ok(()));
+        let with_macro_params = |internal_dim, log2_polynomial_size, glwe_dimension| {
+            ExternalPartition {
+                macro_params: MacroParameters {
+                    glwe_params: GlweParameters {
+                        log2_polynomial_size,
+                        glwe_dimension,
+                    },
+                    internal_dim,
+                },
+                ..tfhers_partition.clone()
+            }
+            .check()
+        };
+        assert_eq!(
+            with_macro_params(841, 2, 1),
+            Err(ExternalPartitionError::PolynomialSizeTooSmall(2))
+        );
+        assert_eq!(
+            with_macro_params(841, 64, 1),
+            Err(ExternalPartitionError::PolynomialSizeTooLarge(64))
+        );
+        assert_eq!(
+            with_macro_params(841, 11, 0),
+            Err(ExternalPartitionError::NullGlweDimension)
+        );
+        assert_eq!(
+            with_macro_params(841, 11, u64::MAX),
+            Err(ExternalPartitionError::GlweDimensionTooLarge(u64::MAX))
+        );
+        assert_eq!(
+            with_macro_params(0, 11, 1),
+            Err(ExternalPartitionError::NullInternalDimension)
+        );
+        assert_eq!(
+            with_macro_params(u64::MAX, 11, 1),
+            Err(ExternalPartitionError::InternalDimensionTooLarge(u64::MAX))
+        );
+        let with_variances = |variance, max_variance| {
+            ExternalPartition {
+                variance,
+                max_variance,
+                ..tfhers_partition.clone()
+            }
+            .check()
+        };
+        assert_eq!(
+            with_variances(0.0, variance),
+            Err(ExternalPartitionError::NonPositiveVariance(0.0))
+        );
+        assert!(matches!(
+            with_variances(variance, f64::NAN),
+            Err(ExternalPartitionError::NonPositiveVariance(_))
+        ));
+        assert_eq!(
+            with_variances(variance, f64::INFINITY),
+            Err(ExternalPartitionError::NonFiniteVariance(f64::INFINITY))
+        );
+        assert_eq!(
+            with_variances(variance, variance / 2.0),
+            Err(ExternalPartitionError::VarianceAboveMax {
+                variance,
+                max_variance: variance / 2.0
+            })
+        );
+    }
+
     #[test]
     fn test_tfhers_in_out_dot_compute() {
         let variance = get_tfhers_noise_br();
@@ -474,6 +602,38 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_tfhers_input_in_partition() {
+        let variance = get_tfhers_noise_br();
+        let tfhers_partition = ExternalPartition {
+            name: String::from("tfhers"),
+            macro_params: TFHERS_MACRO_PARAMS,
+            max_variance: variance * 6.0,
+            variance,
+        };
+        let mut dag = unparametrized::Dag::new();
+        let input = dag.add_input_in_partition(16, Shape::number(), tfhers_partition.clone());
+        let lut = dag.add_lut(input, FunctionTable::UNKWOWN, 16);
+        let change_part = dag.add_change_partition(lut, None, Some(tfhers_partition));
+
+        let partitions = partitionning(&dag);
+        assert!(partitions.nb_partitions == 2);
+        let tfhers_partition_index = PartitionIndex(1);
+        let instrs_partition = partitions.instrs_partition;
+        show_partitionning(&dag, &instrs_partition);
+
+        let consider = |op_i: OperatorIndex| &instrs_partition[op_i.0];
+        assert!(consider(input).instruction_partition == tfhers_partition_index);
+        assert!(consider(lut).instruction_partition == LOW_PRECISION_PARTITION);
+        assert!(
+            consider(lut).inputs_transition
+                == [Some(Transition::Internal {
+                    src_partition: tfhers_partition_index
+                })]
+        );
+        assert!(consider(change_part).instruction_partition == tfhers_partition_index);
+    }
+
     #[test]
     fn test_tfhers_in_out_lut_compute_mix_external() {
         let variance = get_tfhers_noise_br();
@@ -570,6 +730,63 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_tfhers_change_partition_src_and_dst() {
+        let variance = get_tfhers_noise_br();
+        let tfhers_partition_in = ExternalPartition {
+            name: String::from("tfhers_in"),
+            macro_params: TFHERS_MACRO_PARAMS,
+            max_variance: variance * 2.0,
+            variance,
+        };
+        let tfhers_partition_out = ExternalPartition {
+            name: String::from("tfhers_out"),
+            macro_params: TFHERS_MACRO_PARAMS,
+            max_variance: variance * 2.0,
+            variance,
+        };
+        let mut dag = unparametrized::Dag::new();
+        let input = dag.add_input(16, Shape::number());
+        let change_part = dag.add_change_partition(
+            input,
+            Some(tfhers_partition_in.clone()),
+            Some(tfhers_partition_out.clone()),
+        );
+        let lut = dag.add_lut(change_part, FunctionTable::UNKWOWN, 16);
+
+        let p_cut = PartitionCut::for_each_precision(&dag);
+        let partitions = partitionning_with_preferred(&dag, &p_cut, LOW_PRECISION_PARTITION);
+        assert!(partitions.nb_partitions == 3);
+        let tfhers_partition_index_in =
+            get_external_partition_index_or(&tfhers_partition_in, &p_cut, 1);
+        let tfhers_partition_index_out =
+            get_external_partition_index_or(&tfhers_partition_out, &p_cut, 2);
+        let instrs_partition = partitions.instrs_partition;
+        show_partitionning(&dag, &instrs_partition);
+
+        let consider = |op_i: OperatorIndex| &instrs_partition[op_i.0];
+        // The input is directly converted from one external partition to the other
+        assert!(consider(input).instruction_partition == tfhers_partition_index_in);
+        assert!(
+            consider(input).alternative_output_representation
+                == HashSet::from([tfhers_partition_index_out])
+        );
+        assert!(consider(change_part).instruction_partition == tfhers_partition_index_out);
+        assert!(
+            consider(change_part).inputs_transition
+                == [Some(Transition::Additional {
+                    src_partition: tfhers_partition_index_in
+                })]
+        );
+        assert!(consider(lut).instruction_partition == LOW_PRECISION_PARTITION);
+        assert!(
+            consider(lut).inputs_transition
+                == [Some(Transition::Internal {
+                    src_partition: tfhers_partition_index_out
+                })]
+        );
+    }
+
     #[test]
     fn test_tfhers_in_out_2lut_compute() {
         let variance = get_tfhers_noise_br();
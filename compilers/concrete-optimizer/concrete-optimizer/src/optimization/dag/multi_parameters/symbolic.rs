@@ -113,6 +113,7 @@ impl SymbolScheme {
             Symbol::ModulusSwitch(i) => i.0 < self.0,
             Symbol::Keyswitch(i, j) => i.0 < self.0 && j.0 < self.0,
             Symbol::FastKeyswitch(i, j) => i.0 < self.0 && j.0 < self.0,
+            Symbol::Constant => true,
         }
     }
 
@@ -125,23 +126,31 @@ impl SymbolScheme {
             Symbol::ModulusSwitch(i) => self.0 * 2 + i.0,
             Symbol::Keyswitch(i, j) => self.0 * 3 + i.0 * self.0 + j.0,
             Symbol::FastKeyswitch(i, j) => self.0 * (3 + self.0) + i.0 * self.0 + j.0,
+            Symbol::Constant => self.0 * (3 + 2 * self.0),
         }
     }
 
+    /// Returns the number of partitions of the scheme.
+    pub fn nb_partitions(&self) -> usize {
+        self.0
+    }
+
     /// Returns the number of symbols in the scheme.
     pub fn len(&self) -> usize {
-        self.0 * (3 + 2 * self.0)
+        self.0 * (3 + 2 * self.0) + 1
     }
 
     /// Returns an iterator over valid symbols.
     pub fn iter(&self) -> impl Iterator<Item = Symbol> + '_ {
         (0..self.len()).map(|i| {
-            if i < self.len() {
+            if i == self.get_symbol_index(&Symbol::Constant) {
+                Symbol::Constant
+            } else if i < self.0 {
                 Symbol::Input(PartitionIndex(i))
             } else if i < 2 * self.0 {
-                Symbol::Bootstrap(PartitionIndex(i - self.len()))
+                Symbol::Bootstrap(PartitionIndex(i - self.0))
             } else if i < 3 * self.0 {
-                Symbol::ModulusSwitch(PartitionIndex(i - 2 * self.len()))
+                Symbol::ModulusSwitch(PartitionIndex(i - 2 * self.0))
             } else if i < self.0 * (3 + self.0) {
                 let a = i - 3 * self.0;
                 Symbol::Keyswitch(PartitionIndex(a / self.0), PartitionIndex(a % self.0))
@@ -235,6 +244,8 @@ pub enum Symbol {
     Keyswitch(PartitionIndex, PartitionIndex),
     FastKeyswitch(PartitionIndex, PartitionIndex),
     ModulusSwitch(PartitionIndex),
+    // A known variance, not depending on any parameter
+    Constant,
 }
 
 impl Display for Symbol {
@@ -246,6 +257,7 @@ impl Display for Symbol {
             Symbol::Bootstrap(p) => write!(f, "Br[{p}]"),
             Symbol::Input(p) => write!(f, "In[{p}]"),
             Symbol::ModulusSwitch(p) => write!(f, "M[{p}]"),
+            Symbol::Constant => write!(f, "Cst"),
         }
     }
 }
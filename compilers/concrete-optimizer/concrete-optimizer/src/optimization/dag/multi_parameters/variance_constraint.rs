@@ -3,8 +3,8 @@ use crate::optimization::dag::multi_parameters::partitions::PartitionIndex;
 use std::fmt;
 
 use super::noise_expression::{
-    bootstrap_noise, fast_keyswitch_noise, input_noise, keyswitch_noise, modulus_switching_noise,
-    NoiseEvaluator, NoiseExpression,
+    bootstrap_noise, constant_noise, fast_keyswitch_noise, input_noise, keyswitch_noise,
+    modulus_switching_noise, NoiseEvaluator, NoiseExpression,
 };
 use super::symbolic::SymbolScheme;
 
@@ -61,6 +61,9 @@ impl VarianceConstraint {
         let self_renorm = other.safe_variance_bound / self.safe_variance_bound;
         let rel_diff =
             |f: &dyn Fn(&NoiseExpression) -> f64| self_renorm * f(self_var) - f(other_var);
+        if rel_diff(&|expr| expr.coeff(constant_noise())) < 0.0 {
+            return false;
+        }
         for partition in PartitionIndex::range(0, self.nb_partitions) {
             let diffs = [
                 rel_diff(&|expr| expr.coeff(bootstrap_noise(partition))),
@@ -1,12 +1,12 @@
 use crate::{
-    config::ProcessingUnit,
+    config::{KeyDistribution, ProcessingUnit},
     dag::{
         operator::{FunctionTable, LevelledComplexity, Precision, Shape},
         unparametrized,
     },
     optimization::{
         config::{Config, SearchSpace},
-        decomposition::{self},
+        decomposition::{self, PersistDecompCaches},
     },
 };
 
@@ -101,6 +101,45 @@ fn generate_virtual_circuit(
     dag
 }
 
+// One bootstrapped block per partition, composed with itself, and for each conversion the source
+// block composed into the destination one and both mixed in a levelled operation.
+fn generate_virtual_circuit_with_conversions(
+    partitions: &[PartitionDefinition],
+    conversions: &[(usize, usize)],
+) -> (unparametrized::Dag, Vec<PartitionIndex>) {
+    let mut dag = unparametrized::Dag::new();
+    let mut assignment = vec![];
+    let mut blocks = vec![];
+    for (i, def) in partitions.iter().enumerate() {
+        let inp = dag.add_input(def.precision, Shape::number());
+        let lut = dag.add_lut(inp, FunctionTable::UNKWOWN, def.precision);
+        let weighted = dag.add_linear_noise(
+            [lut],
+            LevelledComplexity::ZERO,
+            [def.norm2.sqrt()],
+            Shape::number(),
+            "",
+        );
+        dag.add_composition(weighted, inp);
+        assignment.extend([PartitionIndex(i); 3]);
+        blocks.push((inp, lut, weighted));
+    }
+    for &(src, dst) in conversions {
+        let (_, src_lut, src_weighted) = blocks[src];
+        let (dst_inp, dst_lut, _) = blocks[dst];
+        dag.add_composition(src_weighted, dst_inp);
+        let _ = dag.add_linear_noise(
+            [src_lut, dst_lut],
+            LevelledComplexity::ZERO,
+            [0., 0.],
+            Shape::number(),
+            "",
+        );
+        assignment.push(PartitionIndex(dst));
+    }
+    (dag, assignment)
+}
+
 pub fn generate_virtual_parameters(
     partitions: Vec<PartitionDefinition>,
     generate_fks: bool,
@@ -112,7 +151,15 @@ pub fn generate_virtual_parameters(
     let n_partitions = precisions.len();
     let p_cut = PartitionCut::maximal_partitionning(&dag);
     let search_space = SearchSpace::default_cpu();
-    let cache = decomposition::cache(128, ProcessingUnit::Cpu, None, true, 64, 53);
+    let cache = decomposition::cache(
+        128,
+        ProcessingUnit::Cpu,
+        None,
+        true,
+        64,
+        53,
+        KeyDistribution::Binary,
+    );
     let parameters = optimize(
         &dag,
         config,
@@ -142,10 +189,53 @@ pub fn generate_virtual_parameters(
     ExpandedCircuitKeys::of(&parameters).compacted()
 }
 
+/// Same as [`generate_virtual_parameters`], with keyswitch keys only between the partitions of
+/// `conversions`, given as (source, destination) indices in `partitions`.
+pub fn generate_virtual_parameters_with_conversions(
+    partitions: Vec<PartitionDefinition>,
+    conversions: &[(usize, usize)],
+    config: Config,
+    cache: &PersistDecompCaches,
+) -> Result<CircuitKeys, String> {
+    if let Some(&(src, dst)) = conversions
+        .iter()
+        .find(|&&(src, dst)| src.max(dst) >= partitions.len())
+    {
+        return Err(format!(
+            "Conversion from partition {src} to {dst} is out of the {} partitions",
+            partitions.len()
+        ));
+    }
+    let (dag, assignment) =
+        generate_virtual_circuit_with_conversions(partitions.as_slice(), conversions);
+    let p_cut = PartitionCut::from_assignment(&dag, &assignment)?;
+    let search_space = SearchSpace::default_cpu();
+    let parameters = optimize(
+        &dag,
+        config,
+        &search_space,
+        &NoSearchSpaceRestriction,
+        cache,
+        &Some(p_cut),
+        PartitionIndex(0),
+    )
+    .map(|v| v.1)
+    .map_err(|err| err.to_string())?;
+
+    if let Some(&(src, dst)) = conversions
+        .iter()
+        .find(|&&(src, dst)| parameters.micro_params.ks[src][dst].is_none())
+    {
+        return Err(format!("Ksk[{src},{dst}] missing."));
+    }
+    Ok(ExpandedCircuitKeys::of(&parameters).compacted())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::computing_cost::cpu::CpuComplexity;
+    use crate::optimization::config::Objective;
 
     #[test]
     fn test_generate_generic_parameters() {
@@ -156,6 +246,7 @@ mod test {
             ciphertext_modulus_log: 64,
             fft_precision: 53,
             complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
         };
         let _a = generate_virtual_parameters(
             vec![
@@ -176,4 +267,60 @@ mod test {
             config,
         );
     }
+
+    #[test]
+    fn test_generate_parameters_with_conversions() {
+        let config = Config {
+            security_level: 128,
+            maximum_acceptable_error_probability: _4_SIGMA,
+            key_sharing: true,
+            ciphertext_modulus_log: 64,
+            fft_precision: 53,
+            complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
+        };
+        let partitions = vec![
+            PartitionDefinition {
+                precision: 3,
+                norm2: 1.,
+            },
+            PartitionDefinition {
+                precision: 4,
+                norm2: 100.,
+            },
+            PartitionDefinition {
+                precision: 5,
+                norm2: 1000.,
+            },
+        ];
+        let all = generate_virtual_parameters(partitions.clone(), true, config);
+        let cache = decomposition::cache(
+            128,
+            ProcessingUnit::Cpu,
+            None,
+            true,
+            64,
+            53,
+            KeyDistribution::Binary,
+        );
+        let keys = generate_virtual_parameters_with_conversions(
+            partitions.clone(),
+            &[(0, 2)],
+            config,
+            &cache,
+        )
+        .unwrap();
+        // the converted pair only adds to the keys of each partition, instead of all the pairs
+        assert_eq!(all.keyswitch_keys.len(), 9);
+        assert_eq!(keys.keyswitch_keys.len(), 3);
+        assert_eq!(all.conversion_keyswitch_keys.len(), 3);
+        assert_eq!(keys.conversion_keyswitch_keys.len(), 1);
+        assert!(generate_virtual_parameters_with_conversions(
+            partitions,
+            &[(0, 3)],
+            config,
+            &cache
+        )
+        .is_err());
+    }
 }
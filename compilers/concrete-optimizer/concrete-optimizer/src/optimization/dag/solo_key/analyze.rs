@@ -5,7 +5,7 @@ use crate::dag::operator::{
 use crate::dag::rewrite::round::expand_round;
 use crate::dag::unparametrized::Dag;
 use crate::noise_estimator::error;
-use crate::noise_estimator::p_error::{combine_errors, repeat_p_error};
+use crate::noise_estimator::p_error::{log_success, p_error_of_log_success};
 use crate::optimization::config::NoiseBoundConfig;
 use crate::utils::square;
 use std::collections::{HashMap, HashSet};
@@ -48,7 +48,8 @@ fn assert_inputs_index(op: &Operator, first_bad_index: usize) {
         Operator::Lut { input, .. }
         | Operator::UnsafeCast { input, .. }
         | Operator::Round { input, .. }
-        | Operator::ChangePartition { input, .. } => input.0 < first_bad_index,
+        | Operator::ChangePartition { input, .. }
+        | Operator::Keyswitch { input, .. } => input.0 < first_bad_index,
         Operator::LinearNoise { inputs, .. }
         | Operator::Dot { inputs, .. }
         | Operator::MaxNoise { inputs, .. } => inputs.iter().all(|input| input.0 < first_bad_index),
@@ -76,7 +77,15 @@ pub fn has_round(dag: &Dag) -> bool {
 
 pub fn has_change_partition(dag: &Dag) -> bool {
     for op in &dag.operators {
-        if matches!(op, Operator::ChangePartition { .. }) {
+        if matches!(
+            op,
+            Operator::ChangePartition { .. }
+                | Operator::Keyswitch { .. }
+                | Operator::Input {
+                    partition: Some(_),
+                    ..
+                }
+        ) {
             return true;
         }
     }
@@ -92,6 +101,21 @@ pub fn has_unsafe_cast(dag: &Dag) -> bool {
     false
 }
 
+pub fn has_input_variance(dag: &Dag) -> bool {
+    for op in &dag.operators {
+        if matches!(
+            op,
+            Operator::Input {
+                input_variance: Some(_),
+                ..
+            }
+        ) {
+            return true;
+        }
+    }
+    false
+}
+
 pub fn assert_no_round(dag: &Dag) {
     assert!(!has_round(dag));
 }
@@ -100,18 +124,28 @@ pub fn assert_no_change_partition(dag: &Dag) {
     assert!(!has_change_partition(dag));
 }
 
-fn assert_valid_variances(dag: &SoloKeyDag) {
+fn has_real_weights(dag: &Dag) -> bool {
+    dag.operators.iter().any(|op| {
+        matches!(op, Operator::LinearNoise { weights, .. }
+            if weights.iter().any(|weight| weight.fract() != 0.0))
+    })
+}
+
+fn assert_valid_variances(dag: &SoloKeyDag, real_weights: bool) {
+    // real weights (e.g. float dots) can scale variances below 1, integer weights cannot
+    let min_coeff = if real_weights { f64::MIN_POSITIVE } else { 1.0 };
     for &out_variance in &dag.out_variances {
         assert!(
             SymbolicVariance::ZERO == out_variance // Special case of multiply by 0
-            || 1.0 <= out_variance.input_coeff
-            || 1.0 <= out_variance.lut_coeff
+            || min_coeff <= out_variance.input_coeff
+            || min_coeff <= out_variance.lut_coeff
+            || 0.0 < out_variance.constant
         );
     }
 }
 
-fn assert_properties_correctness(dag: &SoloKeyDag) {
-    assert_valid_variances(dag);
+fn assert_properties_correctness(dag: &SoloKeyDag, real_weights: bool) {
+    assert_valid_variances(dag, real_weights);
 }
 
 #[derive(Clone, Debug)]
@@ -147,7 +181,14 @@ fn out_variance(
     // Maintain a linear combination of input_variance and lut_out_variance
     // TODO: track each elements instead of container
     match op {
-        Operator::Input { .. } => SymbolicVariance::INPUT,
+        Operator::Input {
+            input_variance: None,
+            ..
+        } => SymbolicVariance::INPUT,
+        Operator::Input {
+            input_variance: Some(variance),
+            ..
+        } => SymbolicVariance::constant(*variance),
         Operator::ZeroNoise { .. } => SymbolicVariance::ZERO,
         Operator::Lut { .. } => SymbolicVariance::LUT,
         Operator::LinearNoise {
@@ -166,6 +207,7 @@ fn out_variance(
                 .fold(SymbolicVariance::ZERO, |acc, var| SymbolicVariance {
                     lut_coeff: acc.lut_coeff.max(var.lut_coeff),
                     input_coeff: acc.input_coeff.max(var.input_coeff),
+                    constant: acc.constant.max(var.constant),
                 })
         }
         Operator::Dot {
@@ -200,9 +242,9 @@ fn out_variance(
             .fold(SymbolicVariance::ZERO, |acc, (weight, var)| {
                 acc + var * square(*weight as f64)
             }),
-        Operator::UnsafeCast { input, .. } | Operator::ChangePartition { input, .. } => {
-            out_variances[input.0]
-        }
+        Operator::UnsafeCast { input, .. }
+        | Operator::ChangePartition { input, .. }
+        | Operator::Keyswitch { input, .. } => out_variances[input.0],
         Operator::Round { .. } => {
             unreachable!("Round should have been either expanded or integrated to a lut")
         }
@@ -236,9 +278,13 @@ fn in_luts_variance(
         .iter()
         .enumerate()
         .filter_map(|(i, op)| {
+            // A shared rotation has the input constraint of its rotation lut
             if let &Operator::Lut { input, .. } = op {
+                if dag.rotation_lut(OperatorIndex(i)).0 != i {
+                    return None;
+                }
                 Some((
-                    dag.out_precisions[input.0],
+                    dag.lut_in_precision(OperatorIndex(i)),
                     dag.out_shapes[i].clone(),
                     out_variances[input.0],
                 ))
@@ -267,7 +313,8 @@ fn op_levelled_complexity(op: &Operator, out_shapes: &[Shape]) -> LevelledComple
         | Operator::MaxNoise { .. }
         | Operator::Lut { .. }
         | Operator::UnsafeCast { .. }
-        | Operator::ChangePartition { .. } => LevelledComplexity::ZERO,
+        | Operator::ChangePartition { .. }
+        | Operator::Keyswitch { .. } => LevelledComplexity::ZERO,
         Operator::Round { .. } => {
             unreachable!("Round should have been either expanded or integrated to a lut")
         }
@@ -276,8 +323,12 @@ fn op_levelled_complexity(op: &Operator, out_shapes: &[Shape]) -> LevelledComple
 
 pub fn levelled_complexity(dag: &Dag) -> LevelledComplexity {
     let mut levelled_complexity = LevelledComplexity::ZERO;
-    for op in &dag.operators {
+    for (i, op) in dag.operators.iter().enumerate() {
         levelled_complexity += op_levelled_complexity(op, &dag.out_shapes);
+        // A lut sharing a rotation costs a sample extraction instead of a pbs
+        if dag.rotation_lut(OperatorIndex(i)).0 != i {
+            levelled_complexity += LevelledComplexity::ADDITION * dag.out_shapes[i].flat_size();
+        }
     }
     levelled_complexity
 }
@@ -286,7 +337,9 @@ pub fn lut_count_from_dag(dag: &Dag) -> u64 {
     let mut count = 0;
     for (i, op) in dag.operators.iter().enumerate() {
         if let Operator::Lut { .. } = op {
-            count += dag.out_shapes[i].flat_size();
+            if dag.rotation_lut(OperatorIndex(i)).0 == i {
+                count += dag.out_shapes[i].flat_size();
+            }
         } else if let Operator::Round { out_precision, .. } = op {
             count += dag.out_shapes[i].flat_size() * (dag.out_precisions[i] - out_precision) as u64;
         }
@@ -308,7 +361,12 @@ fn constraints_by_precisions(
     in_luts_variance: &[(Precision, Shape, SymbolicVariance)],
     noise_config: &NoiseBoundConfig,
 ) -> Vec<VariancesAndBound> {
-    let precisions: HashSet<Precision> = out_precisions.iter().copied().collect();
+    // Rounded luts read fewer bits than their input precision
+    let precisions: HashSet<Precision> = out_precisions
+        .iter()
+        .copied()
+        .chain(in_luts_variance.iter().map(|(precision, _, _)| *precision))
+        .collect();
     let mut precisions: Vec<Precision> = precisions.iter().copied().collect();
     let to_noise_summary = |precision: &Precision| {
         constraint_for_one_precision(
@@ -341,10 +399,14 @@ fn select_precision<T1: Clone, T2: Copy>(
 fn counted_symbolic_variance(
     symbolic_variances: &[(Shape, SymbolicVariance)],
 ) -> Vec<(u64, SymbolicVariance)> {
-    pub fn exact_key(v: &SymbolicVariance) -> (u64, u64) {
-        (v.lut_coeff.to_bits(), v.input_coeff.to_bits())
+    pub fn exact_key(v: &SymbolicVariance) -> (u64, u64, u64) {
+        (
+            v.lut_coeff.to_bits(),
+            v.input_coeff.to_bits(),
+            v.constant.to_bits(),
+        )
     }
-    let mut count: HashMap<(u64, u64), u64> = HashMap::new();
+    let mut count: HashMap<(u64, u64, u64), u64> = HashMap::new();
     for (s, v) in symbolic_variances {
         *count.entry(exact_key(v)).or_insert(0) += s.flat_size();
     }
@@ -421,7 +483,7 @@ pub fn analyze(dag: &Dag, noise_config: &NoiseBoundConfig) -> SoloKeyDag {
         levelled_complexity,
         constraints_by_precisions,
     };
-    assert_properties_correctness(&result);
+    assert_properties_correctness(&result, has_real_weights(dag));
     result
 }
 
@@ -498,7 +560,8 @@ pub fn p_error_from_relative_variance(relative_variance: f64, kappa: f64) -> f64
     error::error_probability_of_sigma_scale(sigma_scale)
 }
 
-fn p_error_per_constraint(
+// ln of the success probability, log_success keeps the accuracy near p_error = 0
+fn log_success_per_constraint(
     constraint: &VariancesAndBound,
     input_noise_out: f64,
     blind_rotate_noise_out: f64,
@@ -506,15 +569,14 @@ fn p_error_per_constraint(
     noise_modulus_switching: f64,
     kappa: f64,
 ) -> f64 {
-    // Note: no log probability to keep accuracy near 0, 0 is a fine answer when p_success is very small.
-    let mut p_error = 0.0;
+    let mut log_success_sum = 0.0;
     for &(count, vf) in &constraint.all_output {
         assert!(0 < count);
         let variance = vf.eval(input_noise_out, blind_rotate_noise_out);
         let relative_variance = variance / constraint.safe_variance_bound;
         let vf_p_error = p_error_from_relative_variance(relative_variance, kappa);
 
-        p_error = combine_errors(p_error, repeat_p_error(vf_p_error, count));
+        log_success_sum += count as f64 * log_success(vf_p_error);
     }
     // the maximal variance encountered during a lut computation
     for &(count, vf) in &constraint.all_in_lut {
@@ -524,9 +586,9 @@ fn p_error_per_constraint(
             (variance + noise_keyswitch + noise_modulus_switching) / constraint.safe_variance_bound;
         let vf_p_error = p_error_from_relative_variance(relative_variance, kappa);
 
-        p_error = combine_errors(p_error, repeat_p_error(vf_p_error, count));
+        log_success_sum += count as f64 * log_success(vf_p_error);
     }
-    p_error
+    log_success_sum
 }
 
 impl SoloKeyDag {
@@ -550,6 +612,24 @@ impl SoloKeyDag {
             relative_var * variance_bound,
         )
     }
+    // Headroom of the most constrained output, i.e. its failure bound variance over its variance
+    pub fn noise_margin(
+        &self,
+        input_noise_out: f64,
+        blind_rotate_noise_out: f64,
+        noise_keyswitch: f64,
+        noise_modulus_switching: f64,
+    ) -> f64 {
+        let (relative_var, _) = peak_relative_variance(
+            self,
+            input_noise_out,
+            blind_rotate_noise_out,
+            noise_keyswitch,
+            noise_modulus_switching,
+        );
+        1.0 / relative_var
+    }
+
     pub fn global_p_error(
         &self,
         input_noise_out: f64,
@@ -558,9 +638,9 @@ impl SoloKeyDag {
         noise_modulus_switching: f64,
         kappa: f64,
     ) -> f64 {
-        let mut p_error = 0.0;
+        let mut log_success_sum = 0.0;
         for ns in &self.constraints_by_precisions {
-            let p_error_c = p_error_per_constraint(
+            log_success_sum += log_success_per_constraint(
                 ns,
                 input_noise_out,
                 blind_rotate_noise_out,
@@ -568,9 +648,8 @@ impl SoloKeyDag {
                 noise_modulus_switching,
                 kappa,
             );
-
-            p_error = combine_errors(p_error, p_error_c);
         }
+        let p_error = p_error_of_log_success(log_success_sum);
         assert!((0.0..=1.0).contains(&p_error));
         p_error
     }
@@ -661,6 +740,49 @@ pub mod tests {
         assert!(constraint.pareto_in_lut.is_empty());
     }
 
+    #[test]
+    fn test_multi_lut() {
+        let mut graph = Dag::new();
+        let input1 = graph.add_input(8, Shape::vector(2));
+        let tables = vec![FunctionTable::UNKWOWN; 3];
+        let outputs = graph.add_multi_lut(input1, &tables, 8).unwrap();
+        let analysis = analyze(&graph);
+
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(analysis.nb_luts, 2);
+        assert_eq!(
+            analysis.levelled_complexity,
+            LevelledComplexity::ADDITION * 4
+        );
+        for output in outputs {
+            assert_eq!(analysis.out_variances[output.0], SymbolicVariance::LUT);
+            assert_eq!(graph.out_precisions[output.0], 8);
+            assert_eq!(graph.out_shapes[output.0], Shape::vector(2));
+        }
+    }
+
+    #[test]
+    fn test_input_with_variance() {
+        let mut graph = Dag::new();
+        let variance = 2.0_f64.powi(-60);
+        let input1 = graph.add_input_with_variance(8, Shape::number(), variance);
+        let dot = graph.add_dot([input1], [3]);
+        let lut1 = graph.add_lut(dot, FunctionTable::UNKWOWN, 8);
+        let analysis = analyze(&graph);
+
+        assert_eq!(
+            analysis.out_variances[input1.0],
+            SymbolicVariance::constant(variance)
+        );
+        assert_f64_eq(analysis.out_variances[dot.0].constant, 9.0 * variance);
+        assert_f64_eq(analysis.out_variances[dot.0].input_coeff, 0.0);
+        assert_eq!(analysis.out_variances[lut1.0], SymbolicVariance::LUT);
+        let constraint = analysis.constraint();
+        assert!(constraint.pareto_in_lut.len() == 1);
+        assert_f64_eq(constraint.pareto_in_lut[0].constant, 9.0 * variance);
+        assert_f64_eq(constraint.pareto_in_lut[0].eval(1.0, 1.0), 9.0 * variance);
+    }
+
     #[test]
     fn test_1_lut() {
         let mut graph = Dag::new();
@@ -700,6 +822,7 @@ pub mod tests {
         let expected_var = SymbolicVariance {
             input_coeff: norm2,
             lut_coeff: 0.0,
+            constant: 0.0,
         };
         assert!(analysis.out_variances[dot.0] == expected_var);
         assert!(graph.out_shapes[dot.0] == Shape::number());
@@ -758,18 +881,22 @@ pub mod tests {
         let expected_var_dot1 = SymbolicVariance {
             input_coeff: weights.square_norm2() as f64,
             lut_coeff: 0.0,
+            constant: 0.0,
         };
         let expected_var_lut1 = SymbolicVariance {
             input_coeff: 0.0,
             lut_coeff: 1.0,
+            constant: 0.0,
         };
         let expected_var_dot2 = SymbolicVariance {
             input_coeff: 0.0,
             lut_coeff: weights.square_norm2() as f64,
+            constant: 0.0,
         };
         let expected_var_lut2 = SymbolicVariance {
             input_coeff: 0.0,
             lut_coeff: 1.0,
+            constant: 0.0,
         };
         assert!(analysis.out_variances[dot1.0] == expected_var_dot1);
         assert!(analysis.out_variances[lut1.0] == expected_var_lut1);
@@ -806,6 +933,7 @@ pub mod tests {
         let expected_mixed = SymbolicVariance {
             input_coeff: square(weights.values[0] as f64),
             lut_coeff: square(weights.values[1] as f64),
+            constant: 0.0,
         };
         let constraint = analysis.constraint();
         assert_eq!(constraint.pareto_output.len(), 1);
@@ -1,21 +1,23 @@
-use concrete_cpu_noise_model::gaussian_noise::noise::modulus_switching::estimate_modulus_switching_noise_with_binary_key;
-use concrete_security_curves::gaussian::security::minimal_variance_lwe;
+use crate::security::minimal_variance_lwe;
 
 use super::analyze;
-use crate::dag::operator::LevelledComplexity;
+use crate::dag::operator::{LevelledComplexity, Operator, OperatorIndex};
+use crate::dag::rewrite::round::expand_round_and_index_map;
 use crate::dag::unparametrized;
 use crate::noise_estimator::error;
+use crate::noise_estimator::p_error::{combine_errors, repeat_p_error};
 use crate::optimization::atomic_pattern::{
     OptimizationDecompositionsConsts, OptimizationState, Solution,
 };
-use crate::optimization::config::{Config, NoiseBoundConfig, SearchSpace};
+use crate::optimization::config::{Config, NoiseBoundConfig, Objective, SearchSpace};
 use crate::optimization::decomposition::cmux::{
-    lowest_complexity_br, lowest_noise_br, CmuxComplexityNoise,
+    lowest_complexity_br, lowest_key_size_br, lowest_noise_br, CmuxComplexityNoise,
 };
 use crate::optimization::decomposition::keyswitch::{
-    lowest_complexity_ks, lowest_noise_ks, KsComplexityNoise,
+    lowest_complexity_ks, lowest_key_size_ks, lowest_noise_ks, KsComplexityNoise,
 };
 use crate::optimization::decomposition::PersistDecompCaches;
+use crate::optimization::progress::Progress;
 use crate::parameters::GlweParameters;
 
 #[allow(clippy::too_many_lines)]
@@ -32,7 +34,11 @@ fn update_best_solution_with_best_decompositions(
 ) {
     assert!(dag.nb_luts > 0);
     let input_lwe_dimension = glwe_params.sample_extract_lwe_dimension();
+    let objective = consts.config.objective;
 
+    let mut best_cost = state.best_solution.map_or(f64::INFINITY, |s| {
+        objective.cost(s.complexity, s.key_size())
+    });
     let mut best_complexity = state.best_solution.map_or(f64::INFINITY, |s| s.complexity);
     let mut best_variance = state.best_solution.map_or(f64::INFINITY, |s| s.noise_max);
     let mut best_p_error = state.best_solution.map_or(f64::INFINITY, |s| s.p_error);
@@ -44,10 +50,11 @@ fn update_best_solution_with_best_decompositions(
 
     for &cmux_quantity in cmux_pareto {
         let pbs_cost = cmux_quantity.complexity_br(internal_dim);
+        let bsk_size = cmux_quantity.key_size_br(internal_dim, glwe_params);
         // increasing complexity, decreasing variance
         let complexity = dag.complexity(input_lwe_dimension, pbs_cost);
-        if complexity > best_complexity {
-            // Since br_pareto is scanned by increasing complexity, we can stop
+        if objective.cost(complexity, bsk_size) > best_cost {
+            // Since br_pareto is scanned by increasing complexity and key size, we can stop
             break;
         }
 
@@ -62,9 +69,11 @@ fn update_best_solution_with_best_decompositions(
             let complexity_keyswitch = ks_quantity.complexity(input_lwe_dimension);
             let one_lut_cost = complexity_keyswitch + pbs_cost;
             let complexity = dag.complexity(input_lwe_dimension, one_lut_cost);
-            let worse_complexity = complexity > best_complexity;
-            if worse_complexity {
-                // Since ks_pareto is scanned by increasing complexity, we can stop
+            let key_size = bsk_size + ks_quantity.key_size(input_lwe_dimension, internal_dim);
+            let cost = objective.cost(complexity, key_size);
+            let worse_cost = cost > best_cost;
+            if worse_cost {
+                // Since ks_pareto is scanned by increasing complexity and key size, we can stop
                 break;
             }
             let ks_variance = ks_quantity.noise(input_lwe_dimension);
@@ -87,14 +96,14 @@ fn update_best_solution_with_best_decompositions(
                 consts.kappa,
             );
             #[allow(clippy::float_cmp)]
-            let same_comlexity_no_few_errors =
-                complexity == best_complexity && peek_p_error >= best_p_error;
-            if same_comlexity_no_few_errors {
+            let same_cost_no_few_errors = cost == best_cost && peek_p_error >= best_p_error;
+            if same_cost_no_few_errors {
                 continue;
             }
 
-            // The complexity is either better or equivalent with less errors
+            // The cost is either better or equivalent with less errors
             update_best_solution = true;
+            best_cost = cost;
             best_complexity = complexity;
             best_p_error = peek_p_error;
             best_variance = variance;
@@ -127,6 +136,12 @@ fn update_best_solution_with_best_decompositions(
                 consts.kappa,
             ),
             noise_max: best_variance,
+            noise_margin: dag.noise_margin(
+                input_noise_out,
+                br_variance,
+                ks_variance,
+                noise_modulus_switching,
+            ),
         });
     }
 }
@@ -143,7 +158,10 @@ fn update_no_luts_solution(
     const CHECKED_IGNORED_NOISE: f64 = f64::MAX;
     const UNDEFINED_PARAM: u64 = 0;
 
-    let best_complexity = state.best_solution.map_or(f64::INFINITY, |s| s.complexity);
+    let objective = consts.config.objective;
+    let best_cost = state.best_solution.map_or(f64::INFINITY, |s| {
+        objective.cost(s.complexity, s.key_size())
+    });
     let best_p_error = state.best_solution.map_or(f64::INFINITY, |s| s.p_error);
 
     let complexity = if dag.levelled_complexity == LevelledComplexity::ZERO {
@@ -154,8 +172,10 @@ fn update_no_luts_solution(
     } else {
         dag.levelled_complexity(input_lwe_dimension)
     };
+    // there is no key without luts
+    let cost = objective.cost(complexity, 0.0);
 
-    if complexity > best_complexity {
+    if cost > best_cost {
         return;
     }
 
@@ -168,11 +188,11 @@ fn update_no_luts_solution(
     );
 
     #[allow(clippy::float_cmp)]
-    let same_complexity_no_few_errors = complexity == best_complexity && p_error >= best_p_error;
-    if same_complexity_no_few_errors {
+    let same_cost_no_few_errors = cost == best_cost && p_error >= best_p_error;
+    if same_cost_no_few_errors {
         return;
     }
-    // The complexity is either better or equivalent with less errors
+    // The cost is either better or equivalent with less errors
     state.best_solution = Some(Solution {
         input_lwe_dimension,
         internal_ks_output_lwe_dimension: UNDEFINED_PARAM,
@@ -192,6 +212,12 @@ fn update_no_luts_solution(
             consts.kappa,
         ),
         noise_max: variance,
+        noise_margin: dag.noise_margin(
+            input_noise_out,
+            CHECKED_IGNORED_NOISE,
+            CHECKED_IGNORED_NOISE,
+            CHECKED_IGNORED_NOISE,
+        ),
     });
 }
 
@@ -239,14 +265,15 @@ fn not_feasible_macro_parameters(
 
 fn too_complex_macro_parameters(
     state: &OptimizationState,
+    objective: Objective,
     dag: &analyze::SoloKeyDag,
     internal_dim: u64,
     glwe_params: GlweParameters,
     cmux_pareto: &[CmuxComplexityNoise],
     ks_pareto: &[KsComplexityNoise],
 ) -> bool {
-    let best_complexity = if let Some(sol) = state.best_solution {
-        sol.complexity
+    let best_cost = if let Some(sol) = state.best_solution {
+        objective.cost(sol.complexity, sol.key_size())
     } else {
         return false;
     };
@@ -254,17 +281,19 @@ fn too_complex_macro_parameters(
     let lowest_complexity_br = lowest_complexity_br(cmux_pareto, internal_dim);
     let lowest_complexity_ks = lowest_complexity_ks(ks_pareto, internal_dim);
     let lower_one_lut_complexity = lowest_complexity_ks + lowest_complexity_br;
+    let lower_key_size = lowest_key_size_br(cmux_pareto, internal_dim, glwe_params)
+        + lowest_key_size_ks(ks_pareto, input_lwe_dimension, internal_dim);
 
-    dag.complexity(input_lwe_dimension, lower_one_lut_complexity) > best_complexity
+    objective.cost(
+        dag.complexity(input_lwe_dimension, lower_one_lut_complexity),
+        lower_key_size,
+    ) > best_cost
 }
 
-#[allow(clippy::too_many_lines)]
-pub fn optimize(
+fn analyze_with_consts<'a>(
     dag: &unparametrized::Dag,
-    config: Config,
-    search_space: &SearchSpace,
-    persistent_caches: &PersistDecompCaches,
-) -> OptimizationState {
+    config: Config<'a>,
+) -> (analyze::SoloKeyDag, OptimizationDecompositionsConsts<'a>) {
     let ciphertext_modulus_log = config.ciphertext_modulus_log;
     let security_level = config.security_level;
     let noise_config = NoiseBoundConfig {
@@ -291,6 +320,36 @@ pub fn optimize(
         noise_factor: f64::NAN, // superseeded by dag.lut_variance_max
         safe_variance,
     };
+    (dag, consts)
+}
+
+pub fn optimize(
+    dag: &unparametrized::Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    persistent_caches: &PersistDecompCaches,
+) -> OptimizationState {
+    optimize_with_progress(
+        dag,
+        config,
+        search_space,
+        persistent_caches,
+        &mut Progress::none(),
+    )
+}
+
+/// Same as `optimize`, reporting each macro parameters candidate to `progress`.
+#[allow(clippy::too_many_lines)]
+pub fn optimize_with_progress(
+    dag: &unparametrized::Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    persistent_caches: &PersistDecompCaches,
+    progress: &mut Progress<'_>,
+) -> OptimizationState {
+    let ciphertext_modulus_log = config.ciphertext_modulus_log;
+
+    let (dag, consts) = analyze_with_consts(dag, config);
 
     let mut state = OptimizationState {
         best_solution: None,
@@ -301,8 +360,9 @@ pub fn optimize(
     }
     let mut caches = persistent_caches.caches();
 
+    let key_distribution = persistent_caches.key_distribution;
     let noise_modulus_switching = |glwe_log2_poly_size, internal_lwe_dimensions| {
-        estimate_modulus_switching_noise_with_binary_key(
+        key_distribution.modulus_switching_noise(
             internal_lwe_dimensions,
             glwe_log2_poly_size,
             ciphertext_modulus_log,
@@ -324,6 +384,7 @@ pub fn optimize(
             let cmux_pareto = caches.cmux.pareto_quantities(glwe_params);
 
             for &internal_dim in &search_space.internal_lwe_dimensions {
+                progress.step();
                 let ks_pareto = caches.keyswitch.pareto_quantities(internal_dim);
 
                 let noise_modulus_switching =
@@ -334,6 +395,7 @@ pub fn optimize(
                 }
                 if too_complex_macro_parameters(
                     &state,
+                    config.objective,
                     &dag,
                     internal_dim,
                     glwe_params,
@@ -382,6 +444,514 @@ pub fn optimize(
     state
 }
 
+fn is_dominated(front: &[Solution], complexity: f64, p_error: f64) -> bool {
+    front
+        .iter()
+        .any(|sol| sol.complexity <= complexity && sol.p_error <= p_error)
+}
+
+fn insert_in_pareto_front(front: &mut Vec<Solution>, solution: Solution) {
+    if is_dominated(front, solution.complexity, solution.p_error) {
+        return;
+    }
+    front.retain(|sol| solution.complexity > sol.complexity || solution.p_error > sol.p_error);
+    front.push(solution);
+}
+
+fn update_pareto_front_with_decompositions(
+    front: &mut Vec<Solution>,
+    consts: &OptimizationDecompositionsConsts,
+    dag: &analyze::SoloKeyDag,
+    internal_dim: u64,
+    glwe_params: GlweParameters,
+    input_noise_out: f64,
+    noise_modulus_switching: f64,
+    cmux_pareto: &[CmuxComplexityNoise],
+    ks_pareto: &[KsComplexityNoise],
+) {
+    let input_lwe_dimension = glwe_params.sample_extract_lwe_dimension();
+    let lowest_complexity_ks = lowest_complexity_ks(ks_pareto, input_lwe_dimension);
+    let lowest_noise_ks = lowest_noise_ks(ks_pareto, input_lwe_dimension);
+
+    // Skips the decompositions that cannot beat the current front, even in the best case.
+    let dominated = |front: &[Solution], one_lut_cost: f64, br_variance: f64| {
+        let complexity = dag.complexity(input_lwe_dimension, one_lut_cost);
+        let (p_error, _) = dag.peek_p_error(
+            input_noise_out,
+            br_variance,
+            lowest_noise_ks,
+            noise_modulus_switching,
+            consts.kappa,
+        );
+        is_dominated(front, complexity, p_error)
+    };
+
+    if dominated(
+        front,
+        lowest_complexity_br(cmux_pareto, internal_dim) + lowest_complexity_ks,
+        lowest_noise_br(cmux_pareto, internal_dim),
+    ) {
+        return;
+    }
+
+    for &cmux_quantity in cmux_pareto {
+        let pbs_cost = cmux_quantity.complexity_br(internal_dim);
+        let br_variance = cmux_quantity.noise_br(internal_dim);
+
+        if !dag.feasible(input_noise_out, br_variance, 0.0, noise_modulus_switching) {
+            continue;
+        }
+        if dominated(front, pbs_cost + lowest_complexity_ks, br_variance) {
+            continue;
+        }
+        for &ks_quantity in ks_pareto {
+            let complexity_keyswitch = ks_quantity.complexity(input_lwe_dimension);
+            let complexity = dag.complexity(input_lwe_dimension, complexity_keyswitch + pbs_cost);
+            let ks_variance = ks_quantity.noise(input_lwe_dimension);
+
+            if !dag.feasible(
+                input_noise_out,
+                br_variance,
+                ks_variance,
+                noise_modulus_switching,
+            ) {
+                continue;
+            }
+
+            let (p_error, variance) = dag.peek_p_error(
+                input_noise_out,
+                br_variance,
+                ks_variance,
+                noise_modulus_switching,
+                consts.kappa,
+            );
+            if is_dominated(front, complexity, p_error) {
+                continue;
+            }
+
+            insert_in_pareto_front(
+                front,
+                Solution {
+                    input_lwe_dimension,
+                    internal_ks_output_lwe_dimension: internal_dim,
+                    ks_decomposition_level_count: ks_quantity.decomp.level,
+                    ks_decomposition_base_log: ks_quantity.decomp.log2_base,
+                    glwe_polynomial_size: glwe_params.polynomial_size(),
+                    glwe_dimension: glwe_params.glwe_dimension,
+                    br_decomposition_level_count: cmux_quantity.decomp.level,
+                    br_decomposition_base_log: cmux_quantity.decomp.log2_base,
+                    complexity,
+                    p_error,
+                    global_p_error: dag.global_p_error(
+                        input_noise_out,
+                        br_variance,
+                        ks_variance,
+                        noise_modulus_switching,
+                        consts.kappa,
+                    ),
+                    noise_max: variance,
+                    noise_margin: dag.noise_margin(
+                        input_noise_out,
+                        br_variance,
+                        ks_variance,
+                        noise_modulus_switching,
+                    ),
+                },
+            );
+        }
+    }
+}
+
+/// Returns the solutions that are not dominated in both complexity and error probability, sorted
+/// by increasing complexity.
+///
+/// Unlike [`optimize`], the search is not pruned by the best complexity found so far, so it is
+/// slower.
+pub fn optimize_pareto(
+    dag: &unparametrized::Dag,
+    config: Config,
+    search_space: &SearchSpace,
+    persistent_caches: &PersistDecompCaches,
+) -> Vec<Solution> {
+    let ciphertext_modulus_log = config.ciphertext_modulus_log;
+
+    let (dag, consts) = analyze_with_consts(dag, config);
+
+    if dag.nb_luts == 0 {
+        // Without luts, the best solution is the only relevant one.
+        let state = OptimizationState {
+            best_solution: None,
+        };
+        return optimize_no_luts(state, &consts, &dag, search_space)
+            .best_solution
+            .into_iter()
+            .collect();
+    }
+    let mut caches = persistent_caches.caches();
+    let mut front = vec![];
+
+    for &glwe_dim in &search_space.glwe_dimensions {
+        for &glwe_log_poly_size in &search_space.glwe_log_polynomial_sizes {
+            let glwe_params = GlweParameters {
+                log2_polynomial_size: glwe_log_poly_size,
+                glwe_dimension: glwe_dim,
+            };
+            let input_noise_out = minimal_variance(&config, glwe_params);
+
+            let cmux_pareto = caches.cmux.pareto_quantities(glwe_params);
+
+            for &internal_dim in &search_space.internal_lwe_dimensions {
+                let ks_pareto = caches.keyswitch.pareto_quantities(internal_dim);
+
+                let noise_modulus_switching = caches.key_distribution.modulus_switching_noise(
+                    internal_dim,
+                    glwe_log_poly_size,
+                    ciphertext_modulus_log,
+                );
+                if !dag.feasible(input_noise_out, 0.0, 0.0, noise_modulus_switching) {
+                    // noise_modulus_switching is increasing with internal_dim
+                    break;
+                }
+                if not_feasible_macro_parameters(
+                    &dag,
+                    internal_dim,
+                    input_noise_out,
+                    noise_modulus_switching,
+                    cmux_pareto,
+                    ks_pareto,
+                ) {
+                    continue;
+                }
+                update_pareto_front_with_decompositions(
+                    &mut front,
+                    &consts,
+                    &dag,
+                    internal_dim,
+                    glwe_params,
+                    input_noise_out,
+                    noise_modulus_switching,
+                    cmux_pareto,
+                    ks_pareto,
+                );
+            }
+        }
+    }
+
+    persistent_caches.backport(caches);
+
+    front.sort_by(|a, b| a.complexity.total_cmp(&b.complexity));
+    front
+}
+
+/// Returns the fresh and blind rotate output variances of a solution, and the extra variance of a
+/// lut input due to keyswitch and modulus switching.
+fn solution_base_noises(
+    expanded: &unparametrized::Dag,
+    config: Config,
+    solution: &Solution,
+    persistent_caches: &PersistDecompCaches,
+) -> Option<(f64, f64, f64)> {
+    let ciphertext_modulus_log = config.ciphertext_modulus_log;
+    let has_luts = expanded
+        .operators
+        .iter()
+        .any(|op| matches!(op, Operator::Lut { .. }));
+
+    let noises = if has_luts {
+        let glwe_params = GlweParameters {
+            log2_polynomial_size: solution.glwe_polynomial_size.ilog2() as u64,
+            glwe_dimension: solution.glwe_dimension,
+        };
+        let internal_dim = solution.internal_ks_output_lwe_dimension;
+        let (cmux_quantity, ks_quantity) =
+            solution_decompositions(solution, glwe_params, persistent_caches)?;
+        let noise_modulus_switching = persistent_caches.key_distribution.modulus_switching_noise(
+            internal_dim,
+            glwe_params.log2_polynomial_size,
+            ciphertext_modulus_log,
+        );
+        (
+            minimal_variance(&config, glwe_params),
+            cmux_quantity.noise_br(internal_dim),
+            ks_quantity.noise(solution.input_lwe_dimension) + noise_modulus_switching,
+        )
+    } else {
+        let input_noise_out = minimal_variance_lwe(
+            solution.input_lwe_dimension,
+            ciphertext_modulus_log,
+            config.security_level,
+        );
+        (input_noise_out, 0.0, 0.0)
+    };
+    Some(noises)
+}
+
+/// The worst-case noise of an operator output under fixed parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OperatorNoise {
+    pub index: OperatorIndex,
+    pub variance: f64,
+    // The safe variance bound minus the variance, negative when the bound is exceeded
+    pub margin: f64,
+}
+
+/// Returns the worst-case variance of each operator of the dag for a given solution.
+///
+/// Operators entering a lut account for the keyswitch and modulus switching noises.
+/// Returns `None` when the solution decompositions are not found in the caches.
+pub fn operator_noises(
+    dag: &unparametrized::Dag,
+    config: Config,
+    solution: &Solution,
+    persistent_caches: &PersistDecompCaches,
+) -> Option<Vec<OperatorNoise>> {
+    let noise_config = NoiseBoundConfig {
+        security_level: config.security_level,
+        maximum_acceptable_error_probability: config.maximum_acceptable_error_probability,
+        ciphertext_modulus_log: config.ciphertext_modulus_log,
+    };
+    let (expanded, index_map) = expand_round_and_index_map(dag);
+    let out_variances = analyze::out_variances(&expanded);
+    let (input_noise_out, br_variance, lut_input_noise) =
+        solution_base_noises(&expanded, config, solution, persistent_caches)?;
+
+    let mut enters_lut = vec![false; expanded.len()];
+    for op in &expanded.operators {
+        if let Operator::Lut { input, .. } = op {
+            enters_lut[input.0] = true;
+        }
+    }
+    let expanded_noise = |i: OperatorIndex| {
+        let variance = out_variances[i.0].eval(input_noise_out, br_variance);
+        let variance = if enters_lut[i.0] {
+            variance + lut_input_noise
+        } else {
+            variance
+        };
+        let bound = analyze::safe_noise_bound(expanded.out_precisions[i.0], &noise_config);
+        (variance, bound - variance)
+    };
+    Some(
+        index_map
+            .iter()
+            .enumerate()
+            .map(|(i, expanded_indices)| {
+                let (variance, margin) = expanded_indices
+                    .iter()
+                    .map(|&j| expanded_noise(j))
+                    .fold((0.0_f64, f64::INFINITY), |(v, m), (vj, mj)| {
+                        (v.max(vj), m.min(mj))
+                    });
+                OperatorNoise {
+                    index: OperatorIndex(i),
+                    variance,
+                    margin,
+                }
+            })
+            .collect(),
+    )
+}
+
+/// The failure probability allotted to an operator under fixed parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OperatorBudget {
+    pub index: OperatorIndex,
+    pub p_error: f64,
+}
+
+/// Returns the failure probability of each operator of the dag for a given solution.
+///
+/// An operator can fail as an output and as the input of each lut using it, operators which are
+/// neither have a null failure probability. Combining all budgets gives the global error
+/// probability of the solution.
+/// Returns `None` when the solution decompositions are not found in the caches.
+pub fn operator_budgets(
+    dag: &unparametrized::Dag,
+    config: Config,
+    solution: &Solution,
+    persistent_caches: &PersistDecompCaches,
+) -> Option<Vec<OperatorBudget>> {
+    let noise_config = NoiseBoundConfig {
+        security_level: config.security_level,
+        maximum_acceptable_error_probability: config.maximum_acceptable_error_probability,
+        ciphertext_modulus_log: config.ciphertext_modulus_log,
+    };
+    let kappa =
+        error::sigma_scale_of_error_probability(config.maximum_acceptable_error_probability);
+    let (expanded, index_map) = expand_round_and_index_map(dag);
+    let out_variances = analyze::out_variances(&expanded);
+    let (input_noise_out, br_variance, lut_input_noise) =
+        solution_base_noises(&expanded, config, solution, persistent_caches)?;
+
+    // Number of lut evaluations taking each operator as input
+    let mut lut_counts = vec![0; expanded.len()];
+    for (i, op) in expanded.operators.iter().enumerate() {
+        if let Operator::Lut { input, .. } = op {
+            // A shared rotation fails with its rotation lut
+            if expanded.rotation_lut(OperatorIndex(i)).0 == i {
+                lut_counts[input.0] += expanded.out_shapes[i].flat_size();
+            }
+        }
+    }
+    let expanded_p_error = |i: OperatorIndex| {
+        let bound = analyze::safe_noise_bound(expanded.out_precisions[i.0], &noise_config);
+        let p_error_of = |variance: f64, count: u64| {
+            let p_error = analyze::p_error_from_relative_variance(variance / bound, kappa);
+            repeat_p_error(p_error, count)
+        };
+        let variance = out_variances[i.0].eval(input_noise_out, br_variance);
+        let mut p_error = 0.0;
+        if expanded.get_operator(i).is_output() {
+            p_error = combine_errors(
+                p_error,
+                p_error_of(variance, expanded.out_shapes[i.0].flat_size()),
+            );
+        }
+        if lut_counts[i.0] > 0 {
+            p_error = combine_errors(
+                p_error,
+                p_error_of(variance + lut_input_noise, lut_counts[i.0]),
+            );
+        }
+        p_error
+    };
+    Some(
+        index_map
+            .iter()
+            .enumerate()
+            .map(|(i, expanded_indices)| OperatorBudget {
+                index: OperatorIndex(i),
+                p_error: expanded_indices
+                    .iter()
+                    .map(|&j| expanded_p_error(j))
+                    .fold(0.0, combine_errors),
+            })
+            .collect(),
+    )
+}
+
+/// Finds the blind rotate and keyswitch decompositions of a solution in the caches.
+fn solution_decompositions(
+    solution: &Solution,
+    glwe_params: GlweParameters,
+    persistent_caches: &PersistDecompCaches,
+) -> Option<(CmuxComplexityNoise, KsComplexityNoise)> {
+    let mut caches = persistent_caches.caches();
+    let cmux_quantity = *caches
+        .cmux
+        .pareto_quantities(glwe_params)
+        .iter()
+        .find(|q| {
+            q.decomp.level == solution.br_decomposition_level_count
+                && q.decomp.log2_base == solution.br_decomposition_base_log
+        })?;
+    let ks_quantity = *caches
+        .keyswitch
+        .pareto_quantities(solution.internal_ks_output_lwe_dimension)
+        .iter()
+        .find(|q| {
+            q.decomp.level == solution.ks_decomposition_level_count
+                && q.decomp.log2_base == solution.ks_decomposition_base_log
+        })?;
+    Some((cmux_quantity, ks_quantity))
+}
+
+/// Recomputes the noise, error probabilities and complexity of the dag for the parameters of a
+/// solution, without any search.
+///
+/// Returns `None` when the solution is not feasible for the dag or when its decompositions are
+/// not found in the caches.
+pub fn evaluate(
+    dag: &unparametrized::Dag,
+    config: Config,
+    solution: &Solution,
+    persistent_caches: &PersistDecompCaches,
+) -> Option<Solution> {
+    if dag.len() == 0 {
+        return None;
+    }
+    let ciphertext_modulus_log = config.ciphertext_modulus_log;
+    let (dag, consts) = analyze_with_consts(dag, config);
+    let input_lwe_dimension = solution.input_lwe_dimension;
+
+    if dag.nb_luts == 0 {
+        let input_noise_out = minimal_variance_lwe(
+            input_lwe_dimension,
+            ciphertext_modulus_log,
+            config.security_level,
+        );
+        if !dag.feasible(input_noise_out, 0.0, 0.0, 0.0) {
+            return None;
+        }
+        let mut state = OptimizationState {
+            best_solution: None,
+        };
+        update_no_luts_solution(
+            &mut state,
+            &consts,
+            &dag,
+            input_lwe_dimension,
+            input_noise_out,
+        );
+        return state.best_solution;
+    }
+
+    if !solution.glwe_polynomial_size.is_power_of_two() {
+        return None;
+    }
+    let glwe_params = GlweParameters {
+        log2_polynomial_size: solution.glwe_polynomial_size.ilog2() as u64,
+        glwe_dimension: solution.glwe_dimension,
+    };
+    let internal_dim = solution.internal_ks_output_lwe_dimension;
+    let (cmux_quantity, ks_quantity) =
+        solution_decompositions(solution, glwe_params, persistent_caches)?;
+    let input_noise_out = minimal_variance(&config, glwe_params);
+    let noise_modulus_switching = persistent_caches.key_distribution.modulus_switching_noise(
+        internal_dim,
+        glwe_params.log2_polynomial_size,
+        ciphertext_modulus_log,
+    );
+    let br_variance = cmux_quantity.noise_br(internal_dim);
+    let ks_variance = ks_quantity.noise(input_lwe_dimension);
+    if !dag.feasible(
+        input_noise_out,
+        br_variance,
+        ks_variance,
+        noise_modulus_switching,
+    ) {
+        return None;
+    }
+    let one_lut_cost =
+        ks_quantity.complexity(input_lwe_dimension) + cmux_quantity.complexity_br(internal_dim);
+    let (p_error, noise_max) = dag.peek_p_error(
+        input_noise_out,
+        br_variance,
+        ks_variance,
+        noise_modulus_switching,
+        consts.kappa,
+    );
+    Some(Solution {
+        complexity: dag.complexity(input_lwe_dimension, one_lut_cost),
+        p_error,
+        global_p_error: dag.global_p_error(
+            input_noise_out,
+            br_variance,
+            ks_variance,
+            noise_modulus_switching,
+            consts.kappa,
+        ),
+        noise_max,
+        noise_margin: dag.noise_margin(
+            input_noise_out,
+            br_variance,
+            ks_variance,
+            noise_modulus_switching,
+        ),
+        ..*solution
+    })
+}
+
 pub fn add_v0_dag(dag: &mut unparametrized::Dag, sum_size: u64, precision: u64, noise_factor: f64) {
     use crate::dag::operator::{FunctionTable, Shape};
     let manp = noise_factor;
@@ -442,9 +1012,11 @@ pub(crate) mod tests {
             let mut other = other;
             other.global_p_error = self.global_p_error;
             if small_relative_diff(self.noise_max, other.noise_max)
+                && small_relative_diff(self.noise_margin, other.noise_margin)
                 && small_relative_diff(self.p_error, other.p_error)
             {
                 other.noise_max = self.noise_max;
+                other.noise_margin = self.noise_margin;
                 other.p_error = self.p_error;
             }
             assert_eq!(self, &other);
@@ -466,6 +1038,7 @@ pub(crate) mod tests {
             true,
             CIPHERTEXT_MODULUS_LOG,
             FFT_PRECISION,
+            config::KeyDistribution::Binary,
         )
     });
 
@@ -477,6 +1050,7 @@ pub(crate) mod tests {
             ciphertext_modulus_log: 64,
             fft_precision: 53,
             complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
         };
 
         let search_space = SearchSpace::default_cpu();
@@ -522,6 +1096,7 @@ pub(crate) mod tests {
             ciphertext_modulus_log: 64,
             fft_precision: 53,
             complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
         };
 
         _ = optimize_v0(
@@ -618,6 +1193,7 @@ pub(crate) mod tests {
             ciphertext_modulus_log: 64,
             fft_precision: 53,
             complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
         };
 
         let state = optimize(&dag);
@@ -974,9 +1550,10 @@ pub(crate) mod tests {
         let mut dag = unparametrized::Dag::new();
         let weight = Weights::number(weight);
         let val = dag.add_input(precision, shape);
-        let lut1 = dag.add_rounded_lut(val, FunctionTable::UNKWOWN, rounded_precision, precision);
+        let lut1 =
+            dag.add_expanded_rounded_lut(val, FunctionTable::UNKWOWN, rounded_precision, precision);
         let dot = dag.add_dot([lut1], weight);
-        let _lut2 = dag.add_rounded_lut(
+        let _lut2 = dag.add_expanded_rounded_lut(
             dot,
             FunctionTable::UNKWOWN,
             rounded_precision,
@@ -1039,6 +1616,26 @@ pub(crate) mod tests {
         }
     }
 
+    #[test]
+    fn test_fused_rounded_lut_is_cheaper() {
+        let two_nodes = {
+            let mut dag = unparametrized::Dag::new();
+            let input = dag.add_input(8, Shape::number());
+            let rounded = dag.add_round_op(input, 4);
+            let _ = dag.add_lut(rounded, FunctionTable::UNKWOWN, 8);
+            dag
+        };
+        let fused = {
+            let mut dag = unparametrized::Dag::new();
+            let input = dag.add_input(8, Shape::number());
+            let _ = dag.add_rounded_lut(input, FunctionTable::UNKWOWN, 4, 8);
+            dag
+        };
+        let two_nodes = optimize(&two_nodes).best_solution.unwrap();
+        let fused = optimize(&fused).best_solution.unwrap();
+        assert!(fused.complexity < two_nodes.complexity);
+    }
+
     #[allow(clippy::unnecessary_cast)] // clippy bug refusing as Precision on const
     #[test]
     fn test_global_p_error_increased_accumulator() {
@@ -1050,4 +1647,224 @@ pub(crate) mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_pareto_front() {
+        let dag = v0_dag(1, 4, 1.0);
+        let config = Config {
+            security_level: 128,
+            maximum_acceptable_error_probability: _4_SIGMA,
+            key_sharing: true,
+            ciphertext_modulus_log: 64,
+            fft_precision: 53,
+            complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
+        };
+        let search_space = SearchSpace::default_cpu();
+        let front = optimize_pareto(&dag, config, &search_space, &SHARED_CACHES);
+        let best = optimize(&dag).best_solution.unwrap();
+
+        assert!(!front.is_empty());
+        for (i, sol) in front.iter().enumerate() {
+            assert!(sol.p_error <= _4_SIGMA * REL_EPSILON_PROBA);
+            for other in &front[i + 1..] {
+                assert!(sol.complexity <= other.complexity);
+                assert!(sol.p_error > other.p_error);
+            }
+        }
+        assert_f64_eq(front[0].complexity, best.complexity);
+    }
+
+    #[test]
+    fn test_operator_noises() {
+        let mut dag = unparametrized::Dag::new();
+        let input = dag.add_input(4, Shape::number());
+        let dot = dag.add_dot([input], [4]);
+        let lut = dag.add_lut(dot, FunctionTable::UNKWOWN, 4);
+        let config = Config {
+            security_level: 128,
+            maximum_acceptable_error_probability: _4_SIGMA,
+            key_sharing: true,
+            ciphertext_modulus_log: 64,
+            fft_precision: 53,
+            complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
+        };
+        let sol = optimize(&dag).best_solution.unwrap();
+        let noises = operator_noises(&dag, config, &sol, &SHARED_CACHES).unwrap();
+
+        assert_eq!(noises.len(), 3);
+        assert!(noises.iter().all(|noise| noise.margin >= 0.0));
+        // The dot output enters the lut, it is the noise bottleneck
+        assert!(noises[dot.0].variance > noises[input.0].variance);
+        assert!(noises[dot.0].variance > noises[lut.0].variance);
+        assert!(small_relative_diff(noises[dot.0].variance, sol.noise_max));
+    }
+
+    #[test]
+    fn test_operator_budgets() {
+        let mut dag = unparametrized::Dag::new();
+        let input = dag.add_input(4, Shape::vector(2));
+        let dot = dag.add_dot([input], [4]);
+        let lut1 = dag.add_lut(dot, FunctionTable::UNKWOWN, 4);
+        let lut2 = dag.add_lut(lut1, FunctionTable::UNKWOWN, 4);
+        let config = Config {
+            security_level: 128,
+            maximum_acceptable_error_probability: _4_SIGMA,
+            key_sharing: true,
+            ciphertext_modulus_log: 64,
+            fft_precision: 53,
+            complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
+        };
+        let sol = optimize(&dag).best_solution.unwrap();
+        let budgets = operator_budgets(&dag, config, &sol, &SHARED_CACHES).unwrap();
+
+        assert_eq!(budgets.len(), 4);
+        // The input is neither an output nor a lut input
+        assert_f64_eq(budgets[input.0].p_error, 0.0);
+        // The lut inputs dominate the output noise of the last lut
+        for op in [dot, lut1] {
+            assert!(budgets[op.0].p_error > budgets[lut2.0].p_error);
+        }
+        let global_p_error = budgets
+            .iter()
+            .map(|budget| budget.p_error)
+            .fold(0.0, combine_errors);
+        assert!(small_relative_diff(global_p_error, sol.global_p_error));
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let config = Config {
+            security_level: 128,
+            maximum_acceptable_error_probability: _4_SIGMA,
+            key_sharing: true,
+            ciphertext_modulus_log: 64,
+            fft_precision: 53,
+            complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
+        };
+        for dag in [v0_dag(0, 4, 1.0), v0_dag(1, 4, 8.0)] {
+            let sol = optimize(&dag).best_solution.unwrap();
+            let evaluated = evaluate(&dag, config, &sol, &SHARED_CACHES).unwrap();
+            assert_eq!(evaluated, sol);
+        }
+
+        // The parameters of a low precision dag are too noisy for a higher precision
+        let sol = optimize(&v0_dag(0, 1, 1.0)).best_solution.unwrap();
+        assert!(evaluate(&v0_dag(0, 8, 1.0), config, &sol, &SHARED_CACHES).is_none());
+    }
+
+    #[test]
+    fn test_noise_margin() {
+        let kappa = error::sigma_scale_of_error_probability(_4_SIGMA);
+        for precision in 1..=8 {
+            let sol = optimize(&v0_dag(0, precision, 1.0)).best_solution.unwrap();
+            assert!(sol.noise_margin >= 1.0);
+            let p_error = analyze::p_error_from_relative_variance(1.0 / sol.noise_margin, kappa);
+            assert!(small_relative_diff(p_error, sol.p_error));
+        }
+    }
+
+    #[test]
+    fn test_min_key_size() {
+        let config = Config {
+            security_level: 128,
+            maximum_acceptable_error_probability: _4_SIGMA,
+            key_sharing: true,
+            ciphertext_modulus_log: 64,
+            fft_precision: 53,
+            complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinKeySize,
+        };
+        let search_space = SearchSpace::default_cpu();
+        for precision in [2, 4, 6] {
+            let dag = v0_dag(0, precision, 1.0);
+            let min_complexity = optimize(&dag).best_solution.unwrap();
+            let min_key_size = super::optimize(&dag, config, &search_space, &SHARED_CACHES)
+                .best_solution
+                .unwrap();
+            assert!(min_key_size.p_error <= _4_SIGMA * REL_EPSILON_PROBA);
+            assert!(min_key_size.key_size() <= min_complexity.key_size());
+            assert!(min_key_size.complexity >= min_complexity.complexity);
+        }
+    }
+
+    #[test]
+    fn test_32_bits_modulus() {
+        let modulus_log = 32;
+        let caches = decomposition::cache(
+            128,
+            config::ProcessingUnit::Cpu,
+            None,
+            true,
+            modulus_log,
+            modulus_log,
+            config::KeyDistribution::Binary,
+        );
+        let config = Config {
+            security_level: 128,
+            maximum_acceptable_error_probability: _4_SIGMA,
+            key_sharing: true,
+            ciphertext_modulus_log: modulus_log,
+            fft_precision: modulus_log,
+            complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
+        };
+        assert!(config.validate().is_ok());
+        let search_space = SearchSpace::default_cpu();
+        for precision in 1..=6 {
+            let dag = v0_dag(0, precision, 1.0);
+            let sol = super::optimize(&dag, config, &search_space, &caches)
+                .best_solution
+                .unwrap();
+            let modulus_log = modulus_log as u64;
+            assert!(
+                sol.ks_decomposition_level_count * sol.ks_decomposition_base_log <= modulus_log
+            );
+            assert!(
+                sol.br_decomposition_level_count * sol.br_decomposition_base_log <= modulus_log
+            );
+            assert!(sol.p_error <= _4_SIGMA * REL_EPSILON_PROBA);
+            assert_eq!(evaluate(&dag, config, &sol, &caches), Some(sol));
+            // less bits leave less room for the noise
+            let sol_64 = optimize(&dag).best_solution.unwrap();
+            assert!(sol.complexity >= sol_64.complexity);
+        }
+    }
+
+    #[test]
+    fn test_ternary_keys() {
+        let caches = decomposition::cache(
+            128,
+            config::ProcessingUnit::Cpu,
+            None,
+            false,
+            CIPHERTEXT_MODULUS_LOG,
+            FFT_PRECISION,
+            config::KeyDistribution::Ternary,
+        );
+        let config = Config {
+            security_level: 128,
+            maximum_acceptable_error_probability: _4_SIGMA,
+            key_sharing: true,
+            ciphertext_modulus_log: CIPHERTEXT_MODULUS_LOG,
+            fft_precision: FFT_PRECISION,
+            complexity_model: &CpuComplexity::default(),
+            objective: Objective::MinComplexity,
+        };
+        let search_space = SearchSpace::default_cpu();
+        for precision in 1..=6 {
+            let dag = v0_dag(0, precision, 1.0);
+            let sol = super::optimize(&dag, config, &search_space, &caches)
+                .best_solution
+                .unwrap();
+            assert!(sol.p_error <= _4_SIGMA * REL_EPSILON_PROBA);
+            assert_eq!(evaluate(&dag, config, &sol, &caches), Some(sol));
+            // a wider key distribution adds keyswitch, blind rotate and modulus switching noise
+            let sol_binary = optimize(&dag).best_solution.unwrap();
+            assert!(sol.complexity >= sol_binary.complexity);
+        }
+    }
 }
@@ -5,10 +5,11 @@ use crate::optimization::atomic_pattern::Solution as WpSolution;
 use crate::optimization::config::{Config, SearchSpace};
 use crate::optimization::dag::solo_key::{analyze, optimize};
 use crate::optimization::decomposition::PersistDecompCaches;
+use crate::optimization::progress::Progress;
 use crate::optimization::wop_atomic_pattern::optimize::optimize_one as wop_optimize;
 use crate::optimization::wop_atomic_pattern::Solution as WopSolution;
 
-use super::analyze::{has_round, has_unsafe_cast};
+use super::analyze::{has_input_variance, has_round, has_unsafe_cast};
 
 pub enum Solution {
     WpSolution(WpSolution),
@@ -16,7 +17,7 @@ pub enum Solution {
 }
 
 impl Solution {
-    fn complexity(&self) -> f64 {
+    pub fn complexity(&self) -> f64 {
         match self {
             Self::WpSolution(v) => v.complexity,
             Self::WopSolution(v) => v.complexity,
@@ -69,7 +70,7 @@ fn optimize_with_wop_pbs(
     default_log_norm2_woppbs: f64,
     caches: &PersistDecompCaches,
 ) -> Option<WopSolution> {
-    if has_round(dag) || has_unsafe_cast(dag) {
+    if has_round(dag) || has_unsafe_cast(dag) || has_input_variance(dag) {
         return None;
     }
     let max_precision = max_precision(dag);
@@ -88,9 +89,10 @@ pub fn optimize(
     encoding: Encoding,
     default_log_norm2_woppbs: f64,
     caches: &PersistDecompCaches,
+    progress: &mut Progress<'_>,
 ) -> Option<Solution> {
-    let native = || {
-        optimize::optimize(dag, config, search_space, caches)
+    let mut native = || {
+        optimize::optimize_with_progress(dag, config, search_space, caches, progress)
             .best_solution
             .map(Solution::WpSolution)
     };
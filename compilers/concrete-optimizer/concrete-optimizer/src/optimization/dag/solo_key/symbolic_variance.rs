@@ -12,13 +12,17 @@ use std::iter::Sum;
  * We only kown that the first one is lower or equal to the second one.
  * Each linear coefficient is a variance factor.
  * There are homogenious to squared weight (or summed square weights or squared norm2).
+ *
+ * A known constant variance can be added on top, e.g. for inputs carrying upstream noise.
  */
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct SymbolicVariance {
     pub lut_coeff: f64,
     pub input_coeff: f64,
+    pub constant: f64,
     // variance = vf.lut_coeff * lut_out_noise
     //          + vf.input_coeff * input_out_noise
+    //          + vf.constant
     // E.g. variance(dot([lut, input], [3, 4])) = VariancesFactors {lut_coeff:9, input_coeff: 16}
 
     // NOTE: lut_base_noise is the first field since it has higher impact,
@@ -32,6 +36,7 @@ impl std::ops::Add for SymbolicVariance {
         Self {
             lut_coeff: self.lut_coeff + rhs.lut_coeff,
             input_coeff: self.input_coeff + rhs.input_coeff,
+            constant: self.constant + rhs.constant,
         }
     }
 }
@@ -40,6 +45,7 @@ impl std::ops::AddAssign for SymbolicVariance {
     fn add_assign(&mut self, rhs: Self) {
         self.lut_coeff += rhs.lut_coeff;
         self.input_coeff += rhs.input_coeff;
+        self.constant += rhs.constant;
     }
 }
 
@@ -61,6 +67,7 @@ impl std::ops::Mul<f64> for SymbolicVariance {
         Self {
             input_coeff: self.input_coeff * sq_weight,
             lut_coeff: self.lut_coeff * sq_weight,
+            constant: self.constant * sq_weight,
         }
     }
 }
@@ -76,23 +83,34 @@ impl SymbolicVariance {
     pub const ZERO: Self = Self {
         input_coeff: 0.0,
         lut_coeff: 0.0,
+        constant: 0.0,
     };
     pub const INPUT: Self = Self {
         input_coeff: 1.0,
         lut_coeff: 0.0,
+        constant: 0.0,
     };
     pub const LUT: Self = Self {
         input_coeff: 0.0,
         lut_coeff: 1.0,
+        constant: 0.0,
     };
 
+    pub fn constant(variance: f64) -> Self {
+        Self {
+            constant: variance,
+            ..Self::ZERO
+        }
+    }
+
     pub fn dominate_or_equal(&self, other: &Self) -> bool {
         let extra_other_minimal_base_noise = 0.0_f64.max(other.input_coeff - self.input_coeff);
         other.lut_coeff + extra_other_minimal_base_noise <= self.lut_coeff
+            && other.constant <= self.constant
     }
 
     pub fn eval(&self, minimal_base_noise: f64, lut_base_noise: f64) -> f64 {
-        minimal_base_noise * self.input_coeff + lut_base_noise * self.lut_coeff
+        minimal_base_noise * self.input_coeff + lut_base_noise * self.lut_coeff + self.constant
     }
 
     pub fn reduce_to_pareto_front(mut vfs: Vec<Self>) -> Vec<Self> {
@@ -3,27 +3,33 @@ use crate::config;
 use crate::parameters::{BrDecompositionParameters, CmuxParameters, GlweParameters};
 use crate::utils::cache::ephemeral::{CacheHashMap, EphemeralCache};
 use crate::utils::cache::persistent::{default_cache_dir, PersistentCacheHashMap};
-use concrete_cpu_noise_model::gaussian_noise::noise::cmux::variance_cmux;
+use concrete_cpu_noise_model::gaussian_noise::noise::cmux::variance_cmux_with_key;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use super::common::VERSION;
+use super::common::{bootstrap_key_size, VERSION};
 use super::DecompCaches;
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct CmuxComplexityNoise {
     pub decomp: BrDecompositionParameters,
     pub complexity: f64,
+    // complexity paid once per pbs, independently of its number of cmux
+    pub overhead: f64,
     pub noise: f64,
 }
 
 impl CmuxComplexityNoise {
     pub fn complexity_br(&self, in_lwe_dim: u64) -> f64 {
-        in_lwe_dim as f64 * self.complexity
+        in_lwe_dim as f64 * self.complexity + self.overhead
     }
     pub fn noise_br(&self, in_lwe_dim: u64) -> f64 {
         in_lwe_dim as f64 * self.noise
     }
+    // Size in bytes of the bootstrap key
+    pub fn key_size_br(&self, in_lwe_dim: u64, glwe_params: GlweParameters) -> f64 {
+        bootstrap_key_size(in_lwe_dim, self.decomp.level, glwe_params)
+    }
 }
 
 /* This is strictly variance decreasing and strictly complexity increasing */
@@ -32,6 +38,7 @@ pub fn pareto_quantities(
     ciphertext_modulus_log: u32,
     fft_precision: u32,
     security_level: u64,
+    key_distribution: config::KeyDistribution,
     glwe_params: GlweParameters,
 ) -> Vec<CmuxComplexityNoise> {
     let variance_bsk = glwe_params.minimal_variance(ciphertext_modulus_log, security_level);
@@ -42,18 +49,21 @@ pub fn pareto_quantities(
     let mut counting_no_progress = 0;
 
     let mut prev_best_log2_base = ciphertext_modulus_log as u64;
+    let overhead = complexity_model.pbs_overhead_complexity(glwe_params, ciphertext_modulus_log);
 
-    for level in 1..=ciphertext_modulus_log as u64 {
+    let max_level = ciphertext_modulus_log as u64;
+    for level in 1..=max_level {
         // detect increasing noise
         let mut level_decreasing_base_noise = f64::INFINITY;
         let mut best_log2_base = 0_u64;
         // we know a max is between 1 and prev_best_log2_base
         // and the curve has only 1 maximum close to prev_best_log2_base
         // so we start on prev_best_log2_base
-        let range = (1..=prev_best_log2_base).rev();
+        // and the decomposition must fit in the ciphertext modulus
+        let range = (1..=prev_best_log2_base.min(max_level / level)).rev();
 
         for log2_base in range {
-            let base_noise = variance_cmux(
+            let base_noise = variance_cmux_with_key(
                 glwe_params.glwe_dimension,
                 glwe_params.polynomial_size(),
                 log2_base,
@@ -61,6 +71,8 @@ pub fn pareto_quantities(
                 ciphertext_modulus_log,
                 fft_precision,
                 variance_bsk,
+                key_distribution.variance(),
+                key_distribution.square_expectation(),
             );
             if base_noise > level_decreasing_base_noise {
                 break;
@@ -95,6 +107,7 @@ pub fn pareto_quantities(
             decomp: params.br_decomposition_parameter,
             noise: level_decreasing_base_noise,
             complexity,
+            overhead,
         });
         assert!(increasing_complexity < complexity);
         increasing_complexity = complexity;
@@ -115,6 +128,14 @@ pub fn lowest_complexity_br(quantities: &[CmuxComplexityNoise], in_lwe_dim: u64)
     quantities[0].complexity_br(in_lwe_dim)
 }
 
+pub fn lowest_key_size_br(
+    quantities: &[CmuxComplexityNoise],
+    in_lwe_dim: u64,
+    glwe_params: GlweParameters,
+) -> f64 {
+    quantities[0].key_size_br(in_lwe_dim, glwe_params)
+}
+
 pub type Cache = CacheHashMap<GlweParameters, Vec<CmuxComplexityNoise>>;
 
 impl Cache {
@@ -131,11 +152,13 @@ pub fn cache(
     complexity_model: Arc<dyn ComplexityModel>,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    key_distribution: config::KeyDistribution,
 ) -> PersistDecompCache {
     let cache_dir: String = default_cache_dir();
     let hardware = processing_unit.br_to_string();
+    let key = key_distribution.to_cache_suffix();
     let path =
-        format!("{cache_dir}/cmux-decomp-{hardware}-{ciphertext_modulus_log}-{fft_precision}-{security_level}");
+        format!("{cache_dir}/cmux-decomp-{hardware}-{ciphertext_modulus_log}-{fft_precision}-{security_level}{key}");
 
     let function = move |glwe_params: GlweParameters| {
         pareto_quantities(
@@ -143,6 +166,7 @@ pub fn cache(
             ciphertext_modulus_log,
             fft_precision,
             security_level,
+            key_distribution,
             glwe_params,
         )
     };
@@ -153,6 +177,19 @@ pub fn cache(
 pub enum MaxVarianceError {
     PbsBaseLogNotFound,
     PbsLevelNotFound,
+    KsBaseLogNotFound,
+    KsLevelNotFound,
+}
+
+impl std::fmt::Display for MaxVarianceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::PbsBaseLogNotFound => write!(f, "pbs base log didn't match"),
+            Self::PbsLevelNotFound => write!(f, "pbs level not found"),
+            Self::KsBaseLogNotFound => write!(f, "ks base log didn't match"),
+            Self::KsLevelNotFound => write!(f, "ks level not found"),
+        }
+    }
 }
 
 pub fn get_noise_br(
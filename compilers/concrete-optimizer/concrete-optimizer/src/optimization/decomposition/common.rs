@@ -2,4 +2,23 @@ use crate::parameters::GlweParameters;
 
 pub type MacroParam = (GlweParameters, u64);
 
-pub const VERSION: u64 = 3;
+pub const VERSION: u64 = 4;
+
+// Size in bytes of a key coefficient, for 64 bits ciphertexts
+pub const KEY_COEFFICIENT_BYTES: u64 = 8;
+
+// Size in bytes of a bootstrap key
+pub fn bootstrap_key_size(in_lwe_dim: u64, level: u64, glwe_params: GlweParameters) -> f64 {
+    let glwe_size = glwe_params.glwe_dimension + 1;
+    (in_lwe_dim
+        * level
+        * glwe_size
+        * glwe_size
+        * glwe_params.polynomial_size()
+        * KEY_COEFFICIENT_BYTES) as f64
+}
+
+// Size in bytes of a keyswitch key
+pub fn keyswitch_key_size(in_lwe_dim: u64, level: u64, out_lwe_dim: u64) -> f64 {
+    (in_lwe_dim * level * (out_lwe_dim + 1) * KEY_COEFFICIENT_BYTES) as f64
+}
@@ -1,11 +1,13 @@
-use super::common::VERSION;
+use super::cmux::MaxVarianceError;
+use super::common::{keyswitch_key_size, VERSION};
+use super::DecompCaches;
 use crate::computing_cost::complexity_model::ComplexityModel;
 use crate::config;
 use crate::parameters::{KeyswitchParameters, KsDecompositionParameters, LweDimension};
+use crate::security::minimal_variance_lwe;
 use crate::utils::cache::ephemeral::{CacheHashMap, EphemeralCache};
 use crate::utils::cache::persistent::{default_cache_dir, PersistentCacheHashMap};
-use concrete_cpu_noise_model::gaussian_noise::noise::keyswitch_one_bit::variance_keyswitch_one_bit;
-use concrete_security_curves::gaussian::security::minimal_variance_lwe;
+use concrete_cpu_noise_model::gaussian_noise::noise::keyswitch_one_bit::variance_keyswitch_one_bit_with_key;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
@@ -24,6 +26,10 @@ impl KsComplexityNoise {
     pub fn noise(&self, in_lwe_dim: u64) -> f64 {
         in_lwe_dim as f64 * self.noise
     }
+    // Size in bytes of the keyswitch key
+    pub fn key_size(&self, in_lwe_dim: u64, out_lwe_dim: u64) -> f64 {
+        keyswitch_key_size(in_lwe_dim, self.decomp.level, out_lwe_dim)
+    }
 }
 
 /* This is strictly variance decreasing and strictly complexity increasing */
@@ -31,6 +37,7 @@ pub fn pareto_quantities(
     complexity_model: &dyn ComplexityModel,
     ciphertext_modulus_log: u32,
     security_level: u64,
+    key_distribution: config::KeyDistribution,
     internal_dim: u64,
 ) -> Vec<KsComplexityNoise> {
     let variance_ksk = minimal_variance_lwe(internal_dim, ciphertext_modulus_log, security_level);
@@ -41,7 +48,8 @@ pub fn pareto_quantities(
     let mut counting_no_progress = 0;
     let mut prev_best_log2_base = ciphertext_modulus_log as u64;
 
-    for level in 1..=ciphertext_modulus_log as u64 {
+    let max_level = ciphertext_modulus_log as u64;
+    for level in 1..=max_level {
         // detect increasing noise
         let mut level_decreasing_base_noise = f64::INFINITY;
         let mut best_log2_base = 0_u64;
@@ -49,11 +57,18 @@ pub fn pareto_quantities(
         // we know a max is between 1 and prev_best_log2_base
         // and the curve has only 1 maximum close to prev_best_log2_base
         // so we start on prev_best_log2_base
-        let range = (1..=prev_best_log2_base).rev();
+        // and the decomposition must fit in the ciphertext modulus
+        let range = (1..=prev_best_log2_base.min(max_level / level)).rev();
 
         for log2_base in range {
-            let noise_keyswitch =
-                variance_keyswitch_one_bit(log2_base, level, ciphertext_modulus_log, variance_ksk);
+            let noise_keyswitch = variance_keyswitch_one_bit_with_key(
+                log2_base,
+                level,
+                ciphertext_modulus_log,
+                variance_ksk,
+                key_distribution.variance(),
+                key_distribution.square_expectation(),
+            );
             if noise_keyswitch > level_decreasing_base_noise {
                 break;
             }
@@ -116,6 +131,14 @@ pub fn lowest_complexity_ks(quantities: &[KsComplexityNoise], in_lwe_dim: u64) -
     quantities[0].complexity(in_lwe_dim)
 }
 
+pub fn lowest_key_size_ks(
+    quantities: &[KsComplexityNoise],
+    in_lwe_dim: u64,
+    out_lwe_dim: u64,
+) -> f64 {
+    quantities[0].key_size(in_lwe_dim, out_lwe_dim)
+}
+
 pub type Cache = CacheHashMap<u64, Vec<KsComplexityNoise>>;
 
 impl Cache {
@@ -131,19 +154,41 @@ pub fn cache(
     processing_unit: config::ProcessingUnit,
     complexity_model: Arc<dyn ComplexityModel>,
     ciphertext_modulus_log: u32,
+    key_distribution: config::KeyDistribution,
 ) -> PersistDecompCache {
     let cache_dir: String = default_cache_dir();
     let hardware = processing_unit.ks_to_string();
+    let key = key_distribution.to_cache_suffix();
     let path =
-        format!("{cache_dir}/ks-decomp-{hardware}-{ciphertext_modulus_log}-{security_level}");
+        format!("{cache_dir}/ks-decomp-{hardware}-{ciphertext_modulus_log}-{security_level}{key}");
 
     let function = move |internal_dim: u64| {
         pareto_quantities(
             complexity_model.as_ref(),
             ciphertext_modulus_log,
             security_level,
+            key_distribution,
             internal_dim,
         )
     };
     PersistentCacheHashMap::new_no_read(&path, VERSION, function)
 }
+
+pub fn get_noise_ks(
+    mut cache: DecompCaches,
+    input_lwe_dim: u64,
+    output_lwe_dim: u64,
+    ks_level: u64,
+    ks_log2_base: Option<u64>,
+) -> Result<f64, MaxVarianceError> {
+    let ks_quantities = cache.keyswitch.pareto_quantities(output_lwe_dim);
+    for ks_quantity in ks_quantities {
+        if ks_quantity.decomp.level == ks_level {
+            if ks_log2_base.is_some_and(|log2_base| ks_quantity.decomp.log2_base != log2_base) {
+                return Err(MaxVarianceError::KsBaseLogNotFound);
+            }
+            return Ok(ks_quantity.noise(input_lwe_dim));
+        }
+    }
+    Err(MaxVarianceError::KsLevelNotFound)
+}
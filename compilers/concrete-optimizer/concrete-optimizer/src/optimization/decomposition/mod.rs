@@ -8,6 +8,8 @@ pub use common::MacroParam;
 
 use crate::computing_cost::complexity_model::ComplexityModel;
 use crate::config;
+use crate::optimization::config::SearchSpace;
+use crate::parameters::GlweParameters;
 
 use std::sync::Arc;
 
@@ -17,6 +19,7 @@ pub struct PersistDecompCaches {
     pub pp: pp_switch::PersistDecompCache,
     pub cb: circuit_bootstrap::PersistDecompCache,
     pub cache_on_disk: bool,
+    pub key_distribution: config::KeyDistribution,
 }
 
 pub struct DecompCaches {
@@ -24,6 +27,7 @@ pub struct DecompCaches {
     pub keyswitch: keyswitch::Cache,
     pub pp_switch: pp_switch::Cache,
     pub cb_pbs: circuit_bootstrap::Cache,
+    pub key_distribution: config::KeyDistribution,
 }
 
 pub fn cache(
@@ -33,6 +37,7 @@ pub fn cache(
     cache_on_disk: bool,
     ciphertext_modulus_log: u32,
     fft_precision: u32,
+    key_distribution: config::KeyDistribution,
 ) -> PersistDecompCaches {
     PersistDecompCaches::new(
         security_level,
@@ -41,6 +46,7 @@ pub fn cache(
         cache_on_disk,
         ciphertext_modulus_log,
         fft_precision,
+        key_distribution,
     )
 }
 
@@ -52,6 +58,7 @@ impl PersistDecompCaches {
         cache_on_disk: bool,
         ciphertext_modulus_log: u32,
         fft_precision: u32,
+        key_distribution: config::KeyDistribution,
     ) -> Self {
         let complexity_model =
             complexity_model.unwrap_or_else(|| processing_unit.complexity_model());
@@ -61,6 +68,7 @@ impl PersistDecompCaches {
                 processing_unit,
                 complexity_model.clone(),
                 ciphertext_modulus_log,
+                key_distribution,
             ),
             cmux: cmux::cache(
                 security_level,
@@ -68,6 +76,7 @@ impl PersistDecompCaches {
                 complexity_model.clone(),
                 ciphertext_modulus_log,
                 fft_precision,
+                key_distribution,
             ),
             pp: pp_switch::cache(
                 security_level,
@@ -83,6 +92,7 @@ impl PersistDecompCaches {
                 fft_precision,
             ),
             cache_on_disk,
+            key_distribution,
         };
         if cache_on_disk {
             res.ks.read();
@@ -103,12 +113,41 @@ impl PersistDecompCaches {
         self.cb.backport(cache.cb_pbs);
     }
 
+    // Computes the quantities of the whole search space and saves them on disk, so that the next
+    // optimizations only read them.
+    pub fn warm(&self, search_space: &SearchSpace) {
+        if !self.cache_on_disk {
+            return;
+        }
+        let mut caches = self.caches();
+        for &glwe_dimension in &search_space.glwe_dimensions {
+            for &log2_polynomial_size in &search_space.glwe_log_polynomial_sizes {
+                let glwe_params = GlweParameters {
+                    log2_polynomial_size,
+                    glwe_dimension,
+                };
+                let _ = caches.cmux.pareto_quantities(glwe_params);
+                let _ = caches.pp_switch.pareto_quantities(glwe_params);
+                let _ = caches.cb_pbs.pareto_quantities(glwe_params);
+            }
+        }
+        for &internal_dim in &search_space.internal_lwe_dimensions {
+            let _ = caches.keyswitch.pareto_quantities(internal_dim);
+        }
+        self.backport(caches);
+        self.ks.sync_to_disk();
+        self.cmux.sync_to_disk();
+        self.pp.sync_to_disk();
+        self.cb.sync_to_disk();
+    }
+
     pub fn caches(&self) -> DecompCaches {
         DecompCaches {
             cmux: self.cmux.cache(),
             keyswitch: self.ks.cache(),
             pp_switch: self.pp.cache(),
             cb_pbs: self.cb.cache(),
+            key_distribution: self.key_distribution,
         }
     }
 }
@@ -4,6 +4,7 @@ pub mod atomic_pattern;
 pub mod config;
 pub mod dag;
 pub mod decomposition;
+pub mod progress;
 pub mod wop_atomic_pattern;
 
 #[derive(Clone, Debug, PartialEq)]
@@ -11,6 +12,7 @@ pub enum Err {
     NotComposable(String),
     NoParametersFound,
     UnfeasibleVarianceConstraint(Box<VarianceConstraint>),
+    Cancelled,
 }
 
 impl std::fmt::Display for Err {
@@ -18,6 +20,7 @@ impl std::fmt::Display for Err {
         match self {
             Self::NotComposable(details) => write!(f, "Program can not be composed (see https://docs.zama.ai/concrete/compilation/common_errors#id-9.-non-composable-circuit): {details}"),
             Self::NoParametersFound => write!(f, "No crypto parameters could be found"),
+            Self::Cancelled => write!(f, "cancelled"),
             Self::UnfeasibleVarianceConstraint(constraint) => {
                 write!(
                     f,
@@ -0,0 +1,55 @@
+/// Reports the progress of a parameters search to an optional callback.
+///
+/// The callback receives the number of macro parameters enumerated so far.
+/// Without callback, a step is a single branch.
+#[derive(Clone, Copy, Default)]
+pub struct Progress<'a> {
+    callback: Option<&'a dyn Fn(u32)>,
+    steps: u32,
+}
+
+impl<'a> Progress<'a> {
+    pub fn new(callback: &'a dyn Fn(u32)) -> Self {
+        Self {
+            callback: Some(callback),
+            steps: 0,
+        }
+    }
+
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    #[inline]
+    pub fn step(&mut self) {
+        if let Some(callback) = self.callback {
+            self.steps = self.steps.saturating_add(1);
+            callback(self.steps);
+        }
+    }
+
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_progress() {
+        let last = Cell::new(0);
+        let callback = |steps| last.set(steps);
+        let mut progress = Progress::new(&callback);
+        progress.step();
+        progress.step();
+        assert_eq!(last.get(), 2);
+        assert_eq!(progress.steps(), 2);
+
+        let mut progress = Progress::none();
+        progress.step();
+        assert_eq!(progress.steps(), 0);
+    }
+}
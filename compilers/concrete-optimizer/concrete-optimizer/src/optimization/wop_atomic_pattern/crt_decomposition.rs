@@ -23,6 +23,73 @@ pub fn default_coprimes(precision: Precision) -> Result<Vec<u64>, String> {
     })
 }
 
+// Largest number of moduli accepted in a crt decomposition hint
+const MAX_HINT_LEN: usize = 16;
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// Smallest subset of the hinted moduli able to represent the precision
+pub fn coprimes_from_hint(precision: Precision, hint: &[u64]) -> Result<Vec<u64>, String> {
+    if precision == 0 {
+        return Err("Precision cannot be zero".into());
+    }
+    if hint.len() > MAX_HINT_LEN {
+        return Err(format!(
+            "Crt decomposition hint is limited to {MAX_HINT_LEN} moduli"
+        ));
+    }
+    if hint.iter().any(|&modulus| modulus < 2) {
+        return Err(format!(
+            "Crt decomposition hint {hint:?} has moduli lower than 2"
+        ));
+    }
+    for (i, &a) in hint.iter().enumerate() {
+        if hint[i + 1..].iter().any(|&b| gcd(a, b) != 1) {
+            return Err(format!(
+                "Crt decomposition hint {hint:?} is not made of pairwise coprimes"
+            ));
+        }
+    }
+    let required = 1_u128 << precision;
+    let mut best: Option<Vec<u64>> = None;
+    for mask in 1_u32..(1 << hint.len()) {
+        let mut subset: Vec<u64> = hint
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| mask & (1 << i) != 0)
+            .map(|(_, &modulus)| modulus)
+            .collect();
+        let product = subset
+            .iter()
+            .fold(1_u128, |acc, &modulus| acc.saturating_mul(modulus as u128));
+        if product < required {
+            continue;
+        }
+        subset.sort_unstable();
+        // Fewer blocks first, then smaller blocks
+        let key = |coprimes: &[u64]| (coprimes.len(), coprimes.last().copied());
+        if best.as_ref().is_none_or(|best| key(&subset) < key(best)) {
+            best = Some(subset);
+        }
+    }
+    best.ok_or_else(|| format!("Crt decomposition hint {hint:?} cannot represent {precision} bits"))
+}
+
+// Coprimes from the hint if any, from the default heuristic otherwise
+pub fn coprimes(precision: Precision, hint: &[u64]) -> Result<Vec<u64>, String> {
+    if hint.is_empty() {
+        default_coprimes(precision)
+    } else {
+        coprimes_from_hint(precision, hint)
+    }
+}
+
 fn bitwidth(v: u64) -> f64 {
     assert!(v > 0);
     (v as f64).log2()
@@ -55,4 +122,14 @@ mod tests {
         }
         assert!(default_coprimes(17).is_err());
     }
+
+    #[test]
+    fn test_coprimes_from_hint() {
+        assert_eq!(coprimes_from_hint(8, &[7, 8, 9, 11]), Ok(vec![7, 8, 9]));
+        assert_eq!(coprimes_from_hint(4, &[16, 3]), Ok(vec![16]));
+        assert_eq!(coprimes(8, &[]), default_coprimes(8));
+        assert!(coprimes_from_hint(16, &[7, 8, 9]).is_err());
+        assert!(coprimes_from_hint(4, &[4, 6]).is_err());
+        assert!(coprimes_from_hint(4, &[1, 16]).is_err());
+    }
 }
@@ -53,6 +53,8 @@ pub struct Solution {
     //b(BR)
     pub complexity: f64,
     pub noise_max: f64,
+    pub noise_margin: f64,
+    // failure bound variance / noise_max
     pub p_error: f64,
     pub global_p_error: f64,
     // error probability
@@ -76,6 +78,7 @@ impl Solution {
             br_decomposition_base_log: 0,
             complexity: 0.,
             noise_max: 0.0,
+            noise_margin: 0.0,
             p_error: 0.0,
             global_p_error: 0.0,
             cb_decomposition_level_count: 0,
@@ -100,6 +103,7 @@ impl From<Solution> for atomic_pattern::Solution {
             br_decomposition_base_log: sol.br_decomposition_base_log,
             complexity: sol.complexity,
             noise_max: sol.noise_max,
+            noise_margin: sol.noise_margin,
             p_error: sol.p_error,
             global_p_error: sol.global_p_error,
         }
@@ -392,6 +396,7 @@ fn update_state_with_best_decompositions(
                         br_decomposition_level_count: cmux_decomp.decomp.level,
                         br_decomposition_base_log: cmux_decomp.decomp.log2_base,
                         noise_max: variance_max,
+                        noise_margin: safe_variance_bound / variance_max,
                         complexity,
                         p_error,
                         global_p_error: f64::NAN,
@@ -497,7 +502,9 @@ pub fn optimize_one(
     search_space: &SearchSpace,
     caches: &PersistDecompCaches,
 ) -> OptimizationState {
-    let Ok(coprimes) = crt_decomposition::default_coprimes(precision as Precision) else {
+    let Ok(coprimes) =
+        crt_decomposition::coprimes(precision as Precision, &search_space.crt_decomposition_hint)
+    else {
         return OptimizationState {
             best_solution: None,
         };
@@ -542,10 +549,12 @@ pub fn optimize_to_circuit_solution(
     search_space: &SearchSpace,
     caches: &PersistDecompCaches,
 ) -> keys_spec::CircuitSolution {
-    let Ok(coprimes) = crt_decomposition::default_coprimes(precision as Precision) else {
-        return keys_spec::CircuitSolution::no_solution(
-            "Crt decomposition is not unknown for {precision}:bits",
-        );
+    let coprimes = match crt_decomposition::coprimes(
+        precision as Precision,
+        &search_space.crt_decomposition_hint,
+    ) {
+        Ok(coprimes) => coprimes,
+        Err(err) => return keys_spec::CircuitSolution::no_solution(err),
     };
     let n_functions = 1;
     let state = optimize_raw(
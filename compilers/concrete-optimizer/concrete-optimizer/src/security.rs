@@ -0,0 +1,222 @@
+//! Minimal variances ensuring security.
+//!
+//! They come from the lattice-estimator curves of `concrete_security_curves`, unless a custom
+//! curve is installed for the current thread with `SecurityCurve::install`.
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+use concrete_security_curves::gaussian::security as curves;
+use serde::{Deserialize, Serialize};
+
+// Variance used for dimensions a custom curve does not cover.
+// It is above any noise bound, so parameters using these dimensions are never feasible.
+const UNCOVERED_VARIANCE: f64 = 1.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SecurityCurvePoint {
+    pub lwe_dimension: u64,
+    pub minimal_variance: f64,
+}
+
+/// A custom `lwe_dimension -> minimal_variance` curve, replacing the built-in ones.
+///
+/// The variances are interpolated between the points (linearly on their logarithm) and
+/// dimensions outside of the points range are considered insecure.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SecurityCurve {
+    // sorted by strictly increasing dimension
+    points: Vec<SecurityCurvePoint>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SecurityCurveError {
+    Empty,
+    DuplicatedDimension(u64),
+    InvalidVariance(SecurityCurvePoint),
+}
+
+impl fmt::Display for SecurityCurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "Security curve has no point"),
+            Self::DuplicatedDimension(lwe_dimension) => write!(
+                f,
+                "Security curve has several points for lwe dimension {lwe_dimension}"
+            ),
+            Self::InvalidVariance(SecurityCurvePoint {
+                lwe_dimension,
+                minimal_variance,
+            }) => write!(
+                f,
+                "Security curve has an invalid variance {minimal_variance} for lwe dimension {lwe_dimension}"
+            ),
+        }
+    }
+}
+
+impl SecurityCurve {
+    pub fn new(mut points: Vec<SecurityCurvePoint>) -> Result<Self, SecurityCurveError> {
+        if points.is_empty() {
+            return Err(SecurityCurveError::Empty);
+        }
+        if let Some(point) = points
+            .iter()
+            .find(|p| !(p.minimal_variance.is_finite() && 0.0 < p.minimal_variance))
+        {
+            return Err(SecurityCurveError::InvalidVariance(*point));
+        }
+        points.sort_by_key(|p| p.lwe_dimension);
+        if let Some(pair) = points
+            .windows(2)
+            .find(|pair| pair[0].lwe_dimension == pair[1].lwe_dimension)
+        {
+            return Err(SecurityCurveError::DuplicatedDimension(
+                pair[0].lwe_dimension,
+            ));
+        }
+        Ok(Self { points })
+    }
+
+    pub fn covers(&self, lwe_dimension: u64) -> bool {
+        match (self.points.first(), self.points.last()) {
+            (Some(first), Some(last)) => {
+                (first.lwe_dimension..=last.lwe_dimension).contains(&lwe_dimension)
+            }
+            _ => false,
+        }
+    }
+
+    /// The interpolated minimal variance, None outside of the curve range.
+    pub fn minimal_variance(&self, lwe_dimension: u64) -> Option<f64> {
+        if !self.covers(lwe_dimension) {
+            return None;
+        }
+        let after = self
+            .points
+            .partition_point(|p| p.lwe_dimension < lwe_dimension);
+        let high = self.points[after];
+        if high.lwe_dimension == lwe_dimension {
+            return Some(high.minimal_variance);
+        }
+        let low = self.points[after - 1];
+        let t = (lwe_dimension - low.lwe_dimension) as f64
+            / (high.lwe_dimension - low.lwe_dimension) as f64;
+        let log2_variance =
+            (1.0 - t) * low.minimal_variance.log2() + t * high.minimal_variance.log2();
+        Some(log2_variance.exp2())
+    }
+
+    /// Uses this curve for all minimal variances computed by the current thread,
+    /// until the returned guard is dropped.
+    #[must_use]
+    pub fn install(self) -> SecurityCurveGuard {
+        let previous = CUSTOM_CURVE.with(|curve| curve.replace(Some(Rc::new(self))));
+        SecurityCurveGuard { previous }
+    }
+}
+
+thread_local! {
+    static CUSTOM_CURVE: RefCell<Option<Rc<SecurityCurve>>> = const { RefCell::new(None) };
+}
+
+/// Restores the previously installed curve when dropped.
+pub struct SecurityCurveGuard {
+    previous: Option<Rc<SecurityCurve>>,
+}
+
+impl Drop for SecurityCurveGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CUSTOM_CURVE.with(|curve| {
+            let _ = curve.replace(previous);
+        });
+    }
+}
+
+/// Noise ensuring security
+pub fn minimal_variance_lwe(
+    lwe_dimension: u64,
+    ciphertext_modulus_log: u32,
+    security_level: u64,
+) -> f64 {
+    minimal_variance_glwe(lwe_dimension, 1, ciphertext_modulus_log, security_level)
+}
+
+/// Noise ensuring security
+pub fn minimal_variance_glwe(
+    glwe_dimension: u64,
+    polynomial_size: u64,
+    ciphertext_modulus_log: u32,
+    security_level: u64,
+) -> f64 {
+    let custom = CUSTOM_CURVE.with(|curve| {
+        curve.borrow().as_ref().map(|curve| {
+            curve
+                .minimal_variance(glwe_dimension * polynomial_size)
+                .unwrap_or(UNCOVERED_VARIANCE)
+        })
+    });
+    custom.unwrap_or_else(|| {
+        curves::minimal_variance_glwe(
+            glwe_dimension,
+            polynomial_size,
+            ciphertext_modulus_log,
+            security_level,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(lwe_dimension: u64, minimal_variance: f64) -> SecurityCurvePoint {
+        SecurityCurvePoint {
+            lwe_dimension,
+            minimal_variance,
+        }
+    }
+
+    #[test]
+    fn test_invalid_curves() {
+        assert_eq!(SecurityCurve::new(vec![]), Err(SecurityCurveError::Empty));
+        assert_eq!(
+            SecurityCurve::new(vec![point(512, 1e-8), point(512, 1e-9)]),
+            Err(SecurityCurveError::DuplicatedDimension(512))
+        );
+        assert_eq!(
+            SecurityCurve::new(vec![point(512, 0.0)]),
+            Err(SecurityCurveError::InvalidVariance(point(512, 0.0)))
+        );
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let curve = SecurityCurve::new(vec![
+            point(2048, 2f64.powi(-40)),
+            point(1024, 2f64.powi(-20)),
+        ])
+        .unwrap();
+        assert!(!curve.covers(1023));
+        assert!(!curve.covers(2049));
+        assert_eq!(curve.minimal_variance(1023), None);
+        assert_eq!(curve.minimal_variance(1024), Some(2f64.powi(-20)));
+        assert_eq!(curve.minimal_variance(1536), Some(2f64.powi(-30)));
+        assert_eq!(curve.minimal_variance(2048), Some(2f64.powi(-40)));
+    }
+
+    #[test]
+    fn test_install() {
+        let builtin = minimal_variance_lwe(1024, 64, 128);
+        {
+            let _guard = SecurityCurve::new(vec![point(512, 1e-6), point(1024, 1e-12)])
+                .unwrap()
+                .install();
+            assert_eq!(minimal_variance_lwe(1024, 64, 128), 1e-12);
+            assert_eq!(minimal_variance_glwe(1, 512, 64, 128), 1e-6);
+            assert_eq!(minimal_variance_lwe(2048, 64, 128), UNCOVERED_VARIANCE);
+        }
+        assert_eq!(minimal_variance_lwe(1024, 64, 128), builtin);
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+
+/* In-memory cache keeping at most `capacity` entries, the least recently used is evicted first */
+pub struct LruCache<K, V> {
+    capacity: usize,
+    entries: HashMap<K, V>,
+    recency: VecDeque<K>, // least recently used first
+}
+
+impl<K, V> LruCache<K, V>
+where
+    K: Clone + Eq + Hash,
+{
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "LruCache capacity must be positive");
+        Self {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+            recency: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if self.entries.contains_key(key) {
+            self.touch(key);
+        }
+        self.entries.get(key)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if self.entries.insert(key.clone(), value).is_some() {
+            self.touch(&key);
+            return;
+        }
+        if self.entries.len() > self.capacity {
+            if let Some(oldest) = self.recency.pop_front() {
+                let _ = self.entries.remove(&oldest);
+            }
+        }
+        self.recency.push_back(key);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+
+    fn touch(&mut self, key: &K) {
+        if let Some(position) = self.recency.iter().position(|k| k == key) {
+            let key = self.recency.remove(position).unwrap();
+            self.recency.push_back(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = LruCache::new(2);
+        cache.insert(1, "a");
+        cache.insert(2, "b");
+        assert_eq!(cache.get(&1), Some(&"a"));
+        cache.insert(3, "c");
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&2), None);
+        assert_eq!(cache.get(&1), Some(&"a"));
+        assert_eq!(cache.get(&3), Some(&"c"));
+        cache.insert(3, "d");
+        cache.insert(4, "e");
+        assert_eq!(cache.get(&1), None);
+        assert_eq!(cache.get(&3), Some(&"d"));
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,3 +1,4 @@
 pub mod ephemeral;
+pub mod lru;
 pub mod persistent;
 pub(crate) mod read_only;
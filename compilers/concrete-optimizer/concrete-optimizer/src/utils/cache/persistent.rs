@@ -10,6 +10,7 @@ use file_lock::{FileLock, FileOptions};
 use super::ephemeral;
 use super::ephemeral::{EphemeralCache, KeyValueFunction};
 use super::read_only::{Map, ReadOnlyCache};
+use crate::utils::log::{log, Level};
 
 const SHOW_DISK_ACCESS: bool = false;
 const DISABLE_CACHE: bool = false;
@@ -76,7 +77,8 @@ where
         let t0 = Instant::now();
         let content = Self::read_from_disk(&self.path, self.version).unwrap_or_default();
         if SHOW_DISK_ACCESS {
-            println!(
+            log!(
+                Level::Debug,
                 "PersistentCache: {}, reading time {} msec, {} entries",
                 self.path,
                 t0.elapsed().as_millis(),
@@ -118,7 +120,7 @@ where
         }
     }
 
-    fn own_or_clone_content(&self) -> (RwLockWriteGuard<Arc<ROC>>, ROC) {
+    fn own_or_clone_content(&self) -> (RwLockWriteGuard<'_, Arc<ROC>>, ROC) {
         let mut lock = self.content.write().unwrap();
         // let's take the map ownership if possible, we need to have only one local copy
         let arc_map = std::mem::take(&mut *lock);
@@ -144,7 +146,10 @@ where
         let filelock = match FileLock::lock(path, is_blocking, options) {
             Ok(lock) => lock,
             Err(error) => {
-                println!("PersistentCache::read_from_disk: Cannot lock cache file {path}: {error}");
+                log!(
+                    Level::Warning,
+                    "PersistentCache::read_from_disk: Cannot lock cache file {path}: {error}"
+                );
                 return None;
             }
         };
@@ -154,18 +159,25 @@ where
     pub fn sync_to_disk(&self) {
         if !self.content_changed.load(Ordering::Relaxed) {
             if SHOW_DISK_ACCESS {
-                println!("PersistentCache: skip sync to disk, {}", self.path);
+                log!(
+                    Level::Debug,
+                    "PersistentCache: skip sync to disk, {}",
+                    self.path
+                );
             }
             return;
         }
         if SHOW_DISK_ACCESS {
-            println!("PersistentCache: sync to disk, {}", self.path);
+            log!(Level::Debug, "PersistentCache: sync to disk, {}", self.path);
         }
         match std::fs::create_dir_all(std::path::Path::new(&self.path).parent().unwrap()) {
             Ok(()) => (),
             Err(err) => {
                 let path = &self.path;
-                println!("PersistentCache::sync_to_disk: Cannot create directory {path}, {err}");
+                log!(
+                    Level::Warning,
+                    "PersistentCache::sync_to_disk: Cannot create directory {path}, {err}"
+                );
                 return;
             }
         };
@@ -174,7 +186,8 @@ where
         let mut filelock = match FileLock::lock(&self.path, is_blocking, options) {
             Ok(lock) => lock,
             Err(_err) => {
-                println!(
+                log!(
+                    Level::Warning,
                     "PersistentCache::sync_to_disk: Cannot lock cache file {}",
                     self.path
                 );
@@ -203,7 +216,10 @@ where
                 }
             }
             Err(err) => {
-                println!("PersistentCache::read_from_disk: cannot read size {path} {err}");
+                log!(
+                    Level::Warning,
+                    "PersistentCache::read_from_disk: cannot read size {path} {err}"
+                );
                 return None;
             }
         };
@@ -214,13 +230,19 @@ where
         match disk_version {
             Ok(disk_version) => {
                 if disk_version != version {
-                    println!("PersistentCache:: Invalid version {path}: cleaning");
+                    log!(
+                        Level::Info,
+                        "PersistentCache:: Invalid version {path}: cleaning"
+                    );
                     Self::clear_file(path);
                     return None;
                 }
             }
             Err(error) => {
-                println!("PersistentCache::read_given_lock: Cannot read version {path}: {error}");
+                log!(
+                    Level::Warning,
+                    "PersistentCache::read_given_lock: Cannot read version {path}: {error}"
+                );
                 Self::clear_file(path);
                 return None;
             }
@@ -228,7 +250,10 @@ where
         match bincode::deserialize_from(buf.borrow_mut()) {
             Ok(content) => Some(content),
             Err(error) => {
-                println!("PersistentCache::read_given_lock: Cannot read hashmap {path}: {error}");
+                log!(
+                    Level::Warning,
+                    "PersistentCache::read_given_lock: Cannot read hashmap {path}: {error}"
+                );
                 Self::clear_file(path);
                 None
             }
@@ -237,21 +262,24 @@ where
 
     fn write_given_lock(&self, filelock: &mut FileLock, content: &ROC) {
         if SHOW_DISK_ACCESS {
-            println!(
+            log!(
+                Level::Debug,
                 "PersistentCache::write: to disk {}: {} records",
                 self.path,
                 content.len()
             );
         }
         if let Err(err) = filelock.file.rewind() {
-            println!(
+            log!(
+                Level::Warning,
                 "PersistentCache::write: cannot rewind file: {}, {err}",
                 self.path
             );
             return;
         }
         if let Err(err) = filelock.file.set_len(0) {
-            println!(
+            log!(
+                Level::Warning,
                 "PersistentCache::write: cannot truncate file: {}, {err}",
                 self.path
             );
@@ -272,7 +300,10 @@ where
         let filelock = match FileLock::lock(path, is_blocking, options) {
             Ok(lock) => lock,
             Err(_err) => {
-                println!("PersistentCache::clear: Cannot lock cache file {path}");
+                log!(
+                    Level::Warning,
+                    "PersistentCache::clear: Cannot lock cache file {path}"
+                );
                 return;
             }
         };
@@ -291,6 +322,14 @@ pub fn default_cache_dir() -> String {
     cache_dir.to_str().expect("Invalid tmp dir").into()
 }
 
+// Removes all the caches on disk, e.g. after an upgrade
+pub fn clear_cache_dir() -> std::io::Result<()> {
+    match std::fs::remove_dir_all(default_cache_dir()) {
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::ephemeral::CacheHashMap;
@@ -0,0 +1,89 @@
+use std::cell::Cell;
+use std::sync::OnceLock;
+
+// Diagnostic messages go to stdout, unless a host callback is installed.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Level {
+    Error = 0,
+    Warning = 1,
+    Info = 2,
+    Debug = 3,
+}
+
+// Receives the level as u8 and the message
+pub type Callback = Box<dyn Fn(u8, &str) + Send + Sync>;
+
+static CALLBACK: OnceLock<Callback> = OnceLock::new();
+
+thread_local! {
+    static QUIET: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Installs the callback receiving all diagnostic messages, it can only be installed once.
+pub fn set_callback(callback: Callback) -> Result<(), Callback> {
+    CALLBACK.set(callback)
+}
+
+/// Drops all messages but errors emitted by the current thread while quiet,
+/// until the returned guard is dropped.
+#[must_use]
+pub fn set_quiet(quiet: bool) -> QuietGuard {
+    let previous = QUIET.with(|flag| flag.replace(quiet));
+    QuietGuard { previous }
+}
+
+/// Restores the previous quiet flag of the thread when dropped.
+pub struct QuietGuard {
+    previous: bool,
+}
+
+impl Drop for QuietGuard {
+    fn drop(&mut self) {
+        QUIET.with(|flag| flag.set(self.previous));
+    }
+}
+
+pub fn emit(level: Level, msg: &str) {
+    if level != Level::Error && QUIET.with(Cell::get) {
+        return;
+    }
+    match CALLBACK.get() {
+        Some(callback) => callback(level as u8, msg),
+        None => println!("{msg}"),
+    }
+}
+
+// Formats and logs a message, like println!
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {
+        $crate::utils::log::emit($level, &format!($($arg)*))
+    };
+}
+pub(crate) use log;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_quiet() -> bool {
+        QUIET.with(Cell::get)
+    }
+
+    #[test]
+    fn test_quiet_is_scoped() {
+        {
+            let _quiet = set_quiet(true);
+            assert!(is_quiet());
+            {
+                let _loud = set_quiet(false);
+                assert!(!is_quiet());
+            }
+            assert!(is_quiet());
+            // other threads keep their own flag
+            assert!(!std::thread::spawn(is_quiet).join().unwrap());
+        }
+        assert!(!is_quiet());
+    }
+}
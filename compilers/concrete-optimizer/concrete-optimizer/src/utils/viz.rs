@@ -88,34 +88,44 @@ impl Viz for crate::dag::unparametrized::DagOperator<'_> {
             "lightgreen"
         };
         let location = self.location;
+        let loc = if self.comment.is_empty() {
+            format!("{{loc:|{location}}}")
+        } else {
+            let comment = escape_record_label(self.comment);
+            format!("{{loc:|{location}}} | {{comment:|{comment}}}")
+        };
         match self.operator {
             Operator::Input { out_precision, .. } => {
-                format!("{index} [label =\"{{%{index} = Input({input_string}) |{{out_precision:|{out_precision:?}}} | {{loc:|{location}}}}}\" fillcolor={color}];")
+                format!("{index} [label =\"{{%{index} = Input({input_string}) |{{out_precision:|{out_precision:?}}} | {loc}}}\" fillcolor={color}];")
             }
             Operator::ZeroNoise { out_precision, .. } => {
-                format!("{index} [label =\"{{%{index} = Zero({input_string}) |{{out_precision:|{out_precision:?}}} | {{loc:|{location}}}}}\" fillcolor={color}];")
+                format!("{index} [label =\"{{%{index} = Zero({input_string}) |{{out_precision:|{out_precision:?}}} | {loc}}}\" fillcolor={color}];")
             }
             Operator::Lut { out_precision, .. } => {
-                format!("{index} [label = \"{{%{index} = Lut({input_string}) |{{out_precision:|{out_precision:?}}}| {{loc:|{location}}}}}\" fillcolor={color}];")
+                format!("{index} [label = \"{{%{index} = Lut({input_string}) |{{out_precision:|{out_precision:?}}}| {loc}}}\" fillcolor={color}];")
             }
             Operator::Dot { .. } => {
-                format!("{index} [label = \"{{%{index} = Dot({input_string})| {{loc:|{location}}}}}\" fillcolor={color}];")
+                format!("{index} [label = \"{{%{index} = Dot({input_string})| {loc}}}\" fillcolor={color}];")
             }
             Operator::LinearNoise { weights, .. } => {
-                format!("{index} [label = \"{{%{index} = LevelledOp({input_string}) |{{weights:|{weights:?}}}| {{loc:|{location}}}}}\" fillcolor={color}];")
+                format!("{index} [label = \"{{%{index} = LevelledOp({input_string}) |{{weights:|{weights:?}}}| {loc}}}\" fillcolor={color}];")
             }
             Operator::MaxNoise { .. } => {
-                format!("{index} [label = \"{{%{index} = Max({input_string}) | {{loc:|{location}}}}}\" fillcolor={color}];")
+                format!("{index} [label = \"{{%{index} = Max({input_string}) | {loc}}}\" fillcolor={color}];")
             }
             Operator::UnsafeCast { out_precision, .. } => format!(
-                "{index} [label = \"{{%{index} = UnsafeCast({input_string}) |{{out_precision:|{out_precision:?}}}| {{loc:|{location}}}}}\" fillcolor={color}];"
+                "{index} [label = \"{{%{index} = UnsafeCast({input_string}) |{{out_precision:|{out_precision:?}}}| {loc}}}\" fillcolor={color}];"
             ),
             Operator::Round { out_precision, .. } => {
-                format!("{index} [label = \"{{%{index} = Round({input_string}) |{{out_precision:|{out_precision:?}}}| {{loc:|{location}}}}}\" fillcolor={color}];",)
+                format!("{index} [label = \"{{%{index} = Round({input_string}) |{{out_precision:|{out_precision:?}}}| {loc}}}\" fillcolor={color}];",)
             }
             Operator::ChangePartition { .. } => {
                 format!("{index} [label = \"{{%{index} = ChangePartition({input_string})}}\" fillcolor={color}];",)
             }
+            Operator::Keyswitch { dst_partition, .. } => {
+                let partition = &dst_partition.name;
+                format!("{index} [label = \"{{%{index} = Keyswitch({input_string}) |{{dst_partition:|{partition}}}| {loc}}}\" fillcolor={color}];",)
+            }
         }
     }
 
@@ -125,6 +135,18 @@ impl Viz for crate::dag::unparametrized::DagOperator<'_> {
     }
 }
 
+// Escapes the characters that have a meaning in a dot record label.
+fn escape_record_label(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '"' | '{' | '}' | '|' | '<' | '>' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
 impl Viz for crate::optimization::dag::multi_parameters::analyze::PartitionedDag {
     fn viz_node(&self) -> String {
         let mut output = self.dag.viz_node();
@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
-use v0_parameters::{all_results, Args, MAX_LWE_DIM, MIN_LWE_DIM, _4_SIGMA};
+use v0_parameters::{all_results, Args, _4_SIGMA, MAX_LWE_DIM, MIN_LWE_DIM};
 
 fn v0_pbs_optimization(c: &mut Criterion) {
     let args: Args = Args {
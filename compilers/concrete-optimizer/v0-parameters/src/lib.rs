@@ -12,7 +12,7 @@ use clap::Parser;
 use concrete_optimizer::computing_cost::cpu::CpuComplexity;
 use concrete_optimizer::config;
 use concrete_optimizer::global_parameters::DEFAULT_DOMAINS;
-use concrete_optimizer::optimization::config::{Config, SearchSpace};
+use concrete_optimizer::optimization::config::{Config, Objective, SearchSpace};
 use concrete_optimizer::optimization::dag::solo_key::optimize::{self as optimize_dag};
 use concrete_optimizer::optimization::dag::solo_key::optimize_generic::Solution;
 use concrete_optimizer::optimization::dag::solo_key::optimize_generic::Solution::{
@@ -110,6 +110,9 @@ pub fn all_results(args: &Args) -> Vec<Vec<Option<Solution>>> {
         glwe_dimensions: (args.min_glwe_dim..=args.max_glwe_dim).collect(),
         internal_lwe_dimensions: (args.min_intern_lwe_dim..=args.max_intern_lwe_dim).collect(),
         levelled_only_lwe_dimensions: DEFAULT_DOMAINS.free_lwe,
+        crt_decomposition_hint: vec![],
+        allow_fast_keyswitch: true,
+        key_sharing_groups: vec![],
     };
 
     let precisions = args.min_precision..=args.max_precision;
@@ -126,6 +129,7 @@ pub fn all_results(args: &Args) -> Vec<Vec<Option<Solution>>> {
         ciphertext_modulus_log: args.ciphertext_modulus_log,
         fft_precision: args.fft_precision,
         complexity_model: &CpuComplexity::default(),
+        objective: Objective::MinComplexity,
     };
 
     let cache = decomposition::cache(
@@ -135,6 +139,7 @@ pub fn all_results(args: &Args) -> Vec<Vec<Option<Solution>>> {
         cache_on_disk,
         args.ciphertext_modulus_log,
         args.fft_precision,
+        config::KeyDistribution::Binary,
     );
 
     precisions_iter
@@ -246,7 +251,7 @@ pub fn compute_print_results(mut writer: impl Write, args: &Args) -> Result<(),
         }
         if let Some(no_solution_at) = no_solution_at {
             if no_solution_at == 0 {
-                writeln!(writer, "    # no solution at all",)?;
+                writeln!(writer, "    # no solution at all")?;
             } else {
                 writeln!(
                     writer,